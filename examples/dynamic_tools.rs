@@ -230,6 +230,11 @@ fn main() -> Result<()> {
         ],
         auto_discover_builtin: true,
         enable_all_by_default: false, // Only explicitly enabled handlers
+        auto_discover_all: false,
+        check_annotations: false,
+        plugin_paths: Vec::new(),
+        sandbox: Default::default(),
+        idempotency_ttl: 300,
     };
 
     let custom_handlers = get_tool_handlers_with_config(Some(&custom_config));
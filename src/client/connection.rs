@@ -0,0 +1,534 @@
+//! Minimal MCP client.
+//!
+//! [`McpClient`] drives a server over Streamable HTTP or STDIO. It performs
+//! the `initialize` handshake and wraps the common requests in typed methods.
+//! Requests are sent one at a time; it is meant for end-to-end tests and
+//! tooling rather than as a full client implementation.
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+use crate::error::{McpError, Result, TransportError};
+use crate::protocol::{
+    parse_message, AnyJsonRpcMessage, CallToolResult, GetPromptResult, InitializeRequest,
+    InitializeResult, JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+    ResourceContents, Tool, JSONRPC_VERSION,
+};
+
+/// Input read from the server's stdout
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Output written to the server's stdin
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Connection to the server
+enum ClientTransport {
+    /// Streamable HTTP endpoint
+    Http {
+        client: reqwest::Client,
+        url: String,
+        session_id: Option<String>,
+    },
+
+    /// Newline-delimited JSON over a pair of streams
+    Stdio {
+        lines: Lines<BufReader<BoxedReader>>,
+        writer: BoxedWriter,
+        /// Spawned server process, killed when the client is dropped
+        _child: Option<Box<Child>>,
+    },
+}
+
+/// MCP client connected to a single server
+pub struct McpClient {
+    transport: Mutex<ClientTransport>,
+    next_id: AtomicI64,
+    server: std::sync::Mutex<Option<InitializeResult>>,
+}
+
+impl McpClient {
+    /// Connect to a Streamable HTTP endpoint, e.g. `http://127.0.0.1:8080/mcp`
+    pub fn connect_http(url: impl Into<String>) -> Self {
+        Self::with_transport(ClientTransport::Http {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            session_id: None,
+        })
+    }
+
+    /// Talk to a server reading requests from `writer` and answering on `reader`
+    pub fn connect_stdio<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let reader: BoxedReader = Box::new(reader);
+        Self::with_transport(ClientTransport::Stdio {
+            lines: BufReader::new(reader).lines(),
+            writer: Box::new(writer),
+            _child: None,
+        })
+    }
+
+    /// Spawn a server process and talk to it over its stdin/stdout
+    pub fn spawn(command: &mut Command) -> Result<Self> {
+        let mut child = command
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(TransportError::ConnectionFailed(
+                "Server process has no stdio pipes".to_string(),
+            )
+            .into());
+        };
+
+        let reader: BoxedReader = Box::new(stdout);
+        Ok(Self::with_transport(ClientTransport::Stdio {
+            lines: BufReader::new(reader).lines(),
+            writer: Box::new(stdin),
+            _child: Some(Box::new(child)),
+        }))
+    }
+
+    fn with_transport(transport: ClientTransport) -> Self {
+        Self {
+            transport: Mutex::new(transport),
+            next_id: AtomicI64::new(1),
+            server: std::sync::Mutex::new(None),
+        }
+    }
+
+    /// Perform the `initialize` handshake
+    ///
+    /// Build the request with [`InitializeRequestBuilder`](crate::protocol::InitializeRequestBuilder).
+    pub async fn initialize(&self, request: InitializeRequest) -> Result<InitializeResult> {
+        let result = self
+            .request("initialize", Some(serde_json::to_value(request)?))
+            .await?;
+        let result: InitializeResult = serde_json::from_value(result)?;
+
+        self.notify("notifications/initialized", None).await?;
+        *self.server.lock().unwrap_or_else(|e| e.into_inner()) = Some(result.clone());
+        Ok(result)
+    }
+
+    /// Get the server's answer to `initialize`, if the handshake is done
+    pub fn server_info(&self) -> Option<InitializeResult> {
+        self.server.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Check that the server is responsive
+    pub async fn ping(&self) -> Result<()> {
+        self.request("ping", None).await.map(|_| ())
+    }
+
+    /// List every tool, following pagination cursors
+    pub async fn list_tools(&self) -> Result<Vec<Tool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let params = cursor.map(|cursor| serde_json::json!({ "cursor": cursor }));
+            let mut result = self.request("tools/list", params).await?;
+            let page: Vec<Tool> = serde_json::from_value(result["tools"].take())?;
+            tools.extend(page);
+
+            match result.get("nextCursor").and_then(Value::as_str) {
+                Some(next) => cursor = Some(next.to_string()),
+                None => return Ok(tools),
+            }
+        }
+    }
+
+    /// Call a tool
+    pub async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<CallToolResult> {
+        let mut params = serde_json::json!({ "name": name });
+        if let Some(arguments) = arguments {
+            params["arguments"] = arguments;
+        }
+        let result = self.request("tools/call", Some(params)).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Read a resource
+    pub async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
+        let mut result = self
+            .request("resources/read", Some(serde_json::json!({ "uri": uri })))
+            .await?;
+        Ok(serde_json::from_value(result["contents"].take())?)
+    }
+
+    /// Get a prompt rendered with the given arguments
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: Option<HashMap<String, String>>,
+    ) -> Result<GetPromptResult> {
+        let mut params = serde_json::json!({ "name": name });
+        if let Some(arguments) = arguments {
+            params["arguments"] = serde_json::to_value(arguments)?;
+        }
+        let result = self.request("prompts/get", Some(params)).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Send a request and wait for its result
+    ///
+    /// JSON-RPC errors returned by the server are converted to [`McpError`].
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = JsonRpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: serde_json::json!(id),
+            method: method.to_string(),
+            params,
+        };
+
+        let response = {
+            let mut transport = self.transport.lock().await;
+            transport.send_request(&request).await?
+        };
+
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(error_from_json_rpc(error)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Ok(Value::Null),
+        }
+    }
+
+    /// Send a notification
+    pub async fn notify(&self, method: &str, params: Option<Value>) -> Result<()> {
+        let notification = JsonRpcNotification::new(method.to_string(), params);
+        let mut transport = self.transport.lock().await;
+        transport.send_notification(&notification).await
+    }
+}
+
+impl ClientTransport {
+    async fn send_request(&mut self, request: &JsonRpcRequest) -> Result<JsonRpcResponse> {
+        match self {
+            ClientTransport::Http {
+                client,
+                url,
+                session_id,
+            } => {
+                let response = post(client, url, session_id.as_deref(), request).await?;
+                if let Some(id) = response
+                    .headers()
+                    .get("Mcp-Session-Id")
+                    .and_then(|h| h.to_str().ok())
+                {
+                    *session_id = Some(id.to_string());
+                }
+
+                let is_event_stream = response
+                    .headers()
+                    .get("Content-Type")
+                    .and_then(|h| h.to_str().ok())
+                    .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+                let body = response.text().await?;
+
+                let messages = if is_event_stream {
+                    body.lines()
+                        .filter_map(|line| line.strip_prefix("data:"))
+                        .map(|data| parse_message(data.trim()))
+                        .collect::<Result<Vec<_>>>()?
+                } else {
+                    vec![parse_message(&body)?]
+                };
+                messages
+                    .into_iter()
+                    .find_map(|message| response_for(message, request))
+                    .ok_or_else(|| {
+                        McpError::Protocol(format!("No response to request {}", request.id))
+                    })
+            }
+            ClientTransport::Stdio { lines, writer, .. } => {
+                write_line(writer, &serde_json::to_string(request)?).await?;
+
+                // Skip notifications and server requests until our response arrives
+                loop {
+                    let line = lines.next_line().await?.ok_or_else(|| {
+                        TransportError::ConnectionLost("Server closed its output".to_string())
+                    })?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    if let Some(response) = response_for(parse_message(&line)?, request) {
+                        return Ok(response);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn send_notification(&mut self, notification: &JsonRpcNotification) -> Result<()> {
+        match self {
+            ClientTransport::Http {
+                client,
+                url,
+                session_id,
+            } => {
+                post(client, url, session_id.as_deref(), notification).await?;
+                Ok(())
+            }
+            ClientTransport::Stdio { writer, .. } => {
+                write_line(writer, &serde_json::to_string(notification)?).await
+            }
+        }
+    }
+}
+
+/// POST a message to a Streamable HTTP endpoint
+async fn post<T: serde::Serialize>(
+    client: &reqwest::Client,
+    url: &str,
+    session_id: Option<&str>,
+    message: &T,
+) -> Result<reqwest::Response> {
+    let mut request = client
+        .post(url)
+        .header("Accept", "application/json, text/event-stream")
+        .json(message);
+    if let Some(session_id) = session_id {
+        request = request.header("Mcp-Session-Id", session_id);
+    }
+
+    Ok(request.send().await?)
+}
+
+async fn write_line(writer: &mut BoxedWriter, line: &str) -> Result<()> {
+    writer.write_all(line.as_bytes()).await?;
+    writer.write_all(b"\n").await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Pick out the response to `request`, ignoring anything else
+fn response_for(message: AnyJsonRpcMessage, request: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    match message {
+        AnyJsonRpcMessage::Response(response) if response.id == request.id => Some(response),
+        AnyJsonRpcMessage::Batch(messages) => messages
+            .into_iter()
+            .filter_map(|message| serde_json::from_value(message).ok())
+            .find_map(|message| response_for(message, request)),
+        _ => None,
+    }
+}
+
+/// Convert a JSON-RPC error from the server into an [`McpError`]
+fn error_from_json_rpc(error: JsonRpcError) -> McpError {
+    let limit = |key: &str| {
+        error
+            .data
+            .as_ref()
+            .and_then(|data| data.get(key))
+            .and_then(serde_json::Value::as_u64)
+            .map(|value| value as usize)
+    };
+    match error.code {
+        -32000 => match (limit("size"), limit("limit")) {
+            (Some(size), Some(limit)) => McpError::ResponseTooLarge { size, limit },
+            // Other servers use -32000 as a generic server error
+            _ => McpError::InternalError(error.message),
+        },
+        -32004 => match limit("limit") {
+            Some(limit) => McpError::PayloadTooLarge { limit },
+            None => McpError::InternalError(error.message),
+        },
+        -32700 => McpError::ParseError(error.message),
+        -32600 => McpError::InvalidRequest(error.message),
+        -32601 => McpError::MethodNotFound(error.message),
+        -32602 => McpError::InvalidParams(error.message),
+        -32002 => McpError::ResourceNotFound(error.message),
+        -32001 => McpError::Maintenance(error.message),
+        -32003 => McpError::SessionNotFound(error.message),
+        -32005 => McpError::Unauthorized(error.message),
+        -32006 => McpError::ToolNotImplemented(error.message),
+        -32007 => McpError::Forbidden(error.message),
+        _ => McpError::InternalError(error.message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::client::features::SamplingManager;
+    use crate::protocol::handler::ProtocolHandler;
+    use crate::protocol::{Content, InitializeRequestBuilder, Prompt};
+    use crate::server::features::resources::ResourceProvider;
+    use crate::server::features::tools::EchoToolHandler;
+    use crate::server::features::{PromptManager, ResourceManager, ToolManager};
+    use crate::transport::http::HttpTransport;
+    use crate::transport::Transport;
+
+    /// Provider serving a fixed greeting for `memo://` URIs
+    struct MemoProvider;
+
+    #[async_trait::async_trait]
+    impl ResourceProvider for MemoProvider {
+        fn name(&self) -> &str {
+            "memo"
+        }
+
+        fn can_handle(&self, uri: &str) -> bool {
+            uri.starts_with("memo://")
+        }
+
+        async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
+            Ok(vec![ResourceContents::text(uri, Some("text/plain".to_string()), "hello")])
+        }
+    }
+
+    async fn test_handler() -> Arc<ProtocolHandler> {
+        let resource_manager = Arc::new(ResourceManager::new());
+        resource_manager
+            .register_provider(Box::new(MemoProvider))
+            .await
+            .unwrap();
+        let tool_manager = Arc::new(ToolManager::new());
+        tool_manager
+            .register_handler_with_tool(Box::new(EchoToolHandler))
+            .await
+            .unwrap();
+        let prompt_manager = Arc::new(PromptManager::new());
+        prompt_manager
+            .register_prompt(Prompt {
+                name: "greeting".to_string(),
+                description: Some("Say hello".to_string()),
+                arguments: None,
+            })
+            .await
+            .unwrap();
+
+        Arc::new(ProtocolHandler::new(
+            resource_manager,
+            tool_manager,
+            prompt_manager,
+            Arc::new(SamplingManager::new()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_client_drives_http_server() {
+        let transport = HttpTransport::ephemeral(test_handler().await).unwrap();
+        let _channels = transport.start().await.unwrap();
+        let addr = transport.local_addr().await.unwrap();
+
+        let client = McpClient::connect_http(format!("http://{}/mcp", addr));
+        let result = client
+            .initialize(InitializeRequestBuilder::new("test-client", "1.0").build())
+            .await
+            .unwrap();
+        assert!(result.capabilities.tools.is_some());
+        assert!(client.server_info().is_some());
+        client.ping().await.unwrap();
+
+        let tools = client.list_tools().await.unwrap();
+        assert!(tools.iter().any(|tool| tool.name == "echo"));
+
+        let result = client
+            .call_tool("echo", Some(serde_json::json!({ "message": "hi" })))
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(matches!(
+            result.content.as_slice(),
+            [Content::Text { text, .. }] if text == "Echo: hi"
+        ));
+
+        let contents = client.read_resource("memo://note").await.unwrap();
+        assert!(matches!(
+            contents.as_slice(),
+            [ResourceContents::Text { text, .. }] if text == "hello"
+        ));
+
+        let prompt = client.get_prompt("greeting", None).await.unwrap();
+        assert_eq!(prompt.description.as_deref(), Some("Say hello"));
+
+        // Server errors come back as typed errors
+        assert!(matches!(
+            client.request("tools/call", None).await,
+            Err(McpError::InvalidParams(_))
+        ));
+
+        transport.stop().await.unwrap();
+    }
+
+    #[test]
+    fn test_server_errors_map_back_to_their_variants() {
+        let round_trip = |error: McpError| error_from_json_rpc(error.into());
+
+        assert!(matches!(
+            round_trip(McpError::forbidden("tools/call")),
+            McpError::Forbidden(_)
+        ));
+        assert!(matches!(
+            round_trip(McpError::response_too_large(2048, 1024)),
+            McpError::ResponseTooLarge { size: 2048, limit: 1024 }
+        ));
+        assert!(matches!(
+            round_trip(McpError::payload_too_large(1024)),
+            McpError::PayloadTooLarge { limit: 1024 }
+        ));
+
+        // A -32000 from another server without sizes stays generic
+        let generic = JsonRpcError {
+            code: -32000,
+            message: "Server error".to_string(),
+            data: None,
+        };
+        assert!(matches!(error_from_json_rpc(generic), McpError::InternalError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_client_over_stdio_skips_notifications() {
+        let handler = test_handler().await;
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        let (client_reader, client_writer) = tokio::io::split(client_io);
+        let (server_reader, mut server_writer) = tokio::io::split(server_io);
+
+        // Minimal STDIO server loop that sends a notification before every response
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(server_reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(Some(response)) = handler.handle_message(parse_message(&line).unwrap()).await
+                else {
+                    continue;
+                };
+                let notification = JsonRpcNotification::new("notifications/message".to_string(), None);
+                for message in [
+                    serde_json::to_string(&notification).unwrap(),
+                    serde_json::to_string(&response).unwrap(),
+                ] {
+                    server_writer.write_all(message.as_bytes()).await.unwrap();
+                    server_writer.write_all(b"\n").await.unwrap();
+                }
+            }
+        });
+
+        let client = McpClient::connect_stdio(client_reader, client_writer);
+        client
+            .initialize(InitializeRequestBuilder::new("test-client", "1.0").build())
+            .await
+            .unwrap();
+
+        let result = client
+            .call_tool("echo", Some(serde_json::json!({ "message": "over stdio" })))
+            .await
+            .unwrap();
+        assert!(matches!(
+            result.content.as_slice(),
+            [Content::Text { text, .. }] if text == "Echo: over stdio"
+        ));
+    }
+}
@@ -3,13 +3,56 @@
 //! This module implements the roots feature, allowing clients to provide
 //! root directories that servers can operate on.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 use crate::error::{McpError, Result};
+use crate::server::features::{ListChangedNotifier, NotificationSink};
+use crate::utils::{file_uri_to_path, path_to_file_uri};
+
+/// Configuration for the roots feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RootsConfig {
+    /// Answer `roots/list` with an empty list instead of an error when the feature is disabled
+    #[serde(default)]
+    pub list_empty_when_disabled: bool,
+
+    /// How many directory levels below a root its metadata scan descends (0 = top level only)
+    #[serde(default = "default_scan_max_depth")]
+    pub scan_max_depth: usize,
+
+    /// Most files counted by a root's metadata scan, bounding its cost
+    #[serde(default = "default_scan_max_files")]
+    pub scan_max_files: usize,
+
+    /// Follow symbolic links while scanning; links are skipped when false
+    #[serde(default)]
+    pub follow_symlinks: bool,
+}
+
+fn default_scan_max_depth() -> usize {
+    8
+}
+
+fn default_scan_max_files() -> usize {
+    1000
+}
+
+impl Default for RootsConfig {
+    fn default() -> Self {
+        Self {
+            list_empty_when_disabled: false,
+            scan_max_depth: default_scan_max_depth(),
+            scan_max_files: default_scan_max_files(),
+            follow_symlinks: false,
+        }
+    }
+}
 
 /// Roots manager for handling root directories
 pub struct RootsManager {
@@ -18,6 +61,12 @@ pub struct RootsManager {
 
     /// Whether the feature is enabled
     enabled: Arc<RwLock<bool>>,
+
+    /// Roots configuration
+    config: RootsConfig,
+
+    /// Emits list_changed notifications
+    list_changed: ListChangedNotifier,
 }
 
 /// Root directory information
@@ -71,12 +120,37 @@ pub enum RootType {
 impl RootsManager {
     /// Create a new roots manager
     pub fn new() -> Self {
+        Self::with_config(RootsConfig::default())
+    }
+
+    /// Create a new roots manager with the given configuration
+    pub fn with_config(config: RootsConfig) -> Self {
         Self {
             roots: Arc::new(RwLock::new(HashMap::new())),
             enabled: Arc::new(RwLock::new(true)),
+            config,
+            list_changed: ListChangedNotifier::new("notifications/roots/list_changed"),
         }
     }
 
+    /// Set whether the feature starts out enabled
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = Arc::new(RwLock::new(enabled));
+        self
+    }
+
+    /// Publish `notifications/roots/list_changed` to the given sink when roots are added or removed
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.list_changed = self.list_changed.with_sink(sink);
+        self
+    }
+
+    /// Set the window over which root changes are coalesced into one notification
+    pub fn with_list_changed_debounce(mut self, debounce: std::time::Duration) -> Self {
+        self.list_changed = self.list_changed.with_debounce(debounce);
+        self
+    }
+
     /// Add a root directory
     pub async fn add_root(&self, uri: String, name: Option<String>) -> Result<()> {
         if !self.is_enabled().await {
@@ -109,6 +183,7 @@ impl RootsManager {
             let mut roots = self.roots.write().await;
             roots.insert(uri.clone(), root);
         }
+        self.list_changed.notify();
 
         info!("Added root: {}", uri);
         Ok(())
@@ -120,6 +195,7 @@ impl RootsManager {
         let root = roots.remove(uri);
 
         if root.is_some() {
+            self.list_changed.notify();
             info!("Removed root: {}", uri);
         }
 
@@ -133,8 +209,14 @@ impl RootsManager {
     }
 
     /// List all roots
+    ///
+    /// When the feature is disabled this fails, or returns an empty list if
+    /// `list_empty_when_disabled` is set.
     pub async fn list_roots(&self) -> Result<Vec<Root>> {
         if !self.is_enabled().await {
+            if self.config.list_empty_when_disabled {
+                return Ok(Vec::new());
+            }
             return Err(McpError::Resource("Roots feature is disabled".to_string()));
         }
 
@@ -215,15 +297,7 @@ impl RootsManager {
 
     /// Convert URI to file path
     fn uri_to_path(&self, uri: &str) -> Result<PathBuf> {
-        let url = url::Url::parse(uri)
-            .map_err(|e| McpError::invalid_params(format!("Invalid URI: {}", e)))?;
-
-        if url.scheme() != "file" {
-            return Err(McpError::invalid_params("Only file:// URIs are supported"));
-        }
-
-        url.to_file_path()
-            .map_err(|_| McpError::invalid_params("Invalid file path in URI"))
+        file_uri_to_path(uri).map_err(|e| McpError::invalid_params(e.to_string()))
     }
 
     /// Convert file path to URI
@@ -232,10 +306,7 @@ impl RootsManager {
             .canonicalize()
             .map_err(|e| McpError::invalid_params(format!("Cannot canonicalize path: {}", e)))?;
 
-        let url = url::Url::from_file_path(&canonical_path)
-            .map_err(|_| McpError::invalid_params("Cannot convert path to URI"))?;
-
-        Ok(url.to_string())
+        path_to_file_uri(&canonical_path).map_err(|e| McpError::invalid_params(e.to_string()))
     }
 
     /// Generate metadata for a root path
@@ -258,7 +329,7 @@ impl RootsManager {
         });
 
         // Count files and calculate size (limited scan for performance)
-        let (file_count, total_size) = self.scan_directory(path, 1000).await;
+        let (file_count, total_size) = self.scan_directory(path).await;
 
         Ok(RootMetadata {
             root_type,
@@ -291,24 +362,57 @@ impl RootsManager {
     }
 
     /// Scan directory for file count and size (with limits for performance)
-    async fn scan_directory(
-        &self,
-        path: &PathBuf,
-        max_files: usize,
-    ) -> (Option<usize>, Option<u64>) {
+    ///
+    /// Descends up to `scan_max_depth` levels and stops after `scan_max_files`
+    /// files. Symlinks are followed only if `follow_symlinks` is set, in which
+    /// case each directory is visited at most once to avoid cycles.
+    async fn scan_directory(&self, path: &PathBuf) -> (Option<usize>, Option<u64>) {
+        let max_files = self.config.scan_max_files;
         let mut file_count = 0;
         let mut total_size = 0;
 
-        if let Ok(mut entries) = tokio::fs::read_dir(path).await {
+        let mut visited = HashSet::new();
+        if let Ok(canonical) = tokio::fs::canonicalize(path).await {
+            visited.insert(canonical);
+        }
+        let mut pending = vec![(path.clone(), 0)];
+
+        'scan: while let Some((dir, depth)) = pending.pop() {
+            let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+                continue;
+            };
+
             while let Ok(Some(entry)) = entries.next_entry().await {
                 if file_count >= max_files {
-                    break;
+                    break 'scan;
                 }
 
-                if let Ok(metadata) = entry.metadata().await {
-                    if metadata.is_file() {
-                        file_count += 1;
-                        total_size += metadata.len();
+                let Ok(file_type) = entry.file_type().await else {
+                    continue;
+                };
+                let metadata = if file_type.is_symlink() {
+                    if !self.config.follow_symlinks {
+                        continue;
+                    }
+                    tokio::fs::metadata(entry.path()).await
+                } else {
+                    entry.metadata().await
+                };
+                let Ok(metadata) = metadata else {
+                    continue;
+                };
+
+                if metadata.is_file() {
+                    file_count += 1;
+                    total_size += metadata.len();
+                } else if metadata.is_dir() && depth < self.config.scan_max_depth {
+                    let entry_path = entry.path();
+                    let first_visit = match tokio::fs::canonicalize(&entry_path).await {
+                        Ok(canonical) => visited.insert(canonical),
+                        Err(_) => false,
+                    };
+                    if first_visit {
+                        pending.push((entry_path, depth + 1));
                     }
                 }
             }
@@ -404,6 +508,86 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[tokio::test]
+    async fn test_scan_counts_nested_files_up_to_max_depth() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("b").join("c");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(temp_dir.path().join("top.txt"), "12345").unwrap();
+        std::fs::write(temp_dir.path().join("a").join("one.txt"), "1").unwrap();
+        std::fs::write(temp_dir.path().join("a").join("b").join("two.txt"), "22").unwrap();
+        std::fs::write(nested.join("three.txt"), "333").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(temp_dir.path().join("top.txt"), temp_dir.path().join("link.txt"))
+            .unwrap();
+
+        let scan = |config: RootsConfig| {
+            let path = temp_dir.path().to_path_buf();
+            async move { RootsManager::with_config(config).scan_directory(&path).await }
+        };
+
+        // Two levels down reaches a/ and a/b/ but not a/b/c/
+        let (count, size) = scan(RootsConfig {
+            scan_max_depth: 2,
+            ..RootsConfig::default()
+        })
+        .await;
+        assert_eq!(count, Some(3));
+        assert_eq!(size, Some(8));
+
+        assert_eq!(scan(RootsConfig::default()).await, (Some(4), Some(11)));
+        assert_eq!(
+            scan(RootsConfig {
+                scan_max_depth: 0,
+                ..RootsConfig::default()
+            })
+            .await
+            .0,
+            Some(1)
+        );
+        assert_eq!(
+            scan(RootsConfig {
+                scan_max_files: 2,
+                ..RootsConfig::default()
+            })
+            .await
+            .0,
+            Some(2)
+        );
+
+        #[cfg(unix)]
+        assert_eq!(
+            scan(RootsConfig {
+                follow_symlinks: true,
+                ..RootsConfig::default()
+            })
+            .await,
+            (Some(5), Some(16))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adding_root_emits_list_changed() {
+        let (sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let manager = RootsManager::new()
+            .with_notification_sink(sink)
+            .with_list_changed_debounce(std::time::Duration::from_millis(10));
+        let temp_dir = TempDir::new().unwrap();
+        let uri = format!("file://{}", temp_dir.path().display());
+
+        manager.add_root(uri.clone(), None).await.unwrap();
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.method, "notifications/roots/list_changed");
+
+        manager.remove_root(&uri).await.unwrap();
+        assert!(notifications.recv().await.is_some());
+
+        // Removing an unknown root changes nothing
+        manager.remove_root(&uri).await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(30)).await;
+        assert!(notifications.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn test_root_type_detection() {
         let manager = RootsManager::new();
@@ -1,9 +1,12 @@
 //! MCP Client features implementation.
 //!
 //! This module contains client-side features that the MCP server can use,
-//! such as sampling and root directory management.
+//! such as sampling and root directory management, and [`McpClient`] for
+//! driving a server from tests and tooling.
 
+pub mod connection;
 pub mod features;
 
 // Re-export main types
+pub use connection::McpClient;
 pub use features::*;
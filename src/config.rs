@@ -4,39 +4,54 @@
 //! authentication, logging, and feature enablement.
 
 use crate::error::{McpError, Result};
+use crate::utils::ids::IdFormat;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// Main configuration structure for the MCP server
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct Config {
     /// Server information
+    #[serde(default)]
     pub server: ServerConfig,
 
     /// Transport configuration
+    #[serde(default)]
     pub transport: TransportConfig,
 
     /// Authentication configuration
+    #[serde(default)]
     pub auth: AuthConfig,
 
     /// Logging configuration
+    #[serde(default)]
     pub logging: LoggingConfig,
 
     /// Feature configuration
+    #[serde(default)]
     pub features: FeatureConfig,
 
     /// Tools configuration
     #[serde(default)]
     pub tools: crate::server::features::tools::ToolsConfig,
 
+    /// Resources configuration
+    #[serde(default)]
+    pub resources: crate::server::features::resources::ResourcesConfig,
+
+    /// Roots configuration
+    #[serde(default)]
+    pub roots: crate::client::features::roots::RootsConfig,
+
     /// Custom server-specific settings
     #[serde(default)]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
 /// Server-specific configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ServerConfig {
     /// Server name
     pub name: String,
@@ -54,10 +69,66 @@ pub struct ServerConfig {
     /// Request timeout in seconds
     #[serde(default = "default_request_timeout")]
     pub request_timeout: u64,
+
+    /// Maximum number of items returned per page by list requests
+    #[serde(default = "default_page_size")]
+    pub page_size: usize,
+
+    /// Seconds connected clients are given to finish up after a shutdown notice
+    #[serde(default = "default_shutdown_grace_period")]
+    pub shutdown_grace_period: u64,
+
+    /// Maximum number of messages accepted in one JSON-RPC batch
+    #[serde(default = "default_max_batch_size")]
+    pub max_batch_size: usize,
+
+    /// Number of batch messages handled concurrently
+    #[serde(default = "default_batch_concurrency")]
+    pub batch_concurrency: usize,
+
+    /// Start in maintenance mode, rejecting everything but `ping` and `admin/` methods
+    #[serde(default)]
+    pub maintenance_mode: bool,
+
+    /// Maximum serialized size in bytes of a `tools/call` or `resources/read` result
+    #[serde(default)]
+    pub max_response_size: Option<usize>,
+
+    /// What to do with results larger than `max_response_size`
+    #[serde(default)]
+    pub response_size_policy: ResponseSizePolicy,
+
+    /// Format of generated session and request IDs
+    #[serde(default)]
+    pub id_format: IdFormat,
+
+    /// Prefix prepended to generated session and request IDs (e.g. `sess_`)
+    #[serde(default)]
+    pub id_prefix: String,
+
+    /// Run the built-in feature self-test before starting, refusing to start if it fails
+    #[serde(default)]
+    pub self_test_on_startup: bool,
+
+    /// Treat unknown notification methods as protocol violations, reported to the client
+    #[serde(default)]
+    pub strict_notifications: bool,
+}
+
+/// Handling of results larger than the configured maximum response size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseSizePolicy {
+    /// Fail the request with a `-32000` error carrying the sizes
+    #[default]
+    Error,
+
+    /// Drop or shorten trailing content and mark the result as truncated
+    Truncate,
 }
 
 /// Transport layer configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct TransportConfig {
     /// Transport type (http or stdio)
     #[serde(default = "default_transport_type")]
@@ -70,16 +141,10 @@ pub struct TransportConfig {
     pub stdio: Option<StdioConfig>,
 }
 
-/// Transport type enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum TransportType {
-    Http,
-    Stdio,
-}
+pub use crate::transport::TransportType;
 
 /// HTTP transport configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct HttpConfig {
     /// Bind address
     #[serde(default = "default_bind_address")]
@@ -90,6 +155,9 @@ pub struct HttpConfig {
     pub port: u16,
 
     /// MCP endpoint path
+    ///
+    /// A missing leading slash is added and trailing slashes are dropped,
+    /// see [`normalize_endpoint_path`].
     #[serde(default = "default_endpoint_path")]
     pub endpoint_path: String,
 
@@ -114,10 +182,87 @@ pub struct HttpConfig {
 
     /// SSL private key file path
     pub key_file: Option<PathBuf>,
+
+    /// Paths answered with `204 No Content` to quiet browser and proxy probes
+    #[serde(default = "default_probe_paths")]
+    pub probe_paths: Vec<String>,
+
+    /// Echo `X-Request-Id`/`traceparent` into response headers and result `_meta`
+    #[serde(default = "default_true")]
+    pub echo_request_id: bool,
+
+    /// Maximum number of concurrent sessions; new sessions beyond this get `503`
+    #[serde(default = "default_max_sessions")]
+    pub max_sessions: usize,
+
+    /// Handle POSTs without `Mcp-Session-Id` statelessly: no session is
+    /// created, no session header is returned and responses are never SSE
+    #[serde(default)]
+    pub stateless: bool,
+
+    /// Maximum lifetime of a GET SSE stream in seconds (unlimited when unset)
+    ///
+    /// When reached, the stream ends with a `retry:` hint so clients reconnect.
+    #[serde(default)]
+    pub sse_max_lifetime: Option<u64>,
+
+    /// Reconnect delay in milliseconds sent in the `retry:` hint
+    #[serde(default = "default_sse_retry_ms")]
+    pub sse_retry_ms: u64,
+
+    /// Interval in milliseconds between `: keep-alive` comments on idle SSE streams (0 disables)
+    #[serde(default = "default_sse_heartbeat_ms")]
+    pub sse_heartbeat_ms: u64,
+
+    /// Most notifications buffered per session for `Last-Event-ID` resumption
+    #[serde(default = "default_sse_replay_max_events")]
+    pub sse_replay_max_events: usize,
+
+    /// Most notification bytes buffered per session for resumption
+    #[serde(default = "default_sse_replay_max_bytes")]
+    pub sse_replay_max_bytes: usize,
+
+    /// Interval in milliseconds between `ping` requests sent on open SSE streams (0 disables)
+    #[serde(default)]
+    pub ping_interval_ms: u64,
+
+    /// How long in milliseconds a client may take to answer a ping before its session is reaped
+    #[serde(default = "default_ping_timeout_ms")]
+    pub ping_timeout_ms: u64,
+
+    /// Chunk size in bytes for streaming text `resources/read` results over SSE (0 disables)
+    ///
+    /// When enabled, a client accepting `text/event-stream` that sets
+    /// `"_meta": {"stream": true}` on the read receives the text as
+    /// `notifications/resources/chunk` events instead of one response.
+    #[serde(default)]
+    pub resource_stream_chunk_size: usize,
+
+    /// Largest accepted POST body in bytes; bigger requests get a 413
+    #[serde(default = "default_max_body_size")]
+    pub max_body_size: usize,
+
+    /// Maximum nesting depth of objects and arrays in a request body
+    #[serde(default = "default_max_json_depth")]
+    pub max_json_depth: usize,
+
+    /// Reject POST bodies not sent as `Content-Type: application/json` with a 415
+    #[serde(default = "default_true")]
+    pub require_json_content_type: bool,
+
+    /// Compress JSON responses when the client sends `Accept-Encoding`
+    ///
+    /// SSE streams are never compressed.
+    #[serde(default = "default_true")]
+    pub compression: bool,
+
+    /// Smallest response body, in bytes, that gets compressed
+    #[serde(default = "default_compression_min_size")]
+    pub compression_min_size: usize,
 }
 
 /// STDIO transport configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct StdioConfig {
     /// Buffer size for stdin/stdout
     #[serde(default = "default_buffer_size")]
@@ -126,10 +271,61 @@ pub struct StdioConfig {
     /// Enable stderr logging
     #[serde(default = "default_enable_stderr_logging")]
     pub enable_stderr_logging: bool,
+
+    /// Consecutive stdin read errors to retry before treating them as fatal
+    ///
+    /// 0 makes the first read error fatal. EOF is never retried: it means the
+    /// client has gone away and the server shuts down.
+    #[serde(default = "default_max_read_retries")]
+    pub max_read_retries: u32,
+
+    /// When stdout is flushed
+    #[serde(default)]
+    pub flush: FlushPolicy,
+
+    /// How incoming messages are delimited on stdin
+    #[serde(default)]
+    pub framing: InputFraming,
+
+    /// Maximum nesting depth of objects and arrays in an incoming message
+    #[serde(default = "default_max_json_depth")]
+    pub max_json_depth: usize,
+
+    /// Largest incoming message in bytes; bigger ones are discarded
+    #[serde(default = "default_max_message_size")]
+    pub max_message_size: usize,
+}
+
+/// Stdout flushing strategy for the STDIO transport
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlushPolicy {
+    /// Flush after every message
+    #[default]
+    Always,
+
+    /// Flush once no more messages are queued
+    OnIdle,
+
+    /// Flush pending output every given number of milliseconds
+    Interval(u64),
+}
+
+/// Message framing on the STDIO transport's input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InputFraming {
+    /// One complete JSON message per line
+    #[default]
+    Lines,
+
+    /// Buffer lines until they form a complete JSON value, so messages may
+    /// span several lines (e.g. pretty-printed)
+    Accumulate,
 }
 
 /// Authentication and authorization configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct AuthConfig {
     /// Enable authentication
     #[serde(default)]
@@ -149,10 +345,23 @@ pub struct AuthConfig {
     /// Token expiration time in seconds
     #[serde(default = "default_token_expiration")]
     pub token_expiration: u64,
+
+    /// Scopes granted to each API key
+    #[serde(default)]
+    pub key_scopes: HashMap<String, Vec<String>>,
+
+    /// Experimental capabilities advertised only to clients holding a scope
+    #[serde(default)]
+    pub scoped_experimental: crate::protocol::handler::ScopedExperimentalCapabilities,
+
+    /// Method prefixes callers holding a scope may call; when set, callers
+    /// holding none of the listed scopes are denied with a `-32007` error
+    #[serde(default)]
+    pub scope_methods: crate::protocol::handler::ScopeMethodAllowlist,
 }
 
 /// Authentication method enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AuthMethod {
     None,
@@ -168,7 +377,7 @@ impl Default for AuthMethod {
 }
 
 /// Logging configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct LoggingConfig {
     /// Log level
     #[serde(default = "default_log_level")]
@@ -184,10 +393,18 @@ pub struct LoggingConfig {
     /// Enable request/response logging
     #[serde(default = "default_enable_request_logging")]
     pub enable_request_logging: bool,
+
+    /// Record tool calls and resource reads in an audit log
+    #[serde(default)]
+    pub audit: bool,
+
+    /// Audit log file (if None, audit records go to the `mcp_audit` tracing target)
+    #[serde(default)]
+    pub audit_file: Option<PathBuf>,
 }
 
 /// Log format enumeration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Json,
@@ -196,7 +413,7 @@ pub enum LogFormat {
 }
 
 /// Feature configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct FeatureConfig {
     /// Enable resources feature
     #[serde(default = "default_true")]
@@ -234,6 +451,18 @@ fn default_max_connections() -> usize {
 fn default_request_timeout() -> u64 {
     30
 }
+fn default_shutdown_grace_period() -> u64 {
+    5
+}
+fn default_page_size() -> usize {
+    crate::server::features::DEFAULT_PAGE_SIZE
+}
+fn default_max_batch_size() -> usize {
+    crate::protocol::handler::DEFAULT_MAX_BATCH_SIZE
+}
+fn default_batch_concurrency() -> usize {
+    crate::protocol::handler::DEFAULT_BATCH_CONCURRENCY
+}
 fn default_transport_type() -> TransportType {
     TransportType::Http
 }
@@ -252,12 +481,51 @@ fn default_enable_cors() -> bool {
 fn default_session_timeout() -> u64 {
     3600
 }
+fn default_max_sessions() -> usize {
+    10_000
+}
+fn default_probe_paths() -> Vec<String> {
+    vec!["/favicon.ico".to_string(), "/".to_string()]
+}
+/// Largest STDIO read buffer accepted by validation
+const MAX_STDIO_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+
+fn default_sse_retry_ms() -> u64 {
+    3000
+}
+fn default_sse_heartbeat_ms() -> u64 {
+    15000
+}
+fn default_sse_replay_max_events() -> usize {
+    256
+}
+fn default_sse_replay_max_bytes() -> usize {
+    1024 * 1024
+}
+fn default_max_body_size() -> usize {
+    1024 * 1024
+}
+fn default_max_message_size() -> usize {
+    1024 * 1024
+}
+fn default_ping_timeout_ms() -> u64 {
+    10000
+}
+fn default_compression_min_size() -> usize {
+    1024
+}
+fn default_max_json_depth() -> usize {
+    crate::protocol::DEFAULT_MAX_JSON_DEPTH
+}
 fn default_buffer_size() -> usize {
     8192
 }
 fn default_enable_stderr_logging() -> bool {
     true
 }
+fn default_max_read_retries() -> u32 {
+    3
+}
 fn default_token_expiration() -> u64 {
     3600
 }
@@ -274,26 +542,35 @@ fn default_true() -> bool {
     true
 }
 
-impl Default for Config {
+impl Default for ServerConfig {
     fn default() -> Self {
         Self {
-            server: ServerConfig {
-                name: crate::SERVER_NAME.to_string(),
-                version: crate::SERVER_VERSION.to_string(),
-                instructions: None,
-                max_connections: default_max_connections(),
-                request_timeout: default_request_timeout(),
-            },
-            transport: TransportConfig {
-                transport_type: default_transport_type(),
-                http: Some(HttpConfig::default()),
-                stdio: Some(StdioConfig::default()),
-            },
-            auth: AuthConfig::default(),
-            logging: LoggingConfig::default(),
-            features: FeatureConfig::default(),
-            tools: crate::server::features::tools::ToolsConfig::default(),
-            custom: HashMap::new(),
+            name: crate::SERVER_NAME.to_string(),
+            version: crate::SERVER_VERSION.to_string(),
+            instructions: None,
+            max_connections: default_max_connections(),
+            request_timeout: default_request_timeout(),
+            page_size: default_page_size(),
+            shutdown_grace_period: default_shutdown_grace_period(),
+            max_batch_size: default_max_batch_size(),
+            batch_concurrency: default_batch_concurrency(),
+            maintenance_mode: false,
+            max_response_size: None,
+            response_size_policy: ResponseSizePolicy::default(),
+            id_format: IdFormat::default(),
+            id_prefix: String::new(),
+            self_test_on_startup: false,
+            strict_notifications: false,
+        }
+    }
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            transport_type: default_transport_type(),
+            http: Some(HttpConfig::default()),
+            stdio: Some(StdioConfig::default()),
         }
     }
 }
@@ -307,18 +584,68 @@ impl Default for HttpConfig {
             enable_cors: default_enable_cors(),
             cors_origins: vec!["*".to_string()],
             session_timeout: default_session_timeout(),
+            max_sessions: default_max_sessions(),
             enable_tls: false,
             cert_file: None,
             key_file: None,
+            probe_paths: default_probe_paths(),
+            echo_request_id: default_true(),
+            stateless: false,
+            sse_max_lifetime: None,
+            sse_retry_ms: default_sse_retry_ms(),
+            sse_heartbeat_ms: default_sse_heartbeat_ms(),
+            sse_replay_max_events: default_sse_replay_max_events(),
+            sse_replay_max_bytes: default_sse_replay_max_bytes(),
+            ping_interval_ms: 0,
+            ping_timeout_ms: default_ping_timeout_ms(),
+            resource_stream_chunk_size: 0,
+            max_body_size: default_max_body_size(),
+            max_json_depth: default_max_json_depth(),
+            require_json_content_type: default_true(),
+            compression: default_true(),
+            compression_min_size: default_compression_min_size(),
         }
     }
 }
 
+/// Normalize an HTTP endpoint path to `/segment[/segment...]` form
+///
+/// Adds a missing leading slash and strips trailing ones, so `mcp/` becomes
+/// `/mcp`. Empty paths and paths containing whitespace, empty segments, or
+/// query/fragment characters are rejected.
+pub fn normalize_endpoint_path(path: &str) -> Result<String> {
+    let trimmed = path.trim_matches('/');
+    if path.is_empty() {
+        return Err(McpError::Config("HTTP endpoint path must not be empty".to_string()));
+    }
+    if trimmed.is_empty() {
+        return Ok("/".to_string());
+    }
+
+    let invalid = trimmed
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '?' | '#'))
+        || trimmed.split('/').any(str::is_empty);
+    if invalid {
+        return Err(McpError::Config(format!(
+            "Invalid HTTP endpoint path: {:?}",
+            path
+        )));
+    }
+
+    Ok(format!("/{}", trimmed))
+}
+
 impl Default for StdioConfig {
     fn default() -> Self {
         Self {
             buffer_size: default_buffer_size(),
             enable_stderr_logging: default_enable_stderr_logging(),
+            max_read_retries: default_max_read_retries(),
+            flush: FlushPolicy::default(),
+            framing: InputFraming::default(),
+            max_json_depth: default_max_json_depth(),
+            max_message_size: default_max_message_size(),
         }
     }
 }
@@ -331,6 +658,9 @@ impl Default for AuthConfig {
             api_keys: Vec::new(),
             jwt_secret: None,
             token_expiration: default_token_expiration(),
+            key_scopes: HashMap::new(),
+            scoped_experimental: HashMap::new(),
+            scope_methods: HashMap::new(),
         }
     }
 }
@@ -342,6 +672,8 @@ impl Default for LoggingConfig {
             format: default_log_format(),
             file: None,
             enable_request_logging: default_enable_request_logging(),
+            audit: false,
+            audit_file: None,
         }
     }
 }
@@ -360,22 +692,75 @@ impl Default for FeatureConfig {
     }
 }
 
+/// On-disk configuration file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML (`.toml`)
+    Toml,
+    /// JSON (`.json`)
+    Json,
+    /// YAML (`.yaml` or `.yml`)
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file's extension
+    pub fn from_path(path: &std::path::Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("toml") => Ok(ConfigFormat::Toml),
+            Some("json") => Ok(ConfigFormat::Json),
+            Some("yaml") | Some("yml") => Ok(ConfigFormat::Yaml),
+            _ => Err(McpError::Config(format!(
+                "Unsupported config file extension for {} (expected .toml, .json, .yaml or .yml)",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Parse a configuration from a string in this format
+    pub fn parse(self, content: &str) -> Result<Config> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| McpError::Config(format!("Failed to parse config file: {}", e)))
+    }
+
+    /// Serialize a configuration to a string in this format
+    pub fn serialize(self, config: &Config) -> Result<String> {
+        match self {
+            ConfigFormat::Toml => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(config).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| McpError::Config(format!("Failed to serialize config: {}", e)))
+    }
+}
+
 impl Config {
     /// Load configuration from a file
+    ///
+    /// The format is chosen from the extension (`.toml`, `.json`, `.yaml`/`.yml`).
     pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+
         let content = std::fs::read_to_string(path)
             .map_err(|e| McpError::Config(format!("Failed to read config file: {}", e)))?;
 
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| McpError::Config(format!("Failed to parse config file: {}", e)))?;
-
-        Ok(config)
+        format.parse(&content)
     }
 
-    /// Save configuration to a file
+    /// Save configuration to a file in the format matching its extension
     pub fn to_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| McpError::Config(format!("Failed to serialize config: {}", e)))?;
+        let path = path.as_ref();
+        let content = ConfigFormat::from_path(path)?.serialize(self)?;
 
         std::fs::write(path, content)
             .map_err(|e| McpError::Config(format!("Failed to write config file: {}", e)))?;
@@ -383,24 +768,151 @@ impl Config {
         Ok(())
     }
 
+    /// Overlay settings from `MCP_*` environment variables
+    ///
+    /// Applied after the config file is loaded and before CLI arguments.
+    /// Supported variables:
+    ///
+    /// | Variable | Field |
+    /// |---|---|
+    /// | `MCP_SERVER_NAME` | `server.name` |
+    /// | `MCP_SERVER_VERSION` | `server.version` |
+    /// | `MCP_SERVER_INSTRUCTIONS` | `server.instructions` |
+    /// | `MCP_MAX_CONNECTIONS` | `server.max_connections` |
+    /// | `MCP_REQUEST_TIMEOUT` | `server.request_timeout` |
+    /// | `MCP_TRANSPORT` | `transport.transport_type` (`http` or `stdio`) |
+    /// | `MCP_HTTP_BIND_ADDRESS` | `transport.http.bind_address` |
+    /// | `MCP_HTTP_PORT` | `transport.http.port` |
+    /// | `MCP_HTTP_ENDPOINT_PATH` | `transport.http.endpoint_path` |
+    /// | `MCP_HTTP_SESSION_TIMEOUT` | `transport.http.session_timeout` |
+    /// | `MCP_AUTH_ENABLED` | `auth.enabled` |
+    /// | `MCP_AUTH_API_KEYS` | `auth.api_keys` (comma-separated) |
+    /// | `MCP_JWT_SECRET` | `auth.jwt_secret` |
+    /// | `MCP_LOG_LEVEL` | `logging.level` |
+    pub fn apply_env_overrides(&mut self) -> Result<()> {
+        self.apply_overrides_from(|key| std::env::var(key).ok())
+    }
+
+    /// Overlay settings using the given variable lookup
+    fn apply_overrides_from(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        self.merge(ConfigOverlay::from_vars(var)?);
+        Ok(())
+    }
+
+    /// Layer `overlay` over this configuration; only the fields it sets are replaced
+    pub fn merge(&mut self, overlay: ConfigOverlay) {
+        if let Some(name) = overlay.server_name {
+            self.server.name = name;
+        }
+        if let Some(version) = overlay.server_version {
+            self.server.version = version;
+        }
+        if let Some(instructions) = overlay.server_instructions {
+            self.server.instructions = Some(instructions);
+        }
+        if let Some(max_connections) = overlay.max_connections {
+            self.server.max_connections = max_connections;
+        }
+        if let Some(request_timeout) = overlay.request_timeout {
+            self.server.request_timeout = request_timeout;
+        }
+
+        if let Some(transport_type) = overlay.transport_type {
+            self.transport.transport_type = transport_type;
+        }
+
+        let sets_http = overlay.http_bind_address.is_some()
+            || overlay.http_port.is_some()
+            || overlay.http_endpoint_path.is_some()
+            || overlay.http_session_timeout.is_some();
+        if sets_http {
+            let http = self.transport.http.get_or_insert_with(HttpConfig::default);
+            if let Some(bind_address) = overlay.http_bind_address {
+                http.bind_address = bind_address;
+            }
+            if let Some(port) = overlay.http_port {
+                http.port = port;
+            }
+            if let Some(endpoint_path) = overlay.http_endpoint_path {
+                http.endpoint_path = endpoint_path;
+            }
+            if let Some(session_timeout) = overlay.http_session_timeout {
+                http.session_timeout = session_timeout;
+            }
+        }
+
+        if let Some(enabled) = overlay.auth_enabled {
+            self.auth.enabled = enabled;
+        }
+        if let Some(api_keys) = overlay.auth_api_keys {
+            self.auth.api_keys = api_keys;
+        }
+        if let Some(jwt_secret) = overlay.jwt_secret {
+            self.auth.jwt_secret = Some(jwt_secret);
+        }
+
+        if let Some(level) = overlay.log_level {
+            self.logging.level = level;
+        }
+    }
+
+    /// JSON Schema of the configuration file, for editor validation and completion
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(Config)).unwrap_or_default()
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<()> {
+        if self.server.page_size == 0 {
+            return Err(McpError::Config(
+                "Page size must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.server.max_batch_size == 0 {
+            return Err(McpError::Config(
+                "Maximum batch size must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.server.batch_concurrency == 0 {
+            return Err(McpError::Config(
+                "Batch concurrency must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.server.max_response_size == Some(0) {
+            return Err(McpError::Config(
+                "Maximum response size must be greater than zero".to_string(),
+            ));
+        }
+
+        // IDs travel in headers such as Mcp-Session-Id, so the prefix must be header-safe
+        if !self.server.id_prefix.chars().all(|c| c.is_ascii_graphic()) {
+            return Err(McpError::Config(format!(
+                "ID prefix must only contain visible ASCII characters, got {:?}",
+                self.server.id_prefix
+            )));
+        }
+
         // Validate transport configuration
         match self.transport.transport_type {
-            TransportType::Http => {
-                if self.transport.http.is_none() {
+            TransportType::Http => match &self.transport.http {
+                Some(http) => Self::validate_http(http)?,
+                None => {
                     return Err(McpError::Config(
                         "HTTP transport selected but no HTTP config provided".to_string(),
                     ));
                 }
-            }
-            TransportType::Stdio => {
-                if self.transport.stdio.is_none() {
+            },
+            TransportType::Stdio => match &self.transport.stdio {
+                Some(stdio) => Self::validate_stdio(stdio)?,
+                None => {
                     return Err(McpError::Config(
                         "STDIO transport selected but no STDIO config provided".to_string(),
                     ));
                 }
-            }
+            },
         }
 
         // Validate authentication configuration
@@ -426,4 +938,578 @@ impl Config {
 
         Ok(())
     }
+
+    fn validate_http(http: &HttpConfig) -> Result<()> {
+        normalize_endpoint_path(&http.endpoint_path)?;
+
+        if http.ping_interval_ms > 0 && http.ping_timeout_ms == 0 {
+            return Err(McpError::Config(
+                "HTTP ping timeout must be greater than zero when pings are enabled".to_string(),
+            ));
+        }
+
+        if http.max_body_size == 0 {
+            return Err(McpError::Config(
+                "HTTP maximum body size must be greater than zero".to_string(),
+            ));
+        }
+
+        if http.session_timeout == 0 {
+            return Err(McpError::Config(
+                "HTTP session timeout must be greater than zero".to_string(),
+            ));
+        }
+
+        if http.enable_tls {
+            if http.cert_file.is_none() {
+                return Err(McpError::Config(
+                    "TLS enabled but no certificate file (cert_file) provided".to_string(),
+                ));
+            }
+            if http.key_file.is_none() {
+                return Err(McpError::Config(
+                    "TLS enabled but no private key file (key_file) provided".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_stdio(stdio: &StdioConfig) -> Result<()> {
+        if stdio.buffer_size == 0 || stdio.buffer_size > MAX_STDIO_BUFFER_SIZE {
+            return Err(McpError::Config(format!(
+                "STDIO buffer size must be between 1 and {} bytes, got {}",
+                MAX_STDIO_BUFFER_SIZE, stdio.buffer_size
+            )));
+        }
+
+        if stdio.flush == FlushPolicy::Interval(0) {
+            return Err(McpError::Config(
+                "STDIO flush interval must be greater than 0".to_string(),
+            ));
+        }
+
+        if stdio.max_message_size == 0 {
+            return Err(McpError::Config(
+                "STDIO maximum message size must be greater than zero".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Settings layered over a [`Config`] with [`Config::merge`]
+///
+/// Unset fields leave the base untouched, so layers stack as
+/// defaults < file < environment < command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigOverlay {
+    /// `server.name`
+    pub server_name: Option<String>,
+
+    /// `server.version`
+    pub server_version: Option<String>,
+
+    /// `server.instructions`
+    pub server_instructions: Option<String>,
+
+    /// `server.max_connections`
+    pub max_connections: Option<usize>,
+
+    /// `server.request_timeout`
+    pub request_timeout: Option<u64>,
+
+    /// `transport.transport_type`
+    pub transport_type: Option<TransportType>,
+
+    /// `transport.http.bind_address`
+    pub http_bind_address: Option<String>,
+
+    /// `transport.http.port`
+    pub http_port: Option<u16>,
+
+    /// `transport.http.endpoint_path`
+    pub http_endpoint_path: Option<String>,
+
+    /// `transport.http.session_timeout`
+    pub http_session_timeout: Option<u64>,
+
+    /// `auth.enabled`
+    pub auth_enabled: Option<bool>,
+
+    /// `auth.api_keys`
+    pub auth_api_keys: Option<Vec<String>>,
+
+    /// `auth.jwt_secret`
+    pub jwt_secret: Option<String>,
+
+    /// `logging.level`
+    pub log_level: Option<String>,
+}
+
+impl ConfigOverlay {
+    /// Read the overlay from `MCP_*` environment variables
+    ///
+    /// See [`Config::apply_env_overrides`] for the supported variables.
+    pub fn from_env() -> Result<Self> {
+        Self::from_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Read the overlay using the given variable lookup
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        fn parse<T: std::str::FromStr>(key: &str, value: Option<String>) -> Result<Option<T>>
+        where
+            T::Err: std::fmt::Display,
+        {
+            value
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|e| McpError::Config(format!("Invalid value for {}: {}", key, e)))
+                })
+                .transpose()
+        }
+
+        Ok(Self {
+            server_name: var("MCP_SERVER_NAME"),
+            server_version: var("MCP_SERVER_VERSION"),
+            server_instructions: var("MCP_SERVER_INSTRUCTIONS"),
+            max_connections: parse("MCP_MAX_CONNECTIONS", var("MCP_MAX_CONNECTIONS"))?,
+            request_timeout: parse("MCP_REQUEST_TIMEOUT", var("MCP_REQUEST_TIMEOUT"))?,
+            transport_type: parse("MCP_TRANSPORT", var("MCP_TRANSPORT"))?,
+            http_bind_address: var("MCP_HTTP_BIND_ADDRESS"),
+            http_port: parse("MCP_HTTP_PORT", var("MCP_HTTP_PORT"))?,
+            http_endpoint_path: var("MCP_HTTP_ENDPOINT_PATH"),
+            http_session_timeout: parse("MCP_HTTP_SESSION_TIMEOUT", var("MCP_HTTP_SESSION_TIMEOUT"))?,
+            auth_enabled: parse("MCP_AUTH_ENABLED", var("MCP_AUTH_ENABLED"))?,
+            auth_api_keys: var("MCP_AUTH_API_KEYS").map(|value| {
+                value
+                    .split(',')
+                    .map(|key| key.trim().to_string())
+                    .filter(|key| !key.is_empty())
+                    .collect()
+            }),
+            jwt_secret: var("MCP_JWT_SECRET"),
+            log_level: var("MCP_LOG_LEVEL"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_overrides() {
+        let vars: HashMap<&str, &str> = [
+            ("MCP_SERVER_NAME", "env-server"),
+            ("MCP_HTTP_PORT", "9191"),
+            ("MCP_TRANSPORT", "stdio"),
+            ("MCP_AUTH_API_KEYS", "key-1, key-2"),
+            ("MCP_LOG_LEVEL", "debug"),
+        ]
+        .into_iter()
+        .collect();
+
+        let mut config = Config::default();
+        config
+            .apply_overrides_from(|key| vars.get(key).map(|v| v.to_string()))
+            .unwrap();
+
+        assert_eq!(config.server.name, "env-server");
+        assert_eq!(config.transport.http.as_ref().unwrap().port, 9191);
+        assert!(matches!(config.transport.transport_type, TransportType::Stdio));
+        assert_eq!(config.auth.api_keys, vec!["key-1", "key-2"]);
+        assert_eq!(config.logging.level, "debug");
+
+        // Untouched fields keep their values
+        assert_eq!(config.server.version, crate::SERVER_VERSION);
+    }
+
+    #[test]
+    fn test_env_overrides_from_process_environment() {
+        std::env::set_var("MCP_HTTP_SESSION_TIMEOUT", "120");
+        let mut config = Config::default();
+        let result = config.apply_env_overrides();
+        std::env::remove_var("MCP_HTTP_SESSION_TIMEOUT");
+
+        result.unwrap();
+        assert_eq!(config.transport.http.unwrap().session_timeout, 120);
+    }
+
+    #[test]
+    fn test_env_overrides_invalid_value() {
+        let mut config = Config::default();
+        let result = config.apply_overrides_from(|key| {
+            (key == "MCP_HTTP_PORT").then(|| "not-a-port".to_string())
+        });
+        assert!(matches!(result, Err(McpError::Config(msg)) if msg.contains("MCP_HTTP_PORT")));
+    }
+
+    #[test]
+    fn test_json_schema_describes_sections() {
+        let schema = Config::json_schema();
+        let properties = &schema["properties"];
+        assert!(properties.get("server").is_some());
+        assert!(properties.get("transport").is_some());
+
+        let definitions = &schema["definitions"];
+        assert!(definitions["ServerConfig"]["properties"].get("name").is_some());
+        assert_eq!(
+            definitions["TransportType"]["enum"],
+            serde_json::json!(["http", "stdio"])
+        );
+    }
+
+    #[test]
+    fn test_merge_layers_file_env_and_cli() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[server]\nname = \"file-server\"\nversion = \"1.0.0\"\n\n[transport.http]\nport = 8181\n",
+        )
+        .unwrap();
+
+        let mut config = Config::from_file(&path).unwrap();
+        assert_eq!(config.server.name, "file-server");
+        assert_eq!(config.transport.http.as_ref().unwrap().port, 8181);
+
+        // The environment sets only the port, keeping the file's name
+        let env: HashMap<&str, &str> = [("MCP_HTTP_PORT", "8282")].into_iter().collect();
+        config.merge(ConfigOverlay::from_vars(|key| env.get(key).map(|v| v.to_string())).unwrap());
+        assert_eq!(config.server.name, "file-server");
+        assert_eq!(config.transport.http.as_ref().unwrap().port, 8282);
+
+        // The command line sets only the name, keeping the environment's port
+        config.merge(ConfigOverlay {
+            server_name: Some("cli-server".to_string()),
+            ..ConfigOverlay::default()
+        });
+        assert_eq!(config.server.name, "cli-server");
+        assert_eq!(config.transport.http.as_ref().unwrap().port, 8282);
+
+        // An empty overlay changes nothing
+        let before = config.clone();
+        config.merge(ConfigOverlay::default());
+        assert_eq!(config, before);
+    }
+
+    fn assert_round_trip(extension: &str) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(format!("config.{}", extension));
+
+        let mut config = Config::default();
+        config.server.name = "round-trip".to_string();
+        config.transport.http = Some(HttpConfig {
+            port: 9090,
+            ..HttpConfig::default()
+        });
+        config.auth.api_keys = vec!["key".to_string()];
+
+        config.to_file(&path).unwrap();
+        let loaded = Config::from_file(&path).unwrap();
+
+        assert_eq!(loaded, config);
+    }
+
+    #[test]
+    fn test_round_trip_toml() {
+        assert_round_trip("toml");
+    }
+
+    #[test]
+    fn test_round_trip_json() {
+        assert_round_trip("json");
+    }
+
+    #[test]
+    fn test_round_trip_yaml() {
+        assert_round_trip("yaml");
+        assert_round_trip("yml");
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        let err = Config::default().to_file("config.ini").unwrap_err();
+        assert!(err.to_string().contains("Unsupported config file extension"));
+        assert!(Config::from_file("config").is_err());
+    }
+
+    /// A config with every optional section and non-default value filled in
+    fn fully_populated_config() -> Config {
+        let mut custom = HashMap::new();
+        custom.insert("region".to_string(), serde_json::json!("eu-west-1"));
+        custom.insert("limits".to_string(), serde_json::json!({"burst": 10, "ratio": 0.5}));
+
+        let mut handler_config = HashMap::new();
+        handler_config.insert("base_url".to_string(), serde_json::json!("https://example.com"));
+        handler_config.insert("retries".to_string(), serde_json::json!(3));
+
+        Config {
+            server: ServerConfig {
+                name: "populated".to_string(),
+                version: "9.9.9".to_string(),
+                instructions: Some("Use with care".to_string()),
+                max_connections: 7,
+                request_timeout: 12,
+                page_size: 25,
+                shutdown_grace_period: 2,
+                max_batch_size: 8,
+                batch_concurrency: 3,
+                maintenance_mode: true,
+                max_response_size: Some(1 << 20),
+                response_size_policy: ResponseSizePolicy::Truncate,
+                id_format: IdFormat::Base62,
+                id_prefix: "sess_".to_string(),
+                self_test_on_startup: true,
+                strict_notifications: true,
+            },
+            transport: TransportConfig {
+                transport_type: TransportType::Stdio,
+                http: Some(HttpConfig {
+                    bind_address: "0.0.0.0".to_string(),
+                    port: 8443,
+                    endpoint_path: "/rpc".to_string(),
+                    enable_cors: false,
+                    cors_origins: vec!["https://a.example".to_string(), "https://b.example".to_string()],
+                    session_timeout: 60,
+                    max_sessions: 50,
+                    enable_tls: true,
+                    cert_file: Some(PathBuf::from("/etc/mcp/cert.pem")),
+                    key_file: Some(PathBuf::from("/etc/mcp/key.pem")),
+                    probe_paths: vec!["/healthz".to_string()],
+                    echo_request_id: false,
+                    stateless: true,
+                    sse_max_lifetime: Some(300),
+                    sse_retry_ms: 2500,
+                    sse_heartbeat_ms: 10000,
+                    sse_replay_max_events: 64,
+                    sse_replay_max_bytes: 65536,
+                    ping_interval_ms: 30000,
+                    ping_timeout_ms: 5000,
+                    resource_stream_chunk_size: 16384,
+                    max_body_size: 65536,
+                    max_json_depth: 32,
+                    require_json_content_type: false,
+                    compression: false,
+                    compression_min_size: 4096,
+                }),
+                stdio: Some(StdioConfig {
+                    buffer_size: 1024,
+                    enable_stderr_logging: false,
+                    max_read_retries: 0,
+                    flush: FlushPolicy::Interval(20),
+                    framing: InputFraming::Accumulate,
+                    max_json_depth: 16,
+                    max_message_size: 4096,
+                }),
+            },
+            auth: AuthConfig {
+                enabled: true,
+                method: AuthMethod::Jwt,
+                api_keys: vec!["k1".to_string(), "k2".to_string()],
+                jwt_secret: Some("secret".to_string()),
+                token_expiration: 120,
+                key_scopes: HashMap::from([("k1".to_string(), vec!["beta".to_string()])]),
+                scoped_experimental: HashMap::from([(
+                    "beta".to_string(),
+                    HashMap::from([(
+                        "streaming".to_string(),
+                        serde_json::json!({"enabled": true}),
+                    )]),
+                )]),
+                scope_methods: HashMap::from([(
+                    "beta".to_string(),
+                    vec!["resources/".to_string()],
+                )]),
+            },
+            logging: LoggingConfig {
+                level: "debug".to_string(),
+                format: LogFormat::Json,
+                file: Some(PathBuf::from("/var/log/mcp.log")),
+                enable_request_logging: false,
+                audit: true,
+                audit_file: Some(PathBuf::from("/var/log/mcp-audit.log")),
+            },
+            features: FeatureConfig {
+                resources: false,
+                tools: true,
+                prompts: false,
+                sampling: true,
+                logging: false,
+                completion: true,
+                roots: false,
+            },
+            tools: crate::server::features::tools::ToolsConfig {
+                handlers: vec![crate::server::features::tools::ToolHandlerConfig {
+                    name: "http".to_string(),
+                    enabled: false,
+                    priority: -5,
+                    config: handler_config,
+                }],
+                auto_discover_builtin: false,
+                enable_all_by_default: false,
+                auto_discover_all: true,
+                check_annotations: true,
+                plugin_paths: vec![PathBuf::from("/opt/mcp/plugins/libextra_tools.so")],
+                sandbox: crate::server::features::sandbox::SandboxConfig {
+                    working_dir: Some(PathBuf::from("/srv/mcp/workspace")),
+                    env_allowlist: vec!["PATH".to_string(), "LANG".to_string()],
+                    allow_network: true,
+                },
+                idempotency_ttl: 60,
+            },
+            resources: crate::server::features::resources::ResourcesConfig {
+                max_subscriptions_per_client: 3,
+                allow_subscribe_to_missing: true,
+                manifest: Some(PathBuf::from("resources.toml")),
+                enable_http_provider: true,
+                sniff_http_content_type: false,
+            },
+            roots: crate::client::features::roots::RootsConfig {
+                list_empty_when_disabled: true,
+                scan_max_depth: 3,
+                scan_max_files: 500,
+                follow_symlinks: true,
+            },
+            custom,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_fully_populated() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for config in [fully_populated_config(), Config::default()] {
+            for extension in ["toml", "json", "yaml"] {
+                let path = dir.path().join(format!("config.{}", extension));
+                config.to_file(&path).unwrap();
+                assert_eq!(Config::from_file(&path).unwrap(), config, "{} round trip", extension);
+            }
+        }
+
+        // Optional sections left out entirely also survive a reload
+        let mut config = fully_populated_config();
+        config.transport.http = None;
+        config.transport.stdio = None;
+        config.server.instructions = None;
+        let path = dir.path().join("sparse.toml");
+        config.to_file(&path).unwrap();
+        assert_eq!(Config::from_file(&path).unwrap(), config);
+    }
+
+    #[test]
+    fn test_missing_sections_use_defaults() {
+        let config = ConfigFormat::Toml.parse("[server]\nname = \"partial\"\nversion = \"1.0.0\"\n").unwrap();
+        let expected = Config {
+            server: ServerConfig {
+                name: "partial".to_string(),
+                version: "1.0.0".to_string(),
+                ..ServerConfig::default()
+            },
+            ..Config::default()
+        };
+        assert_eq!(config, expected);
+    }
+
+    fn assert_config_error(config: &Config, expected: &str) {
+        match config.validate() {
+            Err(McpError::Config(message)) => assert_eq!(message, expected),
+            other => panic!("expected config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_http_without_section() {
+        let mut config = Config::default();
+        config.transport.http = None;
+        assert_config_error(&config, "HTTP transport selected but no HTTP config provided");
+
+        // The HTTP section isn't needed when running over STDIO
+        config.transport.transport_type = TransportType::Stdio;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_id_prefix() {
+        let mut config = Config::default();
+        config.server.id_prefix = "sess_".to_string();
+        assert!(config.validate().is_ok());
+
+        config.server.id_prefix = "bad prefix\n".to_string();
+        assert_config_error(
+            &config,
+            "ID prefix must only contain visible ASCII characters, got \"bad prefix\\n\"",
+        );
+    }
+
+    #[test]
+    fn test_validate_stdio_buffer_size() {
+        let mut config = Config::default();
+        config.transport.transport_type = TransportType::Stdio;
+        config.transport.stdio.as_mut().unwrap().buffer_size = 0;
+        assert_config_error(
+            &config,
+            "STDIO buffer size must be between 1 and 67108864 bytes, got 0",
+        );
+
+        let stdio = config.transport.stdio.as_mut().unwrap();
+        stdio.buffer_size = 1024;
+        stdio.flush = FlushPolicy::Interval(0);
+        assert_config_error(&config, "STDIO flush interval must be greater than 0");
+
+        let stdio = config.transport.stdio.as_mut().unwrap();
+        stdio.flush = FlushPolicy::Always;
+        stdio.max_message_size = 0;
+        assert_config_error(&config, "STDIO maximum message size must be greater than zero");
+
+        config.transport.stdio = None;
+        assert_config_error(&config, "STDIO transport selected but no STDIO config provided");
+    }
+
+    #[test]
+    fn test_validate_tls_requires_cert_and_key() {
+        let mut config = Config::default();
+        let http = config.transport.http.as_mut().unwrap();
+        http.enable_tls = true;
+        assert_config_error(&config, "TLS enabled but no certificate file (cert_file) provided");
+
+        config.transport.http.as_mut().unwrap().cert_file = Some(PathBuf::from("cert.pem"));
+        assert_config_error(&config, "TLS enabled but no private key file (key_file) provided");
+
+        config.transport.http.as_mut().unwrap().key_file = Some(PathBuf::from("key.pem"));
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_normalize_endpoint_path() {
+        assert_eq!(normalize_endpoint_path("mcp").unwrap(), "/mcp");
+        assert_eq!(normalize_endpoint_path("/api/mcp/").unwrap(), "/api/mcp");
+        assert_eq!(normalize_endpoint_path("/").unwrap(), "/");
+        assert!(normalize_endpoint_path("").is_err());
+        assert!(normalize_endpoint_path("/api//mcp").is_err());
+        assert!(normalize_endpoint_path("/m cp").is_err());
+
+        let mut config = Config::default();
+        config.transport.http.as_mut().unwrap().endpoint_path = "mcp".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_http_ranges() {
+        let mut config = Config::default();
+        config.transport.http.as_mut().unwrap().endpoint_path = String::new();
+        assert_config_error(&config, "HTTP endpoint path must not be empty");
+
+        let mut config = Config::default();
+        config.transport.http.as_mut().unwrap().endpoint_path = "/mcp?x=1".to_string();
+        assert_config_error(&config, "Invalid HTTP endpoint path: \"/mcp?x=1\"");
+
+        let mut config = Config::default();
+        config.transport.http.as_mut().unwrap().session_timeout = 0;
+        assert_config_error(&config, "HTTP session timeout must be greater than zero");
+    }
 }
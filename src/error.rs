@@ -44,14 +44,58 @@ pub enum McpError {
     #[error("Resource error: {0}")]
     Resource(String),
 
+    /// Requested resource does not exist (-32002)
+    #[error("Resource not found: {0}")]
+    ResourceNotFound(String),
+
+    /// The provider responsible for a resource failed to initialize
+    #[error("Resource provider unavailable: {0}")]
+    ProviderUnavailable(String),
+
+    /// A path reached through a symlink that leads outside the allowed root
+    #[error("Access denied: symlink escapes root directory: {0}")]
+    SymlinkEscape(String),
+
     /// Tool-related errors
     #[error("Tool error: {0}")]
     Tool(String),
 
+    /// A tool is defined but no handler is registered to execute it (-32006)
+    #[error("Tool not implemented: no handler is registered for tool {0}")]
+    ToolNotImplemented(String),
+
     /// Prompt-related errors
     #[error("Prompt error: {0}")]
     Prompt(String),
 
+    /// The request was cancelled before it completed
+    #[error("Request cancelled: {0}")]
+    Cancelled(String),
+
+    /// The server is in maintenance mode; the request may be retried later
+    #[error("Server in maintenance: {0}")]
+    Maintenance(String),
+
+    /// A result exceeded the configured maximum response size (-32000)
+    #[error("Response too large: {size} bytes exceeds the limit of {limit} bytes")]
+    ResponseTooLarge { size: usize, limit: usize },
+
+    /// The request referenced a session the transport doesn't know (-32003)
+    #[error("Session not found: {0}")]
+    SessionNotFound(String),
+
+    /// A request body exceeded the transport's size limit (-32004)
+    #[error("Payload too large: request body exceeds the limit of {limit} bytes")]
+    PayloadTooLarge { limit: usize },
+
+    /// The request lacked valid credentials (-32005)
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// The caller's credentials don't permit the requested method (-32007)
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     /// Authentication/Authorization errors
     #[error("Auth error: {0}")]
     Auth(String),
@@ -102,6 +146,9 @@ pub enum TransportError {
 
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
+
+    #[error("Invalid session data for key '{key}': {reason}")]
+    InvalidSessionData { key: String, reason: String },
 }
 
 // Ensure TransportError is Send + Sync
@@ -126,16 +173,61 @@ impl McpError {
             McpError::MethodNotFound(_) => -32601,
             McpError::InvalidParams(_) => -32602,
             McpError::InternalError(_) => -32603,
+            // Escaping paths are answered like missing ones, revealing nothing beyond the root
+            McpError::ResourceNotFound(_) | McpError::SymlinkEscape(_) => -32002,
+            McpError::Maintenance(_) => -32001,
+            McpError::ResponseTooLarge { .. } => -32000,
+            McpError::SessionNotFound(_)
+            | McpError::Transport(TransportError::SessionExpired(_)) => -32003,
+            McpError::PayloadTooLarge { .. } => -32004,
+            McpError::ToolNotImplemented(_) => -32006,
+            McpError::Forbidden(_) => -32007,
+            McpError::Unauthorized(_) | McpError::Transport(TransportError::Unauthorized(_)) => {
+                -32005
+            }
             _ => -32603, // Default to internal error
         }
     }
 
+    /// HTTP status to answer with when the error ends a request at the transport level
+    pub fn http_status(&self) -> actix_web::http::StatusCode {
+        use actix_web::http::StatusCode;
+
+        match self {
+            McpError::ParseError(_) | McpError::InvalidRequest(_) | McpError::InvalidParams(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            McpError::SessionNotFound(_)
+            | McpError::Transport(TransportError::SessionExpired(_)) => StatusCode::NOT_FOUND,
+            McpError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            McpError::Unauthorized(_) | McpError::Transport(TransportError::Unauthorized(_)) => {
+                StatusCode::UNAUTHORIZED
+            }
+            McpError::Forbidden(_) => StatusCode::FORBIDDEN,
+            McpError::Maintenance(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
     /// Convert to JSON-RPC error structure
     pub fn to_json_rpc_error(&self) -> JsonRpcError {
         JsonRpcError {
             code: self.to_json_rpc_code(),
             message: self.to_string(),
-            data: None,
+            data: self.to_json_rpc_data(),
+        }
+    }
+
+    /// Extra JSON-RPC `error.data` for errors clients can act on
+    pub fn to_json_rpc_data(&self) -> Option<serde_json::Value> {
+        match self {
+            McpError::Maintenance(_) => Some(serde_json::json!({ "retryable": true })),
+            McpError::ResponseTooLarge { size, limit } => {
+                Some(serde_json::json!({ "size": size, "limit": limit }))
+            }
+            McpError::PayloadTooLarge { limit } => Some(serde_json::json!({ "limit": limit })),
+            McpError::ToolNotImplemented(name) => Some(serde_json::json!({ "tool": name })),
+            _ => None,
         }
     }
 
@@ -163,4 +255,101 @@ impl McpError {
     pub fn internal_error(msg: impl Into<String>) -> Self {
         McpError::InternalError(msg.into())
     }
+
+    /// Create a cancellation error
+    pub fn cancelled(msg: impl Into<String>) -> Self {
+        McpError::Cancelled(msg.into())
+    }
+
+    /// Create a maintenance mode error
+    pub fn maintenance(msg: impl Into<String>) -> Self {
+        McpError::Maintenance(msg.into())
+    }
+
+    /// Create an error for a result over the maximum response size
+    pub fn response_too_large(size: usize, limit: usize) -> Self {
+        McpError::ResponseTooLarge { size, limit }
+    }
+
+    /// Create a resource not found error
+    pub fn resource_not_found(uri: impl Into<String>) -> Self {
+        McpError::ResourceNotFound(uri.into())
+    }
+
+    /// Create an error for an unknown session
+    pub fn session_not_found(session_id: impl Into<String>) -> Self {
+        McpError::SessionNotFound(session_id.into())
+    }
+
+    /// Create an error for a request body over the size limit
+    pub fn payload_too_large(limit: usize) -> Self {
+        McpError::PayloadTooLarge { limit }
+    }
+
+    /// Create an error for a request without valid credentials
+    pub fn unauthorized(msg: impl Into<String>) -> Self {
+        McpError::Unauthorized(msg.into())
+    }
+
+    /// Create an error for a tool defined without a handler
+    pub fn tool_not_implemented(name: impl Into<String>) -> Self {
+        McpError::ToolNotImplemented(name.into())
+    }
+
+    /// Create an error for a method the caller isn't allowed to use
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        McpError::Forbidden(msg.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+
+    #[test]
+    fn test_transport_error_codes_and_statuses() {
+        let cases = [
+            (McpError::session_not_found("abc"), -32003, StatusCode::NOT_FOUND),
+            (
+                TransportError::SessionExpired("abc".to_string()).into(),
+                -32003,
+                StatusCode::NOT_FOUND,
+            ),
+            (McpError::payload_too_large(1024), -32004, StatusCode::PAYLOAD_TOO_LARGE),
+            (McpError::unauthorized("bad key"), -32005, StatusCode::UNAUTHORIZED),
+            (
+                TransportError::Unauthorized("bad key".to_string()).into(),
+                -32005,
+                StatusCode::UNAUTHORIZED,
+            ),
+            (McpError::forbidden("tools/call"), -32007, StatusCode::FORBIDDEN),
+            (
+                McpError::response_too_large(2048, 1024),
+                -32000,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (McpError::maintenance("upgrading"), -32001, StatusCode::SERVICE_UNAVAILABLE),
+            (
+                McpError::tool_not_implemented("deploy"),
+                -32006,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (
+                McpError::SymlinkEscape("/srv/root/link".to_string()),
+                -32002,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (McpError::parse_error("bad json"), -32700, StatusCode::BAD_REQUEST),
+            (McpError::internal_error("boom"), -32603, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+
+        for (error, code, status) in cases {
+            assert_eq!(error.to_json_rpc_code(), code, "{}", error);
+            assert_eq!(error.http_status(), status, "{}", error);
+        }
+
+        let error = McpError::payload_too_large(1024).to_json_rpc_error();
+        assert_eq!(error.data, Some(serde_json::json!({ "limit": 1024 })));
+    }
 }
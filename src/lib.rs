@@ -45,9 +45,17 @@ pub use error::{McpError, Result};
 pub use protocol::{
     JsonRpcMessage, JsonRpcRequest, JsonRpcResponse, JsonRpcNotification,
     InitializeRequest, InitializeResult, ServerCapabilities, ClientCapabilities,
+    InitializeRequestBuilder, InitializeResultBuilder,
 };
 pub use server::McpServer;
 
+/// Dependencies of code generated by this crate's macros
+#[doc(hidden)]
+pub mod __private {
+    pub use async_trait::async_trait;
+    pub use serde_json;
+}
+
 /// Current MCP protocol version supported by this implementation
 pub const PROTOCOL_VERSION: &str = "2025-03-26";
 
@@ -6,7 +6,11 @@ use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 use tracing::{error, info};
 
-use mcp_server::server::McpServerBuilder;
+use mcp_server::protocol::Content;
+use mcp_server::server::features::tools::{ToolHandler, ToolHandlerDiscovery, ToolManager};
+use mcp_server::server::selftest::run_self_test;
+use mcp_server::transport::TransportType;
+use mcp_server::config::ConfigOverlay;
 use mcp_server::{Config, McpServer};
 
 /// MCP Server CLI
@@ -23,9 +27,9 @@ struct Cli {
     #[arg(short, long)]
     verbose: bool,
 
-    /// Log level
-    #[arg(long, default_value = "info")]
-    log_level: String,
+    /// Log level (defaults to MCP_LOG_LEVEL, then "info")
+    #[arg(long)]
+    log_level: Option<String>,
 
     /// Subcommands
     #[command(subcommand)]
@@ -48,15 +52,19 @@ enum Commands {
         #[arg(long)]
         instructions: Option<String>,
 
-        /// HTTP bind address
-        #[arg(long, default_value = "127.0.0.1")]
-        bind: String,
+        /// HTTP bind address [default: 127.0.0.1]
+        #[arg(long)]
+        bind: Option<String>,
+
+        /// HTTP port [default: 8080]
+        #[arg(long)]
+        port: Option<u16>,
 
-        /// HTTP port
-        #[arg(long, default_value = "8080")]
-        port: u16,
+        /// Transport to serve on (http or stdio) [default: from config]
+        #[arg(long, value_name = "TRANSPORT", conflicts_with = "stdio")]
+        transport: Option<TransportType>,
 
-        /// Use STDIO transport instead of HTTP
+        /// Use STDIO transport instead of HTTP (same as --transport stdio)
         #[arg(long)]
         stdio: bool,
     },
@@ -70,9 +78,13 @@ enum Commands {
         /// Overwrite existing file
         #[arg(long)]
         force: bool,
+
+        /// Print the JSON Schema of the configuration file instead
+        #[arg(long, conflicts_with_all = ["output", "force"])]
+        schema: bool,
     },
 
-    /// Validate a configuration file
+    /// Validate a configuration file and the input schemas of its tool handlers
     Validate {
         /// Configuration file to validate
         #[arg(value_name = "FILE")]
@@ -81,6 +93,37 @@ enum Commands {
 
     /// Show server information
     Info,
+
+    /// Inspect the tools this binary exposes
+    Tools {
+        #[command(subcommand)]
+        command: ToolsCommand,
+    },
+
+    /// Exercise the built-in tools, resources and prompts, exiting non-zero if any fail
+    Selftest,
+}
+
+#[derive(Subcommand)]
+enum ToolsCommand {
+    /// List available tools with their descriptions
+    List,
+
+    /// Show a tool's input schema and annotations
+    Describe {
+        /// Tool name
+        name: String,
+    },
+
+    /// Execute a tool once without a client and print its result
+    Call {
+        /// Tool name
+        name: String,
+
+        /// Tool arguments as a JSON object
+        #[arg(long, value_name = "JSON")]
+        args: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -88,7 +131,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     // Initialize logging
-    init_logging(&cli.log_level, cli.verbose)?;
+    let log_level = cli
+        .log_level
+        .clone()
+        .or_else(|| std::env::var("MCP_LOG_LEVEL").ok())
+        .unwrap_or_else(|| "info".to_string());
+    init_logging(&log_level, cli.verbose)?;
 
     match cli.command {
         Some(Commands::Start {
@@ -97,11 +145,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             instructions,
             bind,
             port,
+            transport,
             stdio,
         }) => {
-            start_server(cli.config, name, version, instructions, bind, port, stdio).await?;
+            let overlay = ConfigOverlay {
+                server_name: name,
+                server_version: version,
+                server_instructions: instructions,
+                transport_type: transport.or(stdio.then_some(TransportType::Stdio)),
+                http_bind_address: bind,
+                http_port: port,
+                ..ConfigOverlay::default()
+            };
+            start_server(cli.config, overlay).await?;
         }
-        Some(Commands::Config { output, force }) => {
+        Some(Commands::Config { schema: true, .. }) => {
+            println!("{}", serde_json::to_string_pretty(&Config::json_schema())?);
+        }
+        Some(Commands::Config { output, force, .. }) => {
             generate_config(output, force)?;
         }
         Some(Commands::Validate { file }) => {
@@ -110,18 +171,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(Commands::Info) => {
             show_info();
         }
+        Some(Commands::Tools { command }) => {
+            let output = match command {
+                ToolsCommand::List => list_tools(cli.config)?,
+                ToolsCommand::Describe { name } => describe_tool(cli.config, &name)?,
+                ToolsCommand::Call { name, args } => {
+                    call_tool(cli.config, &name, args.as_deref()).await?
+                }
+            };
+            println!("{}", output);
+        }
+        Some(Commands::Selftest) => {
+            self_test(cli.config).await?;
+        }
         None => {
             // Default to starting the server
-            start_server(
-                cli.config,
-                None,
-                None,
-                None,
-                "127.0.0.1".to_string(),
-                8080,
-                false,
-            )
-            .await?;
+            start_server(cli.config, ConfigOverlay::default()).await?;
         }
     }
 
@@ -146,44 +211,19 @@ fn init_logging(level: &str, verbose: bool) -> Result<(), Box<dyn std::error::Er
 /// Start the MCP server
 async fn start_server(
     config_path: Option<PathBuf>,
-    name: Option<String>,
-    version: Option<String>,
-    instructions: Option<String>,
-    bind: String,
-    port: u16,
-    stdio: bool,
+    cli_overlay: ConfigOverlay,
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting MCP server...");
 
-    // Load configuration
-    let mut config = if let Some(config_path) = config_path {
-        info!("Loading configuration from: {}", config_path.display());
-        Config::from_file(config_path)?
-    } else {
-        Config::default()
-    };
-
-    // Override configuration with CLI arguments
-    if let Some(name) = name {
-        config.server.name = name;
-    }
-
-    if let Some(version) = version {
-        config.server.version = version;
-    }
-
-    if let Some(instructions) = instructions {
-        config.server.instructions = Some(instructions);
-    }
+    let config = load_config(config_path, cli_overlay)?;
 
-    // Configure transport
-    if stdio {
-        config.transport.transport_type = mcp_server::config::TransportType::Stdio;
-    } else {
-        config.transport.transport_type = mcp_server::config::TransportType::Http;
-        if let Some(ref mut http_config) = config.transport.http {
-            http_config.bind_address = bind;
-            http_config.port = port;
+    if config.server.self_test_on_startup {
+        let report = run_self_test(&config).await;
+        for line in report.to_string().lines() {
+            info!("Self-test: {}", line);
+        }
+        if !report.passed() {
+            return Err(format!("Startup self-test failed for {} feature(s)", report.failures()).into());
         }
     }
 
@@ -199,7 +239,7 @@ async fn start_server(
 
     for transport_info in server.transport_info() {
         info!(
-            "  Transport: {:?} at {}",
+            "  Transport: {} at {}",
             transport_info.transport_type, transport_info.address
         );
     }
@@ -211,6 +251,24 @@ async fn start_server(
     Ok(())
 }
 
+/// Build the server configuration: file (or defaults), then `MCP_*` env vars, then CLI arguments
+fn load_config(
+    config_path: Option<PathBuf>,
+    cli_overlay: ConfigOverlay,
+) -> Result<Config, Box<dyn std::error::Error>> {
+    let mut config = if let Some(config_path) = config_path {
+        info!("Loading configuration from: {}", config_path.display());
+        Config::from_file(config_path)?
+    } else {
+        Config::default()
+    };
+
+    config.merge(ConfigOverlay::from_env()?);
+    config.merge(cli_overlay);
+
+    Ok(config)
+}
+
 /// Generate a default configuration file
 fn generate_config(output: PathBuf, force: bool) -> Result<(), Box<dyn std::error::Error>> {
     if output.exists() && !force {
@@ -233,10 +291,141 @@ fn validate_config(file: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
     let config = Config::from_file(&file)?;
     config.validate()?;
 
+    // Instantiate the configured tool handlers and check their input schemas
+    let offenders = ToolHandlerDiscovery::check_schemas(Some(&config.tools))?;
+    if !offenders.is_empty() {
+        for (name, reason) in &offenders {
+            error!("Tool '{}' has an invalid input schema: {}", name, reason);
+        }
+        return Err(format!("{} tool(s) have invalid input schemas", offenders.len()).into());
+    }
+
     info!("Configuration file is valid");
     Ok(())
 }
 
+/// Instantiate the tool handlers enabled by the configuration
+fn discover_handlers(
+    config_path: Option<PathBuf>,
+) -> Result<Vec<Box<dyn ToolHandler>>, Box<dyn std::error::Error>> {
+    let config = match config_path {
+        Some(path) => Config::from_file(path)?,
+        None => Config::default(),
+    };
+
+    Ok(ToolHandlerDiscovery::discover_handlers(Some(&config.tools))?)
+}
+
+/// Collect the definitions of the enabled tools, sorted by name
+fn discover_tools(
+    config_path: Option<PathBuf>,
+) -> Result<Vec<mcp_server::protocol::Tool>, Box<dyn std::error::Error>> {
+    let mut tools: Vec<_> = discover_handlers(config_path)?
+        .iter()
+        .map(|handler| handler.tool_definition())
+        .collect();
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(tools)
+}
+
+/// List available tools, one per line with their description
+fn list_tools(config_path: Option<PathBuf>) -> Result<String, Box<dyn std::error::Error>> {
+    let tools = discover_tools(config_path)?;
+
+    let width = tools.iter().map(|tool| tool.name.len()).max().unwrap_or(0);
+    let lines: Vec<String> = tools
+        .iter()
+        .map(|tool| {
+            format!(
+                "{:width$}  {}",
+                tool.name,
+                tool.description.as_deref().unwrap_or(""),
+                width = width
+            )
+            .trim_end()
+            .to_string()
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
+
+/// Describe a tool as pretty-printed JSON, including its input schema and annotations
+fn describe_tool(
+    config_path: Option<PathBuf>,
+    name: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let tools = discover_tools(config_path)?;
+    let tool = tools
+        .iter()
+        .find(|tool| tool.name == name)
+        .ok_or_else(|| format!("Unknown tool: {}", name))?;
+
+    Ok(serde_json::to_string_pretty(tool)?)
+}
+
+/// Validate arguments against a tool's schema, execute it and render the result content
+async fn call_tool(
+    config_path: Option<PathBuf>,
+    name: &str,
+    args: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let arguments = args
+        .map(serde_json::from_str::<serde_json::Value>)
+        .transpose()
+        .map_err(|e| format!("Invalid --args JSON: {}", e))?;
+
+    let handler = discover_handlers(config_path)?
+        .into_iter()
+        .find(|handler| handler.name() == name)
+        .ok_or_else(|| format!("Unknown tool: {}", name))?;
+
+    // Check the arguments against the declared input schema before executing
+    let schema = serde_json::to_value(handler.input_schema())?;
+    let compiled = jsonschema::JSONSchema::compile(&schema)
+        .map_err(|e| format!("Tool '{}' has an invalid input schema: {}", name, e))?;
+    let instance = arguments.clone().unwrap_or_else(|| serde_json::json!({}));
+    let problems: Vec<String> = match compiled.validate(&instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.map(|e| e.to_string()).collect(),
+    };
+    if !problems.is_empty() {
+        return Err(format!("Invalid arguments for '{}': {}", name, problems.join("; ")).into());
+    }
+
+    let manager = ToolManager::new();
+    manager.register_handler_with_tool(handler).await?;
+    let result = manager.call_tool(name, arguments).await?;
+
+    let mut lines = Vec::new();
+    for content in &result.content {
+        match content {
+            Content::Text { text, .. } => lines.push(text.clone()),
+            other => lines.push(serde_json::to_string_pretty(other)?),
+        }
+    }
+    let output = lines.join("\n");
+
+    if result.is_error {
+        return Err(format!("Tool '{}' returned an error: {}", name, output).into());
+    }
+    Ok(output)
+}
+
+/// Run the feature self-test and print one line per feature
+async fn self_test(config_path: Option<PathBuf>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = load_config(config_path, ConfigOverlay::default())?;
+
+    let report = run_self_test(&config).await;
+    print!("{}", report);
+
+    if !report.passed() {
+        return Err(format!("Self-test failed for {} feature(s)", report.failures()).into());
+    }
+    Ok(())
+}
+
 /// Show server information
 fn show_info() {
     info!("MCP Server");
@@ -283,12 +472,43 @@ mod tests {
 
         if let Some(Commands::Start { name, port, .. }) = cli.command {
             assert_eq!(name, Some("test-server".to_string()));
-            assert_eq!(port, 9090);
+            assert_eq!(port, Some(9090));
         } else {
             panic!("Expected Start command");
         }
     }
 
+    #[test]
+    fn test_transport_flag_sets_config_transport() {
+        let cli = Cli::try_parse_from(["mcp-server", "start", "--transport", "stdio"]).unwrap();
+        let Some(Commands::Start { transport, .. }) = cli.command else {
+            panic!("Expected Start command");
+        };
+        assert_eq!(transport, Some(TransportType::Stdio));
+
+        let overlay = |transport_type| ConfigOverlay {
+            transport_type,
+            ..ConfigOverlay::default()
+        };
+        let config = load_config(None, overlay(transport)).unwrap();
+        assert_eq!(config.transport.transport_type, TransportType::Stdio);
+
+        let config = load_config(None, overlay(Some(TransportType::Http))).unwrap();
+        assert_eq!(config.transport.transport_type, TransportType::Http);
+
+        assert!(Cli::try_parse_from(["mcp-server", "start", "--transport", "carrier-pigeon"]).is_err());
+        assert!(Cli::try_parse_from(["mcp-server", "start", "--transport", "http", "--stdio"]).is_err());
+    }
+
+    #[test]
+    fn test_config_schema_flag_conflicts_with_output() {
+        let cli = Cli::try_parse_from(["mcp-server", "config", "--schema"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Config { schema: true, .. })));
+
+        assert!(Cli::try_parse_from(["mcp-server", "config", "--schema", "-o", "out.json"]).is_err());
+        assert!(Cli::try_parse_from(["mcp-server", "config", "--schema", "--force"]).is_err());
+    }
+
     #[test]
     fn test_config_generation() {
         let temp_dir = TempDir::new().unwrap();
@@ -301,4 +521,107 @@ mod tests {
         // Validate generated config
         assert!(validate_config(config_path).is_ok());
     }
+
+    #[test]
+    fn test_cli_overrides_env() {
+        std::env::set_var("MCP_HTTP_PORT", "7070");
+        std::env::set_var("MCP_SERVER_NAME", "env-server");
+
+        let from_env = load_config(None, ConfigOverlay::default());
+        let from_cli = load_config(
+            None,
+            ConfigOverlay {
+                server_name: Some("cli-server".to_string()),
+                http_port: Some(9090),
+                ..ConfigOverlay::default()
+            },
+        );
+
+        std::env::remove_var("MCP_HTTP_PORT");
+        std::env::remove_var("MCP_SERVER_NAME");
+
+        let from_env = from_env.unwrap();
+        assert_eq!(from_env.server.name, "env-server");
+        assert_eq!(from_env.transport.http.unwrap().port, 7070);
+
+        let from_cli = from_cli.unwrap();
+        assert_eq!(from_cli.server.name, "cli-server");
+        assert_eq!(from_cli.transport.http.unwrap().port, 9090);
+    }
+
+    #[test]
+    fn test_tools_subcommands() {
+        let cli = Cli::try_parse_from(["mcp-server", "tools", "list"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Tools {
+                command: ToolsCommand::List
+            })
+        ));
+
+        let output = list_tools(cli.config).unwrap();
+        assert!(output.lines().any(|line| line.starts_with("echo ")));
+        assert!(output.lines().any(|line| line.starts_with("calculator ")));
+
+        let cli = Cli::try_parse_from(["mcp-server", "tools", "describe", "echo"]).unwrap();
+        let Some(Commands::Tools {
+            command: ToolsCommand::Describe { name },
+        }) = cli.command
+        else {
+            panic!("Expected tools describe command");
+        };
+        let description: serde_json::Value =
+            serde_json::from_str(&describe_tool(None, &name).unwrap()).unwrap();
+        assert_eq!(description["name"], "echo");
+        assert!(description["inputSchema"]["properties"].is_object());
+
+        assert!(describe_tool(None, "missing").is_err());
+    }
+
+    #[test]
+    fn test_tools_list_respects_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("tools.toml");
+        let mut config = Config::default();
+        config.tools.handlers.push(mcp_server::server::features::tools::ToolHandlerConfig {
+            name: "calculator".to_string(),
+            enabled: false,
+            ..Default::default()
+        });
+        config.to_file(&config_path).unwrap();
+
+        let output = list_tools(Some(config_path)).unwrap();
+        assert!(output.contains("echo"));
+        assert!(!output.contains("calculator"));
+    }
+
+    #[tokio::test]
+    async fn test_tools_call_calculator() {
+        let cli = Cli::try_parse_from([
+            "mcp-server",
+            "tools",
+            "call",
+            "calculator",
+            "--args",
+            r#"{"operation": "add", "a": 2, "b": 3}"#,
+        ])
+        .unwrap();
+        let Some(Commands::Tools {
+            command: ToolsCommand::Call { name, args },
+        }) = cli.command
+        else {
+            panic!("Expected tools call command");
+        };
+
+        let output = call_tool(cli.config, &name, args.as_deref()).await.unwrap();
+        assert_eq!(output, "2 add 3 = 5");
+
+        // Schema violations are reported without executing the tool
+        let err = call_tool(None, "calculator", Some(r#"{"operation": "add"}"#))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid arguments"));
+
+        assert!(call_tool(None, "calculator", Some("not json")).await.is_err());
+    }
 }
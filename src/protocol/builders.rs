@@ -0,0 +1,228 @@
+//! Builders for the initialization handshake messages.
+//!
+//! Embedders can assemble an [`InitializeRequest`] or [`InitializeResult`]
+//! without hand-building JSON. Both default to [`PROTOCOL_VERSION`] and empty
+//! capabilities.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::protocol::{
+    ClientCapabilities, Implementation, InitializeRequest, InitializeResult, PromptsCapability,
+    ResourcesCapability, RootsCapability, ServerCapabilities, ToolsCapability, PROTOCOL_VERSION,
+};
+
+/// Builder for the result of an `initialize` request
+#[derive(Debug, Clone)]
+pub struct InitializeResultBuilder {
+    protocol_version: String,
+    capabilities: ServerCapabilities,
+    server_info: Implementation,
+    instructions: Option<String>,
+}
+
+impl InitializeResultBuilder {
+    /// Create a builder for a server with the given name and version
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            capabilities: ServerCapabilities::default(),
+            server_info: Implementation {
+                name: name.into(),
+                version: version.into(),
+            },
+            instructions: None,
+        }
+    }
+
+    /// Set the negotiated protocol version
+    pub fn protocol_version(mut self, version: impl Into<String>) -> Self {
+        self.protocol_version = version.into();
+        self
+    }
+
+    /// Replace all capabilities
+    pub fn capabilities(mut self, capabilities: ServerCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Advertise the logging capability
+    pub fn logging(mut self) -> Self {
+        self.capabilities.logging = Some(serde_json::json!({}));
+        self
+    }
+
+    /// Advertise the completion capability
+    pub fn completion(mut self) -> Self {
+        self.capabilities.completion = Some(serde_json::json!({}));
+        self
+    }
+
+    /// Advertise prompts
+    pub fn prompts(mut self, list_changed: bool) -> Self {
+        self.capabilities.prompts = Some(PromptsCapability {
+            list_changed: Some(list_changed),
+        });
+        self
+    }
+
+    /// Advertise resources
+    pub fn resources(mut self, subscribe: bool, list_changed: bool) -> Self {
+        self.capabilities.resources = Some(ResourcesCapability {
+            subscribe: Some(subscribe),
+            list_changed: Some(list_changed),
+        });
+        self
+    }
+
+    /// Advertise tools
+    pub fn tools(mut self, list_changed: bool) -> Self {
+        self.capabilities.tools = Some(ToolsCapability {
+            list_changed: Some(list_changed),
+        });
+        self
+    }
+
+    /// Advertise an experimental capability
+    pub fn experimental(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.capabilities
+            .experimental
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Set instructions describing how to use the server
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
+    /// Build the result
+    pub fn build(self) -> InitializeResult {
+        InitializeResult {
+            protocol_version: self.protocol_version,
+            capabilities: self.capabilities,
+            server_info: self.server_info,
+            instructions: self.instructions,
+        }
+    }
+}
+
+/// Builder for an `initialize` request
+#[derive(Debug, Clone)]
+pub struct InitializeRequestBuilder {
+    protocol_version: String,
+    capabilities: ClientCapabilities,
+    client_info: Implementation,
+}
+
+impl InitializeRequestBuilder {
+    /// Create a builder for a client with the given name and version
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION.to_string(),
+            capabilities: ClientCapabilities::default(),
+            client_info: Implementation {
+                name: name.into(),
+                version: version.into(),
+            },
+        }
+    }
+
+    /// Set the requested protocol version
+    pub fn protocol_version(mut self, version: impl Into<String>) -> Self {
+        self.protocol_version = version.into();
+        self
+    }
+
+    /// Replace all capabilities
+    pub fn capabilities(mut self, capabilities: ClientCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Advertise roots
+    pub fn roots(mut self, list_changed: bool) -> Self {
+        self.capabilities.roots = Some(RootsCapability {
+            list_changed: Some(list_changed),
+        });
+        self
+    }
+
+    /// Advertise the sampling capability
+    pub fn sampling(mut self) -> Self {
+        self.capabilities.sampling = Some(serde_json::json!({}));
+        self
+    }
+
+    /// Advertise an experimental capability
+    pub fn experimental(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.capabilities
+            .experimental
+            .get_or_insert_with(HashMap::new)
+            .insert(name.into(), value);
+        self
+    }
+
+    /// Build the request parameters
+    pub fn build(self) -> InitializeRequest {
+        InitializeRequest {
+            protocol_version: self.protocol_version,
+            capabilities: self.capabilities,
+            client_info: self.client_info,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_result_serialization() {
+        let result = InitializeResultBuilder::new("embedded", "1.2.3")
+            .logging()
+            .tools(true)
+            .resources(true, false)
+            .experimental("streaming", serde_json::json!({ "enabled": true }))
+            .instructions("Call tools/list first")
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&result).unwrap(),
+            serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {
+                    "experimental": { "streaming": { "enabled": true } },
+                    "logging": {},
+                    "resources": { "subscribe": true, "listChanged": false },
+                    "tools": { "listChanged": true }
+                },
+                "serverInfo": { "name": "embedded", "version": "1.2.3" },
+                "instructions": "Call tools/list first"
+            })
+        );
+    }
+
+    #[test]
+    fn test_initialize_request_serialization() {
+        let request = InitializeRequestBuilder::new("client", "0.1.0")
+            .roots(true)
+            .sampling()
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {
+                    "roots": { "listChanged": true },
+                    "sampling": {}
+                },
+                "clientInfo": { "name": "client", "version": "0.1.0" }
+            })
+        );
+    }
+}
@@ -3,20 +3,160 @@
 //! This module provides the main protocol handler that processes incoming
 //! JSON-RPC messages and routes them to appropriate handlers.
 
+use futures_util::StreamExt;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{error, info, warn};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
-use crate::client::features::SamplingManager;
-use crate::error::{McpError, Result};
+use crate::client::features::{RootsManager, SamplingManager};
+use crate::config::ResponseSizePolicy;
+use crate::error::{McpError, Result, TransportError};
+use crate::protocol::outgoing::DEFAULT_OUTGOING_REQUEST_TIMEOUT;
 use crate::protocol::{
-    validation, AnyJsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, RequestId,
+    validation, AnyJsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse,
+    LoggingLevel, MethodRegistry, PendingRequests, RequestId, RequestSink,
 };
-use crate::server::features::{PromptManager, ResourceManager, ToolManager};
+use crate::server::features::logging::SessionNotificationSink;
+use crate::server::features::progress::{progress_token, ProgressCallback, ProgressReporter};
+use crate::server::features::resources::TextChunkStream;
+use crate::server::features::{
+    ExperimentalCapabilities, LoggingManager, NotificationSink, PromptManager, ResourceManager,
+    ServerFeatureManager, ToolManager,
+};
+use crate::utils::audit::{AuditOutcome, AuditRecord, AuditSink, AUDITED_METHODS};
+use crate::utils::clock::{system_clock, SharedClock};
+use crate::utils::ids::SharedIdGenerator;
+use crate::utils::metrics::{MessageCounters, RequestMetrics};
+use crate::utils::response_size::enforce_response_size;
+
+
+
+/// Client ID used when the transport doesn't identify the client
+pub const DEFAULT_CLIENT_ID: &str = "default-client";
+
+/// Default number of batch messages handled concurrently
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 8;
+
+/// Prefix of operator methods that keep working in maintenance mode
+pub const ADMIN_METHOD_PREFIX: &str = "admin/";
+
+/// Scope a caller must hold to call operator (`admin/`) methods
+pub const ADMIN_SCOPE: &str = "admin";
+
+/// Default maximum number of messages accepted in one batch
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Scope -> experimental capability name -> payload
+pub type ScopedExperimentalCapabilities = HashMap<String, HashMap<String, Value>>;
+
+/// Scope -> method prefixes callers holding it may use
+pub type ScopeMethodAllowlist = HashMap<String, Vec<String>>;
+
+/// Per-request context supplied by the transport
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// Session the request arrived on
+    pub session_id: Option<String>,
+
+    /// Client identifier, if the transport knows it
+    pub client_id: Option<String>,
+
+    /// Authenticated identity of the caller, recorded in audit logs
+    pub principal: Option<String>,
+
+    /// Scopes granted to the authenticated subject
+    pub scopes: Vec<String>,
+
+    /// What the transport knows about the request (peer address, headers)
+    pub metadata: Option<crate::transport::TransportMetadata>,
+
+    /// Where a `resources/read` that opted in to streaming sends its text
+    pub resource_stream: Option<ResourceStream>,
+}
+
+/// Receives the text of a streamed `resources/read` chunk by chunk
+#[derive(Debug, Clone)]
+pub struct ResourceStream {
+    /// Largest chunk in bytes
+    pub chunk_size: usize,
+
+    /// Where each chunk is sent as it is read
+    pub sender: tokio::sync::mpsc::Sender<String>,
+}
+
+/// Check whether a request is a `resources/read` asking to be streamed
+///
+/// Clients opt in with `"_meta": {"stream": true}` in the params.
+pub fn requests_streamed_read(request: &JsonRpcRequest) -> bool {
+    request.method == "resources/read"
+        && request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("stream"))
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+}
+
+impl RequestContext {
+    /// Create a context for a request on the given session
+    pub fn with_session(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: Some(session_id.into()),
+            client_id: None,
+            principal: None,
+            scopes: Vec::new(),
+            metadata: None,
+            resource_stream: None,
+        }
+    }
+
+    /// Attach the authenticated identity of the caller
+    pub fn with_principal(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+
+    /// Attach the scopes granted to the authenticated subject
+    pub fn with_scopes(mut self, scopes: Vec<String>) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Attach the transport's metadata about the request
+    pub fn with_metadata(mut self, metadata: crate::transport::TransportMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Stream the text of an opted-in `resources/read` to the given receiver
+    pub fn with_resource_stream(mut self, stream: ResourceStream) -> Self {
+        self.resource_stream = Some(stream);
+        self
+    }
+
+    /// Identifier used to track per-client state such as subscriptions
+    pub fn client_key(&self) -> &str {
+        self.client_id
+            .as_deref()
+            .or(self.session_id.as_deref())
+            .unwrap_or(DEFAULT_CLIENT_ID)
+    }
+}
 
+/// A request being handled
+struct ActiveRequest {
+    /// When handling started
+    started: Instant,
 
+    /// Fired when the client cancels the request
+    cancel: CancellationToken,
+}
 
 /// Protocol handler for processing MCP messages
 #[derive(Clone)]
@@ -33,8 +173,79 @@ pub struct ProtocolHandler {
     /// Sampling manager
     sampling_manager: Arc<SamplingManager>,
 
-    /// Active requests tracking
-    active_requests: Arc<RwLock<HashMap<RequestId, tokio::time::Instant>>>,
+    /// Roots manager
+    roots_manager: Arc<RootsManager>,
+
+    /// Logging manager
+    logging_manager: Arc<LoggingManager>,
+
+    /// In-flight requests by the caller's client key and request ID
+    ///
+    /// IDs are only unique per client, so they're never looked up on their own.
+    active_requests: Arc<RwLock<HashMap<(String, RequestId), ActiveRequest>>>,
+
+    /// Time source for request tracking
+    clock: SharedClock,
+
+    /// Request counters and durations
+    metrics: Arc<RequestMetrics>,
+
+    /// Totals of handled requests, errors and notifications
+    counters: Arc<MessageCounters>,
+
+    /// Maximum number of messages accepted in one batch
+    max_batch_size: usize,
+
+    /// Number of batch messages handled concurrently
+    batch_concurrency: usize,
+
+    /// Whether non-admin requests are rejected as retryable
+    maintenance: Arc<AtomicBool>,
+
+    /// Maximum serialized size of a `tools/call` or `resources/read` result
+    max_response_size: Option<usize>,
+
+    /// What to do with results over `max_response_size`
+    response_size_policy: ResponseSizePolicy,
+
+    /// Experimental capabilities advertised to every client
+    experimental: Arc<ExperimentalCapabilities>,
+
+    /// Handlers of custom methods the handler doesn't dispatch itself
+    methods: Arc<MethodRegistry>,
+
+    /// Requests sent to the client that await its response
+    outgoing: Arc<PendingRequests>,
+
+    /// Source of the IDs of requests sent to the client
+    id_generator: SharedIdGenerator,
+
+    /// Where requests sent to the client go, if any transport accepts them
+    request_sink: Option<RequestSink>,
+
+    /// How long a request sent to the client waits for its response
+    outgoing_timeout: Duration,
+
+    /// Whether unknown notification methods are reported as protocol violations
+    strict_notifications: bool,
+
+    /// Scope -> experimental capabilities advertised to subjects holding it
+    scoped_experimental: Arc<ScopedExperimentalCapabilities>,
+
+    /// Scope -> method prefixes callers holding it are restricted to
+    scope_methods: Arc<ScopeMethodAllowlist>,
+
+    /// Where tool calls and resource reads are audited, if anywhere
+    audit_sink: Option<Arc<dyn AuditSink>>,
+
+    /// Where progress notifications for requests without a session go
+    notification_sink: Option<NotificationSink>,
+
+    /// Where progress notifications for requests on a session go
+    session_sink: Option<SessionNotificationSink>,
+
+    /// Completes once the built-in resources, tools and prompts are registered
+    setup: Arc<tokio::sync::OnceCell<()>>,
 
     /// Server initialized flag
     initialized: Arc<RwLock<bool>>,
@@ -53,24 +264,374 @@ impl ProtocolHandler {
             tool_manager,
             prompt_manager,
             sampling_manager,
+            roots_manager: Arc::new(RootsManager::new()),
+            logging_manager: Arc::new(LoggingManager::new()),
             active_requests: Arc::new(RwLock::new(HashMap::new())),
+            clock: system_clock(),
+            metrics: Arc::new(RequestMetrics::new()),
+            counters: Arc::new(MessageCounters::new()),
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+            maintenance: Arc::new(AtomicBool::new(false)),
+            max_response_size: None,
+            response_size_policy: ResponseSizePolicy::default(),
+            experimental: Arc::new(ExperimentalCapabilities::new()),
+            methods: Arc::new(MethodRegistry::new()),
+            outgoing: Arc::new(PendingRequests::new()),
+            id_generator: crate::utils::ids::id_generator(),
+            request_sink: None,
+            outgoing_timeout: DEFAULT_OUTGOING_REQUEST_TIMEOUT,
+            strict_notifications: false,
+            scoped_experimental: Arc::new(HashMap::new()),
+            scope_methods: Arc::new(HashMap::new()),
+            audit_sink: None,
+            notification_sink: None,
+            session_sink: None,
+            setup: Arc::new(tokio::sync::OnceCell::new()),
             initialized: Arc::new(RwLock::new(false)),
         };
 
-        // Initialize resources, tools, and prompts
+        // Initialize resources, tools, and prompts in the background; requests wait for it
         tokio::spawn({
             let handler = handler.clone();
-            async move {
-                if let Err(e) = handler.setup().await {
+            async move { handler.ready().await }
+        });
+
+        handler
+    }
+
+    /// Wait until the built-in resources, tools and prompts are registered
+    ///
+    /// Requests wait for this on their own; callers can await it to make
+    /// startup deterministic before accepting connections.
+    pub async fn ready(&self) {
+        self.setup
+            .get_or_init(|| async {
+                if let Err(e) = self.setup().await {
                     error!("Failed to setup resources: {}", e);
                 }
+            })
+            .await;
+    }
+
+    /// Check whether the built-in resources, tools and prompts are registered
+    pub fn is_ready(&self) -> bool {
+        self.setup.initialized()
+    }
+
+    /// Create a protocol handler serving the managers of a shared feature manager
+    pub fn from_feature_manager(
+        feature_manager: Arc<ServerFeatureManager>,
+        sampling_manager: Arc<SamplingManager>,
+    ) -> Self {
+        Self::new(
+            feature_manager.resources.clone(),
+            feature_manager.tools.clone(),
+            feature_manager.prompts.clone(),
+            sampling_manager,
+        )
+        .with_logging_manager(feature_manager.logging.clone())
+        .with_experimental_capabilities(feature_manager.experimental.clone())
+    }
+
+    /// Use the given clock for request tracking
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Serve `roots/list` from the given roots manager
+    pub fn with_roots_manager(mut self, roots_manager: Arc<RootsManager>) -> Self {
+        self.roots_manager = roots_manager;
+        self
+    }
+
+    /// Apply `logging/setLevel` to the given logging manager
+    pub fn with_logging_manager(mut self, logging_manager: Arc<LoggingManager>) -> Self {
+        self.logging_manager = logging_manager;
+        self
+    }
+
+    /// Record request metrics in the given registry
+    pub fn with_metrics(mut self, metrics: Arc<RequestMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Advertise the capabilities registered in `experimental` to every client
+    pub fn with_experimental_capabilities(
+        mut self,
+        experimental: Arc<ExperimentalCapabilities>,
+    ) -> Self {
+        self.experimental = experimental;
+        self
+    }
+
+    /// Dispatch methods unknown to the handler to the given registry
+    pub fn with_method_registry(mut self, methods: Arc<MethodRegistry>) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    /// Get the registry of custom methods
+    pub fn method_registry(&self) -> &Arc<MethodRegistry> {
+        &self.methods
+    }
+
+    /// Advertise extra experimental capabilities to subjects holding a scope
+    pub fn with_scoped_experimental(
+        mut self,
+        scoped_experimental: ScopedExperimentalCapabilities,
+    ) -> Self {
+        self.scoped_experimental = Arc::new(scoped_experimental);
+        self
+    }
+
+    /// Restrict callers holding a listed scope to the scope's method prefixes
+    ///
+    /// Callers holding none of the listed scopes are unrestricted.
+    pub fn with_scope_methods(mut self, scope_methods: ScopeMethodAllowlist) -> Self {
+        self.scope_methods = Arc::new(scope_methods);
+        self
+    }
+
+    /// Record an audit entry for every tool call and resource read
+    pub fn with_audit_sink(mut self, audit_sink: Arc<dyn AuditSink>) -> Self {
+        self.audit_sink = Some(audit_sink);
+        self
+    }
+
+    /// Deliver progress notifications of session-less requests through the given sink
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.notification_sink = Some(sink);
+        self
+    }
+
+    /// Deliver progress notifications of requests on a session through the given sink
+    pub fn with_session_sink(mut self, sink: SessionNotificationSink) -> Self {
+        self.session_sink = Some(sink);
+        self
+    }
+
+    /// Create a request to send to the client, tracked under a fresh ID
+    ///
+    /// The client's response to it is delivered to the returned receiver.
+    pub fn begin_outgoing_request(
+        &self,
+        method: impl Into<String>,
+        params: Option<Value>,
+    ) -> Result<(JsonRpcRequest, tokio::sync::oneshot::Receiver<JsonRpcResponse>)> {
+        let id = Value::String(self.id_generator.generate());
+        let receiver = self.outgoing.register(id.clone())?;
+        Ok((JsonRpcRequest::new(id, method.into(), params), receiver))
+    }
+
+    /// Generate the IDs of requests sent to the client with the given generator
+    pub fn with_id_generator(mut self, id_generator: SharedIdGenerator) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    /// Requests sent to the client that await its response
+    pub fn outgoing_requests(&self) -> &Arc<PendingRequests> {
+        &self.outgoing
+    }
+
+    /// Send requests to the client through the given sink
+    pub fn with_request_sink(mut self, sink: RequestSink) -> Self {
+        self.request_sink = Some(sink);
+        self
+    }
+
+    /// Give up on requests sent to the client after `timeout`
+    pub fn with_outgoing_request_timeout(mut self, timeout: Duration) -> Self {
+        self.outgoing_timeout = timeout;
+        self
+    }
+
+    /// Send a request to the client and wait for its result
+    pub async fn send_request(&self, method: &str, params: Option<Value>) -> Result<Value> {
+        self.send_request_with_context(method, params, &RequestContext::default())
+            .await
+    }
+
+    /// Send a request to the client behind the given context's session and wait for its result
+    pub async fn send_request_with_context(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        context: &RequestContext,
+    ) -> Result<Value> {
+        let sink = self.request_sink.as_ref().ok_or_else(|| {
+            McpError::Protocol(format!(
+                "Cannot send {}: no transport accepts server-initiated requests",
+                method
+            ))
+        })?;
+
+        let (request, receiver) = self.begin_outgoing_request(method, params)?;
+        let id = request.id.clone();
+        debug!("Sending {} request {:?} to the client", method, id);
+        if sink.send((context.session_id.clone(), request)).is_err() {
+            self.outgoing.cancel(&id);
+            return Err(TransportError::ConnectionLost(
+                "Outgoing request channel closed".to_string(),
+            )
+            .into());
+        }
+
+        let response = match tokio::time::timeout(self.outgoing_timeout, receiver).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => {
+                return Err(McpError::Protocol(format!(
+                    "Request {} reached no client or was abandoned before it answered",
+                    method
+                )))
             }
-        });
+            Err(_) => {
+                self.outgoing.cancel(&id);
+                return Err(McpError::Protocol(format!(
+                    "Request {} timed out after {:?} waiting for the client",
+                    method, self.outgoing_timeout
+                )));
+            }
+        };
 
-        handler
+        match response.error {
+            Some(error) => Err(McpError::Protocol(format!(
+                "Client rejected {}: {} ({})",
+                method, error.message, error.code
+            ))),
+            None => Ok(response.result.unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Report unknown notification methods as protocol violations, logged at
+    /// error and echoed to the client as a `notifications/message`
+    pub fn with_strict_notifications(mut self, strict: bool) -> Self {
+        self.strict_notifications = strict;
+        self
+    }
+
+    /// Build a callback delivering notifications to the client behind `context`, if any sink reaches it
+    ///
+    /// Requests without a session get none: broadcasting their notifications
+    /// would leak them to every other client.
+    fn client_notifier(&self, context: &RequestContext) -> Option<ProgressCallback> {
+        let session_id = context.session_id.clone()?;
+        let sink = self.session_sink.clone()?;
+        Some(Arc::new(move |notification| {
+            let _ = sink.send((session_id.clone(), notification));
+        }))
+    }
+
+    /// Build the progress reporter for a request, inactive unless it carries a progress token
+    fn progress_reporter(&self, request: &JsonRpcRequest, context: &RequestContext) -> ProgressReporter {
+        let Some(token) = progress_token(request.params.as_ref()) else {
+            return ProgressReporter::none();
+        };
+
+        match self.client_notifier(context) {
+            Some(deliver) => ProgressReporter::new(token, deliver),
+            None => ProgressReporter::none(),
+        }
+    }
+
+    /// Limit the serialized size of `tools/call` and `resources/read` results
+    pub fn with_max_response_size(mut self, max_response_size: Option<usize>) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    /// Set how results over the maximum response size are handled
+    pub fn with_response_size_policy(mut self, policy: ResponseSizePolicy) -> Self {
+        self.response_size_policy = policy;
+        self
+    }
+
+    /// Reject batches with more than `max_batch_size` messages
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Handle up to `batch_concurrency` messages of a batch at once
+    pub fn with_batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = batch_concurrency.max(1);
+        self
+    }
+
+    /// Start in or out of maintenance mode
+    pub fn with_maintenance_mode(self, enabled: bool) -> Self {
+        self.set_maintenance_mode(enabled);
+        self
+    }
+
+    /// Enter or leave maintenance mode
+    pub fn set_maintenance_mode(&self, enabled: bool) {
+        self.maintenance.store(enabled, Ordering::SeqCst);
+        info!("Maintenance mode {}", if enabled { "enabled" } else { "disabled" });
+    }
+
+    /// Check whether the server is in maintenance mode
+    pub fn is_in_maintenance(&self) -> bool {
+        self.maintenance.load(Ordering::SeqCst)
+    }
+
+    /// Check whether the caller's scopes permit a method
+    ///
+    /// Once any scope allowlist is configured, a method is only allowed if one
+    /// of the caller's scopes lists it; callers holding none are denied.
+    /// `initialize` and `ping` are always allowed so restricted clients can
+    /// still connect; operator (`admin/`) methods need [`ADMIN_SCOPE`].
+    pub fn is_method_allowed(&self, method: &str, context: &RequestContext) -> bool {
+        if method == "initialize" || method == "ping" {
+            return true;
+        }
+        if method.starts_with(ADMIN_METHOD_PREFIX) {
+            return context.scopes.iter().any(|scope| scope == ADMIN_SCOPE);
+        }
+
+        if self.scope_methods.is_empty() {
+            return true;
+        }
+        context.scopes.iter().any(|scope| {
+            self.scope_methods.get(scope).is_some_and(|prefixes| {
+                prefixes.iter().any(|prefix| method.starts_with(prefix.as_str()))
+            })
+        })
+    }
+
+    /// Check a batch's size against the configured limit
+    pub fn check_batch_size(&self, len: usize) -> Result<()> {
+        if len > self.max_batch_size {
+            return Err(McpError::invalid_request(format!(
+                "Batch of {} messages exceeds the limit of {}",
+                len, self.max_batch_size
+            )));
+        }
+        Ok(())
     }
 
+    /// Get the request metrics registry
+    pub fn metrics(&self) -> Arc<RequestMetrics> {
+        self.metrics.clone()
+    }
+
+    /// Get the totals of handled requests, errors and notifications
+    pub fn counters(&self) -> Arc<MessageCounters> {
+        self.counters.clone()
+    }
 
+    /// Get how long each in-flight request has been running
+    pub async fn active_request_durations(&self) -> Vec<(RequestId, Duration)> {
+        let now = self.clock.now();
+        let active = self.active_requests.read().await;
+        active
+            .iter()
+            .map(|((_, id), request)| (id.clone(), now.saturating_duration_since(request.started)))
+            .collect()
+    }
 
     /// Register production tools dynamically using available tool handlers
     async fn register_tools(&self) -> Result<()> {
@@ -113,12 +674,34 @@ impl ProtocolHandler {
             info!("Registered file system resource provider for local file access");
         }
 
-        // Register HTTP resource provider for web resource access
-        let http_provider = Box::new(crate::server::features::resources::HttpProvider::new());
-        if let Err(e) = self.resource_manager.register_provider(http_provider).await {
-            error!("Failed to register HTTP resource provider: {}", e);
-        } else {
-            info!("Registered HTTP resource provider for web resource access");
+        // Register HTTP resource provider for web resource access, if the operator opted in
+        if self.resource_manager.http_provider_enabled() {
+            let http_provider = Box::new(
+                crate::server::features::resources::HttpProvider::new().with_content_sniffing(
+                    self.resource_manager.http_content_sniffing_enabled(),
+                ),
+            );
+            if let Err(e) = self.resource_manager.register_provider(http_provider).await {
+                error!("Failed to register HTTP resource provider: {}", e);
+            } else {
+                info!("Registered HTTP resource provider for web resource access");
+            }
+        }
+
+        // Register resources declared in the configured manifest
+        match self.resource_manager.load_configured_manifest().await {
+            Ok(Some(report)) => {
+                info!(
+                    "Loaded resource manifest: {} registered, {} rejected",
+                    report.registered.len(),
+                    report.rejected.len()
+                );
+                for (index, reason) in &report.rejected {
+                    error!("Malformed resource manifest entry {}: {}", index, reason);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => error!("Failed to load resource manifest: {}", e),
         }
 
         // Register all  tools dynamically
@@ -185,26 +768,61 @@ impl ProtocolHandler {
     pub async fn handle_message(
         &self,
         message: AnyJsonRpcMessage,
+    ) -> Result<Option<AnyJsonRpcMessage>> {
+        self.handle_message_with_context(message, &RequestContext::default())
+            .await
+    }
+
+    /// Handle an incoming message with transport-supplied context
+    pub async fn handle_message_with_context(
+        &self,
+        message: AnyJsonRpcMessage,
+        context: &RequestContext,
     ) -> Result<Option<AnyJsonRpcMessage>> {
         match message {
             AnyJsonRpcMessage::Request(request) => {
-                let response = self.handle_request(request).await?;
+                let response = self.handle_request_with_context(request, context).await?;
                 Ok(Some(AnyJsonRpcMessage::Response(response)))
             }
             AnyJsonRpcMessage::Notification(notification) => {
-                self.handle_notification(notification).await?;
+                self.handle_notification(notification, context).await?;
                 Ok(None)
             }
             AnyJsonRpcMessage::Response(response) => {
                 self.handle_response(response).await?;
                 Ok(None)
             }
-            AnyJsonRpcMessage::Batch(batch) => self.handle_batch(batch).await,
+            AnyJsonRpcMessage::Batch(batch) => self.handle_batch(batch, context).await,
         }
     }
 
     /// Handle a JSON-RPC request
     pub async fn handle_request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
+        self.handle_request_with_context(request, &RequestContext::default())
+            .await
+    }
+
+    /// Handle a JSON-RPC request with transport-supplied context
+    pub async fn handle_request_with_context(
+        &self,
+        request: JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<JsonRpcResponse> {
+        self.ready().await;
+        let response = self.dispatch_request(request, context).await;
+        let is_error = response
+            .as_ref()
+            .map_or(true, |response| response.error.is_some());
+        self.counters.record_request(is_error);
+        response
+    }
+
+    /// Validate a request and route it to its method handler
+    async fn dispatch_request(
+        &self,
+        request: JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<JsonRpcResponse> {
         info!(
             "Handling request: {} (id: {:?})",
             request.method, request.id
@@ -212,28 +830,52 @@ impl ProtocolHandler {
 
         // Validate the request
         validation::validate_request(&request)?;
-        validation::validate_method_name(&request.method)?;
+        if !self.methods.contains(&request.method) {
+            validation::validate_method_name(&request.method)?;
+        }
 
         // Track the request
+        let cancel = CancellationToken::new();
+        let active_key = (context.client_key().to_string(), request.id.clone());
         {
             let mut active = self.active_requests.write().await;
-            active.insert(request.id.clone(), tokio::time::Instant::now());
+            active.insert(
+                active_key.clone(),
+                ActiveRequest {
+                    started: self.clock.now(),
+                    cancel: cancel.clone(),
+                },
+            );
         }
 
+        let in_maintenance = self.is_in_maintenance()
+            && request.method != "ping"
+            && !request.method.starts_with(ADMIN_METHOD_PREFIX);
+
         let result = match request.method.as_str() {
-            "initialize" => self.handle_initialize(&request).await,
+            _ if in_maintenance => Err(McpError::maintenance(
+                "Server is temporarily in maintenance, retry later",
+            )),
+            _ if !self.is_method_allowed(&request.method, context) => {
+                Err(McpError::forbidden(request.method.clone()))
+            }
+
+            "initialize" => self.handle_initialize(&request, context).await,
             "ping" => self.handle_ping(&request).await,
 
             // Resource methods
             "resources/list" => self.handle_resources_list(&request).await,
             "resources/templates/list" => self.handle_resource_templates_list(&request).await,
-            "resources/read" => self.handle_resources_read(&request).await,
-            "resources/subscribe" => self.handle_resources_subscribe(&request).await,
-            "resources/unsubscribe" => self.handle_resources_unsubscribe(&request).await,
+            "resources/read" => self.handle_resources_read(&request, context, &cancel).await,
+            "resources/stat" => self.handle_resources_stat(&request).await,
+            "resources/subscribe" => self.handle_resources_subscribe(&request, context).await,
+            "resources/unsubscribe" => {
+                self.handle_resources_unsubscribe(&request, context).await
+            }
 
             // Tool methods
             "tools/list" => self.handle_tools_list(&request).await,
-            "tools/call" => self.handle_tools_call(&request).await,
+            "tools/call" => self.handle_tools_call(&request, context).await,
 
             // Prompt methods
             "prompts/list" => self.handle_prompts_list(&request).await,
@@ -243,7 +885,7 @@ impl ProtocolHandler {
             "sampling/createMessage" => self.handle_sampling_create_message(&request).await,
 
             // Logging methods
-            "logging/setLevel" => self.handle_logging_set_level(&request).await,
+            "logging/setLevel" => self.handle_logging_set_level(&request, context).await,
 
             // Completion methods
             "completion/complete" => self.handle_completion_complete(&request).await,
@@ -251,13 +893,54 @@ impl ProtocolHandler {
             // Roots methods
             "roots/list" => self.handle_roots_list(&request).await,
 
+            // Admin methods
+            "admin/maintenance" => self.handle_admin_maintenance(&request).await,
+
+            // Custom methods, once the client has initialized
+            method if self.methods.contains(method) => {
+                self.metrics.register_method(method);
+                match self.check_initialized().await {
+                    Ok(()) => self
+                        .methods
+                        .call(method, request.params.clone(), context)
+                        .await
+                        .unwrap_or_else(|| Err(McpError::method_not_found(method))),
+                    Err(e) => Err(e),
+                }
+            }
+
             _ => Err(McpError::method_not_found(&request.method)),
         };
 
         // Remove from active requests
-        {
+        let started = {
             let mut active = self.active_requests.write().await;
-            active.remove(&request.id);
+            active.remove(&active_key).map(|request| request.started)
+        };
+
+        if let Some(started) = started {
+            let duration = self.clock.now().saturating_duration_since(started);
+            self.metrics
+                .record_request(&request.method, duration, result.is_err());
+        }
+
+        if let Some(sink) = &self.audit_sink {
+            if AUDITED_METHODS.contains(&request.method.as_str()) {
+                let outcome = match &result {
+                    Ok(_) => AuditOutcome::Success,
+                    Err(error) => AuditOutcome::Error {
+                        code: error.to_json_rpc_code(),
+                        message: error.to_string(),
+                    },
+                };
+                sink.record(&AuditRecord::new(
+                    context.principal.clone().or_else(|| context.client_id.clone()),
+                    context.session_id.clone(),
+                    &request.method,
+                    request.params.as_ref(),
+                    outcome,
+                ));
+            }
         }
 
         match result {
@@ -270,8 +953,13 @@ impl ProtocolHandler {
     }
 
     /// Handle a JSON-RPC notification
-    async fn handle_notification(&self, notification: JsonRpcNotification) -> Result<()> {
+    async fn handle_notification(
+        &self,
+        notification: JsonRpcNotification,
+        context: &RequestContext,
+    ) -> Result<()> {
         info!("Handling notification: {}", notification.method);
+        self.counters.record_notification();
 
         // Validate the notification
         validation::validate_notification(&notification)?;
@@ -281,7 +969,10 @@ impl ProtocolHandler {
             "notifications/initialized" => {
                 self.handle_initialized_notification(&notification).await
             }
-            "notifications/cancelled" => self.handle_cancelled_notification(&notification).await,
+            "notifications/cancelled" => {
+                self.handle_cancelled_notification(&notification, context)
+                    .await
+            }
             "notifications/progress" => self.handle_progress_notification(&notification).await,
             "notifications/resources/list_changed" => {
                 self.handle_resource_list_changed_notification(&notification)
@@ -305,12 +996,61 @@ impl ProtocolHandler {
             }
             "notifications/message" => self.handle_message_notification(&notification).await,
             _ => {
-                error!("Unknown notification method: {}", notification.method);
+                self.handle_unknown_notification(&notification, context)
+                    .await;
                 Ok(())
             }
         }
     }
 
+    /// Log a notification nobody handles, reporting it to the client in strict mode
+    async fn handle_unknown_notification(
+        &self,
+        notification: &JsonRpcNotification,
+        context: &RequestContext,
+    ) {
+        if !self.strict_notifications {
+            warn!("Ignoring unknown notification method: {}", notification.method);
+            return;
+        }
+
+        // Outside `notifications/`, it's most likely a request sent without an ID
+        let hint = if notification.method.starts_with("notifications/") {
+            ""
+        } else {
+            " (a request missing its id?)"
+        };
+        // Params may carry client data, so only their shape is logged
+        let params = match &notification.params {
+            None => "none".to_string(),
+            Some(serde_json::Value::Object(map)) => {
+                format!("keys {:?}", map.keys().collect::<Vec<_>>())
+            }
+            Some(value) => format!("{} bytes", value.to_string().len()),
+        };
+        error!(
+            "Protocol violation: unknown notification method {}{} from {}, params: {}",
+            notification.method,
+            hint,
+            context.client_key(),
+            params
+        );
+
+        if let Some(session_id) = &context.session_id {
+            self.logging_manager
+                .log_to_session(
+                    session_id,
+                    LoggingLevel::Error,
+                    Some("protocol".to_string()),
+                    serde_json::json!({
+                        "error": format!("Unknown notification method{}", hint),
+                        "method": notification.method,
+                    }),
+                )
+                .await;
+        }
+    }
+
     /// Handle a JSON-RPC response
     async fn handle_response(&self, response: JsonRpcResponse) -> Result<()> {
         info!("Handling response for request: {:?}", response.id);
@@ -318,16 +1058,6 @@ impl ProtocolHandler {
         // Validate the response
         validation::validate_response(&response)?;
 
-        // Check if this was an active request
-        let was_active = {
-            let active = self.active_requests.read().await;
-            active.contains_key(&response.id)
-        };
-
-        if !was_active {
-            error!("Received response for unknown request: {:?}", response.id);
-        }
-
         if let Some(error) = &response.error {
             error!(
                 "Request {:?} failed with error: {}",
@@ -337,27 +1067,39 @@ impl ProtocolHandler {
             info!("Request {:?} completed successfully", response.id);
         }
 
+        // Hand the response to whoever sent the request
+        let id = response.id.clone();
+        if !self.outgoing.complete(response) {
+            warn!("Received response for unknown request: {:?}", id);
+        }
+
         Ok(())
     }
 
     /// Handle a batch of messages
-    async fn handle_batch(&self, batch: Vec<Value>) -> Result<Option<AnyJsonRpcMessage>> {
+    async fn handle_batch(
+        &self,
+        batch: Vec<Value>,
+        context: &RequestContext,
+    ) -> Result<Option<AnyJsonRpcMessage>> {
         info!("Handling batch of {} messages", batch.len());
 
         if batch.is_empty() {
             return Err(McpError::invalid_request("Batch cannot be empty"));
         }
+        self.check_batch_size(batch.len())?;
 
-        let mut responses = Vec::new();
-
-        for item in batch {
-            let message: AnyJsonRpcMessage =
-                serde_json::from_value(item).map_err(|e| McpError::parse_error(e.to_string()))?;
+        let messages = batch
+            .into_iter()
+            .map(|item| {
+                serde_json::from_value(item).map_err(|e| McpError::parse_error(e.to_string()))
+            })
+            .collect::<Result<Vec<AnyJsonRpcMessage>>>()?;
 
-            if let Some(response) = Box::pin(self.handle_message(message)).await? {
-                if let AnyJsonRpcMessage::Response(resp) = response {
-                    responses.push(serde_json::to_value(resp)?);
-                }
+        let mut responses = Vec::new();
+        for result in self.handle_batch_messages(messages, context).await {
+            if let Some(AnyJsonRpcMessage::Response(response)) = result? {
+                responses.push(serde_json::to_value(response)?);
             }
         }
 
@@ -368,6 +1110,45 @@ impl ProtocolHandler {
         }
     }
 
+    /// Handle the messages of a batch concurrently, up to the configured bound
+    ///
+    /// Results come back in completion order; responses carry their request IDs.
+    pub async fn handle_batch_messages(
+        &self,
+        messages: Vec<AnyJsonRpcMessage>,
+        context: &RequestContext,
+    ) -> Vec<Result<Option<AnyJsonRpcMessage>>> {
+        futures_util::stream::iter(messages)
+            .map(|message| Box::pin(self.handle_message_with_context(message, context)))
+            .buffer_unordered(self.batch_concurrency)
+            .collect()
+            .await
+    }
+
+    /// Release per-client state when a transport session ends
+    pub async fn handle_session_closed(&self, session_id: &str) {
+        let removed = self.resource_manager.unsubscribe_all(session_id).await;
+        if removed > 0 {
+            info!(
+                "Removed {} resource subscriptions for closed session {}",
+                removed, session_id
+            );
+        }
+        if self.logging_manager.remove_session(session_id).await {
+            self.counters.record_session_closed();
+        }
+    }
+
+    /// Apply the maximum response size to a result with content under `items_key`
+    fn limit_response_size(&self, result: &mut Value, items_key: &str) -> Result<()> {
+        match self.max_response_size {
+            Some(limit) => {
+                enforce_response_size(result, items_key, limit, self.response_size_policy)
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Check if the server is initialized
     async fn check_initialized(&self) -> Result<()> {
         let initialized = *self.initialized.read().await;
@@ -377,7 +1158,11 @@ impl ProtocolHandler {
         Ok(())
     }
 
-    async fn handle_initialize(&self, request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_initialize(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<Value> {
         info!("Handling initialize request");
 
         // Parse initialize request parameters
@@ -400,6 +1185,13 @@ impl ProtocolHandler {
             );
         }
 
+        // Log messages are addressed to the session at its own level
+        if let Some(session_id) = context.session_id.as_deref() {
+            if self.logging_manager.add_session(session_id).await {
+                self.counters.record_session_opened();
+            }
+        }
+
         // Build server capabilities based on available features
         let mut server_capabilities = crate::protocol::ServerCapabilities {
             experimental: None,
@@ -432,6 +1224,33 @@ impl ProtocolHandler {
             });
         }
 
+        // Advertise pagination limits so clients don't have to guess page sizes
+        let max_page_size = [
+            self.resource_manager.page_size(),
+            self.tool_manager.page_size(),
+            self.prompt_manager.page_size(),
+        ]
+        .into_iter()
+        .min()
+        .unwrap_or(crate::server::features::DEFAULT_PAGE_SIZE);
+        let mut experimental = HashMap::new();
+        experimental.insert(
+            "pagination".to_string(),
+            serde_json::json!({
+                "maxPageSize": max_page_size,
+                "opaqueCursors": true
+            }),
+        );
+
+        // Registered capabilities, then extras for the scopes the client authenticated with
+        experimental.extend(self.experimental.snapshot().await);
+        for scope in &context.scopes {
+            if let Some(capabilities) = self.scoped_experimental.get(scope) {
+                experimental.extend(capabilities.clone());
+            }
+        }
+        server_capabilities.experimental = Some(experimental);
+
         // Create initialize result
         let init_result = crate::protocol::InitializeResult {
             protocol_version: crate::protocol::PROTOCOL_VERSION.to_string(),
@@ -529,7 +1348,12 @@ impl ProtocolHandler {
         Ok(response)
     }
 
-    async fn handle_resources_read(&self, request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_resources_read(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+        cancel: &CancellationToken,
+    ) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling resources/read request");
 
@@ -545,19 +1369,116 @@ impl ProtocolHandler {
 
         info!("Reading resource: {}", uri);
 
-        // Read resource contents from resource manager
-        let contents = self.resource_manager.read_resource(uri).await?;
-
+        // Clients that opted in get text as it is read, when the transport can stream
+        let stream = context
+            .resource_stream
+            .as_ref()
+            .filter(|_| requests_streamed_read(request));
+        if let Some(stream) = stream {
+            if let Some(chunks) = self
+                .resource_manager
+                .read_text_chunks(uri, stream.chunk_size)
+                .await?
+            {
+                return self.stream_resource_read(uri, chunks, stream, cancel).await;
+            }
+        }
+
+        // Read resource contents from resource manager
+        let contents = self.resource_manager.read_resource(uri, Some(cancel)).await?;
+        let hash = crate::server::features::resources::contents_hash(&contents);
+
+        // Conditional read: skip the contents if the client already has them
+        let known_hash = params.get("ifNoneMatch").and_then(|v| v.as_str());
+        if known_hash == Some(hash.as_str()) {
+            info!("Resource {} not modified", uri);
+            return Ok(serde_json::json!({
+                "contents": [],
+                "hash": hash,
+                "notModified": true
+            }));
+        }
+
         // Build response
-        let response = serde_json::json!({
-            "contents": contents
+        let contents: Vec<_> = contents.into_iter().map(|c| c.with_hash()).collect();
+        let mut response = serde_json::json!({
+            "contents": contents,
+            "hash": hash
         });
+        self.limit_response_size(&mut response, "contents")?;
 
         info!("Successfully read resource: {}", uri);
         Ok(response)
     }
 
-    async fn handle_resources_subscribe(&self, request: &JsonRpcRequest) -> Result<Value> {
+    /// Send a resource's text to the client chunk by chunk, within the response size limit
+    async fn stream_resource_read(
+        &self,
+        uri: &str,
+        mut chunks: TextChunkStream,
+        stream: &ResourceStream,
+        cancel: &CancellationToken,
+    ) -> Result<Value> {
+        info!("Streaming resource: {}", uri);
+        let mut count = 0usize;
+        let mut size = 0usize;
+        loop {
+            let chunk = tokio::select! {
+                _ = cancel.cancelled() => {
+                    return Err(McpError::cancelled(format!("Read of {} was cancelled", uri)));
+                }
+                chunk = futures_util::StreamExt::next(&mut chunks) => chunk,
+            };
+            let chunk = match chunk {
+                Some(chunk) => chunk?,
+                None => break,
+            };
+
+            size += chunk.len();
+            if let Some(limit) = self.max_response_size {
+                if size > limit {
+                    return Err(McpError::response_too_large(size, limit));
+                }
+            }
+            if stream.sender.send(chunk).await.is_err() {
+                return Err(McpError::cancelled(format!(
+                    "Client stopped receiving {}",
+                    uri
+                )));
+            }
+            count += 1;
+        }
+
+        info!("Successfully streamed resource: {} in {} chunks", uri, count);
+        Ok(serde_json::json!({
+            "contents": [],
+            "_meta": { "chunks": count }
+        }))
+    }
+
+    async fn handle_resources_stat(&self, request: &JsonRpcRequest) -> Result<Value> {
+        self.check_initialized().await?;
+        info!("Handling resources/stat request");
+
+        let params = request.params.as_ref().ok_or_else(|| {
+            McpError::invalid_params("resources/stat request requires parameters")
+        })?;
+
+        let uri = params
+            .get("uri")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| McpError::invalid_params("Missing or invalid 'uri' parameter"))?;
+
+        let metadata = self.resource_manager.stat_resource(uri).await?;
+
+        Ok(serde_json::to_value(metadata)?)
+    }
+
+    async fn handle_resources_subscribe(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling resources/subscribe request");
 
@@ -571,7 +1492,7 @@ impl ProtocolHandler {
             .and_then(|v| v.as_str())
             .ok_or_else(|| McpError::invalid_params("Missing or invalid 'uri' parameter"))?;
 
-        let client_id = "default-client";
+        let client_id = context.client_key();
 
         info!("Subscribing to resource: {}", uri);
 
@@ -585,7 +1506,11 @@ impl ProtocolHandler {
         Ok(response)
     }
 
-    async fn handle_resources_unsubscribe(&self, request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_resources_unsubscribe(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling resources/unsubscribe request");
 
@@ -599,7 +1524,7 @@ impl ProtocolHandler {
             .and_then(|v| v.as_str())
             .ok_or_else(|| McpError::invalid_params("Missing or invalid 'uri' parameter"))?;
 
-        let client_id = "default-client";
+        let client_id = context.client_key();
 
         info!("Unsubscribing from resource: {}", uri);
 
@@ -647,7 +1572,11 @@ impl ProtocolHandler {
         Ok(response)
     }
 
-    async fn handle_tools_call(&self, request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_tools_call(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling tools/call request");
 
@@ -669,14 +1598,41 @@ impl ProtocolHandler {
 
         info!("Calling tool: {} with arguments: {:?}", name, arguments);
 
-        // Call tool through tool manager
-        let result = self.tool_manager.call_tool(name, arguments).await?;
+        // Retries carrying the same idempotency key are answered once per
+        // client; without a session or client ID, callers can't be told apart
+        let client = context.client_id.as_deref().or(context.session_id.as_deref());
+        let idempotency_key = params
+            .get("_meta")
+            .and_then(|meta| meta.get("idempotencyKey"))
+            .and_then(|key| key.as_str())
+            .or_else(|| {
+                context
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.headers.get("idempotency-key"))
+                    .map(String::as_str)
+            })
+            .and_then(|key| match client {
+                Some(client) => Some(format!("{}/{}", client, key)),
+                None => {
+                    debug!("Ignoring idempotency key of a request without a session");
+                    None
+                }
+            });
+
+        // Call tool through tool manager, forwarding any progress it reports
+        let progress = self.progress_reporter(request, context);
+        let result = self
+            .tool_manager
+            .call_tool_idempotent(name, arguments, progress, idempotency_key.as_deref())
+            .await?;
 
         // Build response
-        let response = serde_json::json!({
+        let mut response = serde_json::json!({
             "content": result.content,
             "isError": result.is_error
         });
+        self.limit_response_size(&mut response, "content")?;
 
         info!("Tool call completed: {}", name);
         Ok(response)
@@ -773,7 +1729,11 @@ impl ProtocolHandler {
         Ok(response)
     }
 
-    async fn handle_logging_set_level(&self, request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_logging_set_level(
+        &self,
+        request: &JsonRpcRequest,
+        context: &RequestContext,
+    ) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling logging/setLevel request");
 
@@ -782,43 +1742,86 @@ impl ProtocolHandler {
             McpError::invalid_params("logging/setLevel request requires parameters")
         })?;
 
-        let level = params
+        let level: LoggingLevel = params
             .get("level")
-            .and_then(|v| v.as_str())
+            .cloned()
+            .and_then(|v| serde_json::from_value(v).ok())
             .ok_or_else(|| McpError::invalid_params("Missing or invalid 'level' parameter"))?;
 
-        info!("Setting log level to: {}", level);
+        match context.session_id.as_deref() {
+            Some(session_id) => {
+                self.logging_manager
+                    .set_session_level(session_id, level)
+                    .await?
+            }
+            None => {
+                info!("Setting log level to: {:?}", level);
+                self.logging_manager.set_level(level).await?
+            }
+        }
 
         let response = serde_json::json!({});
 
         Ok(response)
     }
 
-    async fn handle_completion_complete(&self, _request: &JsonRpcRequest) -> Result<Value> {
+    async fn handle_completion_complete(&self, request: &JsonRpcRequest) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling completion/complete request");
 
-        let response = serde_json::json!({
-            "completion": {
-                "values": [],
-                "total": 0,
-                "hasMore": false
-            }
-        });
+        let params = request
+            .params
+            .as_ref()
+            .ok_or_else(|| McpError::invalid_params("Completion request requires parameters"))?;
+        let complete_request: crate::protocol::CompleteRequest =
+            serde_json::from_value(params.clone()).map_err(|e| {
+                McpError::invalid_params(format!("Invalid completion parameters: {}", e))
+            })?;
+        debug!(
+            "Completing argument {} for {:?}",
+            complete_request.argument.name, complete_request.reference
+        );
 
-        Ok(response)
+        let result = crate::protocol::CompleteResult {
+            completion: crate::protocol::Completion {
+                values: Vec::new(),
+                total: Some(0),
+                has_more: Some(false),
+            },
+        };
+
+        Ok(serde_json::to_value(result)?)
+    }
+
+    async fn handle_admin_maintenance(&self, request: &JsonRpcRequest) -> Result<Value> {
+        let enabled = request.params.as_ref().and_then(|params| params.get("enabled"));
+        if let Some(enabled) = enabled {
+            let enabled = enabled
+                .as_bool()
+                .ok_or_else(|| McpError::invalid_params("'enabled' must be a boolean"))?;
+            self.set_maintenance_mode(enabled);
+        }
+
+        Ok(serde_json::json!({ "maintenance": self.is_in_maintenance() }))
     }
 
     async fn handle_roots_list(&self, _request: &JsonRpcRequest) -> Result<Value> {
         self.check_initialized().await?;
         info!("Handling roots/list request");
 
-        let response = serde_json::json!({
-            "roots": []
-        });
-
-        info!("Returning empty roots list");
-        Ok(response)
+        let roots: Vec<crate::protocol::Root> = self
+            .roots_manager
+            .list_roots()
+            .await?
+            .into_iter()
+            .map(|root| crate::protocol::Root {
+                uri: root.uri,
+                name: root.name,
+            })
+            .collect();
+
+        info!("Returning {} roots", roots.len());
+        Ok(serde_json::json!({ "roots": roots }))
     }
 
     // Notification handlers
@@ -832,16 +1835,26 @@ impl ProtocolHandler {
         Ok(())
     }
 
+    /// Cancel one of the caller's own in-flight requests
     async fn handle_cancelled_notification(
         &self,
         notification: &JsonRpcNotification,
+        context: &RequestContext,
     ) -> Result<()> {
-        // Handle request cancellation
         if let Some(params) = &notification.params {
             if let Some(request_id) = params.get("requestId") {
+                let key = (context.client_key().to_string(), request_id.clone());
                 let mut active = self.active_requests.write().await;
-                active.remove(request_id);
-                info!("Request {:?} cancelled", request_id);
+                if let Some(request) = active.remove(&key) {
+                    request.cancel.cancel();
+                    info!("Request {:?} cancelled", request_id);
+                } else {
+                    debug!(
+                        "Ignoring cancellation of unknown request {:?} from {}",
+                        request_id,
+                        context.client_key()
+                    );
+                }
             }
         }
         Ok(())
@@ -901,3 +1914,1303 @@ impl ProtocolHandler {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_handler(resource_manager: Arc<ResourceManager>) -> ProtocolHandler {
+        ProtocolHandler::new(
+            resource_manager,
+            Arc::new(ToolManager::new()),
+            Arc::new(PromptManager::new()),
+            Arc::new(SamplingManager::new()),
+        )
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            method: method.to_string(),
+            params: Some(params),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_tracked_per_client() {
+        let resource_manager = Arc::new(ResourceManager::with_config(
+            crate::server::features::resources::ResourcesConfig {
+                allow_subscribe_to_missing: true,
+                ..Default::default()
+            },
+        ));
+        let handler = test_handler(resource_manager.clone());
+        *handler.initialized.write().await = true;
+
+        let alice = RequestContext {
+            session_id: Some("session-a".to_string()),
+            client_id: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let bob = RequestContext::with_session("session-b");
+        let params = serde_json::json!({"uri": "test://shared"});
+
+        for (id, context) in [(1, &alice), (2, &bob)] {
+            let response = handler
+                .handle_request_with_context(request(id, "resources/subscribe", params.clone()), context)
+                .await
+                .unwrap();
+            assert!(response.error.is_none());
+        }
+        assert_eq!(resource_manager.get_client_subscription_count("alice").await, 1);
+        assert_eq!(resource_manager.get_client_subscription_count("session-b").await, 1);
+
+        // Unsubscribing one client leaves the other subscribed
+        handler
+            .handle_request_with_context(request(3, "resources/unsubscribe", params.clone()), &alice)
+            .await
+            .unwrap();
+        assert_eq!(resource_manager.get_client_subscription_count("alice").await, 0);
+        assert_eq!(resource_manager.get_client_subscription_count("session-b").await, 1);
+
+        handler
+            .handle_request_with_context(request(4, "resources/unsubscribe", params), &bob)
+            .await
+            .unwrap();
+        assert_eq!(resource_manager.get_subscription_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_first_request_sees_builtin_features() {
+        let tool_manager = Arc::new(ToolManager::new());
+        let handler = ProtocolHandler::new(
+            Arc::new(ResourceManager::new()),
+            tool_manager.clone(),
+            Arc::new(PromptManager::new()),
+            Arc::new(SamplingManager::new()),
+        );
+        *handler.initialized.write().await = true;
+        assert!(!handler.is_ready());
+
+        // Served right after construction, before the setup task had a chance to run
+        let response = handler
+            .handle_request(request(1, "tools/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(handler.is_ready());
+        let tools = response.result.unwrap()["tools"].as_array().unwrap().len();
+        assert!(tools > 0);
+        assert_eq!(tools, tool_manager.get_tool_count().await);
+    }
+
+    #[tokio::test]
+    async fn test_setup_registers_builtin_tool_handlers() {
+        let tool_manager = Arc::new(ToolManager::new());
+        let handler = ProtocolHandler::new(
+            Arc::new(ResourceManager::new()),
+            tool_manager.clone(),
+            Arc::new(PromptManager::new()),
+            Arc::new(SamplingManager::new()),
+        );
+        handler.ready().await;
+
+        let builtin = crate::server::features::tools::get_tool_handlers();
+        assert!(!builtin.is_empty());
+        for tool_handler in builtin {
+            assert!(
+                tool_manager.get_tool(tool_handler.name()).await.is_some(),
+                "{} not registered",
+                tool_handler.name()
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_provider_registered_only_when_enabled() {
+        let handler = test_handler(Arc::new(ResourceManager::new()));
+        handler.ready().await;
+        assert!(handler.resource_manager.has_provider("filesystem").await);
+        assert!(!handler.resource_manager.has_provider("http").await);
+
+        let handler = test_handler(Arc::new(ResourceManager::with_config(
+            crate::server::features::resources::ResourcesConfig {
+                enable_http_provider: true,
+                ..Default::default()
+            },
+        )));
+        handler.ready().await;
+        assert!(handler.resource_manager.has_provider("http").await);
+    }
+
+    #[tokio::test]
+    async fn test_custom_method_dispatched_through_registry() {
+        let handler = test_handler(Arc::new(ResourceManager::new()));
+        *handler.initialized.write().await = true;
+        handler
+            .method_registry()
+            .register("x-echo/reverse", |params: Option<Value>, _context| async move {
+                let text = params
+                    .as_ref()
+                    .and_then(|params| params.get("text"))
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| McpError::invalid_params("Missing 'text'"))?;
+                Ok(serde_json::json!({ "text": text.chars().rev().collect::<String>() }))
+            })
+            .unwrap();
+
+        let response = handler
+            .handle_request(request(1, "x-echo/reverse", serde_json::json!({ "text": "abc" })))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["text"], "cba");
+
+        let response = handler
+            .handle_request(request(2, "x-echo/reverse", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, -32602);
+
+        // Registering twice fails; unknown methods are still not found
+        assert!(handler
+            .method_registry()
+            .register("x-echo/reverse", |_, _| async { Ok(Value::Null) })
+            .is_err());
+        let result = handler
+            .handle_request(request(3, "x-echo/unknown", serde_json::json!({})))
+            .await;
+        assert!(matches!(result, Err(McpError::MethodNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_registered_experimental_capability_advertised() {
+        let feature_manager = Arc::new(ServerFeatureManager::new());
+        feature_manager
+            .experimental
+            .register("acme/search", &serde_json::json!({ "methods": ["acme/search"] }))
+            .await
+            .unwrap();
+        let handler = ProtocolHandler::from_feature_manager(
+            feature_manager.clone(),
+            Arc::new(SamplingManager::new()),
+        );
+
+        let params = serde_json::json!({
+            "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "test", "version": "1.0"}
+        });
+        let response = handler
+            .handle_request(request(1, "initialize", params))
+            .await
+            .unwrap();
+        let experimental = &response.result.unwrap()["capabilities"]["experimental"];
+        assert_eq!(experimental["acme/search"]["methods"][0], "acme/search");
+        assert!(experimental.get("pagination").is_some());
+
+        let capabilities = feature_manager.get_capabilities().await;
+        assert!(capabilities.experimental.unwrap().contains_key("acme/search"));
+    }
+
+    #[tokio::test]
+    async fn test_shared_feature_manager_tools_visible() {
+        let feature_manager = Arc::new(ServerFeatureManager::new());
+        let handler = ProtocolHandler::from_feature_manager(
+            feature_manager.clone(),
+            Arc::new(SamplingManager::new()),
+        );
+        *handler.initialized.write().await = true;
+
+        feature_manager
+            .tools
+            .register_tool(crate::protocol::Tool {
+                name: "shared_tool".to_string(),
+                description: None,
+                input_schema: crate::protocol::ToolInputSchema {
+                    schema_type: "object".to_string(),
+                    properties: None,
+                    required: None,
+                },
+                annotations: None,
+            })
+            .await
+            .unwrap();
+
+        let response = handler
+            .handle_request(request(1, "tools/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        let tools = response.result.unwrap()["tools"].clone();
+        assert!(tools
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tool| tool["name"] == "shared_tool"));
+    }
+
+    #[tokio::test]
+    async fn test_set_level_filters_log_notifications() {
+        let (sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let feature_manager = Arc::new(ServerFeatureManager::new().with_logging_manager(
+            Arc::new(LoggingManager::new().with_notification_sink(sink)),
+        ));
+        let handler = ProtocolHandler::from_feature_manager(
+            feature_manager.clone(),
+            Arc::new(SamplingManager::new()),
+        );
+        *handler.initialized.write().await = true;
+
+        handler
+            .handle_request(request(1, "logging/setLevel", serde_json::json!({ "level": "error" })))
+            .await
+            .unwrap();
+        assert!(handler
+            .handle_request(request(2, "logging/setLevel", serde_json::json!({ "level": "loud" })))
+            .await
+            .unwrap()
+            .error
+            .is_some());
+
+        feature_manager.logging.warning(None, "ignored").await;
+        feature_manager.logging.error(None, "disk full").await;
+
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.method, "notifications/message");
+        let params = notification.params.unwrap();
+        assert_eq!(params["level"], "error");
+        assert_eq!(params["data"], "disk full");
+        assert!(notifications.try_recv().is_err());
+    }
+
+    /// Tool reporting two progress steps before finishing
+    struct SteppedTool;
+
+    #[async_trait::async_trait]
+    impl crate::server::features::tools::ToolHandler for SteppedTool {
+        fn name(&self) -> &str {
+            "stepped"
+        }
+
+        fn input_schema(&self) -> crate::protocol::ToolInputSchema {
+            crate::protocol::ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        async fn execute(
+            &self,
+            arguments: Option<Value>,
+        ) -> Result<crate::server::features::tools::ToolResult> {
+            self.execute_with_progress(arguments, ProgressReporter::none())
+                .await
+        }
+
+        async fn execute_with_progress(
+            &self,
+            _arguments: Option<Value>,
+            progress: ProgressReporter,
+        ) -> Result<crate::server::features::tools::ToolResult> {
+            progress.report(1.0, Some(2.0), Some("halfway"));
+            progress.report(2.0, Some(2.0), None);
+            Ok(crate::server::features::tools::ToolResult::text("done".to_string()))
+        }
+    }
+
+    /// Tool counting its executions, deduplicating keyed calls
+    struct CountingTool(Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait::async_trait]
+    impl crate::server::features::tools::ToolHandler for CountingTool {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn input_schema(&self) -> crate::protocol::ToolInputSchema {
+            crate::protocol::ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: Option<Value>,
+        ) -> Result<crate::server::features::tools::ToolResult> {
+            let run = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            Ok(crate::server::features::tools::ToolResult::text(format!("run {}", run)))
+        }
+
+        fn deduplicate_calls(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_key_executes_tool_once() {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tool_manager = Arc::new(ToolManager::new());
+        tool_manager
+            .register_handler_with_tool(Box::new(CountingTool(runs.clone())))
+            .await
+            .unwrap();
+        let handler = ProtocolHandler::new(
+            Arc::new(ResourceManager::new()),
+            tool_manager,
+            Arc::new(PromptManager::new()),
+            Arc::new(SamplingManager::new()),
+        );
+        *handler.initialized.write().await = true;
+
+        let keyed = |key: &str| {
+            serde_json::json!({ "name": "counting", "_meta": { "idempotencyKey": key } })
+        };
+        let session = RequestContext::with_session("session-a");
+        let call = |id: i64, params: Value, context: RequestContext| {
+            let handler = &handler;
+            async move {
+                handler
+                    .handle_request_with_context(request(id, "tools/call", params), &context)
+                    .await
+                    .unwrap()
+            }
+        };
+        let first = call(1, keyed("retry-1"), session.clone()).await;
+        let retry = call(2, keyed("retry-1"), session.clone()).await;
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first.result, retry.result);
+        assert_eq!(retry.result.unwrap()["content"][0]["text"], "run 1");
+
+        // The key may also arrive as a header
+        let mut metadata = crate::transport::TransportMetadata::default();
+        metadata.capture_headers([("Idempotency-Key", "retry-1")]);
+        let context = session.clone().with_metadata(metadata);
+        call(3, serde_json::json!({ "name": "counting" }), context).await;
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Reusing the key for a different call is an error
+        let changed = serde_json::json!({
+            "name": "counting",
+            "arguments": { "n": 1 },
+            "_meta": { "idempotencyKey": "retry-1" }
+        });
+        let rejected = call(4, changed, session.clone()).await;
+        assert_eq!(rejected.error.unwrap().code, -32602);
+
+        // A new key, no key, another session or no session at all runs the tool again
+        call(5, keyed("retry-2"), session.clone()).await;
+        call(6, serde_json::json!({ "name": "counting" }), session.clone()).await;
+        call(7, keyed("retry-1"), RequestContext::with_session("session-b")).await;
+        call(8, keyed("retry-1"), RequestContext::default()).await;
+        call(9, keyed("retry-1"), RequestContext::default()).await;
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 6);
+    }
+
+    #[tokio::test]
+    async fn test_tool_progress_forwarded_with_token() {
+        let (session_sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let tool_manager = Arc::new(ToolManager::new());
+        tool_manager
+            .register_handler_with_tool(Box::new(SteppedTool))
+            .await
+            .unwrap();
+        let handler = ProtocolHandler::new(
+            Arc::new(ResourceManager::new()),
+            tool_manager,
+            Arc::new(PromptManager::new()),
+            Arc::new(SamplingManager::new()),
+        )
+        .with_session_sink(session_sink);
+        *handler.initialized.write().await = true;
+
+        let response = handler
+            .handle_request_with_context(
+                request(
+                    1,
+                    "tools/call",
+                    serde_json::json!({ "name": "stepped", "_meta": { "progressToken": "tok-1" } }),
+                ),
+                &RequestContext::with_session("session-a"),
+            )
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+
+        for (expected_progress, expected_message) in [(1.0, Some("halfway")), (2.0, None)] {
+            let (session_id, notification) = notifications.try_recv().unwrap();
+            assert_eq!(session_id, "session-a");
+            assert_eq!(notification.method, "notifications/progress");
+            let params = notification.params.unwrap();
+            assert_eq!(params["progressToken"], "tok-1");
+            assert_eq!(params["progress"], expected_progress);
+            assert_eq!(params["total"], 2.0);
+            assert_eq!(params["message"].as_str(), expected_message);
+        }
+
+        // Without a token nothing is reported
+        handler
+            .handle_request_with_context(
+                request(2, "tools/call", serde_json::json!({ "name": "stepped" })),
+                &RequestContext::with_session("session-a"),
+            )
+            .await
+            .unwrap();
+        assert!(notifications.try_recv().is_err());
+
+        // Without a session progress is dropped rather than broadcast
+        handler
+            .handle_request_with_context(
+                request(
+                    3,
+                    "tools/call",
+                    serde_json::json!({ "name": "stepped", "_meta": { "progressToken": "tok-2" } }),
+                ),
+                &RequestContext::default(),
+            )
+            .await
+            .unwrap();
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_level_is_tracked_per_session() {
+        let (session_sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let logging = Arc::new(LoggingManager::new().with_session_sink(session_sink));
+        let handler = test_handler(Arc::new(ResourceManager::new()))
+            .with_logging_manager(logging.clone());
+        *handler.initialized.write().await = true;
+
+        for (session_id, level) in [("session-a", "debug"), ("session-b", "warning")] {
+            let context = RequestContext::with_session(session_id);
+            let response = handler
+                .handle_request_with_context(
+                    request(1, "logging/setLevel", serde_json::json!({ "level": level })),
+                    &context,
+                )
+                .await
+                .unwrap();
+            assert!(response.error.is_none());
+        }
+
+        logging.info(None, "progress").await;
+        let (session_id, notification) = notifications.recv().await.unwrap();
+        assert_eq!(session_id, "session-a");
+        assert_eq!(notification.params.unwrap()["level"], "info");
+        assert!(notifications.try_recv().is_err());
+
+        handler.handle_session_closed("session-a").await;
+        logging.warning(None, "careful").await;
+        let (session_id, _) = notifications.recv().await.unwrap();
+        assert_eq!(session_id, "session-b");
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_batch_size_limit() {
+        let handler = test_handler(Arc::new(ResourceManager::new())).with_max_batch_size(3);
+        let batch = |len: i64| {
+            AnyJsonRpcMessage::Batch(
+                (0..len)
+                    .map(|id| serde_json::to_value(request(id, "ping", serde_json::json!({}))).unwrap())
+                    .collect(),
+            )
+        };
+
+        let err = handler.handle_message(batch(4)).await.unwrap_err();
+        assert!(matches!(err, McpError::InvalidRequest(_)));
+        assert_eq!(handler.counters().requests(), 0, "no element should run");
+
+        match handler.handle_message(batch(3)).await.unwrap() {
+            Some(AnyJsonRpcMessage::Batch(responses)) => assert_eq!(responses.len(), 3),
+            other => panic!("unexpected batch result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_mode_rejects_non_admin_requests() {
+        let handler = test_handler(Arc::new(ResourceManager::new()));
+        *handler.initialized.write().await = true;
+        let admin = RequestContext::default().with_scopes(vec![ADMIN_SCOPE.to_string()]);
+
+        // Only operators may toggle maintenance
+        let toggle = serde_json::json!({"enabled": true});
+        let response = handler
+            .handle_request(request(1, "admin/maintenance", toggle.clone()))
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, -32007);
+        assert!(!handler.is_in_maintenance());
+
+        let response = handler
+            .handle_request_with_context(request(1, "admin/maintenance", toggle), &admin)
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap()["maintenance"], true);
+
+        let call = serde_json::json!({"name": "echo", "arguments": {"message": "hi"}});
+        let response = handler
+            .handle_request(request(2, "tools/call", call.clone()))
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32001);
+        assert_eq!(error.data.unwrap()["retryable"], true);
+
+        let response = handler
+            .handle_request(request(3, "ping", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+
+        // Leaving maintenance lets requests through again
+        handler
+            .handle_request_with_context(
+                request(4, "admin/maintenance", serde_json::json!({"enabled": false})),
+                &admin,
+            )
+            .await
+            .unwrap();
+        let response = handler.handle_request(request(5, "tools/call", call)).await.unwrap();
+        assert_ne!(response.error.map(|e| e.code), Some(-32001));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_scope_blocks_tool_calls() {
+        let handler = test_handler(Arc::new(ResourceManager::new())).with_scope_methods(
+            HashMap::from([("read-only".to_string(), vec!["resources/".to_string()])]),
+        );
+        *handler.initialized.write().await = true;
+        let read_only = RequestContext::default().with_scopes(vec!["read-only".to_string()]);
+
+        let response = handler
+            .handle_request_with_context(request(1, "resources/list", serde_json::json!({})), &read_only)
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+
+        let call = serde_json::json!({"name": "echo", "arguments": {"message": "hi"}});
+        let response = handler
+            .handle_request_with_context(request(2, "tools/call", call.clone()), &read_only)
+            .await
+            .unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32007);
+        assert!(error.message.starts_with("Forbidden"));
+
+        let response = handler
+            .handle_request_with_context(request(3, "ping", serde_json::json!({})), &read_only)
+            .await
+            .unwrap();
+        assert!(response.error.is_none());
+
+        // Callers holding no listed scope, e.g. HTTP clients omitting their key, are denied
+        let response = handler
+            .handle_request_with_context(
+                request(4, "tools/call", call.clone()),
+                &RequestContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.error.map(|e| e.code), Some(-32007));
+        let response = handler
+            .handle_request_with_context(
+                request(5, "resources/list", serde_json::json!({})),
+                &RequestContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.error.map(|e| e.code), Some(-32007));
+
+        // Without any allowlist configured, every caller is unrestricted
+        let open = test_handler(Arc::new(ResourceManager::new()));
+        *open.initialized.write().await = true;
+        let response = open
+            .handle_request_with_context(request(6, "tools/call", call), &RequestContext::default())
+            .await
+            .unwrap();
+        assert_ne!(response.error.map(|e| e.code), Some(-32007));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_read_returns_not_modified() {
+        let resource_manager = Arc::new(ResourceManager::new());
+        let handler = test_handler(resource_manager.clone());
+        *handler.initialized.write().await = true;
+        handler.ready().await;
+
+        let resource = crate::protocol::Resource {
+            uri: "memo://note".to_string(),
+            name: "note".to_string(),
+            description: None,
+            mime_type: Some("text/plain".to_string()),
+            annotations: None,
+            size: None,
+        };
+        let contents = |text: &str| {
+            vec![crate::protocol::ResourceContents::text(
+                "memo://note",
+                Some("text/plain".to_string()),
+                text,
+            )]
+        };
+        resource_manager
+            .register_resource_with_contents(resource.clone(), contents("v1"))
+            .await
+            .unwrap();
+
+        let read = |id, params| handler.handle_request(request(id, "resources/read", params));
+        let result = read(1, serde_json::json!({"uri": "memo://note"})).await.unwrap().result.unwrap();
+        let hash = result["hash"].as_str().unwrap().to_string();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(result["contents"][0]["hash"], hash.as_str());
+
+        let result = read(2, serde_json::json!({"uri": "memo://note", "ifNoneMatch": hash}))
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(result["notModified"], true);
+        assert_eq!(result["contents"], serde_json::json!([]));
+
+        // Once the contents change the old hash no longer matches
+        resource_manager
+            .register_resource_with_contents(resource, contents("v2"))
+            .await
+            .unwrap();
+        let result = read(3, serde_json::json!({"uri": "memo://note", "ifNoneMatch": hash}))
+            .await
+            .unwrap()
+            .result
+            .unwrap();
+        assert!(result.get("notModified").is_none());
+        assert_eq!(result["contents"][0]["text"], "v2");
+        assert_ne!(result["hash"], hash.as_str());
+    }
+
+    /// Audit sink keeping records in memory
+    #[derive(Default)]
+    struct CapturingAuditSink(std::sync::Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for CapturingAuditSink {
+        fn record(&self, record: &AuditRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streamed_read_goes_through_limits_and_audit() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "abcdefghij".repeat(10)).unwrap();
+        let resource_manager = Arc::new(ResourceManager::new());
+        let sink = Arc::new(CapturingAuditSink::default());
+        let handler = test_handler(resource_manager.clone())
+            .with_max_response_size(Some(50))
+            .with_audit_sink(sink.clone());
+        handler.ready().await;
+        *handler.initialized.write().await = true;
+        resource_manager
+            .register_provider(Box::new(
+                crate::server::features::resources::FileSystemProvider::new(
+                    temp_dir.path().to_path_buf(),
+                ),
+            ))
+            .await
+            .unwrap();
+
+        let (sender, mut chunks) = tokio::sync::mpsc::channel(16);
+        let context = RequestContext::default().with_resource_stream(ResourceStream {
+            chunk_size: 16,
+            sender,
+        });
+        let uri = format!("file://{}", file.display());
+
+        // Without the opt-in, the read is answered whole (and over the limit)
+        let whole = serde_json::json!({ "uri": uri });
+        let response = handler
+            .handle_request_with_context(request(1, "resources/read", whole), &context)
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, -32000);
+        assert!(chunks.try_recv().is_err());
+
+        // Streaming stops once the limit is crossed, and is audited as failed
+        let streamed = serde_json::json!({ "uri": uri, "_meta": { "stream": true } });
+        let response = handler
+            .handle_request_with_context(request(2, "resources/read", streamed), &context)
+            .await
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, -32000);
+        let mut sent = 0;
+        while chunks.try_recv().is_ok() {
+            sent += 1;
+        }
+        assert_eq!(sent, 3);
+
+        let records = sink.0.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(matches!(records[1].outcome, AuditOutcome::Error { code: -32000, .. }));
+        assert!(handler.metrics().render_prometheus().contains("resources/read"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_audited_with_redacted_arguments() {
+        let sink = Arc::new(CapturingAuditSink::default());
+        let handler = test_handler(Arc::new(ResourceManager::new())).with_audit_sink(sink.clone());
+        *handler.initialized.write().await = true;
+        let context = RequestContext {
+            session_id: Some("session-a".to_string()),
+            client_id: Some("alice".to_string()),
+            ..Default::default()
+        };
+
+        let call = serde_json::json!({
+            "name": "echo",
+            "arguments": {"message": "hi", "password": "hunter2"}
+        });
+        handler
+            .handle_request_with_context(request(1, "tools/call", call), &context)
+            .await
+            .unwrap();
+        // Methods outside the audited set aren't recorded
+        handler
+            .handle_request_with_context(request(2, "tools/list", serde_json::json!({})), &context)
+            .await
+            .unwrap();
+
+        let records = sink.0.lock().unwrap().clone();
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.method, "tools/call");
+        assert_eq!(record.principal.as_deref(), Some("alice"));
+        assert_eq!(record.session_id.as_deref(), Some("session-a"));
+        assert_eq!(record.arguments["arguments"]["message"], "hi");
+        assert_eq!(record.arguments["arguments"]["password"], "[REDACTED:password]");
+        assert_eq!(record.outcome, AuditOutcome::Success);
+
+        // An authenticated identity takes precedence over the client ID
+        let context = context.with_principal("api-key:0123");
+        handler
+            .handle_request_with_context(request(3, "tools/call", serde_json::json!({"name": "echo"})), &context)
+            .await
+            .unwrap();
+        let records = sink.0.lock().unwrap();
+        assert_eq!(records[1].principal.as_deref(), Some("api-key:0123"));
+    }
+
+    /// Provider whose reads take the given time
+    struct SlowProvider(Duration);
+
+    #[async_trait::async_trait]
+    impl crate::server::features::resources::ResourceProvider for SlowProvider {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn can_handle(&self, uri: &str) -> bool {
+            uri.starts_with("slow://")
+        }
+
+        async fn read_resource(&self, _uri: &str) -> Result<Vec<crate::protocol::ResourceContents>> {
+            tokio::time::sleep(self.0).await;
+            Ok(Vec::new())
+        }
+    }
+
+    /// Provider serving 4 KiB of text for any `big://` URI
+    struct BigProvider;
+
+    #[async_trait::async_trait]
+    impl crate::server::features::resources::ResourceProvider for BigProvider {
+        fn name(&self) -> &str {
+            "big"
+        }
+
+        fn can_handle(&self, uri: &str) -> bool {
+            uri.starts_with("big://")
+        }
+
+        async fn read_resource(&self, uri: &str) -> Result<Vec<crate::protocol::ResourceContents>> {
+            Ok(vec![crate::protocol::ResourceContents::text(uri, None, "x".repeat(4096))])
+        }
+    }
+
+    /// Tool returning 4 KiB of text
+    struct BigToolHandler;
+
+    #[async_trait::async_trait]
+    impl crate::server::features::tools::ToolHandler for BigToolHandler {
+        fn name(&self) -> &str {
+            "big"
+        }
+
+        fn input_schema(&self) -> crate::protocol::ToolInputSchema {
+            crate::protocol::ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        async fn execute(
+            &self,
+            _arguments: Option<Value>,
+        ) -> Result<crate::server::features::tools::ToolResult> {
+            Ok(crate::server::features::tools::ToolResult::text("y".repeat(4096)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_response_size_policies() {
+        let resource_manager = Arc::new(ResourceManager::new());
+        resource_manager
+            .register_provider(Box::new(BigProvider))
+            .await
+            .unwrap();
+
+        for policy in [ResponseSizePolicy::Error, ResponseSizePolicy::Truncate] {
+            let handler = test_handler(resource_manager.clone())
+                .with_max_response_size(Some(1024))
+                .with_response_size_policy(policy);
+            handler
+                .tool_manager
+                .register_handler_with_tool(Box::new(BigToolHandler))
+                .await
+                .unwrap();
+            *handler.initialized.write().await = true;
+
+            let read = handler
+                .handle_request(request(1, "resources/read", serde_json::json!({"uri": "big://a"})))
+                .await
+                .unwrap();
+            let call = handler
+                .handle_request(request(2, "tools/call", serde_json::json!({"name": "big"})))
+                .await
+                .unwrap();
+
+            for (response, items_key) in [(read, "contents"), (call, "content")] {
+                match policy {
+                    ResponseSizePolicy::Error => {
+                        let error = response.error.unwrap();
+                        assert_eq!(error.code, -32000);
+                        assert_eq!(error.data.unwrap()["limit"], 1024);
+                    }
+                    ResponseSizePolicy::Truncate => {
+                        let result = response.result.unwrap();
+                        assert!(serde_json::to_vec(&result).unwrap().len() <= 1024);
+                        assert_eq!(result["_meta"]["truncated"], true);
+                        let text = result[items_key][0]["text"].as_str().unwrap();
+                        assert!(text.ends_with(crate::utils::response_size::TRUNCATION_MARKER));
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_resource_read_terminates() {
+        let resource_manager = Arc::new(ResourceManager::new());
+        resource_manager
+            .register_provider(Box::new(SlowProvider(Duration::from_secs(60))))
+            .await
+            .unwrap();
+        let handler = test_handler(resource_manager);
+        *handler.initialized.write().await = true;
+
+        let read = tokio::spawn({
+            let handler = handler.clone();
+            async move {
+                handler
+                    .handle_request(request(7, "resources/read", serde_json::json!({"uri": "slow://big"})))
+                    .await
+            }
+        });
+
+        // Wait for the read to be in flight, then cancel it
+        while handler.active_request_durations().await.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        handler
+            .handle_message(AnyJsonRpcMessage::Notification(JsonRpcNotification::new(
+                "notifications/cancelled".to_string(),
+                Some(serde_json::json!({"requestId": 7})),
+            )))
+            .await
+            .unwrap();
+
+        let response = tokio::time::timeout(Duration::from_secs(1), read)
+            .await
+            .expect("read did not stop after cancellation")
+            .unwrap()
+            .unwrap();
+        assert!(response.error.unwrap().message.contains("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_only_reaches_own_session() {
+        let resource_manager = Arc::new(ResourceManager::new());
+        resource_manager
+            .register_provider(Box::new(SlowProvider(Duration::from_secs(60))))
+            .await
+            .unwrap();
+        let handler = test_handler(resource_manager);
+        *handler.initialized.write().await = true;
+
+        // Both sessions number their requests from 1
+        let read = |session: &'static str| {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                handler
+                    .handle_request_with_context(
+                        request(1, "resources/read", serde_json::json!({"uri": "slow://big"})),
+                        &RequestContext::with_session(session),
+                    )
+                    .await
+            })
+        };
+        let mut read_a = read("session-a");
+        let read_b = read("session-b");
+        while handler.active_request_durations().await.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let cancel = |session: &'static str| {
+            let handler = handler.clone();
+            async move {
+                handler
+                    .handle_message_with_context(
+                        AnyJsonRpcMessage::Notification(JsonRpcNotification::new(
+                            "notifications/cancelled".to_string(),
+                            Some(serde_json::json!({"requestId": 1})),
+                        )),
+                        &RequestContext::with_session(session),
+                    )
+                    .await
+                    .unwrap();
+            }
+        };
+
+        // Session B's cancel stops its own read and leaves A's running
+        cancel("session-b").await;
+        let response = tokio::time::timeout(Duration::from_secs(1), read_b)
+            .await
+            .expect("read did not stop after cancellation")
+            .unwrap()
+            .unwrap();
+        assert!(response.error.unwrap().message.contains("cancelled"));
+        assert!(tokio::time::timeout(Duration::from_millis(100), &mut read_a)
+            .await
+            .is_err());
+        assert_eq!(handler.active_request_durations().await.len(), 1);
+
+        cancel("session-a").await;
+        let response = tokio::time::timeout(Duration::from_secs(1), read_a)
+            .await
+            .expect("read did not stop after cancellation")
+            .unwrap()
+            .unwrap();
+        assert!(response.error.unwrap().message.contains("cancelled"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_reads_run_concurrently() {
+        let resource_manager = Arc::new(ResourceManager::new());
+        resource_manager
+            .register_provider(Box::new(SlowProvider(Duration::from_millis(200))))
+            .await
+            .unwrap();
+        let handler = test_handler(resource_manager).with_batch_concurrency(4);
+        *handler.initialized.write().await = true;
+
+        let mut batch: Vec<Value> = (0..4)
+            .map(|id| {
+                let params = serde_json::json!({"uri": format!("slow://{}", id)});
+                serde_json::to_value(request(id, "resources/read", params)).unwrap()
+            })
+            .collect();
+        batch.push(serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"}));
+
+        let started = Instant::now();
+        let responses = match handler.handle_message(AnyJsonRpcMessage::Batch(batch)).await.unwrap() {
+            Some(AnyJsonRpcMessage::Batch(responses)) => responses,
+            other => panic!("unexpected batch result: {:?}", other),
+        };
+        // Four 200ms reads with a bound of four take one read's time, not the sum
+        assert!(started.elapsed() < Duration::from_millis(600), "took {:?}", started.elapsed());
+
+        // One response per request, none for the notification, each with its ID
+        let mut ids: Vec<i64> = responses.iter().map(|r| r["id"].as_i64().unwrap()).collect();
+        ids.sort();
+        assert_eq!(ids, vec![0, 1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_advertises_page_size() {
+        let resource_manager = Arc::new(ResourceManager::new().with_page_size(7));
+        let handler = ProtocolHandler::new(
+            resource_manager.clone(),
+            Arc::new(ToolManager::new().with_page_size(7)),
+            Arc::new(PromptManager::new().with_page_size(7)),
+            Arc::new(SamplingManager::new()),
+        );
+
+        let params = serde_json::json!({
+            "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "test", "version": "1.0"}
+        });
+        let response = handler
+            .handle_request(request(1, "initialize", params))
+            .await
+            .unwrap();
+        let pagination = &response.result.unwrap()["capabilities"]["experimental"]["pagination"];
+        assert_eq!(pagination["maxPageSize"], 7);
+        assert_eq!(pagination["opaqueCursors"], true);
+
+        // The advertised limit is the one list requests actually use
+        for i in 0..8 {
+            resource_manager
+                .register_resource(crate::protocol::Resource {
+                    uri: format!("test://{}", i),
+                    name: format!("resource {}", i),
+                    description: None,
+                    mime_type: None,
+                    annotations: None,
+                    size: None,
+                })
+                .await
+                .unwrap();
+        }
+        *handler.initialized.write().await = true;
+        let response = handler
+            .handle_request(request(2, "resources/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["resources"].as_array().unwrap().len(), 7);
+        assert!(result["nextCursor"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_manifest_resources_listed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("resources.toml");
+        std::fs::write(
+            &path,
+            r#"
+[[resources]]
+uri = "test://manifest/readme"
+name = "Readme"
+description = "Project readme"
+mime_type = "text/markdown"
+
+[[resources]]
+uri = "https://example.com/data.json"
+name = "Data"
+mime_type = "application/json"
+"#,
+        )
+        .unwrap();
+
+        let resource_manager = Arc::new(ResourceManager::with_config(
+            crate::server::features::resources::ResourcesConfig {
+                manifest: Some(path),
+                ..Default::default()
+            },
+        ));
+        let report = resource_manager.load_configured_manifest().await.unwrap().unwrap();
+        assert_eq!(report.registered.len(), 2);
+        assert!(report.rejected.is_empty());
+
+        let handler = test_handler(resource_manager);
+        *handler.initialized.write().await = true;
+        let response = handler
+            .handle_request(request(1, "resources/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        let result = response.result.unwrap();
+        let resources = result["resources"].as_array().unwrap();
+        let readme = resources
+            .iter()
+            .find(|r| r["uri"] == "test://manifest/readme")
+            .unwrap();
+        assert_eq!(readme["name"], "Readme");
+        assert_eq!(readme["mimeType"], "text/markdown");
+        assert!(resources.iter().any(|r| r["uri"] == "https://example.com/data.json"));
+    }
+
+    #[tokio::test]
+    async fn test_request_metrics_labeled_by_feature() {
+        let handler = test_handler(Arc::new(ResourceManager::new()));
+        *handler.initialized.write().await = true;
+
+        let params = serde_json::json!({"name": "no_such_tool", "arguments": {}});
+        handler
+            .handle_request(request(1, "tools/call", params))
+            .await
+            .unwrap();
+        handler
+            .handle_request(request(2, "ping", serde_json::json!({})))
+            .await
+            .unwrap();
+
+        // Methods nobody handles don't get a label of their own
+        handler
+            .handle_request(request(3, "tools/madeUp", serde_json::json!({})))
+            .await
+            .unwrap();
+
+        let metrics = handler.metrics();
+        assert_eq!(metrics.feature_request_count("tools"), 1);
+        assert_eq!(metrics.feature_request_count("lifecycle"), 1);
+        let rendered = metrics.render_prometheus();
+        assert!(rendered.contains("mcp_requests_total{method=\"tools/call\",feature=\"tools\"} 1"));
+        assert!(rendered.contains("mcp_requests_total{method=\"unknown\",feature=\"other\"} 1"));
+        assert!(!rendered.contains("tools/madeUp"));
+    }
+
+    #[tokio::test]
+    async fn test_roots_list_when_disabled() {
+        use crate::client::features::roots::RootsConfig;
+
+        let disabled = |config: RootsConfig| {
+            test_handler(Arc::new(ResourceManager::new()))
+                .with_roots_manager(Arc::new(RootsManager::with_config(config).with_enabled(false)))
+        };
+
+        // Disabled roots fail like any other disabled feature
+        let handler = disabled(RootsConfig::default());
+        *handler.initialized.write().await = true;
+        let response = handler
+            .handle_request(request(1, "roots/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert!(response.result.is_none());
+        assert!(response.error.unwrap().message.contains("Roots feature is disabled"));
+
+        // ...unless configured to answer with an empty list
+        let handler = disabled(RootsConfig {
+            list_empty_when_disabled: true,
+            ..RootsConfig::default()
+        });
+        *handler.initialized.write().await = true;
+        let response = handler
+            .handle_request(request(2, "roots/list", serde_json::json!({})))
+            .await
+            .unwrap();
+        assert_eq!(response.result.unwrap(), serde_json::json!({ "roots": [] }));
+    }
+
+    #[tokio::test]
+    async fn test_client_response_delivered_to_outgoing_request() {
+        let handler = test_handler(Arc::new(ResourceManager::new()));
+
+        let params = serde_json::json!({
+            "messages": [{ "role": "user", "content": { "type": "text", "text": "Hi" } }],
+            "maxTokens": 16
+        });
+        let (request, receiver) = handler
+            .begin_outgoing_request("sampling/createMessage", Some(params))
+            .unwrap();
+        assert_eq!(request.method, "sampling/createMessage");
+        assert_eq!(handler.outgoing_requests().len(), 1);
+
+        // The client answers with the request's ID
+        let result = serde_json::json!({
+            "role": "assistant",
+            "content": { "type": "text", "text": "Hello" },
+            "model": "test-model"
+        });
+        let reply = handler
+            .handle_message(AnyJsonRpcMessage::Response(JsonRpcResponse::success(
+                request.id.clone(),
+                result.clone(),
+            )))
+            .await
+            .unwrap();
+        assert!(reply.is_none());
+
+        let response = receiver.await.unwrap();
+        assert_eq!(response.id, request.id);
+        assert_eq!(response.result.unwrap(), result);
+        assert!(handler.outgoing_requests().is_empty());
+
+        // Responses nobody is waiting for are dropped
+        let stray = JsonRpcResponse::success(serde_json::json!("unknown"), serde_json::json!({}));
+        assert!(handler
+            .handle_message(AnyJsonRpcMessage::Response(stray))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_outgoing_request_times_out() {
+        let (sink, mut requests) = tokio::sync::mpsc::unbounded_channel();
+        let handler = test_handler(Arc::new(ResourceManager::new()))
+            .with_request_sink(sink)
+            .with_outgoing_request_timeout(Duration::from_millis(20));
+
+        let result = handler
+            .send_request_with_context("roots/list", None, &RequestContext::with_session("s1"))
+            .await;
+        assert!(matches!(result, Err(McpError::Protocol(message)) if message.contains("timed out")));
+        assert!(handler.outgoing_requests().is_empty());
+
+        // The request went out addressed to the caller's session
+        let (session_id, request) = requests.recv().await.unwrap();
+        assert_eq!(session_id.as_deref(), Some("s1"));
+        assert_eq!(request.method, "roots/list");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_notification_reported_only_when_strict() {
+        let unknown = || {
+            AnyJsonRpcMessage::Notification(JsonRpcNotification::new(
+                "tools/call".to_string(),
+                Some(serde_json::json!({ "name": "echo" })),
+            ))
+        };
+        let context = RequestContext::with_session("session-a");
+
+        // Lenient: ignored with a warning, nothing reaches the client
+        let (session_sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let handler = test_handler(Arc::new(ResourceManager::new())).with_logging_manager(
+            Arc::new(LoggingManager::new().with_session_sink(session_sink)),
+        );
+        let reply = handler.handle_message_with_context(unknown(), &context).await.unwrap();
+        assert!(reply.is_none());
+        assert!(notifications.try_recv().is_err());
+
+        // Strict: only the offending session is told which method was rejected
+        let (session_sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let logging = Arc::new(LoggingManager::new().with_session_sink(session_sink));
+        logging.add_session("session-a").await;
+        logging.add_session("session-b").await;
+        let handler = test_handler(Arc::new(ResourceManager::new()))
+            .with_logging_manager(logging.clone())
+            .with_strict_notifications(true);
+        let reply = handler.handle_message_with_context(unknown(), &context).await.unwrap();
+        assert!(reply.is_none());
+
+        let (session_id, notification) = notifications.try_recv().unwrap();
+        assert_eq!(session_id, "session-a");
+        assert_eq!(notification.method, "notifications/message");
+        let params = notification.params.unwrap();
+        assert_eq!(params["level"], "error");
+        assert_eq!(params["logger"], "protocol");
+        assert_eq!(params["data"]["method"], "tools/call");
+        assert!(params["data"]["error"].as_str().unwrap().contains("missing its id"));
+        assert!(notifications.try_recv().is_err());
+
+        // The session's logging level still applies
+        logging
+            .set_session_level("session-a", LoggingLevel::Critical)
+            .await
+            .unwrap();
+        handler.handle_message_with_context(unknown(), &context).await.unwrap();
+        assert!(notifications.try_recv().is_err());
+    }
+}
@@ -8,7 +8,7 @@ use std::collections::HashMap;
 use crate::protocol::Cursor;
 
 /// Server capabilities structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ServerCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<HashMap<String, serde_json::Value>>,
@@ -30,7 +30,7 @@ pub struct ServerCapabilities {
 }
 
 /// Client capabilities structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ClientCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<HashMap<String, serde_json::Value>>,
@@ -117,6 +117,18 @@ pub struct Resource {
     pub size: Option<u64>,
 }
 
+/// Resource metadata returned by `resources/stat`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceMetadata {
+    pub uri: String,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(rename = "lastModified", skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
 /// Resource template
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceTemplate {
@@ -141,6 +153,9 @@ pub enum ResourceContents {
         #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
         text: String,
+        /// Hex SHA-256 of the contents, for conditional reads
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
     },
     #[serde(rename = "blob")]
     Blob {
@@ -148,9 +163,56 @@ pub enum ResourceContents {
         #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>,
         blob: String, // base64 encoded
+        /// Hex SHA-256 of the contents, for conditional reads
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        hash: Option<String>,
     },
 }
 
+impl ResourceContents {
+    /// Create text contents
+    pub fn text(uri: impl Into<String>, mime_type: Option<String>, text: impl Into<String>) -> Self {
+        ResourceContents::Text {
+            uri: uri.into(),
+            mime_type,
+            text: text.into(),
+            hash: None,
+        }
+    }
+
+    /// Create binary contents from base64-encoded data
+    pub fn blob(uri: impl Into<String>, mime_type: Option<String>, blob: impl Into<String>) -> Self {
+        ResourceContents::Blob {
+            uri: uri.into(),
+            mime_type,
+            blob: blob.into(),
+            hash: None,
+        }
+    }
+
+    /// Compute the hex SHA-256 of the text, or of the base64 blob as sent
+    pub fn content_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let data = match self {
+            ResourceContents::Text { text, .. } => text,
+            ResourceContents::Blob { blob, .. } => blob,
+        };
+        crate::utils::hex_encode(&Sha256::digest(data.as_bytes()))
+    }
+
+    /// Fill in the content hash
+    pub fn with_hash(mut self) -> Self {
+        let computed = self.content_hash();
+        match &mut self {
+            ResourceContents::Text { hash, .. } | ResourceContents::Blob { hash, .. } => {
+                *hash = Some(computed);
+            }
+        }
+        self
+    }
+}
+
 /// Tool structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
@@ -175,7 +237,7 @@ pub struct ToolInputSchema {
 }
 
 /// Tool annotations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct ToolAnnotations {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
@@ -189,6 +251,43 @@ pub struct ToolAnnotations {
     pub open_world_hint: Option<bool>,
 }
 
+impl ToolAnnotations {
+    /// Create annotations without any hints
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the human-readable title
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Declare that the tool doesn't modify its environment
+    pub fn read_only(mut self) -> Self {
+        self.read_only_hint = Some(true);
+        self
+    }
+
+    /// Declare whether the tool's updates may be destructive
+    pub fn destructive(mut self, destructive: bool) -> Self {
+        self.destructive_hint = Some(destructive);
+        self
+    }
+
+    /// Declare that repeated calls with the same arguments have no further effect
+    pub fn idempotent(mut self) -> Self {
+        self.idempotent_hint = Some(true);
+        self
+    }
+
+    /// Declare whether the tool interacts with entities outside the server
+    pub fn open_world(mut self, open_world: bool) -> Self {
+        self.open_world_hint = Some(open_world);
+        self
+    }
+}
+
 /// Prompt structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Prompt {
@@ -258,6 +357,22 @@ pub struct PromptMessage {
     pub content: Content,
 }
 
+/// Result of a `tools/call` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallToolResult {
+    pub content: Vec<Content>,
+    #[serde(rename = "isError", default)]
+    pub is_error: bool,
+}
+
+/// Result of a `prompts/get` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
 /// Annotations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Annotations {
@@ -275,6 +390,50 @@ pub struct Root {
     pub name: Option<String>,
 }
 
+/// What a `completion/complete` request completes for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    /// A prompt, by name
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+
+    /// A resource template, by URI template
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+}
+
+/// Argument being completed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    pub value: String,
+}
+
+/// Parameters of a `completion/complete` request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompleteRequest {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+}
+
+/// Completion values
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(rename = "hasMore", skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+}
+
+/// Result of a `completion/complete` request
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
+}
+
 /// Logging level
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -302,3 +461,57 @@ pub struct PaginationResult {
     #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<Cursor>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complete_request_round_trip() {
+        let json = serde_json::json!({
+            "ref": { "type": "ref/prompt", "name": "code_review" },
+            "argument": { "name": "language", "value": "py" }
+        });
+
+        let request: CompleteRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            request.reference,
+            CompletionReference::Prompt {
+                name: "code_review".to_string()
+            }
+        );
+        assert_eq!(request.argument.value, "py");
+        assert_eq!(serde_json::to_value(&request).unwrap(), json);
+
+        let json = serde_json::json!({
+            "ref": { "type": "ref/resource", "uri": "file:///{path}" },
+            "argument": { "name": "path", "value": "src/" }
+        });
+        let request: CompleteRequest = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&request).unwrap(), json);
+    }
+
+    #[test]
+    fn test_complete_result_round_trip() {
+        let result = CompleteResult {
+            completion: Completion {
+                values: vec!["python".to_string(), "pytorch".to_string()],
+                total: Some(10),
+                has_more: Some(true),
+            },
+        };
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "completion": { "values": ["python", "pytorch"], "total": 10, "hasMore": true }
+            })
+        );
+        assert_eq!(serde_json::from_value::<CompleteResult>(json).unwrap(), result);
+
+        // Optional fields are omitted when unknown
+        let json = serde_json::to_value(CompleteResult::default()).unwrap();
+        assert_eq!(json, serde_json::json!({ "completion": { "values": [] } }));
+    }
+}
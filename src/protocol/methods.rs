@@ -0,0 +1,84 @@
+//! Registry of custom request methods.
+//!
+//! The protocol handler dispatches the standard MCP methods itself. Extension
+//! or experimental methods (e.g. `x-acme/search`) are registered here as async
+//! handlers and consulted for any method the handler doesn't know, before
+//! answering with `method_not_found`. Standard methods always take precedence.
+
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+use tracing::info;
+
+use crate::error::{McpError, Result};
+use crate::protocol::handler::RequestContext;
+
+/// Handler of a custom method, given the request params and context
+pub type MethodHandler =
+    Arc<dyn Fn(Option<Value>, RequestContext) -> BoxFuture<'static, Result<Value>> + Send + Sync>;
+
+/// Custom methods by name
+#[derive(Default)]
+pub struct MethodRegistry {
+    handlers: RwLock<HashMap<String, MethodHandler>>,
+}
+
+impl MethodRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an async handler for `method`
+    ///
+    /// Fails if the method already has a handler.
+    pub fn register<F, Fut>(&self, method: impl Into<String>, handler: F) -> Result<()>
+    where
+        F: Fn(Option<Value>, RequestContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Value>> + Send + 'static,
+    {
+        let method = method.into();
+        let mut handlers = self.handlers.write().unwrap_or_else(|e| e.into_inner());
+        if handlers.contains_key(&method) {
+            return Err(McpError::Protocol(format!(
+                "Method {} is already registered",
+                method
+            )));
+        }
+
+        info!("Registered custom method: {}", method);
+        handlers.insert(
+            method,
+            Arc::new(move |params, context| Box::pin(handler(params, context))),
+        );
+        Ok(())
+    }
+
+    /// Remove the handler for `method`, returning whether there was one
+    pub fn unregister(&self, method: &str) -> bool {
+        let mut handlers = self.handlers.write().unwrap_or_else(|e| e.into_inner());
+        handlers.remove(method).is_some()
+    }
+
+    /// Check whether `method` has a handler
+    pub fn contains(&self, method: &str) -> bool {
+        let handlers = self.handlers.read().unwrap_or_else(|e| e.into_inner());
+        handlers.contains_key(method)
+    }
+
+    /// Run the handler for `method`, or return `None` if it has none
+    pub async fn call(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        context: &RequestContext,
+    ) -> Option<Result<Value>> {
+        let handler = {
+            let handlers = self.handlers.read().unwrap_or_else(|e| e.into_inner());
+            handlers.get(method).cloned()
+        }?;
+        Some(handler(params, context.clone()).await)
+    }
+}
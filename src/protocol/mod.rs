@@ -3,13 +3,19 @@
 //! This module contains the core protocol types and message handling
 //! for the Model Context Protocol (MCP) specification 2025-03-26.
 
+pub mod builders;
 pub mod handler;
 pub mod messages;
+pub mod methods;
+pub mod outgoing;
 pub mod validation;
 
 // Re-export commonly used types
+pub use builders::{InitializeRequestBuilder, InitializeResultBuilder};
 pub use handler::*;
 pub use messages::*;
+pub use methods::MethodRegistry;
+pub use outgoing::{PendingRequests, RequestSink};
 pub use validation::*;
 
 use crate::error::McpError;
@@ -213,17 +219,91 @@ impl From<McpError> for JsonRpcError {
         JsonRpcError {
             code: error.to_json_rpc_code(),
             message: error.to_string(),
-            data: None,
+            data: error.to_json_rpc_data(),
         }
     }
 }
 
-/// Parse a JSON-RPC message from a string
+/// Default maximum nesting depth of objects and arrays in an incoming message
+pub const DEFAULT_MAX_JSON_DEPTH: usize = 64;
+
+/// Parse a JSON-RPC message from a string, nested at most [`DEFAULT_MAX_JSON_DEPTH`] deep
 pub fn parse_message(data: &str) -> crate::Result<AnyJsonRpcMessage> {
+    parse_message_with_max_depth(data, DEFAULT_MAX_JSON_DEPTH)
+}
+
+/// Parse a JSON-RPC message from a string, rejecting nesting deeper than `max_depth`
+pub fn parse_message_with_max_depth(
+    data: &str,
+    max_depth: usize,
+) -> crate::Result<AnyJsonRpcMessage> {
+    check_json_depth(data, max_depth)?;
     serde_json::from_str(data).map_err(|e| McpError::parse_error(e.to_string()))
 }
 
+/// Reject JSON whose objects and arrays nest deeper than `max_depth`
+///
+/// Scans the raw text before deserializing, so hostile inputs are refused
+/// without recursing through them. Malformed JSON is left for the parser.
+pub fn check_json_depth(data: &str, max_depth: usize) -> crate::Result<()> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for byte in data.bytes() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'{' | b'[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(McpError::parse_error(format!(
+                        "JSON nesting exceeds the maximum depth of {}",
+                        max_depth
+                    )));
+                }
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
 /// Serialize a JSON-RPC message to a string
 pub fn serialize_message(message: &AnyJsonRpcMessage) -> crate::Result<String> {
     serde_json::to_string(message).map_err(|e| McpError::Serialization(e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_message_rejects_deep_nesting() {
+        let nested = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"arguments":{}1{}}}}}"#,
+            "[".repeat(100),
+            "]".repeat(100)
+        );
+        assert!(matches!(parse_message(&nested), Err(McpError::ParseError(_))));
+        assert!(parse_message_with_max_depth(&nested, 128).is_ok());
+
+        // Brackets inside strings don't count towards the depth
+        let normal = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"tools/call","params":{{"name":"echo","arguments":{{"message":"{}\"]"}}}}}}"#,
+            "[{".repeat(100)
+        );
+        assert!(parse_message(&normal).is_ok());
+    }
+}
@@ -0,0 +1,86 @@
+//! Tracking of requests the server sends to the client.
+//!
+//! Server-initiated requests (e.g. `sampling/createMessage` or `roots/list`)
+//! are registered here under their ID before being sent. When the client's
+//! response arrives, [`PendingRequests::complete`] hands it to the caller
+//! awaiting the matching receiver.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::error::{McpError, Result};
+use crate::protocol::{JsonRpcRequest, JsonRpcResponse, RequestId};
+
+/// How long a server-initiated request waits for the client's response by default
+pub const DEFAULT_OUTGOING_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Delivers a server-initiated request to the client owning the session,
+/// or to every client when no session is given
+pub type RequestSink = mpsc::UnboundedSender<(Option<String>, JsonRpcRequest)>;
+
+/// Server-initiated requests awaiting a response from the client
+#[derive(Debug, Default)]
+pub struct PendingRequests {
+    pending: Mutex<HashMap<RequestId, oneshot::Sender<JsonRpcResponse>>>,
+}
+
+impl PendingRequests {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `id`, returning the receiver its response is delivered to
+    ///
+    /// Fails if a request with the same ID is already in flight.
+    pub fn register(&self, id: RequestId) -> Result<oneshot::Receiver<JsonRpcResponse>> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.contains_key(&id) {
+            return Err(McpError::Protocol(format!(
+                "Outgoing request {} is already in flight",
+                id
+            )));
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        pending.insert(id, sender);
+        Ok(receiver)
+    }
+
+    /// Deliver a response to the caller awaiting it
+    ///
+    /// Returns `false` if no request with the response's ID is in flight.
+    pub fn complete(&self, response: JsonRpcResponse) -> bool {
+        let sender = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.remove(&response.id)
+        };
+
+        match sender {
+            // The caller may have given up waiting; the request is settled either way
+            Some(sender) => {
+                let _ = sender.send(response);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop tracking `id`, returning whether it was in flight
+    pub fn cancel(&self, id: &RequestId) -> bool {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.remove(id).is_some()
+    }
+
+    /// Number of requests in flight
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check whether no request is in flight
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
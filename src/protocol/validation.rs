@@ -103,6 +103,7 @@ pub fn validate_method_name(method: &str) -> Result<()> {
         "logging/",
         "completion/",
         "roots/",
+        "admin/",
     ];
 
     if !valid_prefixes
@@ -205,10 +206,22 @@ pub fn validate_tool_schema(schema: &Value) -> Result<()> {
 
     // Validate required array if present
     if let Some(required) = obj.get("required") {
-        if !required.is_array() {
-            return Err(McpError::invalid_params(
-                "Tool schema required must be an array",
-            ));
+        let required = required.as_array().ok_or_else(|| {
+            McpError::invalid_params("Tool schema required must be an array")
+        })?;
+
+        // Every required field must be declared in properties
+        let properties = obj.get("properties").and_then(Value::as_object);
+        for field in required {
+            let field = field.as_str().ok_or_else(|| {
+                McpError::invalid_params("Tool schema required entries must be strings")
+            })?;
+            if !properties.is_some_and(|properties| properties.contains_key(field)) {
+                return Err(McpError::invalid_params(format!(
+                    "Tool schema requires '{}' but does not declare it in properties",
+                    field
+                )));
+            }
         }
     }
 
@@ -0,0 +1,48 @@
+//! Registry of experimental (non-standard) server capabilities.
+//!
+//! Entries map a capability name to a JSON descriptor and are advertised
+//! under `capabilities.experimental` in every `initialize` result, letting
+//! capable clients discover custom methods.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::error::{McpError, Result};
+
+/// Experimental capabilities advertised at initialize time
+#[derive(Debug, Default)]
+pub struct ExperimentalCapabilities {
+    entries: RwLock<HashMap<String, Value>>,
+}
+
+impl ExperimentalCapabilities {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register (or replace) a capability with the given descriptor
+    pub async fn register<T: Serialize>(&self, name: impl Into<String>, descriptor: &T) -> Result<()> {
+        let name = name.into();
+        let descriptor = serde_json::to_value(descriptor).map_err(|e| {
+            McpError::Config(format!("Invalid descriptor for experimental capability '{}': {}", name, e))
+        })?;
+
+        info!("Registered experimental capability: {}", name);
+        self.entries.write().await.insert(name, descriptor);
+        Ok(())
+    }
+
+    /// Remove a capability, returning its descriptor
+    pub async fn unregister(&self, name: &str) -> Option<Value> {
+        self.entries.write().await.remove(name)
+    }
+
+    /// Get every registered capability
+    pub async fn snapshot(&self) -> HashMap<String, Value> {
+        self.entries.read().await.clone()
+    }
+}
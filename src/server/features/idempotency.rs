@@ -0,0 +1,199 @@
+//! Deduplication of retried tool calls.
+//!
+//! A client retrying a `tools/call` after a network failure can attach an
+//! idempotency key (`_meta.idempotencyKey` in the params, or the
+//! `Idempotency-Key` HTTP header). For tools that opt in, the first call with
+//! a key runs and its result is kept for the TTL window; repeats get that
+//! result instead of running the tool again. Concurrent repeats wait for the
+//! first call, and failed calls aren't kept so they can be retried. Reusing a
+//! key with different arguments is rejected rather than answered with the
+//! result of another call.
+
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+use crate::error::{McpError, Result};
+use crate::server::features::tools::ToolResult;
+
+/// How long the result of a keyed call is kept by default
+pub const DEFAULT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(300);
+
+/// How many keys are held at most by default
+pub const DEFAULT_IDEMPOTENCY_MAX_ENTRIES: usize = 10_000;
+
+/// Result of the first call made with a key
+struct Entry {
+    created: Instant,
+    arguments: u64,
+    result: Arc<OnceCell<ToolResult>>,
+}
+
+/// Results of keyed tool calls, kept for a TTL window
+pub struct IdempotencyCache {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl IdempotencyCache {
+    /// Create a cache keeping results for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            max_entries: DEFAULT_IDEMPOTENCY_MAX_ENTRIES,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hold at most `max_entries` keys, evicting the oldest first
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// How long results are kept
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Return the result kept for `key`, or run `call` and keep its result
+    ///
+    /// Fails with invalid params if `key` was first used with other arguments.
+    pub async fn get_or_run<F, Fut>(
+        &self,
+        key: String,
+        arguments: Option<&Value>,
+        call: F,
+    ) -> Result<ToolResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<ToolResult>>,
+    {
+        let fingerprint = fingerprint(arguments);
+        let result = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            entries.retain(|_, entry| now.duration_since(entry.created) < self.ttl);
+
+            if !entries.contains_key(&key) && entries.len() >= self.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.created)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+
+            let entry = entries.entry(key).or_insert_with(|| Entry {
+                created: now,
+                arguments: fingerprint,
+                result: Arc::new(OnceCell::new()),
+            });
+            if entry.arguments != fingerprint {
+                return Err(McpError::invalid_params(
+                    "Idempotency key was already used with different arguments",
+                ));
+            }
+            entry.result.clone()
+        };
+
+        result.get_or_try_init(call).await.cloned()
+    }
+
+    /// Number of keys currently held
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check whether no key is held
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Hash of a call's arguments, telling a retry apart from a different call
+fn fingerprint(arguments: Option<&Value>) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    arguments.map(Value::to_string).hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IDEMPOTENCY_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_failed_calls_are_not_kept() {
+        let cache = IdempotencyCache::default();
+        let runs = AtomicUsize::new(0);
+        let call = || async {
+            if runs.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(crate::error::McpError::Tool("flaky".to_string()))
+            } else {
+                Ok(ToolResult::text("ok".to_string()))
+            }
+        };
+
+        assert!(cache.get_or_run("k".to_string(), None, call).await.is_err());
+        assert!(cache.get_or_run("k".to_string(), None, call).await.is_ok());
+        assert!(cache.get_or_run("k".to_string(), None, call).await.is_ok());
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_after_ttl() {
+        let cache = IdempotencyCache::new(Duration::from_millis(20));
+        let runs = AtomicUsize::new(0);
+        let call = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            Ok(ToolResult::text("ok".to_string()))
+        };
+
+        cache.get_or_run("k".to_string(), None, call).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        cache.get_or_run("k".to_string(), None, call).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_key_reused_with_other_arguments_is_rejected() {
+        let cache = IdempotencyCache::default();
+        let call = || async { Ok(ToolResult::text("ok".to_string())) };
+        let first = serde_json::json!({ "amount": 10 });
+        let other = serde_json::json!({ "amount": 1000 });
+
+        cache.get_or_run("k".to_string(), Some(&first), call).await.unwrap();
+        cache.get_or_run("k".to_string(), Some(&first), call).await.unwrap();
+        let err = cache
+            .get_or_run("k".to_string(), Some(&other), call)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::InvalidParams(_)));
+    }
+
+    #[tokio::test]
+    async fn test_oldest_keys_evicted_beyond_capacity() {
+        let cache = IdempotencyCache::default().with_max_entries(2);
+        let call = || async { Ok(ToolResult::text("ok".to_string())) };
+
+        for key in ["a", "b", "c"] {
+            cache.get_or_run(key.to_string(), None, call).await.unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+    }
+}
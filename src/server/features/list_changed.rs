@@ -0,0 +1,110 @@
+//! Debounced `list_changed` notifications.
+//!
+//! Feature managers call [`ListChangedNotifier::notify`] whenever their set of
+//! items changes. Bursts of changes (e.g. registering every tool at startup)
+//! are coalesced into a single notification per debounce window.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::protocol::JsonRpcNotification;
+
+/// Default window over which changes are coalesced
+pub const DEFAULT_LIST_CHANGED_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Channel server-initiated notifications are published to
+pub type NotificationSink = mpsc::UnboundedSender<JsonRpcNotification>;
+
+/// Emits a feature's `list_changed` notification, debounced
+#[derive(Clone)]
+pub struct ListChangedNotifier {
+    /// Notification method, e.g. `notifications/tools/list_changed`
+    method: &'static str,
+
+    /// Where notifications go; nothing is emitted without one
+    sink: Option<NotificationSink>,
+
+    /// Whether a notification is already scheduled
+    pending: Arc<AtomicBool>,
+
+    /// Window over which changes are coalesced
+    debounce: Duration,
+}
+
+impl ListChangedNotifier {
+    /// Create a notifier for the given method with no sink attached
+    pub fn new(method: &'static str) -> Self {
+        Self {
+            method,
+            sink: None,
+            pending: Arc::new(AtomicBool::new(false)),
+            debounce: DEFAULT_LIST_CHANGED_DEBOUNCE,
+        }
+    }
+
+    /// Publish notifications to the given sink
+    pub fn with_sink(mut self, sink: NotificationSink) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Set the window over which changes are coalesced
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Record a change, scheduling a notification unless one is already pending
+    pub fn notify(&self) {
+        let Some(sink) = self.sink.clone() else {
+            return;
+        };
+        if self.pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let method = self.method;
+        let pending = self.pending.clone();
+        let debounce = self.debounce;
+        tokio::spawn(async move {
+            tokio::time::sleep(debounce).await;
+            pending.store(false, Ordering::Release);
+
+            if sink
+                .send(JsonRpcNotification::new(method.to_string(), None))
+                .is_err()
+            {
+                debug!("Dropping {}: notification sink closed", method);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bursts_are_coalesced() {
+        let (sink, mut notifications) = mpsc::unbounded_channel();
+        let notifier = ListChangedNotifier::new("notifications/tools/list_changed")
+            .with_sink(sink)
+            .with_debounce(Duration::from_millis(20));
+
+        for _ in 0..5 {
+            notifier.notify();
+        }
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.method, "notifications/tools/list_changed");
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(notifications.try_recv().is_err());
+
+        // A later change gets its own notification
+        notifier.notify();
+        assert!(notifications.recv().await.is_some());
+    }
+}
@@ -1,16 +1,32 @@
 //! Logging management for MCP server.
 //!
 //! This module implements the logging feature of MCP, allowing the server
-//! to send log messages to clients.
+//! to send log messages to clients. With a notification sink attached, every
+//! message at or above the level set through `logging/setLevel` is delivered
+//! as a `notifications/message` notification.
+//!
+//! Sessions track their own level: once any session is known, messages are
+//! addressed to each session whose level they meet instead of being broadcast.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, info};
 
 use crate::error::{McpError, Result};
-use crate::protocol::LoggingLevel;
+use crate::protocol::{JsonRpcNotification, LoggingLevel};
+use crate::server::features::list_changed::NotificationSink;
 use crate::server::features::FeatureManager;
 
+/// Method of the notification carrying a log message to clients
+pub const LOG_MESSAGE_NOTIFICATION: &str = "notifications/message";
+
+/// Level a session receives messages at until it calls `logging/setLevel`
+pub const DEFAULT_SESSION_LEVEL: LoggingLevel = LoggingLevel::Info;
+
+/// Channel notifications addressed to a single session are published to
+pub type SessionNotificationSink = mpsc::UnboundedSender<(String, JsonRpcNotification)>;
+
 /// Logging manager for handling MCP logging
 pub struct LoggingManager {
     /// Current logging level
@@ -19,6 +35,15 @@ pub struct LoggingManager {
     /// Log message sender
     sender: Arc<RwLock<Option<mpsc::Sender<LogMessage>>>>,
 
+    /// Where `notifications/message` notifications go
+    notification_sink: Option<NotificationSink>,
+
+    /// Level requested by each known session
+    session_levels: Arc<RwLock<HashMap<String, LoggingLevel>>>,
+
+    /// Where notifications addressed to a session go
+    session_sink: Option<SessionNotificationSink>,
+
     /// Whether the feature is enabled
     enabled: Arc<RwLock<bool>>,
 }
@@ -45,10 +70,68 @@ impl LoggingManager {
         Self {
             level: Arc::new(RwLock::new(LoggingLevel::Info)),
             sender: Arc::new(RwLock::new(None)),
+            notification_sink: None,
+            session_levels: Arc::new(RwLock::new(HashMap::new())),
+            session_sink: None,
             enabled: Arc::new(RwLock::new(true)),
         }
     }
 
+    /// Deliver log messages to clients through the given sink
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.notification_sink = Some(sink);
+        self
+    }
+
+    /// Deliver log messages to individual sessions through the given sink
+    pub fn with_session_sink(mut self, sink: SessionNotificationSink) -> Self {
+        self.session_sink = Some(sink);
+        self
+    }
+
+    /// Start tracking a session at [`DEFAULT_SESSION_LEVEL`]
+    ///
+    /// Returns false if the session was already tracked.
+    pub async fn add_session(&self, session_id: &str) -> bool {
+        let mut session_levels = self.session_levels.write().await;
+        if session_levels.contains_key(session_id) {
+            return false;
+        }
+        session_levels.insert(session_id.to_string(), DEFAULT_SESSION_LEVEL);
+        true
+    }
+
+    /// Stop tracking a closed session, returning whether it was tracked
+    pub async fn remove_session(&self, session_id: &str) -> bool {
+        self.session_levels.write().await.remove(session_id).is_some()
+    }
+
+    /// Set the logging level for one session
+    pub async fn set_session_level(&self, session_id: &str, level: LoggingLevel) -> Result<()> {
+        if !self.is_enabled() {
+            return Err(McpError::Resource(
+                "Logging feature is disabled".to_string(),
+            ));
+        }
+
+        info!("Set logging level for session {} to: {:?}", session_id, level);
+        self.session_levels
+            .write()
+            .await
+            .insert(session_id.to_string(), level);
+        Ok(())
+    }
+
+    /// Get the logging level for a session
+    pub async fn get_session_level(&self, session_id: &str) -> LoggingLevel {
+        self.session_levels
+            .read()
+            .await
+            .get(session_id)
+            .cloned()
+            .unwrap_or(DEFAULT_SESSION_LEVEL)
+    }
+
     /// Set the logging level
     pub async fn set_level(&self, level: LoggingLevel) -> Result<()> {
         if !self.is_enabled() {
@@ -90,11 +173,6 @@ impl LoggingManager {
             return Ok(()); // Silently ignore if disabled
         }
 
-        // Check if message level is high enough
-        if !self.should_log(&level).await {
-            return Ok(());
-        }
-
         let message = LogMessage {
             level,
             logger,
@@ -102,6 +180,20 @@ impl LoggingManager {
             timestamp: chrono::Utc::now(),
         };
 
+        // Sessions filter by their own level
+        let has_sessions = self.notify_sessions(&message).await;
+
+        // Check if message level is high enough
+        if !self.should_log(&message.level).await {
+            return Ok(());
+        }
+
+        if let (Some(sink), false) = (&self.notification_sink, has_sessions) {
+            if sink.send(message.to_notification()).is_err() {
+                debug!("Dropping log message: notification sink closed");
+            }
+        }
+
         // Send message if sender is configured
         let sender = self.sender.read().await;
         if let Some(sender) = sender.as_ref() {
@@ -114,6 +206,39 @@ impl LoggingManager {
         Ok(())
     }
 
+    /// Send a log message to a single session, filtered by that session's level
+    pub async fn log_to_session(
+        &self,
+        session_id: &str,
+        level: LoggingLevel,
+        logger: Option<String>,
+        data: serde_json::Value,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(sink) = &self.session_sink else {
+            return;
+        };
+        let session_level = self.get_session_level(session_id).await;
+        if self.level_priority(&level) < self.level_priority(&session_level) {
+            return;
+        }
+
+        let message = LogMessage {
+            level,
+            logger,
+            data,
+            timestamp: chrono::Utc::now(),
+        };
+        if sink
+            .send((session_id.to_string(), message.to_notification()))
+            .is_err()
+        {
+            debug!("Dropping log message: session sink closed");
+        }
+    }
+
     /// Log a debug message
     pub async fn debug(&self, logger: Option<String>, message: impl Into<serde_json::Value>) {
         let _ = self.log(LoggingLevel::Debug, logger, message.into()).await;
@@ -160,6 +285,33 @@ impl LoggingManager {
             .await;
     }
 
+    /// Send a message to every session whose level it meets
+    ///
+    /// Returns false if no sessions are known.
+    async fn notify_sessions(&self, message: &LogMessage) -> bool {
+        let session_levels = self.session_levels.read().await;
+        if session_levels.is_empty() {
+            return false;
+        }
+
+        if let Some(sink) = &self.session_sink {
+            let priority = self.level_priority(&message.level);
+            for (session_id, level) in session_levels.iter() {
+                if priority < self.level_priority(level) {
+                    continue;
+                }
+                if sink
+                    .send((session_id.clone(), message.to_notification()))
+                    .is_err()
+                {
+                    debug!("Dropping log message: session sink closed");
+                    break;
+                }
+            }
+        }
+        true
+    }
+
     /// Check if a message should be logged based on current level
     async fn should_log(&self, message_level: &LoggingLevel) -> bool {
         let current_level = self.level.read().await;
@@ -223,6 +375,18 @@ impl LogMessage {
     pub fn structured(level: LoggingLevel, data: serde_json::Value) -> Self {
         Self::new(level, data)
     }
+
+    /// Convert to a `notifications/message` notification
+    pub fn to_notification(&self) -> JsonRpcNotification {
+        let mut params = serde_json::json!({
+            "level": self.level,
+            "data": self.data,
+        });
+        if let Some(logger) = &self.logger {
+            params["logger"] = serde_json::Value::String(logger.clone());
+        }
+        JsonRpcNotification::new(LOG_MESSAGE_NOTIFICATION.to_string(), Some(params))
+    }
 }
 
 /// Log message builder for easier construction
@@ -360,6 +524,77 @@ mod tests {
         assert_eq!(message.logger, Some("test".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_log_at_set_level_notifies_clients() {
+        let (sink, mut notifications) = mpsc::unbounded_channel();
+        let manager = LoggingManager::new().with_notification_sink(sink);
+        manager.set_level(LoggingLevel::Warning).await.unwrap();
+
+        manager
+            .info(Some("db".to_string()), serde_json::json!("below the level"))
+            .await;
+        manager
+            .warning(Some("db".to_string()), serde_json::json!({ "error": "slow query" }))
+            .await;
+
+        let notification = notifications.recv().await.unwrap();
+        assert_eq!(notification.method, LOG_MESSAGE_NOTIFICATION);
+        assert_eq!(
+            notification.params.unwrap(),
+            serde_json::json!({
+                "level": "warning",
+                "logger": "db",
+                "data": { "error": "slow query" }
+            })
+        );
+        assert!(notifications.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_filter_by_their_own_level() {
+        let (broadcast_sink, mut broadcasts) = mpsc::unbounded_channel();
+        let (session_sink, mut session_notifications) = mpsc::unbounded_channel();
+        let manager = LoggingManager::new()
+            .with_notification_sink(broadcast_sink)
+            .with_session_sink(session_sink);
+
+        manager.add_session("verbose").await;
+        manager.add_session("quiet").await;
+        manager
+            .set_session_level("verbose", LoggingLevel::Debug)
+            .await
+            .unwrap();
+        manager
+            .set_session_level("quiet", LoggingLevel::Error)
+            .await
+            .unwrap();
+
+        manager.debug(None, "details").await;
+        manager.error(None, "failure").await;
+
+        let mut received = Vec::new();
+        while let Ok((session_id, notification)) = session_notifications.try_recv() {
+            received.push((session_id, notification.params.unwrap()["data"].clone()));
+        }
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            received,
+            vec![
+                ("quiet".to_string(), serde_json::json!("failure")),
+                ("verbose".to_string(), serde_json::json!("details")),
+                ("verbose".to_string(), serde_json::json!("failure")),
+            ]
+        );
+        // Known sessions are not also reached by broadcast
+        assert!(broadcasts.try_recv().is_err());
+
+        manager.remove_session("quiet").await;
+        assert!(matches!(
+            manager.get_session_level("quiet").await,
+            LoggingLevel::Info
+        ));
+    }
+
     #[test]
     fn test_log_message_builder() {
         let message = LogMessageBuilder::new(LoggingLevel::Info)
@@ -4,16 +4,29 @@
 //! resources, tools, prompts, and other capabilities.
 
 pub mod completion;
+pub mod experimental;
+pub mod idempotency;
+pub mod list_changed;
 pub mod logging;
+pub mod pagination;
+#[cfg(feature = "plugins")]
+pub mod plugins;
+pub mod progress;
 pub mod prompts;
 pub mod resources;
+pub mod sandbox;
+pub mod tool_fn;
 pub mod tools;
 
 // Re-export main types
 pub use completion::CompletionManager;
+pub use experimental::ExperimentalCapabilities;
+pub use list_changed::{ListChangedNotifier, NotificationSink};
 pub use logging::LoggingManager;
+pub use progress::ProgressReporter;
 pub use prompts::PromptManager;
 pub use resources::ResourceManager;
+pub use sandbox::SandboxConfig;
 pub use tools::ToolManager;
 
 use std::collections::HashMap;
@@ -25,6 +38,9 @@ use crate::protocol::{
     PromptsCapability, ResourcesCapability, ServerCapabilities, ToolsCapability,
 };
 
+/// Default number of items returned per page by list operations
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+
 /// Feature manager trait for common functionality
 pub trait FeatureManager: Send + Sync {
     /// Get the feature name
@@ -54,6 +70,9 @@ pub struct ServerFeatureManager {
     /// Completion manager
     pub completion: Arc<CompletionManager>,
 
+    /// Experimental capabilities advertised at initialize time
+    pub experimental: Arc<ExperimentalCapabilities>,
+
     /// Feature enablement flags
     enabled_features: Arc<RwLock<HashMap<String, bool>>>,
 }
@@ -74,10 +93,35 @@ impl ServerFeatureManager {
             prompts: Arc::new(PromptManager::new()),
             logging: Arc::new(LoggingManager::new()),
             completion: Arc::new(CompletionManager::new()),
+            experimental: Arc::new(ExperimentalCapabilities::new()),
             enabled_features: Arc::new(RwLock::new(enabled_features)),
         }
     }
 
+    /// Use the given resource manager
+    pub fn with_resource_manager(mut self, resources: Arc<ResourceManager>) -> Self {
+        self.resources = resources;
+        self
+    }
+
+    /// Use the given tool manager
+    pub fn with_tool_manager(mut self, tools: Arc<ToolManager>) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Use the given prompt manager
+    pub fn with_prompt_manager(mut self, prompts: Arc<PromptManager>) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    /// Use the given logging manager
+    pub fn with_logging_manager(mut self, logging: Arc<LoggingManager>) -> Self {
+        self.logging = logging;
+        self
+    }
+
     /// Check if a feature is enabled
     pub async fn is_feature_enabled(&self, feature: &str) -> bool {
         let features = self.enabled_features.read().await;
@@ -93,9 +137,10 @@ impl ServerFeatureManager {
     /// Get server capabilities based on enabled features
     pub async fn get_capabilities(&self) -> ServerCapabilities {
         let features = self.enabled_features.read().await;
+        let experimental = self.experimental.snapshot().await;
 
         ServerCapabilities {
-            experimental: None,
+            experimental: (!experimental.is_empty()).then_some(experimental),
             logging: if *features.get("logging").unwrap_or(&false) {
                 Some(serde_json::json!({}))
             } else {
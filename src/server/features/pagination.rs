@@ -0,0 +1,95 @@
+//! Key-based pagination of list results.
+//!
+//! Lists are sorted by a unique key (tool or prompt name, resource URI, URI
+//! template) and the cursor handed to the client encodes the last key it
+//! received. The next page starts after that key, so items added or removed
+//! between requests never make iteration skip or repeat the others.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::error::{McpError, Result};
+use crate::protocol::{PaginationParams, PaginationResult};
+
+/// Encode the last key of a page as the cursor of the next one
+pub fn encode_cursor(last_key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(last_key)
+}
+
+/// Decode a cursor into the last key the client received
+pub fn decode_cursor(cursor: &str) -> Result<String> {
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|key| String::from_utf8(key).ok())
+        .ok_or_else(|| McpError::invalid_params("Invalid pagination cursor"))
+}
+
+/// Take the page of `items` following the cursor in `params`
+///
+/// `items` must be sorted by `key`.
+pub fn paginate_by_key<T>(
+    items: Vec<T>,
+    params: PaginationParams,
+    page_size: usize,
+    key: impl Fn(&T) -> &str,
+) -> Result<(Vec<T>, PaginationResult)> {
+    let start = match params.cursor {
+        Some(cursor) => {
+            let last_key = decode_cursor(&cursor)?;
+            items.partition_point(|item| key(item) <= last_key.as_str())
+        }
+        None => 0,
+    };
+
+    let end = std::cmp::min(start.saturating_add(page_size), items.len());
+    let has_more = end < items.len();
+    let page: Vec<T> = items.into_iter().skip(start).take(end - start).collect();
+
+    let next_cursor = match page.last() {
+        Some(last) if has_more => Some(encode_cursor(key(last))),
+        _ => None,
+    };
+
+    Ok((page, PaginationResult { next_cursor }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(items: &[&str], cursor: Option<String>) -> (Vec<String>, Option<String>) {
+        let items = items.iter().map(|s| s.to_string()).collect();
+        let (page, result) =
+            paginate_by_key(items, PaginationParams { cursor }, 2, |s: &String| s.as_str()).unwrap();
+        (page, result.next_cursor)
+    }
+
+    #[test]
+    fn test_insert_mid_pagination_neither_skips_nor_repeats() {
+        let (first, cursor) = page(&["b", "d", "f", "h"], None);
+        assert_eq!(first, vec!["b", "d"]);
+
+        // Items land before and after the cursor between requests
+        let (second, cursor) = page(&["a", "b", "c", "d", "e", "f", "h"], cursor);
+        assert_eq!(second, vec!["e", "f"]);
+
+        // The last seen item is removed before the final request
+        let (third, cursor) = page(&["a", "b", "c", "d", "e", "h"], cursor);
+        assert_eq!(third, vec!["h"]);
+        assert!(cursor.is_none());
+    }
+
+    #[test]
+    fn test_invalid_cursor_rejected() {
+        let result = paginate_by_key(
+            vec!["a".to_string()],
+            PaginationParams {
+                cursor: Some("not base64!".to_string()),
+            },
+            2,
+            |s: &String| s.as_str(),
+        );
+        assert!(matches!(result, Err(McpError::InvalidParams(_))));
+    }
+}
@@ -0,0 +1,400 @@
+//! Tool handler plugins loaded from shared libraries.
+//!
+//! Libraries listed in `tools.plugin_paths` are loaded at startup and their
+//! tools registered in the global [`ToolHandlerRegistry`]. A plugin exports a
+//! single C-ABI entry point named [`REGISTER_SYMBOL`]:
+//!
+//! ```c
+//! int32_t register_tools(const PluginRegistrar *registrar);
+//! ```
+//!
+//! It calls `registrar->register_tool` once per tool and returns 0 on success.
+//! Arguments and results cross the boundary as NUL-terminated JSON/UTF-8
+//! strings, so plugins don't need to share Rust types with the server.
+//!
+//! # Safety
+//!
+//! Loading a plugin runs arbitrary native code in the server process; only
+//! list libraries you trust. The server relies on the plugin to uphold the
+//! following, none of which can be checked:
+//!
+//! - `register_tools` has exactly the signature above.
+//! - Strings passed to `register_tool` are NUL-terminated and valid for the
+//!   duration of that call; the server copies them.
+//! - `call` is thread-safe, may run concurrently, and returns a NUL-terminated
+//!   UTF-8 string (or null) that stays valid until passed to `free_result`.
+//! - Neither function unwinds across the FFI boundary.
+//!
+//! Loaded libraries are never unloaded, since handlers may outlive any
+//! registry entry that refers to them.
+
+use std::collections::HashSet;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use libloading::Library;
+use serde_json::Value;
+use tracing::info;
+
+use super::tools::{ToolHandler, ToolHandlerRegistration, ToolHandlerRegistry, ToolResult};
+use crate::error::{McpError, Result};
+use crate::protocol::ToolInputSchema;
+
+/// Symbol every plugin library must export
+pub const REGISTER_SYMBOL: &str = "register_tools";
+
+/// Version of the plugin ABI described by [`PluginRegistrar`] and [`PluginTool`]
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Priority given to plugin tool handlers
+const PLUGIN_PRIORITY: i32 = 0;
+
+/// Executes a plugin tool: takes the arguments as JSON, returns the result text
+pub type PluginCallFn = unsafe extern "C" fn(arguments: *const c_char, is_error: *mut bool) -> *mut c_char;
+
+/// Releases a string returned by [`PluginCallFn`]
+pub type PluginFreeFn = unsafe extern "C" fn(result: *mut c_char);
+
+/// Tool description passed from a plugin to [`PluginRegistrar::register_tool`]
+#[repr(C)]
+pub struct PluginTool {
+    /// Tool name
+    pub name: *const c_char,
+
+    /// Tool description (may be null)
+    pub description: *const c_char,
+
+    /// Input schema as JSON (may be null for an empty object schema)
+    pub input_schema: *const c_char,
+
+    /// Executes the tool
+    pub call: PluginCallFn,
+
+    /// Releases results returned by `call`
+    pub free_result: PluginFreeFn,
+}
+
+/// Callback table handed to a plugin's `register_tools`
+#[repr(C)]
+pub struct PluginRegistrar {
+    /// ABI version the server implements
+    pub abi_version: u32,
+
+    /// Opaque server state to pass back to `register_tool`
+    pub context: *mut c_void,
+
+    /// Registers one tool
+    pub register_tool: unsafe extern "C" fn(context: *mut c_void, tool: *const PluginTool),
+}
+
+type RegisterToolsFn = unsafe extern "C" fn(registrar: *const PluginRegistrar) -> i32;
+
+/// Tool description copied out of the plugin
+struct PluginToolSpec {
+    name: String,
+    description: Option<String>,
+    input_schema: ToolInputSchema,
+    call: PluginCallFn,
+    free_result: PluginFreeFn,
+}
+
+/// Tool handler backed by a plugin library
+#[derive(Clone)]
+pub struct PluginToolHandler {
+    name: String,
+    description: Option<String>,
+    input_schema: ToolInputSchema,
+    call: PluginCallFn,
+    free_result: PluginFreeFn,
+
+    /// Keeps the library mapped while the handler exists
+    _library: Arc<Library>,
+}
+
+#[async_trait::async_trait]
+impl ToolHandler for PluginToolHandler {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<String> {
+        self.description.clone()
+    }
+
+    fn input_schema(&self) -> ToolInputSchema {
+        self.input_schema.clone()
+    }
+
+    async fn execute(&self, arguments: Option<Value>) -> Result<ToolResult> {
+        let arguments = arguments.unwrap_or_else(|| Value::Object(Default::default()));
+        let arguments = CString::new(serde_json::to_string(&arguments)?)
+            .map_err(|e| McpError::Tool(format!("Invalid plugin arguments: {}", e)))?;
+
+        let mut is_error = false;
+        // SAFETY: the plugin contract guarantees `call` accepts a NUL-terminated
+        // string and returns null or a NUL-terminated string owned by the plugin.
+        let text = unsafe {
+            let result = (self.call)(arguments.as_ptr(), &mut is_error);
+            if result.is_null() {
+                return Err(McpError::Tool(format!(
+                    "Plugin tool '{}' returned no result",
+                    self.name
+                )));
+            }
+            let text = CStr::from_ptr(result).to_string_lossy().into_owned();
+            (self.free_result)(result);
+            text
+        };
+
+        Ok(if is_error {
+            ToolResult::error_text(text)
+        } else {
+            ToolResult::text(text)
+        })
+    }
+
+    fn cpu_bound(&self) -> bool {
+        // Plugin calls are synchronous native code
+        true
+    }
+}
+
+/// Paths of libraries already loaded, so rediscovery doesn't load them twice
+fn loaded_paths() -> &'static Mutex<HashSet<PathBuf>> {
+    static LOADED: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    LOADED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Load every plugin in `paths`, returning the ones that failed
+pub fn load_plugins(paths: &[PathBuf]) -> Vec<(PathBuf, McpError)> {
+    paths
+        .iter()
+        .filter_map(|path| load_plugin(path).err().map(|e| (path.clone(), e)))
+        .collect()
+}
+
+/// Load a plugin library and register its tools
+///
+/// Returns the registrations added; a library that was already loaded adds none.
+/// A library only counts as loaded once all its tools are registered, so a
+/// failed load can be retried.
+pub fn load_plugin(path: &Path) -> Result<Vec<ToolHandlerRegistration>> {
+    let canonical = path.canonicalize().map_err(|e| {
+        McpError::Tool(format!("Cannot resolve plugin path {}: {}", path.display(), e))
+    })?;
+    // Held for the whole load so concurrent discoveries can't load it twice
+    let mut loaded = loaded_paths().lock().unwrap_or_else(|e| e.into_inner());
+    if loaded.contains(&canonical) {
+        return Ok(Vec::new());
+    }
+
+    // SAFETY: loading runs the library's initializers; see the module docs.
+    let library = unsafe { Library::new(&canonical) }
+        .map_err(|e| McpError::Tool(format!("Failed to load plugin: {}", e)))?;
+    let library = Arc::new(library);
+
+    let mut specs: Vec<std::result::Result<PluginToolSpec, String>> = Vec::new();
+    let registrar = PluginRegistrar {
+        abi_version: PLUGIN_ABI_VERSION,
+        context: &mut specs as *mut _ as *mut c_void,
+        register_tool: register_tool_callback,
+    };
+
+    // SAFETY: the plugin contract fixes the symbol's signature.
+    let status = unsafe {
+        let register = library
+            .get::<RegisterToolsFn>(REGISTER_SYMBOL.as_bytes())
+            .map_err(|e| McpError::Tool(format!("Missing `{}` symbol: {}", REGISTER_SYMBOL, e)))?;
+        register(&registrar)
+    };
+    if status != 0 {
+        return Err(McpError::Tool(format!(
+            "`{}` failed with status {}",
+            REGISTER_SYMBOL, status
+        )));
+    }
+
+    let specs = specs
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(McpError::Tool)?;
+    let mut registrations = Vec::new();
+    for spec in specs {
+        let handler = PluginToolHandler {
+            name: spec.name,
+            description: spec.description,
+            input_schema: spec.input_schema,
+            call: spec.call,
+            free_result: spec.free_result,
+            _library: library.clone(),
+        };
+        let registration = ToolHandlerRegistration {
+            name: handler.name.clone(),
+            factory: Arc::new(move || Ok(Box::new(handler.clone()) as Box<dyn ToolHandler>)),
+            priority: PLUGIN_PRIORITY,
+            is_builtin: false,
+            from_plugin: true,
+        };
+        ToolHandlerRegistry::insert(registration.clone())?;
+        registrations.push(registration);
+    }
+    loaded.insert(canonical.clone());
+
+    info!(
+        "Loaded {} tool(s) from plugin {}",
+        registrations.len(),
+        canonical.display()
+    );
+    Ok(registrations)
+}
+
+/// `register_tool` callback handed to plugins
+unsafe extern "C" fn register_tool_callback(context: *mut c_void, tool: *const PluginTool) {
+    // SAFETY: `context` is the `specs` vector owned by `load_plugin`, which
+    // outlives the `register_tools` call this callback runs in.
+    let specs = &mut *(context as *mut Vec<std::result::Result<PluginToolSpec, String>>);
+    specs.push(read_tool(tool));
+}
+
+/// Copy a plugin's tool description into owned values
+unsafe fn read_tool(tool: *const PluginTool) -> std::result::Result<PluginToolSpec, String> {
+    let tool = tool.as_ref().ok_or("Plugin registered a null tool")?;
+    let name = read_str(tool.name)?.ok_or("Plugin tool is missing a name")?;
+
+    let input_schema = match read_str(tool.input_schema)? {
+        Some(schema) => serde_json::from_str(&schema)
+            .map_err(|e| format!("Invalid input schema for plugin tool '{}': {}", name, e))?,
+        None => ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: None,
+            required: None,
+        },
+    };
+
+    Ok(PluginToolSpec {
+        description: read_str(tool.description)?,
+        name,
+        input_schema,
+        call: tool.call,
+        free_result: tool.free_result,
+    })
+}
+
+unsafe fn read_str(ptr: *const c_char) -> std::result::Result<Option<String>, String> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|e| format!("Plugin string is not valid UTF-8: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    /// A minimal plugin using only the C ABI, as a third party would write it
+    const SAMPLE_PLUGIN: &str = r#"
+use std::ffi::{c_char, c_void, CStr, CString};
+
+#[repr(C)]
+pub struct PluginTool {
+    name: *const c_char,
+    description: *const c_char,
+    input_schema: *const c_char,
+    call: unsafe extern "C" fn(*const c_char, *mut bool) -> *mut c_char,
+    free_result: unsafe extern "C" fn(*mut c_char),
+}
+
+#[repr(C)]
+pub struct PluginRegistrar {
+    abi_version: u32,
+    context: *mut c_void,
+    register_tool: unsafe extern "C" fn(*mut c_void, *const PluginTool),
+}
+
+unsafe extern "C" fn shout(arguments: *const c_char, _is_error: *mut bool) -> *mut c_char {
+    let arguments = CStr::from_ptr(arguments).to_string_lossy().to_uppercase();
+    CString::new(arguments).unwrap().into_raw()
+}
+
+unsafe extern "C" fn free_result(result: *mut c_char) {
+    drop(CString::from_raw(result));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn register_tools(registrar: *const PluginRegistrar) -> i32 {
+    let registrar = &*registrar;
+    if registrar.abi_version != 1 {
+        return 1;
+    }
+    let tool = PluginTool {
+        name: c"plugin_shout".as_ptr(),
+        description: c"Upper-cases its arguments".as_ptr(),
+        input_schema: c"{\"type\":\"object\",\"properties\":{\"text\":{\"type\":\"string\"}}}".as_ptr(),
+        call: shout,
+        free_result,
+    };
+    (registrar.register_tool)(registrar.context, &tool);
+    0
+}
+"#;
+
+    fn build_sample_plugin(dir: &Path) -> PathBuf {
+        let source = dir.join("sample_plugin.rs");
+        std::fs::write(&source, SAMPLE_PLUGIN).unwrap();
+
+        let output = dir.join(format!(
+            "{}sample_plugin{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        ));
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let status = Command::new(rustc)
+            .args(["--edition", "2021", "--crate-type", "cdylib", "-o"])
+            .arg(&output)
+            .arg(&source)
+            .status()
+            .unwrap();
+        assert!(status.success(), "failed to compile sample plugin");
+        output
+    }
+
+    #[tokio::test]
+    async fn test_sample_plugin_registers_tool() {
+        let dir = tempfile::tempdir().unwrap();
+        let built = build_sample_plugin(dir.path());
+
+        // A failed load isn't remembered, so the library can be loaded once fixed
+        let path = dir.path().join("retry").join(built.file_name().unwrap());
+        std::fs::create_dir(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "not a library").unwrap();
+        assert!(load_plugin(&path).is_err());
+        std::fs::copy(&built, &path).unwrap();
+
+        let registrations = load_plugin(&path).unwrap();
+        assert_eq!(registrations.len(), 1);
+        assert_eq!(registrations[0].name, "plugin_shout");
+        assert!(registrations[0].from_plugin);
+
+        let handler = (registrations[0].factory)().unwrap();
+        assert_eq!(handler.description().as_deref(), Some("Upper-cases its arguments"));
+        assert!(handler.input_schema().properties.unwrap().contains_key("text"));
+
+        let result = handler
+            .execute(Some(serde_json::json!({ "text": "hi" })))
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        match &result.content[0] {
+            crate::protocol::Content::Text { text, .. } => assert_eq!(text, r#"{"TEXT":"HI"}"#),
+            other => panic!("unexpected content: {:?}", other),
+        }
+
+        // Loading the same library again registers nothing new
+        assert!(load_plugin(&path).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Progress reporting for long-running requests.
+//!
+//! A request carrying `_meta.progressToken` gets a [`ProgressReporter`] bound
+//! to that token; each [`ProgressReporter::report`] becomes a
+//! `notifications/progress` sent back to the requesting client. Requests
+//! without a token get an inactive reporter whose reports are dropped.
+
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::protocol::JsonRpcNotification;
+
+/// Method of progress notifications
+pub const PROGRESS_NOTIFICATION: &str = "notifications/progress";
+
+/// Delivers a progress notification to the client that made the request
+pub type ProgressCallback = Arc<dyn Fn(JsonRpcNotification) + Send + Sync>;
+
+/// Reports progress of one request under its progress token
+#[derive(Clone, Default)]
+pub struct ProgressReporter {
+    target: Option<(Value, ProgressCallback)>,
+}
+
+impl ProgressReporter {
+    /// Create a reporter emitting notifications for `token` through `deliver`
+    pub fn new(token: Value, deliver: ProgressCallback) -> Self {
+        Self {
+            target: Some((token, deliver)),
+        }
+    }
+
+    /// Create a reporter that drops every report
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Check whether reports reach a client
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+
+    /// Report progress, with the total and a message if known
+    pub fn report(&self, progress: f64, total: Option<f64>, message: Option<&str>) {
+        let Some((token, deliver)) = &self.target else {
+            return;
+        };
+
+        let mut params = serde_json::json!({
+            "progressToken": token,
+            "progress": progress,
+        });
+        if let Some(total) = total {
+            params["total"] = serde_json::json!(total);
+        }
+        if let Some(message) = message {
+            params["message"] = serde_json::json!(message);
+        }
+
+        deliver(JsonRpcNotification::new(
+            PROGRESS_NOTIFICATION.to_string(),
+            Some(params),
+        ));
+    }
+}
+
+impl fmt::Debug for ProgressReporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressReporter")
+            .field("token", &self.target.as_ref().map(|(token, _)| token))
+            .finish()
+    }
+}
+
+/// Get the progress token from a request's `_meta`, if any
+pub fn progress_token(params: Option<&Value>) -> Option<Value> {
+    params?
+        .get("_meta")?
+        .get("progressToken")
+        .filter(|token| token.is_string() || token.is_number())
+        .cloned()
+}
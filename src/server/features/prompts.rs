@@ -5,13 +5,16 @@
 
 use handlebars::Handlebars;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 use crate::error::{McpError, Result};
 use crate::protocol::{PaginationParams, PaginationResult, Prompt, PromptMessage};
-use crate::server::features::FeatureManager;
+use crate::server::features::pagination::paginate_by_key;
+use crate::server::features::{FeatureManager, ListChangedNotifier, NotificationSink};
 
 /// Prompt manager for handling MCP prompts
 pub struct PromptManager {
@@ -25,7 +28,12 @@ pub struct PromptManager {
     handlebars: Arc<Handlebars<'static>>,
 
     /// Whether the feature is enabled
-    enabled: Arc<RwLock<bool>>,
+    enabled: Arc<AtomicBool>,
+
+    /// Maximum number of items per list page
+    page_size: usize,
+    /// Emits list_changed notifications
+    list_changed: ListChangedNotifier,
 }
 
 /// Prompt generator trait for dynamic prompt creation
@@ -66,10 +74,35 @@ impl PromptManager {
             prompts: Arc::new(RwLock::new(HashMap::new())),
             generators: Arc::new(RwLock::new(HashMap::new())),
             handlebars: Arc::new(Handlebars::new()),
-            enabled: Arc::new(RwLock::new(*enabled)),
+            enabled: Arc::new(AtomicBool::new(*enabled)),
+            page_size: super::DEFAULT_PAGE_SIZE,
+            list_changed: ListChangedNotifier::new("notifications/prompts/list_changed"),
         }
     }
 
+    /// Set the maximum number of prompts returned per list page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Get the maximum number of prompts returned per list page
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Publish `notifications/prompts/list_changed` to the given sink when the prompts change
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.list_changed = self.list_changed.with_sink(sink);
+        self
+    }
+
+    /// Set the window over which prompts changes are coalesced into one notification
+    pub fn with_list_changed_debounce(mut self, debounce: Duration) -> Self {
+        self.list_changed = self.list_changed.with_debounce(debounce);
+        self
+    }
+
     /// Register a prompt
     pub async fn register_prompt(&self, prompt: Prompt) -> Result<()> {
         if !self.is_enabled() {
@@ -82,6 +115,7 @@ impl PromptManager {
             let mut prompts = self.prompts.write().await;
             prompts.insert(name.clone(), prompt);
         }
+        self.list_changed.notify();
 
         info!("Registered prompt: {}", name);
         Ok(())
@@ -93,6 +127,7 @@ impl PromptManager {
         let prompt = prompts.remove(name);
 
         if prompt.is_some() {
+            self.list_changed.notify();
             info!("Unregistered prompt: {}", name);
         }
 
@@ -207,53 +242,26 @@ impl PromptManager {
 
     /// Check if the feature is enabled
     pub fn is_enabled(&self) -> bool {
-        // Use try_read to avoid blocking in sync context
-        self.enabled
-            .try_read()
-            .map(|enabled| *enabled)
-            .unwrap_or(true)
+        self.enabled.load(Ordering::Acquire)
     }
 
     /// Check if the feature is enabled (async version)
     pub async fn is_enabled_async(&self) -> bool {
-        let enabled = self.enabled.read().await;
-        *enabled
+        self.is_enabled()
     }
 
     /// Set enabled state
     pub async fn set_enabled(&self, enabled: bool) {
-        let mut state = self.enabled.write().await;
-        *state = enabled;
+        self.enabled.store(enabled, Ordering::Release);
     }
 
     /// Apply pagination to prompts
     fn apply_pagination(
         &self,
-        mut prompts: Vec<Prompt>,
+        prompts: Vec<Prompt>,
         params: PaginationParams,
     ) -> Result<(Vec<Prompt>, PaginationResult)> {
-        let start_index = if let Some(cursor) = params.cursor {
-            cursor.parse::<usize>().unwrap_or(0)
-        } else {
-            0
-        };
-
-        let page_size = 50; // Default page size
-        let end_index = std::cmp::min(start_index + page_size, prompts.len());
-
-        let page_prompts = if start_index < prompts.len() {
-            prompts.drain(start_index..end_index).collect()
-        } else {
-            Vec::new()
-        };
-
-        let next_cursor = if end_index < prompts.len() {
-            Some(end_index.to_string())
-        } else {
-            None
-        };
-
-        Ok((page_prompts, PaginationResult { next_cursor }))
+        paginate_by_key(prompts, params, self.page_size, |prompt| prompt.name.as_str())
     }
 }
 
@@ -267,9 +275,7 @@ impl FeatureManager for PromptManager {
     }
 
     fn set_enabled(&mut self, enabled: bool) {
-        if let Ok(mut state) = self.enabled.try_write() {
-            *state = enabled;
-        }
+        self.enabled.store(enabled, Ordering::Release);
     }
 }
 
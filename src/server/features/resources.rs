@@ -4,24 +4,149 @@
 //! to expose resources (files, data, etc.) to clients.
 
 use base64::Engine;
+use futures_util::StreamExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 use url::Url;
 
 use crate::error::{McpError, Result};
 use crate::protocol::{
-    PaginationParams, PaginationResult, Resource, ResourceContents, ResourceTemplate,
+    PaginationParams, PaginationResult, Resource, ResourceContents, ResourceMetadata,
+    ResourceTemplate,
 };
-use crate::server::features::FeatureManager;
+use crate::server::features::pagination::paginate_by_key;
+use crate::server::features::{FeatureManager, ListChangedNotifier, NotificationSink};
+use crate::utils::file_uri_to_path;
+
+/// Configuration for the resources feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ResourcesConfig {
+    /// Maximum number of resources a single client may subscribe to
+    #[serde(default = "default_max_subscriptions_per_client")]
+    pub max_subscriptions_per_client: usize,
+
+    /// Allow subscribing to URIs that don't exist yet (e.g. a file that will be created later)
+    ///
+    /// When false, subscribing requires a registered resource or a provider
+    /// that can currently stat the URI.
+    #[serde(default)]
+    pub allow_subscribe_to_missing: bool,
+
+    /// Manifest (TOML or JSON) of resources to register at startup
+    #[serde(default)]
+    pub manifest: Option<PathBuf>,
+
+    /// Register the HTTP provider, letting clients have the server fetch `http(s)://` URIs
+    ///
+    /// Off by default since it lets any client make the server issue web requests.
+    #[serde(default)]
+    pub enable_http_provider: bool,
+
+    /// Classify HTTP responses without a `Content-Type` by sniffing their bytes
+    ///
+    /// When false, such responses are always returned as binary blobs.
+    #[serde(default = "default_sniff_http_content_type")]
+    pub sniff_http_content_type: bool,
+}
+
+fn default_sniff_http_content_type() -> bool {
+    true
+}
+
+fn default_max_subscriptions_per_client() -> usize {
+    100
+}
+
+impl Default for ResourcesConfig {
+    fn default() -> Self {
+        Self {
+            max_subscriptions_per_client: default_max_subscriptions_per_client(),
+            allow_subscribe_to_missing: false,
+            manifest: None,
+            enable_http_provider: false,
+            sniff_http_content_type: default_sniff_http_content_type(),
+        }
+    }
+}
+
+/// Resources declared in a manifest file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceManifest {
+    /// Declared resources
+    #[serde(default)]
+    pub resources: Vec<ManifestEntry>,
+}
+
+/// A single resource declared in a manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Resource URI
+    pub uri: String,
+
+    /// Resource name
+    pub name: String,
+
+    /// Resource description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// MIME type
+    #[serde(default, alias = "mimeType")]
+    pub mime_type: Option<String>,
+
+    /// Name of the provider serving the resource
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+impl ResourceManifest {
+    /// Load a manifest, picking TOML or JSON from the file extension
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| {
+                McpError::Resource(format!("Invalid manifest {}: {}", path.display(), e))
+            }),
+            Some("json") => serde_json::from_str(&content).map_err(|e| {
+                McpError::Resource(format!("Invalid manifest {}: {}", path.display(), e))
+            }),
+            _ => Err(McpError::Resource(format!(
+                "Unsupported manifest file extension: {}",
+                path.display()
+            ))),
+        }
+    }
+}
+
+/// Outcome of loading a resource manifest
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ManifestReport {
+    /// URIs of the registered resources
+    pub registered: Vec<String>,
+
+    /// Malformed entries as (entry index, reason)
+    pub rejected: Vec<(usize, String)>,
+}
 
 /// Resource manager for handling MCP resources
 pub struct ResourceManager {
     /// Registered resources
     resources: Arc<RwLock<HashMap<String, Resource>>>,
 
+    /// Contents served directly for resources registered with them
+    inline_contents: Arc<RwLock<HashMap<String, Vec<ResourceContents>>>>,
+
     /// Resource templates
     templates: Arc<RwLock<HashMap<String, ResourceTemplate>>>,
 
@@ -32,7 +157,59 @@ pub struct ResourceManager {
     subscriptions: Arc<RwLock<HashMap<String, Vec<String>>>>, // URI -> client IDs
 
     /// Whether the feature is enabled
-    enabled: Arc<RwLock<bool>>,
+    enabled: Arc<AtomicBool>,
+
+    /// Feature configuration
+    config: ResourcesConfig,
+
+    /// Providers that failed to initialize
+    failed_providers: Arc<RwLock<HashMap<String, FailedProvider>>>,
+
+    /// Maximum number of items per list page
+    page_size: usize,
+    /// Emits list_changed notifications
+    list_changed: ListChangedNotifier,
+}
+
+/// A provider whose initialization failed
+struct FailedProvider {
+    /// The provider, kept to match URIs it would have served
+    provider: Box<dyn ResourceProvider>,
+
+    /// Why initialization failed
+    reason: String,
+}
+
+/// Size of the chunks file reads are split into between cancellation checks
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Method of the notifications carrying the chunks of a streamed `resources/read`
+pub const RESOURCE_CHUNK_NOTIFICATION: &str = "notifications/resources/chunk";
+
+/// Text of a resource, read piece by piece
+pub type TextChunkStream = futures_util::stream::BoxStream<'static, Result<String>>;
+
+fn cancelled_read(uri: &str) -> McpError {
+    McpError::cancelled(format!("Read of {} was cancelled", uri))
+}
+
+/// Hash identifying a read's contents as a whole
+///
+/// For a single content item this is that item's hash; otherwise it's the
+/// SHA-256 of the items' hashes in order.
+pub fn contents_hash(contents: &[ResourceContents]) -> String {
+    use sha2::{Digest, Sha256};
+
+    match contents {
+        [single] => single.content_hash(),
+        _ => {
+            let mut hasher = Sha256::new();
+            for content in contents {
+                hasher.update(content.content_hash().as_bytes());
+            }
+            crate::utils::hex_encode(&hasher.finalize())
+        }
+    }
 }
 
 /// Resource provider trait for different resource types
@@ -44,9 +221,79 @@ pub trait ResourceProvider: Send + Sync {
     /// Check if the provider can handle the given URI
     fn can_handle(&self, uri: &str) -> bool;
 
+    /// Prepare the provider before it serves reads (optional)
+    async fn initialize(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Read resource contents
     async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>>;
 
+    /// Read resource contents, giving up once `cancel` fires
+    ///
+    /// The default drops the in-flight read; providers doing long reads can
+    /// override this to stop between chunks.
+    async fn read_resource_cancellable(
+        &self,
+        uri: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<ResourceContents>> {
+        tokio::select! {
+            _ = cancel.cancelled() => Err(cancelled_read(uri)),
+            result = self.read_resource(uri) => result,
+        }
+    }
+
+    /// Read a text resource in chunks of at most about `chunk_size` bytes (optional)
+    ///
+    /// Returns `None` when the provider can't stream the URI, in which case
+    /// callers fall back to [`ResourceProvider::read_resource`].
+    async fn read_text_chunks(
+        &self,
+        uri: &str,
+        chunk_size: usize,
+    ) -> Result<Option<TextChunkStream>> {
+        let _ = (uri, chunk_size);
+        Ok(None)
+    }
+
+    /// Get resource metadata without returning its contents
+    ///
+    /// The default reads the resource and measures it; providers that can
+    /// look up size and type cheaply should override this.
+    async fn stat(&self, uri: &str) -> Result<ResourceMetadata> {
+        let contents = self.read_resource(uri).await?;
+
+        let mut mime_type = None;
+        let mut size = 0u64;
+        for content in &contents {
+            match content {
+                ResourceContents::Text {
+                    mime_type: mt, text, ..
+                } => {
+                    mime_type = mime_type.or_else(|| mt.clone());
+                    size += text.len() as u64;
+                }
+                ResourceContents::Blob {
+                    mime_type: mt, blob, ..
+                } => {
+                    mime_type = mime_type.or_else(|| mt.clone());
+                    let decoded = base64::engine::general_purpose::STANDARD
+                        .decode(blob)
+                        .map_err(|e| McpError::Resource(format!("Invalid blob contents: {}", e)))?;
+                    size += decoded.len() as u64;
+                }
+            }
+        }
+
+        Ok(ResourceMetadata {
+            uri: uri.to_string(),
+            mime_type,
+            size: Some(size),
+            last_modified: None,
+        })
+    }
+
     /// List resources (optional)
     async fn list_resources(&self, pattern: Option<&str>) -> Result<Vec<Resource>> {
         let _ = pattern;
@@ -69,17 +316,78 @@ pub trait ResourceProvider: Send + Sync {
 impl ResourceManager {
     /// Create a new resource manager
     pub fn new() -> Self {
+        Self::with_config(ResourcesConfig::default())
+    }
+
+    /// Create a new resource manager with the given configuration
+    pub fn with_config(config: ResourcesConfig) -> Self {
         Self {
             resources: Arc::new(RwLock::new(HashMap::new())),
+            inline_contents: Arc::new(RwLock::new(HashMap::new())),
             templates: Arc::new(RwLock::new(HashMap::new())),
             providers: Arc::new(RwLock::new(HashMap::new())),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
-            enabled: Arc::new(RwLock::new(true)),
+            enabled: Arc::new(AtomicBool::new(true)),
+            config,
+            failed_providers: Arc::new(RwLock::new(HashMap::new())),
+            page_size: super::DEFAULT_PAGE_SIZE,
+            list_changed: ListChangedNotifier::new("notifications/resources/list_changed"),
         }
     }
 
+    /// Set the maximum number of resources and templates returned per list page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Get the maximum number of resources and templates returned per list page
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Check whether the HTTP provider should be registered
+    pub fn http_provider_enabled(&self) -> bool {
+        self.config.enable_http_provider
+    }
+
+    /// Check whether the HTTP provider should sniff responses lacking a content type
+    pub fn http_content_sniffing_enabled(&self) -> bool {
+        self.config.sniff_http_content_type
+    }
+
+    /// Publish `notifications/resources/list_changed` to the given sink when the resources change
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.list_changed = self.list_changed.with_sink(sink);
+        self
+    }
+
+    /// Set the window over which resources changes are coalesced into one notification
+    pub fn with_list_changed_debounce(mut self, debounce: Duration) -> Self {
+        self.list_changed = self.list_changed.with_debounce(debounce);
+        self
+    }
+
     /// Register a resource
     pub async fn register_resource(&self, resource: Resource) -> Result<()> {
+        self.insert_resource(resource, None).await
+    }
+
+    /// Register a resource served from the given contents rather than a provider
+    pub async fn register_resource_with_contents(
+        &self,
+        resource: Resource,
+        contents: Vec<ResourceContents>,
+    ) -> Result<()> {
+        self.insert_resource(resource, Some(contents)).await
+    }
+
+    /// Register a resource, replacing any inline contents it was registered with
+    async fn insert_resource(
+        &self,
+        resource: Resource,
+        contents: Option<Vec<ResourceContents>>,
+    ) -> Result<()> {
         if !self.is_enabled() {
             return Err(McpError::Resource(
                 "Resource feature is disabled".to_string(),
@@ -88,10 +396,18 @@ impl ResourceManager {
 
         let uri = resource.uri.clone();
 
+        {
+            let mut inline_contents = self.inline_contents.write().await;
+            match contents {
+                Some(contents) => inline_contents.insert(uri.clone(), contents),
+                None => inline_contents.remove(&uri),
+            };
+        }
         {
             let mut resources = self.resources.write().await;
             resources.insert(uri.clone(), resource);
         }
+        self.list_changed.notify();
 
         info!("Registered resource: {}", uri);
         Ok(())
@@ -99,10 +415,12 @@ impl ResourceManager {
 
     /// Unregister a resource
     pub async fn unregister_resource(&self, uri: &str) -> Result<Option<Resource>> {
+        self.inline_contents.write().await.remove(uri);
         let mut resources = self.resources.write().await;
         let resource = resources.remove(uri);
 
         if resource.is_some() {
+            self.list_changed.notify();
             info!("Unregistered resource: {}", uri);
         }
 
@@ -173,6 +491,7 @@ impl ResourceManager {
             let mut templates = self.templates.write().await;
             templates.insert(uri_template.clone(), template);
         }
+        self.list_changed.notify();
 
         info!("Registered resource template: {}", uri_template);
         Ok(())
@@ -206,20 +525,33 @@ impl ResourceManager {
     }
 
     /// Read resource contents
-    pub async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
+    ///
+    /// Firing `cancel` aborts the provider's read with [`McpError::Cancelled`].
+    pub async fn read_resource(
+        &self,
+        uri: &str,
+        cancel: Option<&CancellationToken>,
+    ) -> Result<Vec<ResourceContents>> {
         if !self.is_enabled() {
             return Err(McpError::Resource(
                 "Resource feature is disabled".to_string(),
             ));
         }
 
+        let never_cancelled = CancellationToken::new();
+        let cancel = cancel.unwrap_or(&never_cancelled);
+
+        if let Some(contents) = self.inline_contents.read().await.get(uri) {
+            return Ok(contents.clone());
+        }
+
         // First check if we have a registered resource
         if let Some(_resource) = self.get_resource(uri).await {
             // Try to find a provider that can handle this URI
             let providers = self.providers.read().await;
             for provider in providers.values() {
                 if provider.can_handle(uri) {
-                    return provider.read_resource(uri).await;
+                    return provider.read_resource_cancellable(uri, cancel).await;
                 }
             }
         }
@@ -228,14 +560,91 @@ impl ResourceManager {
         let providers = self.providers.read().await;
         for provider in providers.values() {
             if provider.can_handle(uri) {
-                return provider.read_resource(uri).await;
+                return provider.read_resource_cancellable(uri, cancel).await;
+            }
+        }
+
+        Err(self.missing_provider_error(uri).await)
+    }
+
+    /// Read a text resource in chunks, if its provider can stream it
+    ///
+    /// Returns `None` for resources that must be read whole, such as those
+    /// registered with inline contents.
+    pub async fn read_text_chunks(
+        &self,
+        uri: &str,
+        chunk_size: usize,
+    ) -> Result<Option<TextChunkStream>> {
+        if !self.is_enabled() {
+            return Err(McpError::Resource(
+                "Resource feature is disabled".to_string(),
+            ));
+        }
+
+        if self.inline_contents.read().await.contains_key(uri) {
+            return Ok(None);
+        }
+
+        let providers = self.providers.read().await;
+        for provider in providers.values() {
+            if provider.can_handle(uri) {
+                return provider.read_text_chunks(uri, chunk_size).await;
             }
         }
 
-        Err(McpError::Resource(format!(
-            "No provider found for resource: {}",
-            uri
-        )))
+        Err(self.missing_provider_error(uri).await)
+    }
+
+    /// Get resource metadata without reading its contents where possible
+    pub async fn stat_resource(&self, uri: &str) -> Result<ResourceMetadata> {
+        if !self.is_enabled() {
+            return Err(McpError::Resource(
+                "Resource feature is disabled".to_string(),
+            ));
+        }
+
+        if let Some(contents) = self.inline_contents.read().await.get(uri) {
+            let resource = self.get_resource(uri).await;
+            let size = contents
+                .iter()
+                .map(|content| match content {
+                    ResourceContents::Text { text, .. } => Some(text.len() as u64),
+                    ResourceContents::Blob { .. } => None,
+                })
+                .sum();
+            return Ok(ResourceMetadata {
+                uri: uri.to_string(),
+                mime_type: resource.and_then(|resource| resource.mime_type),
+                size,
+                last_modified: None,
+            });
+        }
+
+        let providers = self.providers.read().await;
+        for provider in providers.values() {
+            if provider.can_handle(uri) {
+                return provider.stat(uri).await;
+            }
+        }
+
+        Err(self.missing_provider_error(uri).await)
+    }
+
+    /// Check whether a resource is registered or can currently be resolved by a provider
+    pub async fn resource_exists(&self, uri: &str) -> bool {
+        if self.get_resource(uri).await.is_some() {
+            return true;
+        }
+
+        let providers = self.providers.read().await;
+        for provider in providers.values() {
+            if provider.can_handle(uri) && provider.stat(uri).await.is_ok() {
+                return true;
+            }
+        }
+
+        false
     }
 
     /// Subscribe to resource updates
@@ -246,14 +655,34 @@ impl ResourceManager {
             ));
         }
 
-        // Add to subscriptions
+        if !self.config.allow_subscribe_to_missing && !self.resource_exists(uri).await {
+            return Err(McpError::resource_not_found(uri));
+        }
+
+        // Add to subscriptions, enforcing the per-client limit
         {
             let mut subscriptions = self.subscriptions.write().await;
-            let clients = subscriptions
-                .entry(uri.to_string())
-                .or_insert_with(Vec::new);
-            if !clients.contains(&client_id.to_string()) {
-                clients.push(client_id.to_string());
+            let already_subscribed = subscriptions
+                .get(uri)
+                .is_some_and(|clients| clients.iter().any(|id| id == client_id));
+
+            if !already_subscribed {
+                let client_subscriptions = subscriptions
+                    .values()
+                    .filter(|clients| clients.iter().any(|id| id == client_id))
+                    .count();
+                let limit = self.config.max_subscriptions_per_client;
+                if client_subscriptions >= limit {
+                    return Err(McpError::Resource(format!(
+                        "Client {} has reached the subscription limit of {}",
+                        client_id, limit
+                    )));
+                }
+
+                subscriptions
+                    .entry(uri.to_string())
+                    .or_insert_with(Vec::new)
+                    .push(client_id.to_string());
             }
         }
 
@@ -316,10 +745,59 @@ impl ResourceManager {
         Ok(())
     }
 
+    /// Remove every subscription held by a client, returning how many were removed
+    pub async fn unsubscribe_all(&self, client_id: &str) -> usize {
+        let uris: Vec<String> = {
+            let subscriptions = self.subscriptions.read().await;
+            subscriptions
+                .iter()
+                .filter(|(_, clients)| clients.iter().any(|id| id == client_id))
+                .map(|(uri, _)| uri.clone())
+                .collect()
+        };
+
+        for uri in &uris {
+            if let Err(e) = self.unsubscribe(uri, client_id).await {
+                warn!("Failed to unsubscribe {} from {}: {}", client_id, uri, e);
+            }
+        }
+
+        uris.len()
+    }
+
+    /// Get the number of resources a client is subscribed to
+    pub async fn get_client_subscription_count(&self, client_id: &str) -> usize {
+        let subscriptions = self.subscriptions.read().await;
+        subscriptions
+            .values()
+            .filter(|clients| clients.iter().any(|id| id == client_id))
+            .count()
+    }
+
     /// Register a resource provider
+    ///
+    /// The provider is initialized first. If that fails it is not registered,
+    /// and reads it would have served report the provider as unavailable.
     pub async fn register_provider(&self, provider: Box<dyn ResourceProvider>) -> Result<()> {
         let name = provider.name().to_string();
 
+        if let Err(e) = provider.initialize().await {
+            let reason = e.to_string();
+            let mut failed = self.failed_providers.write().await;
+            failed.insert(
+                name.clone(),
+                FailedProvider {
+                    provider,
+                    reason: reason.clone(),
+                },
+            );
+            return Err(McpError::ProviderUnavailable(format!(
+                "{} failed to initialize: {}",
+                name, reason
+            )));
+        }
+
+        self.failed_providers.write().await.remove(&name);
         {
             let mut providers = self.providers.write().await;
             providers.insert(name.clone(), provider);
@@ -329,6 +807,114 @@ impl ResourceManager {
         Ok(())
     }
 
+    /// Check whether a provider with the given name is registered
+    pub async fn has_provider(&self, name: &str) -> bool {
+        self.providers.read().await.contains_key(name)
+    }
+
+    /// Get the providers that failed to initialize, with the failure reason
+    pub async fn get_provider_failures(&self) -> Vec<(String, String)> {
+        let failed = self.failed_providers.read().await;
+        failed
+            .iter()
+            .map(|(name, failure)| (name.clone(), failure.reason.clone()))
+            .collect()
+    }
+
+    /// Register the resources declared in a manifest file
+    ///
+    /// Malformed entries are skipped and reported; valid ones are still registered.
+    pub async fn load_manifest<P: AsRef<Path>>(&self, path: P) -> Result<ManifestReport> {
+        let manifest = ResourceManifest::from_file(path)?;
+        self.register_manifest(manifest).await
+    }
+
+    /// Register the resources declared in a parsed manifest
+    pub async fn register_manifest(&self, manifest: ResourceManifest) -> Result<ManifestReport> {
+        let mut report = ManifestReport::default();
+
+        for (index, entry) in manifest.resources.into_iter().enumerate() {
+            if let Err(reason) = self.validate_manifest_entry(&entry).await {
+                warn!("Skipping manifest entry {} ({}): {}", index, entry.uri, reason);
+                report.rejected.push((index, reason));
+                continue;
+            }
+
+            let resource = Resource {
+                uri: entry.uri,
+                name: entry.name,
+                description: entry.description,
+                mime_type: entry.mime_type,
+                annotations: None,
+                size: None,
+            };
+            report.registered.push(resource.uri.clone());
+            self.register_resource(resource).await?;
+        }
+
+        Ok(report)
+    }
+
+    /// Register the manifest named in the configuration, if any
+    pub async fn load_configured_manifest(&self) -> Result<Option<ManifestReport>> {
+        match &self.config.manifest {
+            Some(path) => self.load_manifest(path).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Check a manifest entry, returning the reason it is malformed
+    async fn validate_manifest_entry(&self, entry: &ManifestEntry) -> std::result::Result<(), String> {
+        if entry.name.trim().is_empty() {
+            return Err("name must not be empty".to_string());
+        }
+
+        if let Err(e) = Url::parse(&entry.uri) {
+            return Err(format!("invalid URI '{}': {}", entry.uri, e));
+        }
+
+        if let Some(mime_type) = &entry.mime_type {
+            if mime_type.parse::<mime_guess::mime::Mime>().is_err() {
+                return Err(format!("invalid MIME type '{}'", mime_type));
+            }
+        }
+
+        if let Some(name) = &entry.provider {
+            let providers = self.providers.read().await;
+            match providers.get(name) {
+                Some(provider) if !provider.can_handle(&entry.uri) => {
+                    return Err(format!("provider '{}' cannot handle {}", name, entry.uri));
+                }
+                Some(_) => {}
+                None => return Err(format!("unknown provider '{}'", name)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build the error for a URI no registered provider can handle
+    async fn missing_provider_error(&self, uri: &str) -> McpError {
+        let failed = self.failed_providers.read().await;
+        for (name, failure) in failed.iter() {
+            if failure.provider.can_handle(uri) {
+                return McpError::ProviderUnavailable(format!(
+                    "{} cannot serve {} because it failed to initialize: {}",
+                    name, uri, failure.reason
+                ));
+            }
+        }
+
+        if self.get_resource(uri).await.is_some() {
+            return McpError::Resource(format!(
+                "Resource {} is registered but has no contents and no provider can read it",
+                uri
+            ));
+        }
+
+        McpError::resource_not_found(uri)
+    }
+
     /// Get resource count
     pub async fn get_resource_count(&self) -> usize {
         let resources = self.resources.read().await;
@@ -343,83 +929,35 @@ impl ResourceManager {
 
     /// Check if the feature is enabled
     pub fn is_enabled(&self) -> bool {
-        // Use try_read to avoid blocking in sync context
-        self.enabled
-            .try_read()
-            .map(|enabled| *enabled)
-            .unwrap_or(true)
+        self.enabled.load(Ordering::Acquire)
     }
 
     /// Check if the feature is enabled (async version)
     pub async fn is_enabled_async(&self) -> bool {
-        let enabled = self.enabled.read().await;
-        *enabled
+        self.is_enabled()
     }
 
     /// Set enabled state
     pub async fn set_enabled(&self, enabled: bool) {
-        let mut state = self.enabled.write().await;
-        *state = enabled;
+        self.enabled.store(enabled, Ordering::Release);
     }
 
     /// Apply pagination to resources
     fn apply_pagination(
         &self,
-        mut resources: Vec<Resource>,
+        resources: Vec<Resource>,
         params: PaginationParams,
     ) -> Result<(Vec<Resource>, PaginationResult)> {
-        let start_index = if let Some(cursor) = params.cursor {
-            cursor.parse::<usize>().unwrap_or(0)
-        } else {
-            0
-        };
-
-        let page_size = 50; // Default page size
-        let end_index = std::cmp::min(start_index + page_size, resources.len());
-
-        let page_resources = if start_index < resources.len() {
-            resources.drain(start_index..end_index).collect()
-        } else {
-            Vec::new()
-        };
-
-        let next_cursor = if end_index < resources.len() {
-            Some(end_index.to_string())
-        } else {
-            None
-        };
-
-        Ok((page_resources, PaginationResult { next_cursor }))
+        paginate_by_key(resources, params, self.page_size, |resource| resource.uri.as_str())
     }
 
     /// Apply pagination to templates
     fn apply_template_pagination(
         &self,
-        mut templates: Vec<ResourceTemplate>,
+        templates: Vec<ResourceTemplate>,
         params: PaginationParams,
     ) -> Result<(Vec<ResourceTemplate>, PaginationResult)> {
-        let start_index = if let Some(cursor) = params.cursor {
-            cursor.parse::<usize>().unwrap_or(0)
-        } else {
-            0
-        };
-
-        let page_size = 50;
-        let end_index = std::cmp::min(start_index + page_size, templates.len());
-
-        let page_templates = if start_index < templates.len() {
-            templates.drain(start_index..end_index).collect()
-        } else {
-            Vec::new()
-        };
-
-        let next_cursor = if end_index < templates.len() {
-            Some(end_index.to_string())
-        } else {
-            None
-        };
-
-        Ok((page_templates, PaginationResult { next_cursor }))
+        paginate_by_key(templates, params, self.page_size, |template| template.uri_template.as_str())
     }
 }
 
@@ -433,9 +971,7 @@ impl FeatureManager for ResourceManager {
     }
 
     fn set_enabled(&mut self, enabled: bool) {
-        if let Ok(mut state) = self.enabled.try_write() {
-            *state = enabled;
-        }
+        self.enabled.store(enabled, Ordering::Release);
     }
 }
 
@@ -446,51 +982,92 @@ pub struct FileSystemProvider {
 
     /// Whether to allow access outside root directory
     allow_outside_root: bool,
+
+    /// Canonical form of `root_dir`, resolved once it exists
+    canonical_root: std::sync::OnceLock<PathBuf>,
 }
 
 impl FileSystemProvider {
     /// Create a new file system provider
     pub fn new(root_dir: PathBuf) -> Self {
-        Self {
-            root_dir,
-            allow_outside_root: false,
-        }
+        Self::with_settings(root_dir, false)
     }
 
     /// Create a new file system provider with custom settings
     pub fn with_settings(root_dir: PathBuf, allow_outside_root: bool) -> Self {
-        Self {
+        let provider = Self {
             root_dir,
             allow_outside_root,
+            canonical_root: std::sync::OnceLock::new(),
+        };
+        // The root usually exists already; if not, it's resolved on first use
+        let _ = provider.canonical_root();
+        provider
+    }
+
+    /// Get the canonical root directory, canonicalizing it only until that succeeds
+    fn canonical_root(&self) -> Result<&Path> {
+        if let Some(root) = self.canonical_root.get() {
+            return Ok(root);
         }
+
+        let root = self
+            .root_dir
+            .canonicalize()
+            .map_err(|e| McpError::Resource(format!("Failed to canonicalize root: {}", e)))?;
+        Ok(self.canonical_root.get_or_init(|| root))
     }
 
-    /// Validate and resolve file path
-    fn resolve_path(&self, uri: &str) -> Result<PathBuf> {
-        let url = Url::parse(uri).map_err(|e| McpError::Resource(format!("Invalid URI: {}", e)))?;
+    /// Reject paths with a component below the root that is a symlink leading outside it
+    ///
+    /// Unlike canonicalizing the whole path, this also catches escapes in
+    /// paths whose final components don't exist yet.
+    fn check_symlinks(&self, path: &Path) -> Result<()> {
+        let root = self.canonical_root()?;
+        let relative = match path
+            .strip_prefix(&self.root_dir)
+            .or_else(|_| path.strip_prefix(root))
+        {
+            Ok(relative) => relative,
+            // Not under the root at all; the canonical check rejects it
+            Err(_) => return Ok(()),
+        };
 
-        if url.scheme() != "file" {
-            return Err(McpError::Resource(
-                "Only file:// URIs are supported".to_string(),
-            ));
+        let mut current = root.to_path_buf();
+        for component in relative.components() {
+            current.push(component);
+            let Ok(metadata) = std::fs::symlink_metadata(&current) else {
+                break;
+            };
+            if metadata.file_type().is_symlink() && !self.symlink_stays_in_root(&current, root) {
+                warn!("Rejected symlink escaping root: {}", current.display());
+                return Err(McpError::SymlinkEscape(current.display().to_string()));
+            }
         }
 
-        let path = url
-            .to_file_path()
-            .map_err(|_| McpError::Resource("Invalid file path".to_string()))?;
+        Ok(())
+    }
+
+    /// Check whether a symlink resolves to somewhere inside the root (dangling links don't)
+    fn symlink_stays_in_root(&self, link: &Path, root: &Path) -> bool {
+        link.canonicalize()
+            .map(|target| target.starts_with(root))
+            .unwrap_or(false)
+    }
+
+    /// Validate and resolve file path
+    fn resolve_path(&self, uri: &str) -> Result<PathBuf> {
+        let path = file_uri_to_path(uri).map_err(|e| McpError::Resource(e.to_string()))?;
 
         // Security check: ensure path is within root directory
         if !self.allow_outside_root {
+            self.check_symlinks(&path)?;
+
             let canonical_path = path
                 .canonicalize()
                 .map_err(|e| McpError::Resource(format!("Failed to canonicalize path: {}", e)))?;
 
-            let canonical_root = self
-                .root_dir
-                .canonicalize()
-                .map_err(|e| McpError::Resource(format!("Failed to canonicalize root: {}", e)))?;
-
-            if !canonical_path.starts_with(&canonical_root) {
+            if !canonical_path.starts_with(self.canonical_root()?) {
                 return Err(McpError::Resource(
                     "Access denied: path outside root directory".to_string(),
                 ));
@@ -511,7 +1088,26 @@ impl ResourceProvider for FileSystemProvider {
         uri.starts_with("file://")
     }
 
+    async fn initialize(&self) -> Result<()> {
+        if !self.root_dir.is_dir() {
+            return Err(McpError::Resource(format!(
+                "Root directory {} is not an accessible directory",
+                self.root_dir.display()
+            )));
+        }
+        Ok(())
+    }
+
     async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
+        self.read_resource_cancellable(uri, &CancellationToken::new())
+            .await
+    }
+
+    async fn read_resource_cancellable(
+        &self,
+        uri: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<ResourceContents>> {
         let path = self.resolve_path(uri)?;
 
         if !path.exists() {
@@ -528,10 +1124,25 @@ impl ResourceProvider for FileSystemProvider {
             )));
         }
 
-        // Read file contents
-        let contents = tokio::fs::read(&path)
+        // Read file contents in chunks so a cancelled read stops early
+        let mut file = tokio::fs::File::open(&path)
             .await
             .map_err(|e| McpError::Resource(format!("Failed to read file: {}", e)))?;
+        let mut contents = Vec::new();
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        loop {
+            if cancel.is_cancelled() {
+                return Err(cancelled_read(uri));
+            }
+            let read = file
+                .read(&mut chunk)
+                .await
+                .map_err(|e| McpError::Resource(format!("Failed to read file: {}", e)))?;
+            if read == 0 {
+                break;
+            }
+            contents.extend_from_slice(&chunk[..read]);
+        }
 
         // Determine MIME type
         let mime_type = mime_guess::from_path(&path)
@@ -540,20 +1151,63 @@ impl ResourceProvider for FileSystemProvider {
 
         // Try to read as text first
         if let Ok(text) = String::from_utf8(contents.clone()) {
-            Ok(vec![ResourceContents::Text {
-                uri: uri.to_string(),
-                mime_type: Some(mime_type),
-                text,
-            }])
+            Ok(vec![ResourceContents::text(uri, Some(mime_type), text)])
         } else {
             // Fall back to binary
             let blob = base64::engine::general_purpose::STANDARD.encode(&contents);
-            Ok(vec![ResourceContents::Blob {
-                uri: uri.to_string(),
-                mime_type: Some(mime_type),
-                blob,
-            }])
+            Ok(vec![ResourceContents::blob(uri, Some(mime_type), blob)])
+        }
+    }
+
+    async fn read_text_chunks(
+        &self,
+        uri: &str,
+        chunk_size: usize,
+    ) -> Result<Option<TextChunkStream>> {
+        let path = self.resolve_path(uri)?;
+        if !path.is_file() {
+            return Err(McpError::Resource(format!(
+                "Path is not a file: {}",
+                path.display()
+            )));
+        }
+
+        // Only text is streamed; binary files are returned whole as blobs
+        let mime_type = mime_guess::from_path(&path).first_or_octet_stream();
+        if mime_type.type_() != mime_guess::mime::TEXT {
+            return Ok(None);
+        }
+
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| McpError::Resource(format!("Failed to read file: {}", e)))?;
+        Ok(Some(text_chunks(file, chunk_size.max(1)).boxed()))
+    }
+
+    async fn stat(&self, uri: &str) -> Result<ResourceMetadata> {
+        let path = self.resolve_path(uri)?;
+
+        let metadata = tokio::fs::metadata(&path).await.map_err(|e| {
+            McpError::Resource(format!("File not found: {} ({})", path.display(), e))
+        })?;
+
+        if !metadata.is_file() {
+            return Err(McpError::Resource(format!(
+                "Path is not a file: {}",
+                path.display()
+            )));
         }
+
+        let mime_type = mime_guess::from_path(&path)
+            .first_or_octet_stream()
+            .to_string();
+
+        Ok(ResourceMetadata {
+            uri: uri.to_string(),
+            mime_type: Some(mime_type),
+            size: Some(metadata.len()),
+            last_modified: metadata.modified().ok().map(chrono::DateTime::from),
+        })
     }
 
     async fn list_resources(&self, pattern: Option<&str>) -> Result<Vec<Resource>> {
@@ -571,6 +1225,17 @@ impl ResourceProvider for FileSystemProvider {
         {
             let path = entry.path();
 
+            let is_symlink = entry.file_type().await.is_ok_and(|t| t.is_symlink());
+            if is_symlink && !self.allow_outside_root {
+                let stays_in_root = self
+                    .canonical_root()
+                    .is_ok_and(|root| self.symlink_stays_in_root(&path, root));
+                if !stays_in_root {
+                    debug!("Skipping symlink escaping root: {}", path.display());
+                    continue;
+                }
+            }
+
             if path.is_file() {
                 let uri = format!("file://{}", path.display());
 
@@ -611,6 +1276,55 @@ impl ResourceProvider for FileSystemProvider {
     }
 }
 
+/// Decode a file as UTF-8 text in chunks of at most `chunk_size` bytes
+///
+/// A multi-byte character split across reads is carried over to the next
+/// chunk, so only one chunk is held in memory at a time.
+fn text_chunks(
+    file: tokio::fs::File,
+    chunk_size: usize,
+) -> impl futures_util::Stream<Item = Result<String>> {
+    futures_util::stream::unfold(Some((file, Vec::new())), move |state| async move {
+        let (mut file, mut pending) = state?;
+        loop {
+            let start = pending.len();
+            pending.resize(start + chunk_size, 0);
+            let read = match file.read(&mut pending[start..]).await {
+                Ok(read) => read,
+                Err(e) => {
+                    let error = McpError::Resource(format!("Failed to read file: {}", e));
+                    return Some((Err(error), None));
+                }
+            };
+            pending.truncate(start + read);
+
+            if read == 0 {
+                if pending.is_empty() {
+                    return None;
+                }
+                let error = McpError::Resource("File is not valid UTF-8 text".to_string());
+                return Some((Err(error), None));
+            }
+
+            let valid = match std::str::from_utf8(&pending) {
+                Ok(text) => text.len(),
+                Err(e) if e.error_len().is_none() => e.valid_up_to(),
+                Err(_) => {
+                    let error = McpError::Resource("File is not valid UTF-8 text".to_string());
+                    return Some((Err(error), None));
+                }
+            };
+            if valid == 0 {
+                continue;
+            }
+
+            let rest = pending.split_off(valid);
+            let text = String::from_utf8(pending).unwrap_or_default();
+            return Some((Ok(text), Some((file, rest))));
+        }
+    })
+}
+
 /// HTTP resource provider
 pub struct HttpProvider {
     /// HTTP client
@@ -618,15 +1332,15 @@ pub struct HttpProvider {
 
     /// Allowed URL patterns
     allowed_patterns: Vec<String>,
+
+    /// Sniff the type of responses that lack a `Content-Type`
+    sniff_content: bool,
 }
 
 impl HttpProvider {
     /// Create a new HTTP provider
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            allowed_patterns: vec!["https://".to_string(), "http://".to_string()],
-        }
+        Self::with_patterns(vec!["https://".to_string(), "http://".to_string()])
     }
 
     /// Create a new HTTP provider with custom patterns
@@ -634,8 +1348,45 @@ impl HttpProvider {
         Self {
             client: reqwest::Client::new(),
             allowed_patterns: patterns,
+            sniff_content: true,
         }
     }
+
+    /// Set whether responses without a `Content-Type` are sniffed for text
+    pub fn with_content_sniffing(mut self, sniff_content: bool) -> Self {
+        self.sniff_content = sniff_content;
+        self
+    }
+}
+
+/// Leading bytes of common binary formats that happen to be valid UTF-8
+const BINARY_SIGNATURES: [&[u8]; 4] = [b"GIF8", b"%PDF-", b"PK\x03\x04", b"\x1f\x8b"];
+
+/// Guess the MIME type of an untyped body, returning `None` unless it looks like text
+pub fn sniff_text_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    if BINARY_SIGNATURES.iter().any(|signature| bytes.starts_with(signature))
+        || bytes.contains(&0)
+    {
+        return None;
+    }
+
+    let text = std::str::from_utf8(bytes).ok()?;
+    let start = text.trim_start_matches('\u{feff}').trim_start();
+    let lowercase_start = start
+        .get(..start.len().min(16))
+        .unwrap_or(start)
+        .to_ascii_lowercase();
+
+    let mime_type = if start.starts_with('{') || start.starts_with('[') {
+        "application/json"
+    } else if lowercase_start.starts_with("<?xml") {
+        "application/xml"
+    } else if lowercase_start.starts_with("<!doctype html") || lowercase_start.starts_with("<html") {
+        "text/html"
+    } else {
+        "text/plain"
+    };
+    Some(mime_type)
 }
 
 #[async_trait::async_trait]
@@ -651,12 +1402,21 @@ impl ResourceProvider for HttpProvider {
     }
 
     async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
-        let response = self
-            .client
-            .get(uri)
-            .send()
+        self.read_resource_cancellable(uri, &CancellationToken::new())
             .await
-            .map_err(|e| McpError::Resource(format!("HTTP request failed: {}", e)))?;
+    }
+
+    async fn read_resource_cancellable(
+        &self,
+        uri: &str,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<ResourceContents>> {
+        // Dropping the in-flight request future aborts it
+        let response = tokio::select! {
+            _ = cancel.cancelled() => return Err(cancelled_read(uri)),
+            response = self.client.get(uri).send() => response
+                .map_err(|e| McpError::Resource(format!("HTTP request failed: {}", e)))?,
+        };
 
         if !response.status().is_success() {
             return Err(McpError::Resource(format!(
@@ -671,31 +1431,45 @@ impl ResourceProvider for HttpProvider {
             .and_then(|h| h.to_str().ok())
             .map(|s| s.to_string());
 
-        let bytes = response
-            .bytes()
-            .await
-            .map_err(|e| McpError::Resource(format!("Failed to read response body: {}", e)))?;
+        let mut bytes = Vec::new();
+        let mut body = response.bytes_stream();
+        loop {
+            let chunk = tokio::select! {
+                _ = cancel.cancelled() => return Err(cancelled_read(uri)),
+                chunk = body.next() => chunk,
+            };
+            match chunk {
+                Some(chunk) => bytes.extend_from_slice(&chunk.map_err(|e| {
+                    McpError::Resource(format!("Failed to read response body: {}", e))
+                })?),
+                None => break,
+            }
+        }
 
         // Try to decode as text if content type suggests it
         if let Some(ref ct) = content_type {
             if ct.starts_with("text/") || ct.contains("json") || ct.contains("xml") {
-                if let Ok(text) = String::from_utf8(bytes.to_vec()) {
-                    return Ok(vec![ResourceContents::Text {
-                        uri: uri.to_string(),
-                        mime_type: content_type,
-                        text,
-                    }]);
+                if let Ok(text) = String::from_utf8(bytes.clone()) {
+                    return Ok(vec![ResourceContents::text(uri, content_type, text)]);
                 }
             }
         }
 
+        // Without a declared type, keep text that is clearly text readable
+        if content_type.is_none() && self.sniff_content {
+            if let Some(mime_type) = sniff_text_mime_type(&bytes) {
+                debug!("Sniffed {} as {}", uri, mime_type);
+                return Ok(vec![ResourceContents::text(
+                    uri,
+                    Some(mime_type.to_string()),
+                    String::from_utf8(bytes).unwrap_or_default(),
+                )]);
+            }
+        }
+
         // Fall back to binary
         let blob = base64::engine::general_purpose::STANDARD.encode(&bytes);
-        Ok(vec![ResourceContents::Blob {
-            uri: uri.to_string(),
-            mime_type: content_type,
-            blob,
-        }])
+        Ok(vec![ResourceContents::blob(uri, content_type, blob)])
     }
 }
 
@@ -763,4 +1537,411 @@ mod tests {
             panic!("Expected text content");
         }
     }
+
+    #[tokio::test]
+    async fn test_filesystem_root_canonicalized_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let file = root.join("hot.txt");
+        let uri = format!("file://{}", file.display());
+
+        // A root that doesn't exist yet is resolved on first use
+        let provider = FileSystemProvider::new(root.clone());
+        assert!(provider.read_resource(&uri).await.is_err());
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(&file, "hot path").unwrap();
+        for _ in 0..100 {
+            provider.read_resource(&uri).await.unwrap();
+        }
+        assert_eq!(provider.canonical_root().unwrap(), root.canonicalize().unwrap());
+
+        // Paths outside the root are still rejected
+        let outside = TempDir::new().unwrap();
+        let stray = outside.path().join("stray.txt");
+        std::fs::write(&stray, "nope").unwrap();
+        let err = provider
+            .read_resource(&format!("file://{}", stray.display()))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Access denied"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_symlink_escaping_root_is_denied() {
+        let root = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+        std::fs::write(root.path().join("inside.txt"), "inside").unwrap();
+        std::os::unix::fs::symlink(outside.path().join("secret.txt"), root.path().join("leak.txt"))
+            .unwrap();
+        std::os::unix::fs::symlink(outside.path(), root.path().join("escape")).unwrap();
+        std::os::unix::fs::symlink(root.path().join("inside.txt"), root.path().join("alias.txt"))
+            .unwrap();
+
+        let provider = FileSystemProvider::new(root.path().to_path_buf());
+        let uri = |name: &str| format!("file://{}", root.path().join(name).display());
+
+        let err = provider.read_resource(&uri("leak.txt")).await.unwrap_err();
+        assert!(matches!(err, McpError::SymlinkEscape(_)));
+
+        // Also caught for paths that don't exist (yet) below the link
+        let err = provider.stat(&uri("escape/new.txt")).await.unwrap_err();
+        assert!(matches!(err, McpError::SymlinkEscape(_)));
+
+        // Links that stay inside the root keep working
+        provider.read_resource(&uri("alias.txt")).await.unwrap();
+
+        let listed: Vec<_> = provider
+            .list_resources(None)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|resource| resource.name)
+            .collect();
+        assert!(listed.contains(&"alias.txt".to_string()));
+        assert!(!listed.contains(&"leak.txt".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_large_text_file_read_in_bounded_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("large.txt");
+        // Multi-byte characters make chunk boundaries fall mid-character
+        let text = "line of text with ünïcödé\n".repeat(100_000);
+        std::fs::write(&file, &text).unwrap();
+
+        let manager = ResourceManager::new();
+        manager
+            .register_provider(Box::new(FileSystemProvider::new(temp_dir.path().to_path_buf())))
+            .await
+            .unwrap();
+
+        let chunk_size = 64 * 1024;
+        let uri = format!("file://{}", file.display());
+        let mut chunks = manager
+            .read_text_chunks(&uri, chunk_size)
+            .await
+            .unwrap()
+            .expect("text files are streamed");
+
+        let mut count = 0;
+        let mut read = String::new();
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.len() <= chunk_size + 3);
+            read.push_str(&chunk);
+            count += 1;
+        }
+        assert!(text.len() > 2 * 1024 * 1024);
+        assert!(count >= text.len() / (chunk_size + 3));
+        assert_eq!(read, text);
+
+        // Binary files aren't streamed
+        let binary = temp_dir.path().join("data.bin");
+        std::fs::write(&binary, [0u8, 159, 146, 150]).unwrap();
+        let uri = format!("file://{}", binary.display());
+        assert!(manager.read_text_chunks(&uri, chunk_size).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_sniff_text_mime_type() {
+        assert_eq!(sniff_text_mime_type(b"plain words"), Some("text/plain"));
+        assert_eq!(sniff_text_mime_type(b"  {\"a\": 1}"), Some("application/json"));
+        assert_eq!(sniff_text_mime_type(b"<?xml version=\"1.0\"?><a/>"), Some("application/xml"));
+        assert_eq!(sniff_text_mime_type(b"<!DOCTYPE html><html></html>"), Some("text/html"));
+        assert_eq!(sniff_text_mime_type(b"GIF89a"), None);
+        assert_eq!(sniff_text_mime_type(b"text\0with nul"), None);
+        assert_eq!(sniff_text_mime_type(&[0xff, 0xfe, 0x41]), None);
+    }
+
+    /// Serve one HTTP response with the given body and no `Content-Type`, returning its URL
+    async fn serve_untyped_body(body: &'static str) -> String {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = socket.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        format!("http://{}/notes", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_untyped_text_is_sniffed() {
+        let url = serve_untyped_body("hello from a bare server").await;
+        let contents = HttpProvider::new().read_resource(&url).await.unwrap();
+        match &contents[..] {
+            [ResourceContents::Text { text, mime_type, .. }] => {
+                assert_eq!(text, "hello from a bare server");
+                assert_eq!(mime_type.as_deref(), Some("text/plain"));
+            }
+            other => panic!("expected text contents, got {:?}", other),
+        }
+
+        // With sniffing off the body stays an opaque blob
+        let url = serve_untyped_body("hello from a bare server").await;
+        let contents = HttpProvider::new()
+            .with_content_sniffing(false)
+            .read_resource(&url)
+            .await
+            .unwrap();
+        assert!(matches!(&contents[..], [ResourceContents::Blob { .. }]));
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_read_stops_when_cancelled() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("large.bin");
+        std::fs::write(&path, vec![0u8; READ_CHUNK_SIZE * 4]).unwrap();
+        let provider = FileSystemProvider::new(temp_dir.path().to_path_buf());
+        let uri = format!("file://{}", path.display());
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = provider
+            .read_resource_cancellable(&uri, &cancel)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::Cancelled(_)));
+
+        // An uncancelled read still returns everything
+        assert_eq!(provider.read_resource(&uri).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_filesystem_stat_large_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let test_file = temp_dir.path().join("large.bin");
+
+        // Sparse 4 GiB file: reading it would be prohibitively slow
+        let file = std::fs::File::create(&test_file).unwrap();
+        file.set_len(4 * 1024 * 1024 * 1024).unwrap();
+
+        let manager = ResourceManager::new();
+        manager
+            .register_provider(Box::new(FileSystemProvider::new(temp_dir.path().to_path_buf())))
+            .await
+            .unwrap();
+
+        let uri = format!("file://{}", test_file.display());
+        let metadata = manager.stat_resource(&uri).await.unwrap();
+        assert_eq!(metadata.size, Some(4 * 1024 * 1024 * 1024));
+        assert_eq!(metadata.mime_type.as_deref(), Some("application/octet-stream"));
+        assert!(metadata.last_modified.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_subscription_limit_per_client() {
+        let manager = ResourceManager::with_config(ResourcesConfig {
+            max_subscriptions_per_client: 2,
+            allow_subscribe_to_missing: true,
+            manifest: None,
+            enable_http_provider: false,
+            sniff_http_content_type: true,
+        });
+
+        manager.subscribe("test://a", "client-1").await.unwrap();
+        manager.subscribe("test://b", "client-1").await.unwrap();
+
+        // Re-subscribing to the same URI doesn't count against the limit
+        manager.subscribe("test://a", "client-1").await.unwrap();
+
+        assert!(manager.subscribe("test://c", "client-1").await.is_err());
+        assert_eq!(manager.get_client_subscription_count("client-1").await, 2);
+
+        // Other clients have their own budget
+        manager.subscribe("test://c", "client-2").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribe_all() {
+        let manager = ResourceManager::with_config(ResourcesConfig {
+            allow_subscribe_to_missing: true,
+            ..ResourcesConfig::default()
+        });
+
+        manager.subscribe("test://a", "client-1").await.unwrap();
+        manager.subscribe("test://b", "client-1").await.unwrap();
+        manager.subscribe("test://a", "client-2").await.unwrap();
+
+        assert_eq!(manager.unsubscribe_all("client-1").await, 2);
+        assert_eq!(manager.get_client_subscription_count("client-1").await, 0);
+        assert_eq!(manager.get_client_subscription_count("client-2").await, 1);
+        assert_eq!(manager.get_subscription_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_to_missing_resource() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("exists.txt");
+        std::fs::write(&existing, "hello").unwrap();
+        let missing = format!("file://{}", temp_dir.path().join("later.txt").display());
+
+        let strict = ResourceManager::new();
+        strict
+            .register_provider(Box::new(FileSystemProvider::new(temp_dir.path().to_path_buf())))
+            .await
+            .unwrap();
+
+        let err = strict.subscribe(&missing, "client-1").await.unwrap_err();
+        assert!(matches!(err, McpError::ResourceNotFound(_)));
+        assert_eq!(err.to_json_rpc_code(), -32002);
+        assert!(strict.subscribe("test://unknown", "client-1").await.is_err());
+        assert_eq!(strict.get_subscription_count().await, 0);
+
+        strict
+            .subscribe(&format!("file://{}", existing.display()), "client-1")
+            .await
+            .unwrap();
+
+        let permissive = ResourceManager::with_config(ResourcesConfig {
+            allow_subscribe_to_missing: true,
+            ..ResourcesConfig::default()
+        });
+        permissive
+            .register_provider(Box::new(FileSystemProvider::new(temp_dir.path().to_path_buf())))
+            .await
+            .unwrap();
+        permissive.subscribe(&missing, "client-1").await.unwrap();
+        assert_eq!(permissive.get_client_subscription_count("client-1").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_provider_reported_as_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing_root = temp_dir.path().join("does-not-exist");
+
+        let manager = ResourceManager::new();
+        let err = manager
+            .register_provider(Box::new(FileSystemProvider::new(missing_root)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::ProviderUnavailable(_)));
+
+        let failures = manager.get_provider_failures().await;
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0, "filesystem");
+
+        // A URI the failed provider would have served
+        let uri = format!("file://{}", temp_dir.path().join("a.txt").display());
+        let err = manager.read_resource(&uri, None).await.unwrap_err();
+        assert!(matches!(err, McpError::ProviderUnavailable(_)));
+        assert!(err.to_string().contains("filesystem"));
+
+        // A URI no provider ever claimed is simply not found
+        let err = manager.read_resource("unknown://thing", None).await.unwrap_err();
+        assert!(matches!(err, McpError::ResourceNotFound(_)));
+    }
+
+    fn test_resource(uri: &str) -> Resource {
+        Resource {
+            uri: uri.to_string(),
+            name: "Test".to_string(),
+            description: None,
+            mime_type: Some("text/plain".to_string()),
+            annotations: None,
+            size: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_resource_with_inline_contents() {
+        let manager = ResourceManager::new();
+        let contents = vec![ResourceContents::text(
+            "test://greeting",
+            Some("text/plain".to_string()),
+            "hello",
+        )];
+        manager
+            .register_resource_with_contents(test_resource("test://greeting"), contents)
+            .await
+            .unwrap();
+
+        let read = manager.read_resource("test://greeting", None).await.unwrap();
+        assert!(matches!(&read[..], [ResourceContents::Text { text, .. }] if text == "hello"));
+
+        let metadata = manager.stat_resource("test://greeting").await.unwrap();
+        assert_eq!(metadata.size, Some(5));
+        assert_eq!(metadata.mime_type.as_deref(), Some("text/plain"));
+
+        // Re-registering without contents drops the old ones
+        manager.register_resource(test_resource("test://greeting")).await.unwrap();
+        let err = manager.read_resource("test://greeting", None).await.unwrap_err();
+        assert!(err.to_string().contains("is registered but has no contents"));
+
+        // Unregistering drops the contents with the resource
+        manager
+            .register_resource_with_contents(
+                test_resource("test://greeting"),
+                vec![ResourceContents::text("test://greeting", None, "hi again")],
+            )
+            .await
+            .unwrap();
+        let read = manager.read_resource("test://greeting", None).await.unwrap();
+        assert!(matches!(&read[..], [ResourceContents::Text { text, .. }] if text == "hi again"));
+        manager.unregister_resource("test://greeting").await.unwrap();
+        let err = manager.read_resource("test://greeting", None).await.unwrap_err();
+        assert!(matches!(err, McpError::ResourceNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_known_resource_without_provider_is_distinguished() {
+        let manager = ResourceManager::new();
+        manager.register_resource(test_resource("test://orphan")).await.unwrap();
+
+        let err = manager.read_resource("test://orphan", None).await.unwrap_err();
+        assert!(matches!(err, McpError::Resource(_)));
+        assert!(err.to_string().contains("is registered but has no contents"));
+
+        let err = manager.read_resource("test://unknown", None).await.unwrap_err();
+        assert!(matches!(err, McpError::ResourceNotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn test_manifest_rejects_malformed_entries() {
+        let manager = ResourceManager::new();
+        manager
+            .register_provider(Box::new(HttpProvider::new()))
+            .await
+            .unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("resources.json");
+        std::fs::write(
+            &path,
+            serde_json::json!({
+                "resources": [
+                    {"uri": "https://example.com/a", "name": "A", "provider": "http"},
+                    {"uri": "not a uri", "name": "Bad URI"},
+                    {"uri": "test://b", "name": ""},
+                    {"uri": "test://c", "name": "C", "provider": "http"},
+                    {"uri": "test://d", "name": "D", "provider": "missing"},
+                    {"uri": "test://e", "name": "E", "mimeType": "not-a-mime"}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let report = manager.load_manifest(&path).await.unwrap();
+        assert_eq!(report.registered, vec!["https://example.com/a".to_string()]);
+        let rejected: Vec<usize> = report.rejected.iter().map(|(index, _)| *index).collect();
+        assert_eq!(rejected, vec![1, 2, 3, 4, 5]);
+        assert!(report.rejected[3].1.contains("unknown provider"));
+        assert_eq!(manager.get_resource_count().await, 1);
+
+        // Unparseable files fail as a whole
+        let bad = dir.path().join("resources.yaml");
+        std::fs::write(&bad, "resources: []").unwrap();
+        assert!(manager.load_manifest(&bad).await.is_err());
+    }
 }
@@ -0,0 +1,213 @@
+//! Sandbox for commands run on behalf of tools.
+//!
+//! Commands never inherit the server's working directory or environment:
+//! they run in a fixed directory inside the configured roots, see only the
+//! allowlisted environment variables and, on Linux, get a network namespace
+//! of their own unless network access is allowed.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::error::{McpError, Result};
+
+/// Restrictions applied to commands run on behalf of tools
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SandboxConfig {
+    /// Directory commands run in; the first root when unset
+    #[serde(default)]
+    pub working_dir: Option<PathBuf>,
+
+    /// Environment variables passed through; all others are cleared
+    #[serde(default)]
+    pub env_allowlist: Vec<String>,
+
+    /// Let commands use the network (isolation is only enforced on Linux)
+    #[serde(default)]
+    pub allow_network: bool,
+}
+
+impl SandboxConfig {
+    /// Resolve the directory commands run in
+    ///
+    /// The configured directory must lie within one of `roots` when any are
+    /// given; without one the first root is used.
+    pub fn resolve_working_dir(&self, roots: &[PathBuf]) -> Result<PathBuf> {
+        let dir = match (&self.working_dir, roots.first()) {
+            (Some(dir), _) => dir.clone(),
+            (None, Some(root)) => root.clone(),
+            (None, None) => {
+                return Err(McpError::Config(
+                    "Sandbox needs a working_dir or at least one root".to_string(),
+                ))
+            }
+        };
+
+        let canonical = dir.canonicalize().map_err(|e| {
+            McpError::Config(format!(
+                "Sandbox working directory {} is unusable: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+        if !canonical.is_dir() {
+            return Err(McpError::Config(format!(
+                "Sandbox working directory {} is not a directory",
+                dir.display()
+            )));
+        }
+
+        let in_root = roots.is_empty()
+            || roots.iter().any(|root| {
+                root.canonicalize()
+                    .is_ok_and(|root| canonical.starts_with(root))
+            });
+        if !in_root {
+            return Err(McpError::Config(format!(
+                "Sandbox working directory {} is outside the configured roots",
+                dir.display()
+            )));
+        }
+
+        Ok(canonical)
+    }
+
+    /// Build a command for `program` confined by this sandbox
+    pub fn command(&self, program: impl AsRef<OsStr>, roots: &[PathBuf]) -> Result<Command> {
+        self.command_with_env(program, roots, |name| std::env::var_os(name))
+    }
+
+    /// Build a confined command, looking up allowlisted variables with `env`
+    fn command_with_env(
+        &self,
+        program: impl AsRef<OsStr>,
+        roots: &[PathBuf],
+        env: impl Fn(&str) -> Option<OsString>,
+    ) -> Result<Command> {
+        let working_dir = self.resolve_working_dir(roots)?;
+
+        let mut command = Command::new(program);
+        command.current_dir(&working_dir).env_clear();
+        for name in &self.env_allowlist {
+            if let Some(value) = env(name) {
+                command.env(name, value);
+            }
+        }
+
+        if !self.allow_network {
+            isolate_network(&mut command, &working_dir);
+        }
+
+        Ok(command)
+    }
+}
+
+/// Run the command in a network namespace of its own
+#[cfg(target_os = "linux")]
+fn isolate_network(command: &mut Command, _working_dir: &Path) {
+    // SAFETY: the closure only makes the async-signal-safe unshare(2) call
+    unsafe {
+        command.pre_exec(|| {
+            if libc::unshare(libc::CLONE_NEWNET) == 0 {
+                return Ok(());
+            }
+            // Unprivileged processes may only create one inside a new user namespace
+            if libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) == 0 {
+                return Ok(());
+            }
+            Err(std::io::Error::last_os_error())
+        });
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn isolate_network(_command: &mut Command, working_dir: &Path) {
+    tracing::warn!(
+        "Network isolation is not supported on this platform; command in {} keeps network access",
+        working_dir.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_command_sees_only_allowlisted_env() {
+        let root = tempfile::tempdir().unwrap();
+        let env = |name: &str| match name {
+            "MCP_SANDBOX_ALLOWED" => Some(OsString::from("visible")),
+            "MCP_SANDBOX_SECRET" => Some(OsString::from("hidden")),
+            _ => None,
+        };
+
+        let sandbox = SandboxConfig {
+            working_dir: None,
+            env_allowlist: vec!["MCP_SANDBOX_ALLOWED".to_string(), "MCP_SANDBOX_UNSET".to_string()],
+            allow_network: true,
+        };
+        let output = sandbox
+            .command_with_env("/usr/bin/env", &[root.path().to_path_buf()], env)
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "MCP_SANDBOX_ALLOWED=visible\n");
+
+        let output = sandbox
+            .command("/bin/pwd", &[root.path().to_path_buf()])
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+        let pwd = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(Path::new(pwd.trim()), root.path().canonicalize().unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_command_without_network_sees_only_loopback() {
+        let root = tempfile::tempdir().unwrap();
+        let sandbox = SandboxConfig::default();
+
+        let mut command = sandbox
+            .command("/bin/cat", &[root.path().to_path_buf()])
+            .unwrap();
+        let output = match command.arg("/proc/net/dev").output().await {
+            Ok(output) => output,
+            Err(e) => {
+                // Namespaces can be unavailable, e.g. inside some containers
+                eprintln!("Skipping network isolation test, unshare failed: {}", e);
+                return;
+            }
+        };
+        assert!(output.status.success());
+
+        // The first two lines are headers, then one line per interface
+        let interfaces: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(2)
+            .filter_map(|line| line.split(':').next())
+            .map(|name| name.trim().to_string())
+            .collect();
+        assert_eq!(interfaces, ["lo"]);
+    }
+
+    #[test]
+    fn test_working_dir_must_be_inside_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let roots = [root.path().to_path_buf()];
+
+        let sandbox = SandboxConfig {
+            working_dir: Some(outside.path().to_path_buf()),
+            ..SandboxConfig::default()
+        };
+        assert!(sandbox.resolve_working_dir(&roots).is_err());
+        assert!(SandboxConfig::default().resolve_working_dir(&[]).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Tool handlers generated from typed Rust functions.
+//!
+//! [`tool_fn!`](crate::tool_fn) turns a plain function into a
+//! [`ToolHandler`](super::tools::ToolHandler): each argument becomes a
+//! property of the input schema (derived from its [`JsonSchema`] impl) and is
+//! deserialized from the call's arguments before the function runs. Arguments
+//! that accept `null`, such as `Option<T>`, are optional.
+//!
+//! ```rust,ignore
+//! mcp_server::tool_fn! {
+//!     /// Add two numbers
+//!     pub struct AddTool => fn add(a: f64, b: f64) -> f64 {
+//!         a + b
+//!     }
+//! }
+//!
+//! // Register it with a manager directly...
+//! tool_manager.register_handler_with_tool(Box::new(AddTool)).await?;
+//! // ...or for discovery alongside the built-in handlers
+//! mcp_server::register_tool_handler!(AddTool, "add")?;
+//! ```
+
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::error::{McpError, Result};
+use crate::protocol::ToolInputSchema;
+use crate::server::features::tools::ToolResult;
+
+/// Builds the input schema of a typed tool, one argument at a time
+pub struct ToolArgsSchema {
+    generator: SchemaGenerator,
+    properties: HashMap<String, Value>,
+    required: Vec<String>,
+}
+
+impl ToolArgsSchema {
+    /// Start an empty schema
+    pub fn new() -> Self {
+        Self {
+            generator: SchemaSettings::draft07()
+                .with(|settings| settings.inline_subschemas = true)
+                .into_generator(),
+            properties: HashMap::new(),
+            required: Vec::new(),
+        }
+    }
+
+    /// Add an argument, required unless its type accepts `null`
+    pub fn arg<T: JsonSchema + DeserializeOwned>(mut self, name: &str) -> Self {
+        let schema = self.generator.subschema_for::<T>();
+        self.properties.insert(
+            name.to_string(),
+            serde_json::to_value(schema).unwrap_or_default(),
+        );
+        if serde_json::from_value::<T>(Value::Null).is_err() {
+            self.required.push(name.to_string());
+        }
+        self
+    }
+
+    /// Finish the schema
+    pub fn build(self) -> ToolInputSchema {
+        ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(self.properties),
+            required: (!self.required.is_empty()).then_some(self.required),
+        }
+    }
+}
+
+impl Default for ToolArgsSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the arguments of a typed tool call as an object
+pub fn argument_map(arguments: Option<Value>) -> Result<Map<String, Value>> {
+    match arguments {
+        None | Some(Value::Null) => Ok(Map::new()),
+        Some(Value::Object(map)) => Ok(map),
+        Some(_) => Err(McpError::invalid_params("Tool arguments must be an object")),
+    }
+}
+
+/// Take and deserialize one argument; a missing argument reads as `null`
+pub fn take_argument<T: DeserializeOwned>(arguments: &mut Map<String, Value>, name: &str) -> Result<T> {
+    let value = arguments.remove(name).unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| McpError::invalid_params(format!("Invalid argument '{}': {}", name, e)))
+}
+
+/// Join the doc comment lines of a typed tool into its description
+pub fn description_from_doc(lines: &[&str]) -> Option<String> {
+    let description = lines
+        .iter()
+        .map(|line| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Return value of a typed tool
+pub trait IntoToolResult {
+    /// Convert into the result of the tool call
+    fn into_tool_result(self) -> Result<ToolResult>;
+}
+
+impl IntoToolResult for ToolResult {
+    fn into_tool_result(self) -> Result<ToolResult> {
+        Ok(self)
+    }
+}
+
+impl IntoToolResult for String {
+    fn into_tool_result(self) -> Result<ToolResult> {
+        Ok(ToolResult::text(self))
+    }
+}
+
+impl IntoToolResult for &str {
+    fn into_tool_result(self) -> Result<ToolResult> {
+        Ok(ToolResult::text(self.to_string()))
+    }
+}
+
+impl IntoToolResult for Value {
+    fn into_tool_result(self) -> Result<ToolResult> {
+        Ok(ToolResult::text(self.to_string()))
+    }
+}
+
+impl<T: IntoToolResult> IntoToolResult for Result<T> {
+    fn into_tool_result(self) -> Result<ToolResult> {
+        self?.into_tool_result()
+    }
+}
+
+macro_rules! impl_into_tool_result_for_display {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoToolResult for $ty {
+                fn into_tool_result(self) -> Result<ToolResult> {
+                    Ok(ToolResult::text(self.to_string()))
+                }
+            }
+        )*
+    };
+}
+
+impl_into_tool_result_for_display!(bool, i32, i64, u32, u64, usize, f32, f64);
+
+/// Define a function and a tool handler calling it with typed arguments
+///
+/// The tool is named after the function and described by its doc comment.
+/// Argument types implement `Deserialize` and `JsonSchema`; the return type
+/// implements [`IntoToolResult`](crate::server::features::tool_fn::IntoToolResult).
+#[macro_export]
+macro_rules! tool_fn {
+    (
+        $(#[doc = $doc:literal])*
+        $vis:vis struct $handler:ident => fn $name:ident($($arg:ident : $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $(#[doc = $doc])*
+        $vis fn $name($($arg: $ty),*) -> $ret $body
+
+        #[doc = concat!("Tool handler calling [`", stringify!($name), "`]")]
+        #[derive(Debug, Clone, Copy, Default)]
+        $vis struct $handler;
+
+        #[$crate::__private::async_trait]
+        impl $crate::server::features::tools::ToolHandler for $handler {
+            fn name(&self) -> &str {
+                stringify!($name)
+            }
+
+            fn description(&self) -> Option<String> {
+                $crate::server::features::tool_fn::description_from_doc(&[$($doc),*])
+            }
+
+            fn input_schema(&self) -> $crate::protocol::ToolInputSchema {
+                $crate::server::features::tool_fn::ToolArgsSchema::new()
+                    $(.arg::<$ty>(stringify!($arg)))*
+                    .build()
+            }
+
+            #[allow(unused_mut)]
+            async fn execute(
+                &self,
+                arguments: Option<$crate::__private::serde_json::Value>,
+            ) -> $crate::error::Result<$crate::server::features::tools::ToolResult> {
+                let mut arguments = $crate::server::features::tool_fn::argument_map(arguments)?;
+                $(
+                    let $arg: $ty =
+                        $crate::server::features::tool_fn::take_argument(&mut arguments, stringify!($arg))?;
+                )*
+                $crate::server::features::tool_fn::IntoToolResult::into_tool_result($name($($arg),*))
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::features::tools::{ToolHandler, ToolManager};
+    use crate::protocol::Content;
+
+    crate::tool_fn! {
+        /// Add two numbers
+        struct AddTool => fn add(a: f64, b: f64) -> f64 {
+            a + b
+        }
+    }
+
+    crate::tool_fn! {
+        /// Greet someone, optionally loudly
+        struct GreetTool => fn greet(name: String, loud: Option<bool>) -> String {
+            let greeting = format!("Hello, {}", name);
+            if loud.unwrap_or(false) { greeting.to_uppercase() } else { greeting }
+        }
+    }
+
+    fn text(result: &crate::server::features::tools::ToolResult) -> &str {
+        match &result.content[0] {
+            Content::Text { text, .. } => text,
+            other => panic!("expected text content, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typed_function_called_through_manager() {
+        let manager = ToolManager::new();
+        manager.register_handler_with_tool(Box::new(AddTool)).await.unwrap();
+        manager.register_handler_with_tool(Box::new(GreetTool)).await.unwrap();
+
+        let tool = manager.get_tool("add").await.unwrap();
+        assert_eq!(tool.description.as_deref(), Some("Add two numbers"));
+        let properties = tool.input_schema.properties.unwrap();
+        assert_eq!(properties["a"]["type"], "number");
+        assert_eq!(tool.input_schema.required.unwrap(), vec!["a", "b"]);
+        assert_eq!(GreetTool.input_schema().required.unwrap(), vec!["name"]);
+
+        let result = manager
+            .call_tool("add", Some(serde_json::json!({"a": 2.5, "b": 4})))
+            .await
+            .unwrap();
+        assert_eq!(text(&result), "6.5");
+
+        let result = manager
+            .call_tool("greet", Some(serde_json::json!({"name": "Ada", "loud": true})))
+            .await
+            .unwrap();
+        assert_eq!(text(&result), "HELLO, ADA");
+
+        let error = manager
+            .call_tool("add", Some(serde_json::json!({"a": "two"})))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Invalid argument 'a'"));
+    }
+}
@@ -5,18 +5,26 @@
 
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
 
 use crate::error::{McpError, Result};
 use crate::protocol::{Content, PaginationParams, PaginationResult, Tool};
-use crate::server::features::FeatureManager;
+use crate::server::features::idempotency::{IdempotencyCache, DEFAULT_IDEMPOTENCY_TTL};
+use crate::server::features::pagination::paginate_by_key;
+use crate::server::features::{
+    FeatureManager, ListChangedNotifier, NotificationSink, ProgressReporter,
+};
 
 /// Configuration for tool handlers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ToolHandlerConfig {
     /// Tool handler name
     pub name: String,
@@ -35,7 +43,18 @@ pub struct ToolHandlerConfig {
 }
 
 /// Configuration for all tool handlers
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// A handler listed in `handlers` is enabled or disabled as configured there.
+/// An unlisted handler is enabled by default (`enable_all_by_default`) when:
+///
+/// | Handler                    | Condition               |
+/// |----------------------------|-------------------------|
+/// | built-in                   | `auto_discover_builtin` |
+/// | loaded from `plugin_paths` | always                  |
+/// | registered by the embedder | `auto_discover_all`     |
+///
+/// and disabled otherwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ToolsConfig {
     /// List of tool handler configurations
     #[serde(default)]
@@ -48,10 +67,31 @@ pub struct ToolsConfig {
     /// Whether to enable all discovered handlers by default
     #[serde(default = "default_true")]
     pub enable_all_by_default: bool,
+
+    /// Whether to also auto-discover handlers registered outside this crate,
+    /// such as through `register_tool_handler!`
+    #[serde(default)]
+    pub auto_discover_all: bool,
+
+    /// Cross-check declared tool annotations against observed side effects (development aid)
+    #[serde(default)]
+    pub check_annotations: bool,
+
+    /// Shared libraries to load tool handlers from (requires the `plugins` feature)
+    #[serde(default)]
+    pub plugin_paths: Vec<PathBuf>,
+
+    /// Restrictions on commands run on behalf of tools
+    #[serde(default)]
+    pub sandbox: crate::server::features::sandbox::SandboxConfig,
+
+    /// Seconds the result of a call carrying an idempotency key is reused for
+    #[serde(default = "default_idempotency_ttl")]
+    pub idempotency_ttl: u64,
 }
 
 /// Tool handler factory function type
-pub type ToolHandlerFactory = fn() -> Result<Box<dyn ToolHandler>>;
+pub type ToolHandlerFactory = Arc<dyn Fn() -> Result<Box<dyn ToolHandler>> + Send + Sync>;
 
 /// Tool handler registration entry
 #[derive(Clone)]
@@ -67,6 +107,9 @@ pub struct ToolHandlerRegistration {
 
     /// Whether this is a built-in handler
     pub is_builtin: bool,
+
+    /// Whether the handler was loaded from a plugin library
+    pub from_plugin: bool,
 }
 
 /// Global tool handler registry
@@ -79,6 +122,10 @@ fn default_true() -> bool {
     true
 }
 
+fn default_idempotency_ttl() -> u64 {
+    DEFAULT_IDEMPOTENCY_TTL.as_secs()
+}
+
 impl ToolHandlerRegistry {
     /// Initialize the global registry
     fn get_registry() -> &'static Arc<std::sync::Mutex<Vec<ToolHandlerRegistration>>> {
@@ -90,29 +137,34 @@ impl ToolHandlerRegistry {
     /// Register a tool handler factory
     pub fn register(
         name: impl Into<String>,
-        factory: ToolHandlerFactory,
+        factory: impl Fn() -> Result<Box<dyn ToolHandler>> + Send + Sync + 'static,
         priority: i32,
         is_builtin: bool,
     ) -> Result<()> {
-        let name = name.into();
+        Self::insert(ToolHandlerRegistration {
+            name: name.into(),
+            factory: Arc::new(factory),
+            priority,
+            is_builtin,
+            from_plugin: false,
+        })
+    }
+
+    /// Add a registration, rejecting duplicate names
+    pub(crate) fn insert(registration: ToolHandlerRegistration) -> Result<()> {
         let registry = Self::get_registry();
         let mut handlers = registry.lock().map_err(|e| {
             McpError::Tool(format!("Failed to lock registry: {}", e))
         })?;
 
         // Check for duplicate names
-        if handlers.iter().any(|h| h.name == name) {
+        if handlers.iter().any(|h| h.name == registration.name) {
             return Err(McpError::Tool(format!(
-                "Tool handler '{}' is already registered", name
+                "Tool handler '{}' is already registered", registration.name
             )));
         }
 
-        handlers.push(ToolHandlerRegistration {
-            name,
-            factory,
-            priority,
-            is_builtin,
-        });
+        handlers.push(registration);
 
         // Sort by priority (higher priority first)
         handlers.sort_by(|a, b| b.priority.cmp(&a.priority));
@@ -179,10 +231,65 @@ pub struct ToolManager {
     tools: Arc<RwLock<HashMap<String, Tool>>>,
 
     /// Tool handlers
-    handlers: Arc<RwLock<HashMap<String, Box<dyn ToolHandler>>>>,
+    handlers: Arc<RwLock<HashMap<String, Arc<dyn ToolHandler>>>>,
 
     /// Whether the feature is enabled
-    enabled: Arc<RwLock<bool>>,
+    enabled: Arc<AtomicBool>,
+
+    /// Whether to check declared annotations against observed side effects
+    check_annotations: bool,
+
+    /// Annotation violations observed while checking is enabled
+    violations: Arc<RwLock<Vec<AnnotationViolation>>>,
+
+    /// Maximum number of items per list page
+    page_size: usize,
+
+    /// Results of calls carrying an idempotency key
+    idempotency: Arc<IdempotencyCache>,
+
+    /// Emits list_changed notifications
+    list_changed: ListChangedNotifier,
+}
+
+/// Externally visible side effect performed by a tool during execution
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToolSideEffect {
+    /// A resource was written or modified
+    ResourceWrite(String),
+
+    /// Some other state was modified
+    StateChange(String),
+}
+
+/// A tool whose observed behavior contradicts its declared annotations
+#[derive(Debug, Clone)]
+pub struct AnnotationViolation {
+    /// Tool name
+    pub tool: String,
+
+    /// Side effect that violated the annotations
+    pub side_effect: ToolSideEffect,
+
+    /// Description of the violation
+    pub message: String,
+}
+
+tokio::task_local! {
+    static SIDE_EFFECTS: Arc<std::sync::Mutex<Vec<ToolSideEffect>>>;
+}
+
+/// Record a side effect performed by the currently executing tool
+///
+/// Handlers call this when they modify state so that annotation checks can
+/// catch tools that misdeclare themselves as read-only. Outside of a checked
+/// tool call this is a no-op.
+pub fn record_side_effect(effect: ToolSideEffect) {
+    let _ = SIDE_EFFECTS.try_with(|effects| {
+        if let Ok(mut effects) = effects.lock() {
+            effects.push(effect);
+        }
+    });
 }
 
 /// Tool handler trait for executing tools
@@ -217,11 +324,35 @@ pub trait ToolHandler: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, arguments: Option<Value>) -> Result<ToolResult>;
 
+    /// Execute the tool, reporting progress to the caller as it goes
+    ///
+    /// Long-running tools override this; by default progress isn't reported.
+    async fn execute_with_progress(
+        &self,
+        arguments: Option<Value>,
+        progress: ProgressReporter,
+    ) -> Result<ToolResult> {
+        let _ = progress;
+        self.execute(arguments).await
+    }
+
     /// Validate tool arguments (optional)
     async fn validate_arguments(&self, arguments: Option<&Value>) -> Result<()> {
         let _ = arguments;
         Ok(())
     }
+
+    /// Whether execution is CPU-heavy and should run on the blocking thread pool
+    /// instead of the async runtime
+    fn cpu_bound(&self) -> bool {
+        false
+    }
+
+    /// Whether calls repeating an idempotency key reuse the first call's result
+    /// instead of running again; tools with side effects opt in
+    fn deduplicate_calls(&self) -> bool {
+        false
+    }
 }
 
 /// Tool execution result
@@ -240,10 +371,55 @@ impl ToolManager {
         Self {
             tools: Arc::new(RwLock::new(HashMap::new())),
             handlers: Arc::new(RwLock::new(HashMap::new())),
-            enabled: Arc::new(RwLock::new(true)),
+            enabled: Arc::new(AtomicBool::new(true)),
+            check_annotations: false,
+            violations: Arc::new(RwLock::new(Vec::new())),
+            page_size: super::DEFAULT_PAGE_SIZE,
+            idempotency: Arc::new(IdempotencyCache::default()),
+            list_changed: ListChangedNotifier::new("notifications/tools/list_changed"),
         }
     }
 
+    /// Enable or disable annotation checks on tool calls
+    pub fn with_annotation_checks(mut self, enabled: bool) -> Self {
+        self.check_annotations = enabled;
+        self
+    }
+
+    /// Set how long the result of a call carrying an idempotency key is reused
+    pub fn with_idempotency_ttl(mut self, ttl: Duration) -> Self {
+        self.idempotency = Arc::new(IdempotencyCache::new(ttl));
+        self
+    }
+
+    /// Set the maximum number of tools returned per list page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Get the maximum number of tools returned per list page
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    /// Publish `notifications/tools/list_changed` to the given sink when the tools change
+    pub fn with_notification_sink(mut self, sink: NotificationSink) -> Self {
+        self.list_changed = self.list_changed.with_sink(sink);
+        self
+    }
+
+    /// Set the window over which tools changes are coalesced into one notification
+    pub fn with_list_changed_debounce(mut self, debounce: Duration) -> Self {
+        self.list_changed = self.list_changed.with_debounce(debounce);
+        self
+    }
+
+    /// Get the annotation violations observed so far
+    pub async fn get_annotation_violations(&self) -> Vec<AnnotationViolation> {
+        self.violations.read().await.clone()
+    }
+
     /// Register a tool
     pub async fn register_tool(&self, tool: Tool) -> Result<()> {
         if !self.is_enabled() {
@@ -251,22 +427,36 @@ impl ToolManager {
         }
 
         let name = tool.name.clone();
+        Self::validate_definition(&tool)?;
 
         {
             let mut tools = self.tools.write().await;
             tools.insert(name.clone(), tool);
         }
+        self.list_changed.notify();
 
         info!("Registered tool: {}", name);
         Ok(())
     }
 
-    /// Unregister a tool
+    /// Reject tool definitions whose input schema is internally inconsistent
+    fn validate_definition(tool: &Tool) -> Result<()> {
+        crate::protocol::validation::validate_tool_schema(&serde_json::to_value(&tool.input_schema)?)
+            .map_err(|e| {
+                McpError::Tool(format!("Invalid input schema for tool '{}': {}", tool.name, e))
+            })
+    }
+
+    /// Unregister a tool along with its handler
     pub async fn unregister_tool(&self, name: &str) -> Result<Option<Tool>> {
+        // Lock order is always tools, then handlers
         let mut tools = self.tools.write().await;
+        let mut handlers = self.handlers.write().await;
         let tool = tools.remove(name);
+        handlers.remove(name);
 
         if tool.is_some() {
+            self.list_changed.notify();
             info!("Unregistered tool: {}", name);
         }
 
@@ -306,27 +496,47 @@ impl ToolManager {
 
     /// Execute a tool
     pub async fn call_tool(&self, name: &str, arguments: Option<Value>) -> Result<ToolResult> {
+        self.call_tool_with_progress(name, arguments, ProgressReporter::none())
+            .await
+    }
+
+    /// Execute a tool, forwarding its progress reports through `progress`
+    pub async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        progress: ProgressReporter,
+    ) -> Result<ToolResult> {
         if !self.is_enabled() {
             return Err(McpError::Tool("Tool feature is disabled".to_string()));
         }
 
         // Check if tool exists
-        let _tool = self
+        let tool = self
             .get_tool(name)
             .await
             .ok_or_else(|| McpError::Tool(format!("Tool not found: {}", name)))?;
 
-        // Find handler
-        let handlers = self.handlers.read().await;
-        let handler = handlers
-            .get(name)
-            .ok_or_else(|| McpError::Tool(format!("No handler found for tool: {}", name)))?;
+        // Find handler; a defined tool without one is a server misconfiguration
+        let handler = self.handlers.read().await.get(name).cloned().ok_or_else(|| {
+            warn!("Tool {} is defined but has no registered handler", name);
+            McpError::tool_not_implemented(name)
+        })?;
 
         // Validate arguments
         handler.validate_arguments(arguments.as_ref()).await?;
 
-        // Execute tool
-        let result = handler.execute(arguments).await?;
+        // Execute tool, recording side effects when annotation checks are on
+        let result = if self.check_annotations {
+            let effects = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let result =
+                Self::execute_handler(handler, arguments, progress, Some(effects.clone())).await;
+            let effects = effects.lock().map(|e| e.clone()).unwrap_or_default();
+            self.check_side_effects(&tool, effects).await;
+            result?
+        } else {
+            Self::execute_handler(handler, arguments, progress, None).await?
+        };
 
         info!(
             "Executed tool: {} -> {} content items",
@@ -336,13 +546,96 @@ impl ToolManager {
         Ok(result)
     }
 
+    /// Execute a tool, reusing the result of an earlier call with the same
+    /// idempotency key when the tool deduplicates calls
+    pub async fn call_tool_idempotent(
+        &self,
+        name: &str,
+        arguments: Option<Value>,
+        progress: ProgressReporter,
+        idempotency_key: Option<&str>,
+    ) -> Result<ToolResult> {
+        let deduplicate = match idempotency_key {
+            Some(_) => self
+                .handlers
+                .read()
+                .await
+                .get(name)
+                .is_some_and(|handler| handler.deduplicate_calls()),
+            None => false,
+        };
+
+        match idempotency_key {
+            Some(key) if deduplicate => {
+                self.idempotency
+                    .get_or_run(format!("{}\0{}", name, key), arguments.as_ref(), || {
+                        self.call_tool_with_progress(name, arguments.clone(), progress)
+                    })
+                    .await
+            }
+            _ => self.call_tool_with_progress(name, arguments, progress).await,
+        }
+    }
+
+    /// Run a handler, moving CPU-bound ones onto the blocking thread pool
+    async fn execute_handler(
+        handler: Arc<dyn ToolHandler>,
+        arguments: Option<Value>,
+        progress: ProgressReporter,
+        effects: Option<Arc<std::sync::Mutex<Vec<ToolSideEffect>>>>,
+    ) -> Result<ToolResult> {
+        let cpu_bound = handler.cpu_bound();
+        let execute = async move {
+            let execution = handler.execute_with_progress(arguments, progress);
+            match effects {
+                Some(effects) => SIDE_EFFECTS.scope(effects, execution).await,
+                None => execution.await,
+            }
+        };
+
+        if !cpu_bound {
+            return execute.await;
+        }
+
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || runtime.block_on(execute))
+            .await
+            .map_err(|e| McpError::Tool(format!("Tool execution task failed: {}", e)))?
+    }
+
+    /// Compare observed side effects against the tool's declared annotations
+    async fn check_side_effects(&self, tool: &Tool, effects: Vec<ToolSideEffect>) {
+        let read_only = tool
+            .annotations
+            .as_ref()
+            .and_then(|a| a.read_only_hint)
+            .unwrap_or(false);
+        if !read_only || effects.is_empty() {
+            return;
+        }
+
+        let mut violations = self.violations.write().await;
+        for effect in effects {
+            let message = format!(
+                "Tool '{}' declares readOnlyHint but performed {:?}",
+                tool.name, effect
+            );
+            error!("Annotation violation: {}", message);
+            violations.push(AnnotationViolation {
+                tool: tool.name.clone(),
+                side_effect: effect,
+                message,
+            });
+        }
+    }
+
     /// Register a tool handler
     pub async fn register_handler(&self, handler: Box<dyn ToolHandler>) -> Result<()> {
         let name = handler.name().to_string();
 
         {
             let mut handlers = self.handlers.write().await;
-            handlers.insert(name.clone(), handler);
+            handlers.insert(name.clone(), Arc::from(handler));
         }
 
         info!("Registered tool handler: {}", name);
@@ -357,12 +650,17 @@ impl ToolManager {
 
         let tool_definition = handler.tool_definition();
         let name = handler.name().to_string();
+        Self::validate_definition(&tool_definition)?;
 
-        // Register the tool definition first
-        self.register_tool(tool_definition).await?;
-
-        // Then register the handler
-        self.register_handler(handler).await?;
+        // Insert both under one lock scope so a concurrent unregister can't
+        // leave a definition without a handler or vice versa
+        {
+            let mut tools = self.tools.write().await;
+            let mut handlers = self.handlers.write().await;
+            tools.insert(name.clone(), tool_definition);
+            handlers.insert(name.clone(), Arc::from(handler));
+        }
+        self.list_changed.notify();
 
         info!("Registered tool and handler: {}", name);
         Ok(())
@@ -386,53 +684,26 @@ impl ToolManager {
 
     /// Check if the feature is enabled
     pub fn is_enabled(&self) -> bool {
-        // Use try_read to avoid blocking in sync context
-        self.enabled
-            .try_read()
-            .map(|enabled| *enabled)
-            .unwrap_or(true)
+        self.enabled.load(Ordering::Acquire)
     }
 
     /// Check if the feature is enabled (async version)
     pub async fn is_enabled_async(&self) -> bool {
-        let enabled = self.enabled.read().await;
-        *enabled
+        self.is_enabled()
     }
 
     /// Set enabled state
     pub async fn set_enabled(&self, enabled: bool) {
-        let mut state = self.enabled.write().await;
-        *state = enabled;
+        self.enabled.store(enabled, Ordering::Release);
     }
 
     /// Apply pagination to tools
     fn apply_pagination(
         &self,
-        mut tools: Vec<Tool>,
+        tools: Vec<Tool>,
         params: PaginationParams,
     ) -> Result<(Vec<Tool>, PaginationResult)> {
-        let start_index = if let Some(cursor) = params.cursor {
-            cursor.parse::<usize>().unwrap_or(0)
-        } else {
-            0
-        };
-
-        let page_size = 50; // Default page size
-        let end_index = std::cmp::min(start_index + page_size, tools.len());
-
-        let page_tools = if start_index < tools.len() {
-            tools.drain(start_index..end_index).collect()
-        } else {
-            Vec::new()
-        };
-
-        let next_cursor = if end_index < tools.len() {
-            Some(end_index.to_string())
-        } else {
-            None
-        };
-
-        Ok((page_tools, PaginationResult { next_cursor }))
+        paginate_by_key(tools, params, self.page_size, |tool| tool.name.as_str())
     }
 }
 
@@ -446,9 +717,7 @@ impl FeatureManager for ToolManager {
     }
 
     fn set_enabled(&mut self, enabled: bool) {
-        if let Ok(mut state) = self.enabled.try_write() {
-            *state = enabled;
-        }
+        self.enabled.store(enabled, Ordering::Release);
     }
 }
 
@@ -477,6 +746,15 @@ impl ToolResult {
         }])
     }
 
+    /// Create a result holding a single audio clip
+    pub fn audio(data: String, mime_type: String) -> Self {
+        Self::success(vec![Content::Audio {
+            data,
+            mime_type,
+            annotations: None,
+        }])
+    }
+
     /// Create a simple error text result
     pub fn error_text(text: String) -> Self {
         Self::error(vec![Content::Text {
@@ -499,6 +777,15 @@ impl ToolHandler for EchoToolHandler {
         Some("Echo back the provided message".to_string())
     }
 
+    fn annotations(&self) -> Option<crate::protocol::ToolAnnotations> {
+        Some(
+            crate::protocol::ToolAnnotations::new()
+                .read_only()
+                .idempotent()
+                .open_world(false),
+        )
+    }
+
     fn input_schema(&self) -> crate::protocol::ToolInputSchema {
         use std::collections::HashMap;
 
@@ -563,6 +850,15 @@ impl ToolHandler for CalculatorToolHandler {
         Some("Perform mathematical calculations".to_string())
     }
 
+    fn annotations(&self) -> Option<crate::protocol::ToolAnnotations> {
+        Some(
+            crate::protocol::ToolAnnotations::new()
+                .read_only()
+                .idempotent()
+                .open_world(false),
+        )
+    }
+
     fn input_schema(&self) -> crate::protocol::ToolInputSchema {
         use std::collections::HashMap;
 
@@ -715,6 +1011,10 @@ impl ToolHandlerDiscovery {
             }
         }
 
+        if let Some(config) = config {
+            Self::load_plugins(config);
+        }
+
         // Get all registered handlers
         let registrations = ToolHandlerRegistry::get_all()?;
 
@@ -750,6 +1050,22 @@ impl ToolHandlerDiscovery {
         Ok(handlers)
     }
 
+    /// Load the plugin libraries listed in the configuration
+    #[cfg(feature = "plugins")]
+    fn load_plugins(config: &ToolsConfig) {
+        for (path, e) in super::plugins::load_plugins(&config.plugin_paths) {
+            warn!("Failed to load tool plugin {}: {}", path.display(), e);
+        }
+    }
+
+    /// Load the plugin libraries listed in the configuration
+    #[cfg(not(feature = "plugins"))]
+    fn load_plugins(config: &ToolsConfig) {
+        if !config.plugin_paths.is_empty() {
+            warn!("Ignoring tools.plugin_paths: the server was built without the `plugins` feature");
+        }
+    }
+
     /// Filter registrations based on configuration
     fn filter_by_config(
         registrations: &[ToolHandlerRegistration],
@@ -779,6 +1095,12 @@ impl ToolHandlerDiscovery {
             } else if registration.is_builtin && config.auto_discover_builtin {
                 // Built-in handler with auto-discovery enabled
                 config.enable_all_by_default
+            } else if registration.from_plugin {
+                // Plugin handler listed via `plugin_paths`
+                config.enable_all_by_default
+            } else if !registration.is_builtin && config.auto_discover_all {
+                // Embedder-registered handler with auto-discovery enabled
+                config.enable_all_by_default
             } else {
                 // Non-built-in handler without explicit config
                 false
@@ -805,6 +1127,37 @@ impl ToolHandlerDiscovery {
     pub fn is_handler_available(name: &str) -> Result<bool> {
         Ok(ToolHandlerRegistry::get(name)?.is_some())
     }
+
+    /// Check that a handler's input schema is a well-formed JSON Schema object
+    pub fn check_input_schema(handler: &dyn ToolHandler) -> Result<()> {
+        let schema = serde_json::to_value(handler.input_schema())?;
+        crate::protocol::validation::validate_tool_schema(&schema)?;
+
+        jsonschema::JSONSchema::compile(&schema)
+            .map_err(|e| McpError::invalid_params(format!("Invalid JSON Schema: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Instantiate the configured handlers and report any with malformed input schemas
+    ///
+    /// Returns `(handler name, reason)` pairs; an empty list means every schema is valid.
+    pub fn check_schemas(config: Option<&ToolsConfig>) -> Result<Vec<(String, String)>> {
+        let handlers = Self::discover_handlers(config)?;
+        Ok(Self::find_invalid_schemas(&handlers))
+    }
+
+    /// Collect handlers whose input schema fails `check_input_schema`
+    fn find_invalid_schemas(handlers: &[Box<dyn ToolHandler>]) -> Vec<(String, String)> {
+        handlers
+            .iter()
+            .filter_map(|handler| {
+                Self::check_input_schema(handler.as_ref())
+                    .err()
+                    .map(|e| (handler.name().to_string(), e.to_string()))
+            })
+            .collect()
+    }
 }
 
 /// Get all available tool handlers (backward compatibility)
@@ -862,6 +1215,11 @@ impl Default for ToolsConfig {
             handlers: Vec::new(),
             auto_discover_builtin: true, 
             enable_all_by_default: true,
+            auto_discover_all: false,
+            check_annotations: false,
+            plugin_paths: Vec::new(),
+            sandbox: Default::default(),
+            idempotency_ttl: default_idempotency_ttl(),
         }
     }
 }
@@ -917,6 +1275,101 @@ mod tests {
         assert!(not_found.is_none());
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_disabled_state_consistent_under_concurrent_toggles() {
+        let manager = Arc::new(ToolManager::new());
+        manager.set_enabled(false).await;
+
+        // Keep rewriting the flag while readers poll it; a disabled manager
+        // must never be observed as enabled
+        let writer = {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                for _ in 0..10_000 {
+                    manager.set_enabled(false).await;
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    (0..10_000).filter(|_| manager.is_enabled()).count()
+                })
+            })
+            .collect();
+
+        writer.await.unwrap();
+        for reader in readers {
+            assert_eq!(reader.await.unwrap(), 0);
+        }
+
+        manager.set_enabled(true).await;
+        assert!(manager.is_enabled());
+        assert!(manager.is_enabled_async().await);
+    }
+
+    #[tokio::test]
+    async fn test_tool_without_handler_is_not_implemented() {
+        let manager = ToolManager::new();
+        manager
+            .register_tool(Tool {
+                name: "unimplemented".to_string(),
+                description: None,
+                input_schema: ToolInputSchema {
+                    schema_type: "object".to_string(),
+                    properties: None,
+                    required: None,
+                },
+                annotations: None,
+            })
+            .await
+            .unwrap();
+
+        let err = manager.call_tool("unimplemented", None).await.unwrap_err();
+        assert!(matches!(err, McpError::ToolNotImplemented(ref name) if name == "unimplemented"));
+        assert_eq!(err.to_json_rpc_code(), -32006);
+
+        // An unknown tool stays a distinct, generic tool error
+        let err = manager.call_tool("missing", None).await.unwrap_err();
+        assert!(matches!(err, McpError::Tool(_)));
+        assert_ne!(err.to_json_rpc_code(), -32006);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_register_and_unregister_keep_definitions_and_handlers_in_sync() {
+        let manager = Arc::new(ToolManager::new());
+
+        let tasks: Vec<_> = (0..8)
+            .map(|i| {
+                let manager = manager.clone();
+                tokio::spawn(async move {
+                    for _ in 0..200 {
+                        if i % 2 == 0 {
+                            manager
+                                .register_handler_with_tool(Box::new(EchoToolHandler))
+                                .await
+                                .unwrap();
+                        } else {
+                            manager.unregister_tool("echo").await.unwrap();
+                        }
+
+                        let tools = manager.tools.read().await;
+                        let handlers = manager.handlers.read().await;
+                        assert_eq!(tools.contains_key("echo"), handlers.contains_key("echo"));
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let registered = manager.get_tool("echo").await.is_some();
+        assert_eq!(registered, manager.handlers.read().await.contains_key("echo"));
+    }
+
     #[tokio::test]
     async fn test_echo_tool() {
         let handler = EchoToolHandler;
@@ -933,6 +1386,16 @@ mod tests {
         assert_eq!(result.content.len(), 1);
     }
 
+    #[test]
+    fn test_calculator_definition_reports_annotations() {
+        let definition = serde_json::to_value(CalculatorToolHandler.tool_definition()).unwrap();
+        let annotations = &definition["annotations"];
+        assert_eq!(annotations["readOnlyHint"], true);
+        assert_eq!(annotations["idempotentHint"], true);
+        assert_eq!(annotations["openWorldHint"], false);
+        assert!(annotations.get("destructiveHint").is_none());
+    }
+
     #[tokio::test]
     async fn test_calculator_tool() {
         let handler = CalculatorToolHandler;
@@ -1074,6 +1537,11 @@ mod tests {
             ],
             auto_discover_builtin: true,
             enable_all_by_default: false,
+            auto_discover_all: false,
+            check_annotations: false,
+            plugin_paths: Vec::new(),
+            sandbox: Default::default(),
+            idempotency_ttl: 300,
         };
 
         let handlers = ToolHandlerDiscovery::discover_handlers(Some(&config)).unwrap();
@@ -1081,6 +1549,245 @@ mod tests {
         assert_eq!(handlers[0].name(), "echo");
     }
 
+    #[test]
+    fn test_custom_handler_enabled_by_default_with_auto_discover_all() {
+        let registration = |name: &str, is_builtin: bool| ToolHandlerRegistration {
+            name: name.to_string(),
+            factory: Arc::new(|| Ok(Box::new(EchoToolHandler) as Box<dyn ToolHandler>)),
+            priority: 0,
+            is_builtin,
+            from_plugin: false,
+        };
+        let registrations = [registration("echo", true), registration("custom", false)];
+        let enabled = |config: &ToolsConfig| -> Vec<String> {
+            ToolHandlerDiscovery::filter_by_config(&registrations, Some(config))
+                .unwrap()
+                .into_iter()
+                .map(|r| r.name)
+                .collect()
+        };
+
+        let mut config = ToolsConfig::default();
+        assert_eq!(enabled(&config), vec!["echo"]);
+
+        config.auto_discover_all = true;
+        assert_eq!(enabled(&config), vec!["echo", "custom"]);
+
+        // Still subject to enable_all_by_default and explicit entries
+        config.handlers.push(ToolHandlerConfig {
+            name: "echo".to_string(),
+            enabled: false,
+            ..ToolHandlerConfig::default()
+        });
+        assert_eq!(enabled(&config), vec!["custom"]);
+        config.enable_all_by_default = false;
+        assert!(enabled(&config).is_empty());
+    }
+
+    struct MalformedSchemaToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for MalformedSchemaToolHandler {
+        fn name(&self) -> &str {
+            "malformed"
+        }
+
+        fn input_schema(&self) -> ToolInputSchema {
+            let mut props = HashMap::new();
+            props.insert("count".to_string(), serde_json::json!({"type": 5}));
+            props.insert("mode".to_string(), serde_json::json!({"enum": "fast"}));
+            ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: Some(props),
+                required: None,
+            }
+        }
+
+        async fn execute(&self, _arguments: Option<Value>) -> Result<ToolResult> {
+            Ok(ToolResult::text("unreachable".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_check_input_schemas() {
+        assert!(ToolHandlerDiscovery::check_input_schema(&EchoToolHandler).is_ok());
+        assert!(ToolHandlerDiscovery::check_input_schema(&CalculatorToolHandler).is_ok());
+        assert!(ToolHandlerDiscovery::check_input_schema(&MalformedSchemaToolHandler).is_err());
+
+        let handlers: Vec<Box<dyn ToolHandler>> = vec![
+            Box::new(EchoToolHandler),
+            Box::new(MalformedSchemaToolHandler),
+            Box::new(CalculatorToolHandler),
+        ];
+        let offenders = ToolHandlerDiscovery::find_invalid_schemas(&handlers);
+        assert_eq!(offenders.len(), 1);
+        assert_eq!(offenders[0].0, "malformed");
+    }
+
+    struct UndeclaredRequiredToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for UndeclaredRequiredToolHandler {
+        fn name(&self) -> &str {
+            "undeclared_required"
+        }
+
+        fn input_schema(&self) -> ToolInputSchema {
+            let mut properties = HashMap::new();
+            properties.insert("a".to_string(), serde_json::json!({"type": "number"}));
+            ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: Some(properties),
+                required: Some(vec!["a".to_string(), "b".to_string()]),
+            }
+        }
+
+        async fn execute(&self, _arguments: Option<Value>) -> Result<ToolResult> {
+            Ok(ToolResult::text("unreachable".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_required_field_missing_from_properties_rejected() {
+        let manager = ToolManager::new();
+        let err = manager
+            .register_handler_with_tool(Box::new(UndeclaredRequiredToolHandler))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("'b'"));
+
+        // Neither the definition nor the handler was registered
+        assert!(manager.get_tool("undeclared_required").await.is_none());
+        assert!(manager.call_tool("undeclared_required", None).await.is_err());
+    }
+
+    struct ReadOnlyWritingToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for ReadOnlyWritingToolHandler {
+        fn name(&self) -> &str {
+            "sneaky_writer"
+        }
+
+        fn input_schema(&self) -> ToolInputSchema {
+            ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        fn annotations(&self) -> Option<crate::protocol::ToolAnnotations> {
+            Some(crate::protocol::ToolAnnotations {
+                title: None,
+                read_only_hint: Some(true),
+                destructive_hint: None,
+                idempotent_hint: None,
+                open_world_hint: None,
+            })
+        }
+
+        async fn execute(&self, _arguments: Option<Value>) -> Result<ToolResult> {
+            record_side_effect(ToolSideEffect::ResourceWrite("file:///tmp/out.txt".to_string()));
+            Ok(ToolResult::text("done".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_only_tool_write_flagged() {
+        let manager = ToolManager::new().with_annotation_checks(true);
+        manager
+            .register_handler_with_tool(Box::new(ReadOnlyWritingToolHandler))
+            .await
+            .unwrap();
+        manager
+            .register_handler_with_tool(Box::new(EchoToolHandler))
+            .await
+            .unwrap();
+
+        manager.call_tool("echo", None).await.unwrap();
+        assert!(manager.get_annotation_violations().await.is_empty());
+
+        manager.call_tool("sneaky_writer", None).await.unwrap();
+        let violations = manager.get_annotation_violations().await;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].tool, "sneaky_writer");
+        assert_eq!(
+            violations[0].side_effect,
+            ToolSideEffect::ResourceWrite("file:///tmp/out.txt".to_string())
+        );
+
+        // Checks are off by default
+        let manager = ToolManager::new();
+        manager
+            .register_handler_with_tool(Box::new(ReadOnlyWritingToolHandler))
+            .await
+            .unwrap();
+        manager.call_tool("sneaky_writer", None).await.unwrap();
+        assert!(manager.get_annotation_violations().await.is_empty());
+    }
+
+    #[test]
+    fn test_audio_content_round_trip() {
+        let content = Content::Audio {
+            data: "UklGRg==".to_string(),
+            mime_type: "audio/wav".to_string(),
+            annotations: None,
+        };
+
+        let json = serde_json::to_value(&content).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "type": "audio", "data": "UklGRg==", "mimeType": "audio/wav" })
+        );
+
+        match serde_json::from_value(json).unwrap() {
+            Content::Audio { data, mime_type, annotations } => {
+                assert_eq!(data, "UklGRg==");
+                assert_eq!(mime_type, "audio/wav");
+                assert!(annotations.is_none());
+            }
+            other => panic!("expected audio content, got {:?}", other),
+        }
+    }
+
+    struct ChimeToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for ChimeToolHandler {
+        fn name(&self) -> &str {
+            "chime"
+        }
+
+        fn input_schema(&self) -> ToolInputSchema {
+            ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        async fn execute(&self, _arguments: Option<Value>) -> Result<ToolResult> {
+            Ok(ToolResult::audio("UklGRg==".to_string(), "audio/wav".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_returns_audio() {
+        let manager = ToolManager::new();
+        manager
+            .register_handler_with_tool(Box::new(ChimeToolHandler))
+            .await
+            .unwrap();
+
+        let result = manager.call_tool("chime", None).await.unwrap();
+        assert!(!result.is_error);
+        assert!(matches!(
+            result.content.as_slice(),
+            [Content::Audio { mime_type, .. }] if mime_type == "audio/wav"
+        ));
+    }
+
     #[tokio::test]
     async fn test_get_tool_handlers_with_config() {
         // Clear registry for clean test
@@ -1098,9 +1805,146 @@ mod tests {
             handlers: Vec::new(),
             auto_discover_builtin: false,
             enable_all_by_default: false,
+            auto_discover_all: false,
+            check_annotations: false,
+            plugin_paths: Vec::new(),
+            sandbox: Default::default(),
+            idempotency_ttl: 300,
         };
 
         let handlers = get_tool_handlers_with_config(Some(&config));
         assert!(handlers.is_empty());
     }
+
+    struct SpinToolHandler;
+
+    #[async_trait::async_trait]
+    impl ToolHandler for SpinToolHandler {
+        fn name(&self) -> &str {
+            "spin"
+        }
+
+        fn input_schema(&self) -> ToolInputSchema {
+            ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            }
+        }
+
+        fn cpu_bound(&self) -> bool {
+            true
+        }
+
+        async fn execute(&self, _arguments: Option<Value>) -> Result<ToolResult> {
+            // Stand-in for heavy computation that never yields to the runtime
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            Ok(ToolResult::text("spun".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cpu_bound_tool_does_not_block_runtime() {
+        let manager = Arc::new(ToolManager::new());
+        manager
+            .register_handler_with_tool(Box::new(SpinToolHandler))
+            .await
+            .unwrap();
+        manager
+            .register_handler_with_tool(Box::new(EchoToolHandler))
+            .await
+            .unwrap();
+
+        // The test runtime is single-threaded, so an inline spin would stall everything
+        let spinning = tokio::spawn({
+            let manager = manager.clone();
+            async move { manager.call_tool("spin", None).await }
+        });
+        tokio::task::yield_now().await;
+
+        let echo = tokio::time::timeout(
+            std::time::Duration::from_millis(100),
+            manager.call_tool("echo", Some(serde_json::json!({"message": "still here"}))),
+        )
+        .await
+        .expect("echo should complete while the CPU-bound tool runs")
+        .unwrap();
+        assert!(!echo.is_error);
+        assert!(!spinning.is_finished());
+
+        let spun = spinning.await.unwrap().unwrap();
+        assert!(!spun.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_runtime_registration_notifies_list_changed() {
+        let (sink, mut notifications) = tokio::sync::mpsc::unbounded_channel();
+        let manager = ToolManager::new()
+            .with_notification_sink(sink)
+            .with_list_changed_debounce(Duration::from_millis(10));
+
+        let tool = |name: &str| Tool {
+            name: name.to_string(),
+            description: None,
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            },
+            annotations: None,
+        };
+
+        // A burst of registrations yields a single notification
+        manager.register_tool(tool("a")).await.unwrap();
+        manager.register_tool(tool("b")).await.unwrap();
+        let notification = tokio::time::timeout(Duration::from_secs(1), notifications.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(notification.method, "notifications/tools/list_changed");
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(notifications.try_recv().is_err());
+
+        // Removing an unknown tool changes nothing
+        manager.unregister_tool("missing").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(notifications.try_recv().is_err());
+
+        manager.unregister_tool("a").await.unwrap();
+        assert!(notifications.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_tool_pages_stable_across_registration() {
+        let manager = ToolManager::new().with_page_size(2);
+        let tool = |name: &str| Tool {
+            name: name.to_string(),
+            description: None,
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: None,
+                required: None,
+            },
+            annotations: None,
+        };
+        for name in ["b", "d", "f"] {
+            manager.register_tool(tool(name)).await.unwrap();
+        }
+
+        let (first, result) = manager
+            .list_tools(Some(PaginationParams { cursor: None }))
+            .await
+            .unwrap();
+
+        // A tool sorting before the cursor arrives between pages
+        manager.register_tool(tool("a")).await.unwrap();
+        let (second, result) = manager
+            .list_tools(Some(PaginationParams { cursor: result.next_cursor }))
+            .await
+            .unwrap();
+        assert!(result.next_cursor.is_none());
+
+        let names: Vec<_> = first.iter().chain(&second).map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["b", "d", "f"]);
+    }
 }
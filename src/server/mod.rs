@@ -4,16 +4,26 @@
 //! for resources, tools, prompts, and other MCP server capabilities.
 
 pub mod features;
+pub mod selftest;
 
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, Notify};
 use tracing::{error, info, warn};
 
-use crate::client::features::SamplingManager;
-use crate::config::Config;
-use crate::error::Result;
+use crate::client::features::{RootsManager, SamplingManager};
+use crate::config::{Config, LoggingConfig};
+use crate::error::{Result, TransportError};
 use crate::protocol::handler::ProtocolHandler;
-use crate::server::features::{PromptManager, ResourceManager, ToolManager};
-use crate::transport::{Transport, TransportFactory, TransportManager};
+use crate::protocol::{AnyJsonRpcMessage, JsonRpcNotification, JsonRpcRequest, RequestContext};
+use crate::server::features::{
+    LoggingManager, PromptManager, ResourceManager, ServerFeatureManager, ToolManager,
+};
+use crate::transport::{
+    NotificationTarget, Transport, TransportFactory, TransportInfo, TransportManager,
+};
+use crate::utils::audit::{AuditSink, FileAuditSink, TracingAuditSink};
+use crate::utils::ids::id_generator_for;
 
 /// Main MCP server implementation
 pub struct McpServer {
@@ -23,45 +33,87 @@ pub struct McpServer {
     /// Transport manager
     transport_manager: TransportManager,
 
+    /// Feature managers shared with the protocol handler
+    feature_manager: Arc<ServerFeatureManager>,
+
     /// Protocol handler
     protocol_handler: Arc<ProtocolHandler>,
 
     /// Server running state
     running: Arc<tokio::sync::RwLock<bool>>,
+
+    /// When the server was last started
+    started_at: Arc<tokio::sync::RwLock<Option<Instant>>>,
+
+    /// Wakes the message loop when a shutdown signal arrives
+    shutdown_signal: Arc<Notify>,
+
+    /// Notifications published by the feature managers
+    notification_receiver: Option<mpsc::UnboundedReceiver<JsonRpcNotification>>,
+
+    /// Notifications addressed to a single session
+    session_notification_receiver: Option<SessionNotificationReceiver>,
+
+    /// Requests the protocol handler sends to clients
+    request_receiver: Option<RequestReceiver>,
 }
 
+/// Receiving end of the session-addressed notification channel
+type SessionNotificationReceiver = mpsc::UnboundedReceiver<(String, JsonRpcNotification)>;
+
+/// Receiving end of the server-initiated request channel
+type RequestReceiver = mpsc::UnboundedReceiver<(Option<String>, JsonRpcRequest)>;
+
+/// Protocol handler and the channels it publishes to clients through
+struct HandlerParts {
+    feature_manager: Arc<ServerFeatureManager>,
+    protocol_handler: Arc<ProtocolHandler>,
+    notification_receiver: mpsc::UnboundedReceiver<JsonRpcNotification>,
+    session_notification_receiver: SessionNotificationReceiver,
+    request_receiver: RequestReceiver,
+}
+
+/// Notification sent to connected clients when the server starts shutting down
+pub const SHUTDOWN_NOTIFICATION: &str = "notifications/server/shutdown";
+
 impl McpServer {
     /// Create a new MCP server with the given configuration
     pub fn new(config: Config) -> Result<Self> {
         // Validate configuration
         config.validate()?;
 
-        // Create feature managers
-        let resource_manager = Arc::new(ResourceManager::new());
-        let tool_manager = Arc::new(ToolManager::new());
-        let prompt_manager = Arc::new(PromptManager::new());
-        let sampling_manager = Arc::new(SamplingManager::new());
-
-        // Create protocol handler
-        let protocol_handler = Arc::new(ProtocolHandler::new(
-            resource_manager,
-            tool_manager,
-            prompt_manager,
-            sampling_manager,
-        ));
+        let HandlerParts {
+            feature_manager,
+            protocol_handler,
+            notification_receiver,
+            session_notification_receiver,
+            request_receiver,
+        } = Self::create_protocol_handler(&config);
 
         // Create transport manager
         let mut transport_manager = TransportManager::new();
 
         // Create and add transport based on configuration
-        let transport = TransportFactory::create(&config.transport)?;
+        let transport =
+            TransportFactory::create_with_handler(
+                &config.transport,
+                &config.auth,
+                protocol_handler.clone(),
+                id_generator_for(config.server.id_format, &config.server.id_prefix),
+            )?;
         transport_manager.add_transport(transport);
 
         Ok(Self {
             config,
             transport_manager,
+            feature_manager,
             protocol_handler,
             running: Arc::new(tokio::sync::RwLock::new(false)),
+            started_at: Arc::new(tokio::sync::RwLock::new(None)),
+            shutdown_signal: Arc::new(Notify::new()),
+            notification_receiver: Some(notification_receiver),
+            session_notification_receiver: Some(session_notification_receiver),
+            request_receiver: Some(request_receiver),
         })
     }
 
@@ -70,19 +122,13 @@ impl McpServer {
         // Validate configuration
         config.validate()?;
 
-        // Create feature managers
-        let resource_manager = Arc::new(ResourceManager::new());
-        let tool_manager = Arc::new(ToolManager::new());
-        let prompt_manager = Arc::new(PromptManager::new());
-        let sampling_manager = Arc::new(SamplingManager::new());
-
-        // Create protocol handler
-        let protocol_handler = Arc::new(ProtocolHandler::new(
-            resource_manager,
-            tool_manager,
-            prompt_manager,
-            sampling_manager,
-        ));
+        let HandlerParts {
+            feature_manager,
+            protocol_handler,
+            notification_receiver,
+            session_notification_receiver,
+            request_receiver,
+        } = Self::create_protocol_handler(&config);
 
         // Create transport manager and add the custom transport
         let mut transport_manager = TransportManager::new();
@@ -91,11 +137,90 @@ impl McpServer {
         Ok(Self {
             config,
             transport_manager,
+            feature_manager,
             protocol_handler,
             running: Arc::new(tokio::sync::RwLock::new(false)),
+            started_at: Arc::new(tokio::sync::RwLock::new(None)),
+            shutdown_signal: Arc::new(Notify::new()),
+            notification_receiver: Some(notification_receiver),
+            session_notification_receiver: Some(session_notification_receiver),
+            request_receiver: Some(request_receiver),
         })
     }
 
+    /// Create the feature managers and a protocol handler serving them
+    fn create_protocol_handler(config: &Config) -> HandlerParts {
+        // Create feature managers, publishing list changes to connected clients
+        let (notification_sink, notification_receiver) = mpsc::unbounded_channel();
+        let (session_sink, session_notification_receiver) = mpsc::unbounded_channel();
+        let (request_sink, request_receiver) = mpsc::unbounded_channel();
+        let page_size = config.server.page_size;
+        let feature_manager = Arc::new(
+            ServerFeatureManager::new()
+                .with_resource_manager(Arc::new(
+                    ResourceManager::with_config(config.resources.clone())
+                        .with_page_size(page_size)
+                        .with_notification_sink(notification_sink.clone()),
+                ))
+                .with_tool_manager(Arc::new(
+                    ToolManager::new()
+                        .with_annotation_checks(config.tools.check_annotations)
+                        .with_idempotency_ttl(Duration::from_secs(config.tools.idempotency_ttl))
+                        .with_page_size(page_size)
+                        .with_notification_sink(notification_sink.clone()),
+                ))
+                .with_prompt_manager(Arc::new(
+                    PromptManager::new()
+                        .with_page_size(page_size)
+                        .with_notification_sink(notification_sink.clone()),
+                ))
+                .with_logging_manager(Arc::new(
+                    LoggingManager::new()
+                        .with_notification_sink(notification_sink.clone())
+                        .with_session_sink(session_sink.clone()),
+                )),
+        );
+        let sampling_manager = Arc::new(SamplingManager::new());
+
+        let roots_manager = Arc::new(
+            RootsManager::with_config(config.roots.clone())
+                .with_enabled(config.features.roots)
+                .with_notification_sink(notification_sink.clone()),
+        );
+
+        // Create protocol handler
+        let mut protocol_handler =
+            ProtocolHandler::from_feature_manager(feature_manager.clone(), sampling_manager)
+                .with_roots_manager(roots_manager)
+                .with_scoped_experimental(config.auth.scoped_experimental.clone())
+                .with_scope_methods(config.auth.scope_methods.clone())
+                .with_max_batch_size(config.server.max_batch_size)
+                .with_batch_concurrency(config.server.batch_concurrency)
+                .with_maintenance_mode(config.server.maintenance_mode)
+                .with_strict_notifications(config.server.strict_notifications)
+                .with_max_response_size(config.server.max_response_size)
+                .with_response_size_policy(config.server.response_size_policy)
+                .with_notification_sink(notification_sink)
+                .with_session_sink(session_sink)
+                .with_request_sink(request_sink)
+                .with_id_generator(id_generator_for(
+                    config.server.id_format,
+                    &config.server.id_prefix,
+                ));
+        if config.logging.audit {
+            protocol_handler = protocol_handler.with_audit_sink(audit_sink(&config.logging));
+        }
+        let protocol_handler = Arc::new(protocol_handler);
+
+        HandlerParts {
+            feature_manager,
+            protocol_handler,
+            notification_receiver,
+            session_notification_receiver,
+            request_receiver,
+        }
+    }
+
     /// Start the MCP server
     pub async fn start(&mut self) -> Result<()> {
         info!("Starting MCP server");
@@ -115,13 +240,94 @@ impl McpServer {
             *running = true;
         }
 
+        // Register the built-in features before any client can connect
+        self.protocol_handler.ready().await;
+
         // Start transport manager
-        let mut message_receiver = self.transport_manager.start().await?;
+        let mut message_receiver = match self.transport_manager.start().await {
+            Ok(receiver) => receiver,
+            Err(e) => {
+                *self.running.write().await = false;
+                return Err(e);
+            }
+        };
 
+        *self.started_at.write().await = Some(Instant::now());
         info!("MCP server started successfully");
 
+        let mut notifications = self.notification_receiver.take();
+        let mut session_notifications = self.session_notification_receiver.take();
+        let mut requests = self.request_receiver.take();
+        let mut input_closed = false;
+
         // Main message processing loop
-        while let Some(transport_message) = message_receiver.recv().await {
+        loop {
+            let transport_message = tokio::select! {
+                _ = self.shutdown_signal.notified() => break,
+                notification = async {
+                    match notifications.as_mut() {
+                        Some(receiver) => receiver.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    match notification {
+                        Some(notification) => {
+                            self.transport_manager
+                                .send_notification(notification, NotificationTarget::Broadcast)
+                                .await;
+                        }
+                        None => notifications = None,
+                    }
+                    continue;
+                }
+                session_notification = async {
+                    match session_notifications.as_mut() {
+                        Some(receiver) => receiver.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    match session_notification {
+                        Some((session_id, notification)) => {
+                            let target = NotificationTarget::Session(session_id);
+                            self.transport_manager
+                                .send_notification(notification, target)
+                                .await;
+                        }
+                        None => session_notifications = None,
+                    }
+                    continue;
+                }
+                request = async {
+                    match requests.as_mut() {
+                        Some(receiver) => receiver.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    match request {
+                        Some((session_id, request)) => {
+                            let target = session_id
+                                .map_or(NotificationTarget::Broadcast, NotificationTarget::Session);
+                            let id = request.id.clone();
+                            let method = request.method.clone();
+                            if self.transport_manager.send_request(request, target).await == 0 {
+                                // Nobody will answer; fail the caller now rather than at its timeout
+                                warn!("No client received {} request {}", method, id);
+                                self.protocol_handler.outgoing_requests().cancel(&id);
+                            }
+                        }
+                        None => requests = None,
+                    }
+                    continue;
+                }
+                message = message_receiver.recv() => match message {
+                    Some(message) => message,
+                    None => {
+                        input_closed = true;
+                        break;
+                    }
+                },
+            };
+
             // Check if we should stop
             {
                 let running = self.running.read().await;
@@ -130,33 +336,58 @@ impl McpServer {
                 }
             }
 
-            // Handle the message
-            match self
-                .protocol_handler
-                .handle_message(transport_message.message)
-                .await
-            {
-                Ok(Some(response)) => {
-                    // Send response back through transport
-                    info!("Generated response: {:?}", response);
-                }
-                Ok(None) => {
-                    // No response needed (e.g., for notifications)
-                }
-                Err(e) => {
-                    error!("Error handling message: {}", e);
+            // Handle the message on behalf of the client that sent it. Requests
+            // run on their own task so a handler can await the client's answer
+            // to a request it sends, which arrives through this same loop.
+            let context = transport_message.request_context();
+            match transport_message.message {
+                message @ (AnyJsonRpcMessage::Request(_) | AnyJsonRpcMessage::Batch(_)) => {
+                    let handler = self.protocol_handler.clone();
+                    tokio::spawn(async move {
+                        handle_message(&handler, message, &context).await;
+                    });
                 }
+                message => handle_message(&self.protocol_handler, message, &context).await,
             }
         }
 
         info!("MCP server message loop ended");
+        self.notification_receiver = notifications;
+        self.session_notification_receiver = session_notifications;
+        self.request_receiver = requests;
+
+        // The loop ends on a shutdown signal or when every transport has
+        // closed its input (e.g. the STDIO client went away)
+        let still_running = self.is_running().await;
+        let failed = self.transport_manager.failed_transports();
+        if input_closed && still_running && !failed.is_empty() {
+            let failed = failed
+                .iter()
+                .map(|info| format!("{} ({})", info.transport_type, info.address))
+                .collect::<Vec<_>>()
+                .join(", ");
+            error!(
+                "Message channel closed unexpectedly, likely failed transport(s): {}",
+                failed
+            );
+            self.stop().await?;
+            return Err(TransportError::ConnectionLost(format!(
+                "Message channel closed unexpectedly: {}",
+                failed
+            ))
+            .into());
+        }
+        if still_running {
+            self.stop().await?;
+        }
+
         Ok(())
     }
 
     /// Run the server (blocking)
     pub async fn run(&mut self) -> Result<()> {
         // Set up signal handling for graceful shutdown
-        let running = self.running.clone();
+        let shutdown_signal = self.shutdown_signal.clone();
 
         tokio::spawn(async move {
             let mut sigterm =
@@ -176,8 +407,7 @@ impl McpServer {
                 }
             }
 
-            let mut running_guard = running.write().await;
-            *running_guard = false;
+            shutdown_signal.notify_one();
         });
 
         // Start the server
@@ -186,6 +416,13 @@ impl McpServer {
 
     /// Stop the MCP server
     pub async fn stop(&self) -> Result<()> {
+        self.shutdown().await.map(|_| ())
+    }
+
+    /// Stop the MCP server and return its final statistics
+    ///
+    /// The statistics are also logged as a summary.
+    pub async fn shutdown(&self) -> Result<ServerStats> {
         info!("Stopping MCP server");
 
         // Mark as not running
@@ -194,11 +431,34 @@ impl McpServer {
             *running = false;
         }
 
+        // Warn connected clients, then give them the grace period to finish up
+        let grace_period = Duration::from_secs(self.config.server.shutdown_grace_period);
+        let notification = JsonRpcNotification::new(
+            SHUTDOWN_NOTIFICATION.to_string(),
+            Some(serde_json::json!({ "gracePeriodMs": grace_period.as_millis() as u64 })),
+        );
+        let notified = self.transport_manager.notify_shutdown(notification).await;
+        if notified > 0 {
+            info!(
+                "Notified {} client(s) of shutdown, draining for {:?}",
+                notified, grace_period
+            );
+            tokio::time::sleep(grace_period).await;
+        }
+
         // Stop transport manager
         self.transport_manager.stop().await?;
 
-        info!("MCP server stopped");
-        Ok(())
+        let stats = self.get_stats().await;
+        info!(
+            "MCP server stopped after {}: {} requests ({} errors), {} notifications, peak of {} sessions",
+            crate::utils::format_duration(stats.uptime),
+            stats.total_requests,
+            stats.total_errors,
+            stats.total_notifications,
+            stats.peak_sessions
+        );
+        Ok(stats)
     }
 
     /// Check if the server is running
@@ -212,17 +472,43 @@ impl McpServer {
         &self.config
     }
 
+    /// Get the feature managers the server exposes
+    pub fn feature_manager(&self) -> Arc<ServerFeatureManager> {
+        self.feature_manager.clone()
+    }
+
     /// Get transport information
     pub fn transport_info(&self) -> Vec<crate::transport::TransportInfo> {
         self.transport_manager.get_transport_info()
     }
 
+    /// Get server health
+    pub fn health(&self) -> ServerHealth {
+        let failed = self.transport_manager.failed_transports();
+        if failed.is_empty() {
+            ServerHealth::Healthy
+        } else {
+            ServerHealth::TransportFailed(failed)
+        }
+    }
+
     /// Get server statistics
     pub async fn get_stats(&self) -> ServerStats {
+        let counters = self.protocol_handler.counters();
         ServerStats {
             running: self.is_running().await,
+            health: self.health(),
             transport_count: self.transport_info().len(),
-            // Add more statistics as needed
+            total_requests: counters.requests(),
+            total_errors: counters.errors(),
+            total_notifications: counters.notifications(),
+            peak_sessions: counters.peak_sessions(),
+            uptime: self
+                .started_at
+                .read()
+                .await
+                .map(|started_at| started_at.elapsed())
+                .unwrap_or_default(),
         }
     }
 }
@@ -231,7 +517,42 @@ impl McpServer {
 #[derive(Debug, Clone)]
 pub struct ServerStats {
     pub running: bool,
+    pub health: ServerHealth,
     pub transport_count: usize,
+    pub total_requests: u64,
+    pub total_errors: u64,
+    pub total_notifications: u64,
+    pub peak_sessions: u64,
+    /// Time since the server was last started (zero if it never was)
+    pub uptime: Duration,
+}
+
+/// Build the audit sink described by the logging config
+///
+/// Falls back to the tracing target if the audit file can't be opened, so
+/// records are never silently dropped.
+fn audit_sink(config: &LoggingConfig) -> Arc<dyn AuditSink> {
+    if let Some(path) = &config.audit_file {
+        match FileAuditSink::open(path) {
+            Ok(sink) => return Arc::new(sink),
+            Err(e) => error!(
+                "Failed to open audit log {}: {}; auditing to the log instead",
+                path.display(),
+                e
+            ),
+        }
+    }
+    Arc::new(TracingAuditSink)
+}
+
+/// Server health
+#[derive(Debug, Clone)]
+pub enum ServerHealth {
+    /// Running normally, or stopped on request
+    Healthy,
+
+    /// The message channel closed because these transports stopped unexpectedly
+    TransportFailed(Vec<TransportInfo>),
 }
 
 /// Server builder for easier configuration
@@ -295,6 +616,26 @@ impl Default for McpServerBuilder {
     }
 }
 
+/// Handle one incoming message, logging the outcome
+async fn handle_message(
+    handler: &ProtocolHandler,
+    message: AnyJsonRpcMessage,
+    context: &RequestContext,
+) {
+    match handler.handle_message_with_context(message, context).await {
+        Ok(Some(response)) => {
+            // Send response back through transport
+            info!("Generated response: {:?}", response);
+        }
+        Ok(None) => {
+            // No response needed (e.g., for notifications)
+        }
+        Err(e) => {
+            error!("Error handling message: {}", e);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,4 +667,446 @@ mod tests {
         let server = server.unwrap();
         assert!(!server.is_running().await);
     }
+
+    #[tokio::test]
+    async fn test_shutdown_returns_final_stats() {
+        let server = McpServer::new(Config::default()).unwrap();
+        let handler = server.protocol_handler.clone();
+
+        let initialize = crate::protocol::JsonRpcRequest::new(
+            serde_json::json!(1),
+            "initialize".to_string(),
+            Some(serde_json::to_value(
+                crate::protocol::InitializeRequestBuilder::new("test-client", "1.0").build(),
+            )
+            .unwrap()),
+        );
+        for session_id in ["session-a", "session-b"] {
+            let context = crate::protocol::handler::RequestContext::with_session(session_id.to_string());
+            handler
+                .handle_request_with_context(initialize.clone(), &context)
+                .await
+                .unwrap();
+        }
+        handler.handle_session_closed("session-a").await;
+
+        let stats = server.shutdown().await.unwrap();
+        assert!(!stats.running);
+        assert_eq!(stats.total_requests, 2);
+        assert_eq!(stats.total_errors, 0);
+        assert_eq!(stats.peak_sessions, 2);
+        assert_eq!(stats.uptime, Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_requests_errors_and_notifications() {
+        let server = McpServer::new(Config::default()).unwrap();
+        let handler = server.protocol_handler.clone();
+
+        let request = |id: i64, method: &str| {
+            crate::protocol::AnyJsonRpcMessage::Request(crate::protocol::JsonRpcRequest {
+                jsonrpc: "2.0".to_string(),
+                id: serde_json::json!(id),
+                method: method.to_string(),
+                params: None,
+            })
+        };
+        for id in 0..3 {
+            handler.handle_message(request(id, "ping")).await.unwrap();
+        }
+        assert!(handler.handle_message(request(3, "no/such/method")).await.is_err());
+        handler
+            .handle_message(crate::protocol::AnyJsonRpcMessage::Notification(
+                crate::protocol::JsonRpcNotification::new(
+                    "notifications/initialized".to_string(),
+                    None,
+                ),
+            ))
+            .await
+            .unwrap();
+
+        let stats = server.get_stats().await;
+        assert_eq!(stats.total_requests, 4);
+        assert_eq!(stats.total_errors, 1);
+        assert_eq!(stats.total_notifications, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stdio_eof_shuts_down_server() {
+        let transport = crate::transport::stdio::StdioTransport::new(Default::default())
+            .unwrap()
+            .with_input(&b""[..]);
+        let mut server = McpServer::with_transport(Config::default(), Arc::new(transport)).unwrap();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), server.start()).await;
+        assert!(result.expect("server did not shut down on EOF").is_ok());
+        assert!(!server.is_running().await);
+        assert!(matches!(server.health(), ServerHealth::Healthy));
+    }
+
+    /// Transport that records shutdown notices and stops
+    struct RecordingTransport {
+        events: Arc<std::sync::Mutex<Vec<String>>>,
+        input: std::sync::Mutex<Option<tokio::sync::mpsc::Sender<crate::transport::TransportMessage>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for RecordingTransport {
+        async fn start(
+            &self,
+        ) -> Result<(
+            tokio::sync::mpsc::Receiver<crate::transport::TransportMessage>,
+            tokio::sync::mpsc::Sender<crate::transport::TransportMessage>,
+        )> {
+            let (message_tx, message_rx) = tokio::sync::mpsc::channel(1);
+            let (response_tx, _) = tokio::sync::mpsc::channel(1);
+            // Keep the input open so only the shutdown signal ends the loop
+            *self.input.lock().unwrap() = Some(message_tx);
+            Ok((message_rx, response_tx))
+        }
+
+        async fn stop(&self) -> Result<()> {
+            self.events.lock().unwrap().push("stop".to_string());
+            Ok(())
+        }
+
+        async fn send_notification(
+            &self,
+            notification: JsonRpcNotification,
+            _target: crate::transport::NotificationTarget,
+        ) -> Result<usize> {
+            self.events.lock().unwrap().push(format!(
+                "{} {}",
+                notification.method,
+                notification.params.unwrap()["gracePeriodMs"]
+            ));
+            Ok(1)
+        }
+
+        fn info(&self) -> crate::transport::TransportInfo {
+            crate::transport::TransportInfo {
+                transport_type: crate::transport::TransportType::Stdio,
+                address: "recording".to_string(),
+                secure: false,
+                max_message_size: None,
+            }
+        }
+    }
+
+    /// Transport whose message channel is closed by dropping its sender
+    struct DroppingTransport;
+
+    #[async_trait::async_trait]
+    impl Transport for DroppingTransport {
+        async fn start(
+            &self,
+        ) -> Result<(
+            tokio::sync::mpsc::Receiver<crate::transport::TransportMessage>,
+            tokio::sync::mpsc::Sender<crate::transport::TransportMessage>,
+        )> {
+            let (_, message_rx) = tokio::sync::mpsc::channel(1);
+            let (response_tx, _) = tokio::sync::mpsc::channel(1);
+            Ok((message_rx, response_tx))
+        }
+
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_notification(
+            &self,
+            _notification: JsonRpcNotification,
+            _target: crate::transport::NotificationTarget,
+        ) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn info(&self) -> TransportInfo {
+            TransportInfo {
+                transport_type: crate::transport::TransportType::Http,
+                address: "dropping".to_string(),
+                secure: false,
+                max_message_size: None,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unexpected_channel_closure_is_reported() {
+        let mut server =
+            McpServer::with_transport(Config::default(), Arc::new(DroppingTransport)).unwrap();
+
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), server.start())
+            .await
+            .expect("server did not stop on channel closure");
+        assert!(matches!(
+            result,
+            Err(crate::error::McpError::Transport(TransportError::ConnectionLost(_)))
+        ));
+
+        let stats = server.get_stats().await;
+        assert!(!stats.running);
+        match stats.health {
+            ServerHealth::TransportFailed(failed) => {
+                assert_eq!(failed.len(), 1);
+                assert_eq!(failed[0].address, "dropping");
+            }
+            ServerHealth::Healthy => panic!("closure was treated as a normal stop"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_notifies_clients_before_stopping() {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transport = RecordingTransport {
+            events: events.clone(),
+            input: std::sync::Mutex::new(None),
+        };
+        let mut config = Config::default();
+        config.server.shutdown_grace_period = 0;
+        let mut server = McpServer::with_transport(config, Arc::new(transport)).unwrap();
+        let shutdown_signal = server.shutdown_signal.clone();
+
+        let handle = tokio::spawn(async move {
+            server.start().await.unwrap();
+            server
+        });
+        shutdown_signal.notify_one();
+        let server = tokio::time::timeout(std::time::Duration::from_secs(5), handle)
+            .await
+            .expect("server did not shut down")
+            .unwrap();
+
+        assert!(!server.is_running().await);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![format!("{} 0", SHUTDOWN_NOTIFICATION), "stop".to_string()]
+        );
+    }
+
+    /// Transport whose client answers every server-initiated request
+    struct LoopbackTransport {
+        input: std::sync::Mutex<Option<tokio::sync::mpsc::Sender<crate::transport::TransportMessage>>>,
+        /// Whether a client is connected to receive requests
+        connected: bool,
+    }
+
+    impl LoopbackTransport {
+        fn new(connected: bool) -> Self {
+            Self {
+                input: std::sync::Mutex::new(None),
+                connected,
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for LoopbackTransport {
+        async fn start(
+            &self,
+        ) -> Result<(
+            tokio::sync::mpsc::Receiver<crate::transport::TransportMessage>,
+            tokio::sync::mpsc::Sender<crate::transport::TransportMessage>,
+        )> {
+            let (message_tx, message_rx) = tokio::sync::mpsc::channel(8);
+            let (response_tx, _) = tokio::sync::mpsc::channel(1);
+            *self.input.lock().unwrap() = Some(message_tx);
+            Ok((message_rx, response_tx))
+        }
+
+        async fn stop(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_notification(
+            &self,
+            _notification: JsonRpcNotification,
+            _target: NotificationTarget,
+        ) -> Result<usize> {
+            Ok(0)
+        }
+
+        async fn send_request(
+            &self,
+            request: JsonRpcRequest,
+            _target: NotificationTarget,
+        ) -> Result<usize> {
+            if !self.connected {
+                return Ok(0);
+            }
+            let input = self.input.lock().unwrap().clone().unwrap();
+            let response = crate::protocol::JsonRpcResponse::success(
+                request.id,
+                serde_json::json!({ "echo": request.method }),
+            );
+            input
+                .send(crate::transport::TransportMessage {
+                    message: crate::protocol::AnyJsonRpcMessage::Response(response),
+                    session_id: None,
+                    client_id: None,
+                    metadata: crate::transport::TransportMetadata::default(),
+                })
+                .await
+                .unwrap();
+            Ok(1)
+        }
+
+        fn info(&self) -> TransportInfo {
+            TransportInfo {
+                transport_type: crate::transport::TransportType::Stdio,
+                address: "loopback".to_string(),
+                secure: false,
+                max_message_size: None,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_server_initiated_request_answered_through_transport() {
+        let transport = LoopbackTransport::new(true);
+        let mut config = Config::default();
+        config.server.shutdown_grace_period = 0;
+        let mut server = McpServer::with_transport(config, Arc::new(transport)).unwrap();
+        let handler = server.protocol_handler.clone();
+        let shutdown_signal = server.shutdown_signal.clone();
+        let running = tokio::spawn(async move { server.start().await });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            handler.send_request("roots/list", None),
+        )
+        .await
+        .expect("request was not answered")
+        .unwrap();
+        assert_eq!(result, serde_json::json!({ "echo": "roots/list" }));
+        assert!(handler.outgoing_requests().is_empty());
+
+        shutdown_signal.notify_one();
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_handler_awaits_client_response_to_its_own_request() {
+        let transport = Arc::new(LoopbackTransport::new(true));
+        let mut config = Config::default();
+        config.server.shutdown_grace_period = 0;
+        let mut server = McpServer::with_transport(config, transport.clone()).unwrap();
+        let handler = server.protocol_handler.clone();
+        let shutdown_signal = server.shutdown_signal.clone();
+
+        // The method asks the client for its roots before answering
+        let this = Arc::new(std::sync::OnceLock::<Arc<ProtocolHandler>>::new());
+        this.set(handler.clone()).ok();
+        let (result_tx, mut result_rx) = tokio::sync::mpsc::unbounded_channel();
+        handler
+            .method_registry()
+            .register("test/askRoots", move |_params, context| {
+                let this = this.clone();
+                let result_tx = result_tx.clone();
+                async move {
+                    let handler = this.get().unwrap();
+                    let result = handler
+                        .send_request_with_context("roots/list", None, &context)
+                        .await;
+                    result_tx.send(result.is_ok()).unwrap();
+                    result
+                }
+            })
+            .unwrap();
+
+        let params = serde_json::json!({
+            "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "test", "version": "1.0"}
+        });
+        handler
+            .handle_request(JsonRpcRequest::new(
+                serde_json::json!(1),
+                "initialize".to_string(),
+                Some(params),
+            ))
+            .await
+            .unwrap();
+
+        let running = tokio::spawn(async move { server.start().await });
+        let input = loop {
+            if let Some(input) = transport.input.lock().unwrap().clone() {
+                break input;
+            }
+            tokio::task::yield_now().await;
+        };
+        input
+            .send(crate::transport::TransportMessage {
+                message: AnyJsonRpcMessage::Request(JsonRpcRequest::new(
+                    serde_json::json!(2),
+                    "test/askRoots".to_string(),
+                    None,
+                )),
+                session_id: None,
+                client_id: None,
+                metadata: crate::transport::TransportMetadata::default(),
+            })
+            .await
+            .unwrap();
+
+        let answered = tokio::time::timeout(Duration::from_secs(5), result_rx.recv())
+            .await
+            .expect("handler deadlocked waiting for the client")
+            .unwrap();
+        assert!(answered);
+
+        shutdown_signal.notify_one();
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_reaching_no_client_fails_immediately() {
+        let mut config = Config::default();
+        config.server.shutdown_grace_period = 0;
+        let mut server =
+            McpServer::with_transport(config, Arc::new(LoopbackTransport::new(false))).unwrap();
+        let handler = server.protocol_handler.clone();
+        let shutdown_signal = server.shutdown_signal.clone();
+        let running = tokio::spawn(async move { server.start().await });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            handler.send_request("roots/list", None),
+        )
+        .await
+        .expect("caller waited for a request nobody received");
+        assert!(result.is_err());
+        assert!(handler.outgoing_requests().is_empty());
+
+        shutdown_signal.notify_one();
+        running.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_id_format_configured_per_server() {
+        let mut config = Config::default();
+        config.server.id_format = crate::utils::ids::IdFormat::Base62;
+        config.server.id_prefix = "req_".to_string();
+        let prefixed =
+            McpServer::with_transport(config, Arc::new(LoopbackTransport::new(true))).unwrap();
+        let plain = McpServer::with_transport(
+            Config::default(),
+            Arc::new(LoopbackTransport::new(true)),
+        )
+        .unwrap();
+
+        let (request, _) = prefixed
+            .protocol_handler
+            .begin_outgoing_request("roots/list", None)
+            .unwrap();
+        let id = request.id.as_str().unwrap();
+        assert_eq!(id.len(), 4 + crate::utils::ids::BASE62_ID_LENGTH);
+        assert!(id.starts_with("req_"));
+
+        let (request, _) = plain
+            .protocol_handler
+            .begin_outgoing_request("roots/list", None)
+            .unwrap();
+        assert!(uuid::Uuid::parse_str(request.id.as_str().unwrap()).is_ok());
+    }
 }
@@ -0,0 +1,268 @@
+//! Self-test of the built-in features.
+//!
+//! Exercises each enabled feature end to end against fresh managers: the
+//! configured tool handlers are registered and `calculator` adds two numbers
+//! (skipped when it isn't enabled), a temporary file is read through the file
+//! system provider, and the `greeting` prompt is rendered. Used by
+//! `mcp-server selftest` and by the optional startup check.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::{McpError, Result};
+use crate::protocol::{Content, Prompt, PromptArgument, ResourceContents};
+use crate::server::features::prompts::GreetingPromptGenerator;
+use crate::server::features::resources::FileSystemProvider;
+use crate::server::features::tools::{ToolHandler, ToolHandlerDiscovery};
+use crate::server::features::{PromptManager, ResourceManager, ToolManager};
+
+/// Outcome of checking one feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckOutcome {
+    /// The feature works; carries what was observed
+    Passed(String),
+
+    /// The feature failed; carries the reason
+    Failed(String),
+
+    /// The feature wasn't checked; carries why, e.g. that it is disabled
+    Skipped(String),
+}
+
+/// Result of checking one feature
+#[derive(Debug, Clone)]
+pub struct SelfTestCheck {
+    /// Feature name (`tools`, `resources` or `prompts`)
+    pub feature: &'static str,
+
+    /// What happened
+    pub outcome: CheckOutcome,
+}
+
+/// Results of a self-test run
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    /// One entry per feature, in the order they were checked
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Check whether no feature failed
+    pub fn passed(&self) -> bool {
+        self.failures() == 0
+    }
+
+    /// Number of features that failed
+    pub fn failures(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|check| matches!(check.outcome, CheckOutcome::Failed(_)))
+            .count()
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                CheckOutcome::Passed(detail) => writeln!(f, "PASS {}: {}", check.feature, detail)?,
+                CheckOutcome::Failed(reason) => writeln!(f, "FAIL {}: {}", check.feature, reason)?,
+                CheckOutcome::Skipped(reason) => writeln!(f, "SKIP {}: {}", check.feature, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run the self-test against the given configuration
+pub async fn run_self_test(config: &Config) -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+
+    let features = &config.features;
+    report.checks.push(SelfTestCheck {
+        feature: "tools",
+        outcome: if features.tools {
+            check_tools(config).await
+        } else {
+            disabled()
+        },
+    });
+    report.checks.push(SelfTestCheck {
+        feature: "resources",
+        outcome: if features.resources {
+            outcome(check_resources(config).await)
+        } else {
+            disabled()
+        },
+    });
+    report.checks.push(SelfTestCheck {
+        feature: "prompts",
+        outcome: if features.prompts {
+            outcome(check_prompts().await)
+        } else {
+            disabled()
+        },
+    });
+
+    report
+}
+
+fn disabled() -> CheckOutcome {
+    CheckOutcome::Skipped("disabled".to_string())
+}
+
+fn outcome(result: Result<String>) -> CheckOutcome {
+    match result {
+        Ok(detail) => CheckOutcome::Passed(detail),
+        Err(e) => CheckOutcome::Failed(e.to_string()),
+    }
+}
+
+/// First text of a list of content items
+fn first_text(content: &[Content]) -> Option<&str> {
+    content.iter().find_map(|content| match content {
+        Content::Text { text, .. } => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+/// Register the configured tool handlers and add two numbers with `calculator`
+///
+/// Skipped when the configuration doesn't enable `calculator`.
+async fn check_tools(config: &Config) -> CheckOutcome {
+    let handlers = match ToolHandlerDiscovery::discover_handlers(Some(&config.tools)) {
+        Ok(handlers) => handlers,
+        Err(e) => return CheckOutcome::Failed(e.to_string()),
+    };
+    if !handlers.iter().any(|handler| handler.name() == "calculator") {
+        return CheckOutcome::Skipped("calculator tool is not enabled".to_string());
+    }
+    outcome(call_calculator(handlers).await)
+}
+
+async fn call_calculator(handlers: Vec<Box<dyn ToolHandler>>) -> Result<String> {
+    let manager = ToolManager::new();
+    for handler in handlers {
+        manager.register_handler_with_tool(handler).await?;
+    }
+
+    let arguments = serde_json::json!({ "operation": "add", "a": 2, "b": 3 });
+    let result = manager.call_tool("calculator", Some(arguments)).await?;
+    let text = first_text(&result.content).unwrap_or_default();
+    if result.is_error || !text.ends_with("= 5") {
+        return Err(McpError::Tool(format!("calculator returned '{}'", text)));
+    }
+
+    Ok(format!("calculator answered '{}'", text))
+}
+
+/// Read a temporary file through the file system provider
+async fn check_resources(config: &Config) -> Result<String> {
+    let dir = std::env::temp_dir().join(format!("mcp-selftest-{}", uuid::Uuid::new_v4()));
+    let result = read_temp_resource(config, dir.clone()).await;
+    let _ = std::fs::remove_dir_all(&dir);
+    result
+}
+
+async fn read_temp_resource(config: &Config, dir: PathBuf) -> Result<String> {
+    const CONTENT: &str = "mcp-server self-test";
+
+    std::fs::create_dir_all(&dir)?;
+    let file = dir.join("selftest.txt");
+    std::fs::write(&file, CONTENT)?;
+
+    let manager = ResourceManager::with_config(config.resources.clone());
+    manager
+        .register_provider(Box::new(FileSystemProvider::new(dir)))
+        .await?;
+
+    let uri = crate::utils::path_to_file_uri(&file.canonicalize()?)
+        .map_err(|e| McpError::Resource(e.to_string()))?;
+    let contents = manager.read_resource(&uri, None).await?;
+    match contents.first() {
+        Some(ResourceContents::Text { text, .. }) if text == CONTENT => {
+            Ok(format!("read {} bytes from {}", text.len(), uri))
+        }
+        other => Err(McpError::Resource(format!(
+            "unexpected contents of {}: {:?}",
+            uri, other
+        ))),
+    }
+}
+
+/// Render the `greeting` prompt
+async fn check_prompts() -> Result<String> {
+    let manager = PromptManager::new();
+    manager
+        .register_prompt(Prompt {
+            name: "greeting".to_string(),
+            description: Some("A friendly greeting".to_string()),
+            arguments: Some(vec![PromptArgument {
+                name: "name".to_string(),
+                description: Some("Who to greet".to_string()),
+                required: Some(false),
+            }]),
+        })
+        .await?;
+    manager
+        .register_generator(Box::new(GreetingPromptGenerator))
+        .await?;
+
+    let arguments = HashMap::from([("name".to_string(), "self-test".to_string())]);
+    let result = manager
+        .get_prompt_with_args("greeting", Some(arguments))
+        .await?;
+    let text = result
+        .messages
+        .iter()
+        .find_map(|message| match &message.content {
+            Content::Text { text, .. } => Some(text.clone()),
+            _ => None,
+        })
+        .unwrap_or_default();
+    if !text.contains("self-test") {
+        return Err(McpError::Prompt(format!("greeting rendered '{}'", text)));
+    }
+
+    Ok(format!("greeting rendered '{}'", text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_self_test_passes_on_default_config() {
+        let report = run_self_test(&Config::default()).await;
+        assert!(report.passed(), "self-test failed:\n{}", report);
+        assert_eq!(report.checks.len(), 3);
+        assert!(report
+            .checks
+            .iter()
+            .all(|check| matches!(check.outcome, CheckOutcome::Passed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_features_are_skipped() {
+        let mut config = Config::default();
+        config.features.prompts = false;
+
+        let report = run_self_test(&config).await;
+        assert!(report.passed());
+        assert_eq!(report.checks[2].outcome, disabled());
+        assert!(report.to_string().contains("SKIP prompts: disabled"));
+    }
+
+    #[tokio::test]
+    async fn test_tools_skipped_without_calculator() {
+        let mut config = Config::default();
+        config.tools.enable_all_by_default = false;
+
+        let report = run_self_test(&config).await;
+        assert!(report.passed(), "self-test failed:\n{}", report);
+        assert!(matches!(report.checks[0].outcome, CheckOutcome::Skipped(_)));
+        assert!(report.to_string().contains("SKIP tools: calculator tool is not enabled"));
+    }
+}
@@ -9,16 +9,30 @@ use async_trait::async_trait;
 use futures_util;
 use serde_json;
 
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::{mpsc, oneshot, RwLock};
-use tracing::{error, info, warn};
-use uuid::Uuid;
+use tracing::{debug, error, info, warn};
 
-use crate::config::HttpConfig;
-use crate::error::Result;
-use crate::protocol::parse_message;
-use crate::transport::session::{Session, SessionManager};
-use crate::transport::{Transport, TransportInfo, TransportMessage, TransportType};
+use crate::config::{normalize_endpoint_path, HttpConfig};
+use crate::error::{McpError, Result, TransportError};
+use crate::protocol::handler::{requests_streamed_read, RequestContext, ResourceStream};
+use crate::protocol::{check_json_depth, parse_message_with_max_depth, JsonRpcNotification};
+use crate::transport::keepalive::PingTracker;
+use crate::transport::notifications::{NotificationHub, PublishedNotification};
+use crate::transport::pending::PendingResponses;
+use crate::server::features::resources::RESOURCE_CHUNK_NOTIFICATION;
+use crate::transport::replay::{ReplayBuffers, ReplayError};
+use crate::transport::session::SessionManager;
+use crate::utils::ids::SharedIdGenerator;
+use crate::transport::{
+    NotificationTarget, Transport, TransportInfo, TransportMessage, TransportMetadata,
+    TransportType,
+};
 
 use std::sync::OnceLock;
 
@@ -30,17 +44,12 @@ static GLOBAL_PROTOCOL_HANDLER: OnceLock<Arc<crate::protocol::handler::ProtocolH
 pub fn init_global_protocol_handler() -> Arc<crate::protocol::handler::ProtocolHandler> {
     GLOBAL_PROTOCOL_HANDLER
         .get_or_init(|| {
-            let resource_manager =
-                Arc::new(crate::server::features::resources::ResourceManager::new());
-            let tool_manager = Arc::new(crate::server::features::tools::ToolManager::new());
-            let prompt_manager = Arc::new(crate::server::features::prompts::PromptManager::new());
+            let feature_manager = Arc::new(crate::server::features::ServerFeatureManager::new());
             let sampling_manager =
                 Arc::new(crate::client::features::sampling::SamplingManager::new());
 
-            Arc::new(crate::protocol::handler::ProtocolHandler::new(
-                resource_manager,
-                tool_manager,
-                prompt_manager,
+            Arc::new(crate::protocol::handler::ProtocolHandler::from_feature_manager(
+                feature_manager,
                 sampling_manager,
             ))
         })
@@ -58,6 +67,14 @@ pub struct HttpTransport {
     session_manager: Arc<SessionManager>,
     message_sender: Arc<RwLock<Option<mpsc::Sender<TransportMessage>>>>,
     shutdown_sender: Arc<RwLock<Option<oneshot::Sender<()>>>>,
+    protocol_handler: Option<Arc<crate::protocol::handler::ProtocolHandler>>,
+    notification_hub: Arc<NotificationHub>,
+    pending_responses: Arc<PendingResponses>,
+    pings: Arc<PingTracker>,
+    replay: Arc<ReplayBuffers>,
+    key_scopes: Arc<HashMap<String, Vec<String>>>,
+    api_keys: Option<Arc<Vec<String>>>,
+    local_addr: Arc<RwLock<Option<SocketAddr>>>,
 }
 
 /// Shared application state
@@ -67,23 +84,102 @@ struct AppState {
     message_sender: Arc<RwLock<Option<mpsc::Sender<TransportMessage>>>>,
     config: HttpConfig,
     protocol_handler: Arc<crate::protocol::handler::ProtocolHandler>,
+    notification_hub: Arc<NotificationHub>,
+    pings: Arc<PingTracker>,
+    replay: Arc<ReplayBuffers>,
+    key_scopes: Arc<HashMap<String, Vec<String>>>,
+
+    /// Keys one of which every request must present, when authentication is on
+    api_keys: Option<Arc<Vec<String>>>,
 }
 
 impl HttpTransport {
     /// Create a new HTTP transport
     pub fn new(config: HttpConfig) -> Result<Self> {
-        let session_manager = Arc::new(SessionManager::new(std::time::Duration::from_secs(
-            config.session_timeout,
-        )));
+        Self::with_id_generator(config, crate::utils::ids::id_generator())
+    }
+
+    /// Create a new HTTP transport generating session IDs with `id_generator`
+    pub fn with_id_generator(
+        mut config: HttpConfig,
+        id_generator: SharedIdGenerator,
+    ) -> Result<Self> {
+        config.endpoint_path = normalize_endpoint_path(&config.endpoint_path)?;
+        let session_manager = Arc::new(
+            SessionManager::new(std::time::Duration::from_secs(config.session_timeout))
+                .with_max_sessions(config.max_sessions)
+                .with_id_generator(id_generator),
+        );
+
+        let replay = Arc::new(ReplayBuffers::new(
+            config.sse_replay_max_events,
+            config.sse_replay_max_bytes,
+        ));
 
         Ok(Self {
             config,
             session_manager,
             message_sender: Arc::new(RwLock::new(None)),
             shutdown_sender: Arc::new(RwLock::new(None)),
+            protocol_handler: None,
+            notification_hub: Arc::new(NotificationHub::new().with_replay(replay.clone())),
+            pending_responses: Arc::new(PendingResponses::new()),
+            pings: Arc::new(PingTracker::new()),
+            replay,
+            key_scopes: Arc::new(HashMap::new()),
+            api_keys: None,
+            local_addr: Arc::new(RwLock::new(None)),
         })
     }
 
+    /// Create a transport on an ephemeral localhost port dispatching to `protocol_handler`
+    ///
+    /// Useful for tests; the chosen port is available from [`Self::local_addr`] once started.
+    pub fn ephemeral(protocol_handler: Arc<crate::protocol::handler::ProtocolHandler>) -> Result<Self> {
+        let config = HttpConfig {
+            bind_address: "127.0.0.1".to_string(),
+            port: 0,
+            ..HttpConfig::default()
+        };
+        Ok(Self::new(config)?.with_protocol_handler(protocol_handler))
+    }
+
+    /// Get the address the server is bound to, once started
+    pub async fn local_addr(&self) -> Option<SocketAddr> {
+        *self.local_addr.read().await
+    }
+
+    /// Get the registry correlating outbound responses with waiting requests
+    pub fn pending_responses(&self) -> Arc<PendingResponses> {
+        self.pending_responses.clone()
+    }
+
+    /// Get the hub used to push notifications to connected SSE streams
+    pub fn notification_hub(&self) -> Arc<NotificationHub> {
+        self.notification_hub.clone()
+    }
+
+    /// Use the given protocol handler instead of the global one
+    pub fn with_protocol_handler(
+        mut self,
+        protocol_handler: Arc<crate::protocol::handler::ProtocolHandler>,
+    ) -> Self {
+        self.protocol_handler = Some(protocol_handler);
+        self
+    }
+
+    /// Grant requests presenting one of these API keys the mapped scopes
+    pub fn with_key_scopes(mut self, key_scopes: HashMap<String, Vec<String>>) -> Self {
+        self.key_scopes = Arc::new(key_scopes);
+        self
+    }
+
+    /// Reject requests that don't present one of these API keys with a 401
+    pub fn with_required_api_keys(mut self, api_keys: Vec<String>) -> Self {
+        self.api_keys = Some(Arc::new(api_keys));
+        self
+    }
+
     /// Create the Actix Web application
     fn create_app(
         state: AppState,
@@ -96,14 +192,22 @@ impl HttpTransport {
             InitError = (),
         >,
     > {
-        let app = App::new().app_data(web::Data::new(state.clone())).service(
+        let mut app = App::new().app_data(web::Data::new(state.clone())).service(
             web::resource(&state.config.endpoint_path)
                 .route(web::post().to(handle_streamable_http_post))
                 .route(web::get().to(handle_streamable_http_get))
-                .route(web::delete().to(handle_delete_request)),
+                .route(web::delete().to(handle_delete_request))
+                .default_service(web::to(handle_method_not_allowed)),
         );
 
-        app
+        // Answer browser/proxy probes quietly instead of falling through to error logs
+        for path in &state.config.probe_paths {
+            if path != &state.config.endpoint_path {
+                app = app.service(web::resource(path).to(handle_probe_request));
+            }
+        }
+
+        app.default_service(web::to(handle_not_found))
     }
 }
 
@@ -116,7 +220,7 @@ impl Transport for HttpTransport {
         mpsc::Sender<TransportMessage>,
     )> {
         let (message_tx, message_rx) = mpsc::channel(1000);
-        let (response_tx, response_rx) = mpsc::channel(1000);
+        let (response_tx, response_rx) = mpsc::channel::<TransportMessage>(1000);
 
         // Store the message sender
         {
@@ -124,11 +228,42 @@ impl Transport for HttpTransport {
             *sender = Some(message_tx.clone());
         }
 
+        // Deliver responses sent back through the transport to the requests awaiting them
+        let pending_responses = self.pending_responses.clone();
+        tokio::spawn(async move {
+            let mut response_rx = response_rx;
+            while let Some(message) = response_rx.recv().await {
+                match (message.message, message.session_id) {
+                    (crate::protocol::AnyJsonRpcMessage::Response(response), Some(session_id)) => {
+                        pending_responses.complete(&session_id, response);
+                    }
+                    (other, _) => debug!("Ignoring outbound message with no pending request: {:?}", other),
+                }
+            }
+        });
+
+        let protocol_handler = self
+            .protocol_handler
+            .clone()
+            .unwrap_or_else(init_global_protocol_handler);
+        spawn_session_cleanup(
+            &self.session_manager,
+            protocol_handler.clone(),
+            self.notification_hub.clone(),
+            self.pings.clone(),
+            self.replay.clone(),
+        );
+
         let state = AppState {
             session_manager: self.session_manager.clone(),
             message_sender: self.message_sender.clone(),
             config: self.config.clone(),
-            protocol_handler: init_global_protocol_handler(),
+            protocol_handler,
+            notification_hub: self.notification_hub.clone(),
+            pings: self.pings.clone(),
+            replay: self.replay.clone(),
+            key_scopes: self.key_scopes.clone(),
+            api_keys: self.api_keys.clone(),
         };
 
         let bind_addr = format!("{}:{}", self.config.bind_address, self.config.port);
@@ -150,14 +285,21 @@ impl Transport for HttpTransport {
         // Clone the bind address for the spawned task
         let bind_addr_clone = bind_addr.clone();
 
+        // Reports whether binding succeeded so start() can fail instead of the task
+        let (bound_tx, bound_rx) = oneshot::channel();
+
         // Start the server in a separate task to avoid Send issues
         tokio::spawn(async move {
             let server = match HttpServer::new(move || Self::create_app(state.clone()))
                 .bind(&bind_addr_clone)
             {
-                Ok(server) => server,
+                Ok(server) => {
+                    let _ = bound_tx.send(Ok(server.addrs().first().copied()));
+                    server
+                }
                 Err(e) => {
                     error!("Failed to bind to {}: {}", bind_addr_clone, e);
+                    let _ = bound_tx.send(Err(e.to_string()));
                     return;
                 }
             };
@@ -175,6 +317,22 @@ impl Transport for HttpTransport {
             }
         });
 
+        let bind_result = bound_rx
+            .await
+            .unwrap_or_else(|_| Err("HTTP server task exited before binding".to_string()));
+        match bind_result {
+            Ok(local_addr) => *self.local_addr.write().await = local_addr,
+            Err(e) => {
+                self.shutdown_sender.write().await.take();
+                self.message_sender.write().await.take();
+                return Err(TransportError::ConnectionFailed(format!(
+                    "Failed to bind to {}: {}",
+                    bind_addr, e
+                ))
+                .into());
+            }
+        }
+
         Ok((message_rx, response_tx))
     }
 
@@ -194,11 +352,26 @@ impl Transport for HttpTransport {
             let mut message_sender = self.message_sender.write().await;
             *message_sender = None;
         }
+        self.local_addr.write().await.take();
 
         info!("HTTP transport stopped");
         Ok(())
     }
 
+    async fn send_notification(
+        &self,
+        notification: JsonRpcNotification,
+        target: NotificationTarget,
+    ) -> Result<usize> {
+        let delivered = match target {
+            NotificationTarget::Broadcast => self.notification_hub.broadcast(notification).await,
+            NotificationTarget::Session(session_id) => {
+                self.notification_hub.send(&session_id, notification).await
+            }
+        };
+        Ok(delivered)
+    }
+
     fn info(&self) -> TransportInfo {
         TransportInfo {
             transport_type: TransportType::Http,
@@ -207,7 +380,7 @@ impl Transport for HttpTransport {
                 self.config.bind_address, self.config.port, self.config.endpoint_path
             ),
             secure: self.config.enable_tls,
-            max_message_size: Some(1024 * 1024), // 1MB default
+            max_message_size: Some(self.config.max_body_size),
         }
     }
 }
@@ -216,7 +389,7 @@ impl Transport for HttpTransport {
 /// Supports both single JSON responses and SSE streaming based on request content
 async fn handle_streamable_http_post(
     req: HttpRequest,
-    body: web::Bytes,
+    payload: web::Payload,
     state: web::Data<AppState>,
 ) -> ActixResult<HttpResponse> {
     info!("Handling Streamable HTTP POST request");
@@ -233,28 +406,61 @@ async fn handle_streamable_http_post(
         }
     }
 
-    // Validate Accept header - must support both application/json and text/event-stream
-    let accept_header = req
-        .headers()
-        .get("Accept")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("");
+    let principal = match authenticate(&req, &state) {
+        Ok(principal) => principal,
+        Err(e) => {
+            warn!("Rejected request: {}", e);
+            return Ok(error_response(&e));
+        }
+    };
 
-    if !accept_header.contains("application/json") || !accept_header.contains("text/event-stream") {
-        warn!("Invalid Accept header: {}", accept_header);
-        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Accept header must include both application/json and text/event-stream"
+    // Validate Accept header - JSON is required, SSE is only needed for streamed responses
+    let accepted = AcceptedFormats::from_request(&req);
+    if !accepted.json {
+        warn!("Client does not accept application/json responses");
+        return Ok(HttpResponse::NotAcceptable().json(serde_json::json!({
+            "error": "Accept header must include application/json"
         })));
     }
 
-    // Get or create session
-    let session_id = get_or_create_session(&req, &state.session_manager).await?;
+    if state.config.require_json_content_type && !has_json_content_type(&req) {
+        let content_type = req
+            .headers()
+            .get("Content-Type")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("none");
+        warn!("Rejected request with Content-Type {}", content_type);
+        return Ok(HttpResponse::UnsupportedMediaType().json(json_rpc_error_body(
+            -32700,
+            &format!(
+                "Unsupported Content-Type: {}, expected application/json",
+                content_type
+            ),
+        )));
+    }
+
+    let body = match payload.to_bytes_limited(state.config.max_body_size).await {
+        Ok(body) => body?,
+        Err(_) => {
+            let e = McpError::payload_too_large(state.config.max_body_size);
+            warn!("Rejected request: {}", e);
+            return Ok(error_response(&e));
+        }
+    };
+
+    // Get or create session, unless the request is handled statelessly
+    let session_id = if state.config.stateless && get_session_id(&req).is_none() {
+        debug!("Handling request without a session");
+        None
+    } else {
+        Some(get_or_create_session(&req, &state.session_manager).await?)
+    };
 
     // Parse the request body
     let body_str = String::from_utf8_lossy(&body);
 
     // Try to parse as single message or batch
-    let messages = match parse_message_or_batch(&body_str) {
+    let messages = match parse_message_or_batch(&body_str, state.config.max_json_depth) {
         Ok(msgs) => msgs,
         Err(e) => {
             error!("Failed to parse JSON-RPC message(s): {}", e);
@@ -274,6 +480,17 @@ async fn handle_streamable_http_post(
         .iter()
         .any(|msg| matches!(msg, crate::protocol::AnyJsonRpcMessage::Request(_)));
 
+    // Responses answer server-initiated requests such as keepalive pings
+    if let Some(session_id) = &session_id {
+        for message in &messages {
+            if let crate::protocol::AnyJsonRpcMessage::Response(response) = message {
+                if state.pings.acknowledge(session_id, &response.id) {
+                    debug!("Session {} answered ping", session_id);
+                }
+            }
+        }
+    }
+
     if !has_requests {
         // Only responses/notifications - return 202 Accepted
         info!("Received only responses/notifications, returning 202 Accepted");
@@ -282,27 +499,61 @@ async fn handle_streamable_http_post(
 
     // Has requests - process them and decide response format
     let protocol_handler = &state.protocol_handler;
+    let trace = state
+        .config
+        .echo_request_id
+        .then(|| TraceContext::from_request(&req, state.session_manager.id_generator()));
+    let context = match &session_id {
+        Some(session_id) => RequestContext::with_session(session_id.clone()),
+        None => RequestContext::default(),
+    }
+    .with_scopes(request_scopes(&req, &state))
+    .with_metadata(request_metadata(&req));
+    let context = match principal {
+        Some(principal) => context.with_principal(principal),
+        None => context,
+    };
+    // Stateless requests can't be answered with an SSE stream
+    let event_stream = accepted.event_stream && session_id.is_some();
 
-    // For now, return JSON response for single requests
-    // TODO: Implement SSE streaming for complex scenarios
     if messages.len() == 1 {
         if let crate::protocol::AnyJsonRpcMessage::Request(request) = &messages[0] {
             info!("Processing single JSON-RPC request: {}", request.method);
 
-            match protocol_handler.handle_request(request.clone()).await {
-                Ok(response) => {
+            // Reads that opted in are answered with their text as chunk events
+            let chunk_size = state.config.resource_stream_chunk_size;
+            if event_stream && chunk_size > 0 && requests_streamed_read(request) {
+                let (sender, chunks) = mpsc::channel(RESOURCE_STREAM_BUFFER);
+                let context = context.with_resource_stream(ResourceStream { chunk_size, sender });
+                let protocol_handler = protocol_handler.clone();
+                let streamed = request.clone();
+                let response = actix_web::rt::spawn(async move {
+                    protocol_handler
+                        .handle_request_with_context(streamed, &context)
+                        .await
+                });
+
+                let mut http_response = HttpResponse::Ok();
+                insert_session_header(&mut http_response, session_id.as_deref());
+                return Ok(http_response
+                    .content_type("text/event-stream")
+                    .insert_header(("Cache-Control", "no-cache"))
+                    .streaming(resource_chunk_events(request.clone(), chunks, response)));
+            }
+
+            match protocol_handler
+                .handle_request_with_context(request.clone(), &context)
+                .await
+            {
+                Ok(mut response) => {
                     info!("Request processed successfully");
-                    let mut http_response = HttpResponse::Ok().json(response);
-
-                    // Include session ID in response header if present
-                    if let Some(session_id) = get_session_id(&req) {
-                        http_response.headers_mut().insert(
-                            actix_web::http::header::HeaderName::from_static("mcp-session-id"),
-                            actix_web::http::header::HeaderValue::from_str(&session_id).unwrap(),
-                        );
+                    let mut http_response = HttpResponse::Ok();
+                    insert_session_header(&mut http_response, session_id.as_deref());
+                    if let Some(trace) = &trace {
+                        trace.attach_meta(&mut response);
+                        trace.insert_headers(&mut http_response);
                     }
-
-                    Ok(http_response)
+                    json_response(&req, &state.config, http_response, &response)
                 }
                 Err(e) => {
                     error!("Failed to process request: {}", e);
@@ -322,14 +573,115 @@ async fn handle_streamable_http_post(
             })))
         }
     } else {
-        // Multiple messages - would need SSE streaming
-        // For now, return error
-        Ok(HttpResponse::NotImplemented().json(serde_json::json!({
-            "error": "Batch requests not yet implemented"
-        })))
+        // Batch - stream responses as SSE events when the client supports it
+        info!("Processing batch of {} JSON-RPC messages", messages.len());
+
+        if let Err(e) = protocol_handler.check_batch_size(messages.len()) {
+            warn!("Rejected batch: {}", e);
+            return Ok(HttpResponse::BadRequest()
+                .json(json_rpc_error_body(e.to_json_rpc_code(), &e.to_string())));
+        }
+
+        let mut responses = Vec::new();
+        for result in protocol_handler
+            .handle_batch_messages(messages, &context)
+            .await
+        {
+            match result {
+                Ok(Some(crate::protocol::AnyJsonRpcMessage::Response(mut response))) => {
+                    if let Some(trace) = &trace {
+                        trace.attach_meta(&mut response);
+                    }
+                    responses.push(serde_json::to_value(response)?);
+                }
+                Ok(_) => {}
+                Err(e) => error!("Failed to process batch message: {}", e),
+            }
+        }
+
+        let mut http_response = HttpResponse::Ok();
+        insert_session_header(&mut http_response, session_id.as_deref());
+        if let Some(trace) = &trace {
+            trace.insert_headers(&mut http_response);
+        }
+
+        if event_stream {
+            let events = responses
+                .into_iter()
+                .map(|response| {
+                    Ok::<_, actix_web::Error>(web::Bytes::from(format!("data: {}\n\n", response)))
+                })
+                .collect::<Vec<_>>();
+
+            Ok(http_response
+                .content_type("text/event-stream")
+                .insert_header(("Cache-Control", "no-cache"))
+                .streaming(futures_util::stream::iter(events)))
+        } else {
+            json_response(&req, &state.config, http_response, &responses)
+        }
     }
 }
 
+/// Chunks of a streamed `resources/read` buffered ahead of the client
+const RESOURCE_STREAM_BUFFER: usize = 16;
+
+/// Turn a streamed `resources/read` into SSE events
+///
+/// Each chunk of text is sent as a `notifications/resources/chunk`
+/// notification, followed by the request's response giving the chunk count
+/// (or the error that ended the read).
+fn resource_chunk_events(
+    request: crate::protocol::JsonRpcRequest,
+    chunks: mpsc::Receiver<String>,
+    response: tokio::task::JoinHandle<Result<crate::protocol::JsonRpcResponse>>,
+) -> impl futures_util::Stream<Item = std::result::Result<web::Bytes, actix_web::Error>> {
+    let uri = request
+        .params
+        .as_ref()
+        .and_then(|params| params.get("uri"))
+        .cloned()
+        .unwrap_or_default();
+
+    futures_util::stream::unfold(Some((chunks, response, 0usize)), move |state| {
+        let request_id = request.id.clone();
+        let uri = uri.clone();
+        async move {
+            let (mut chunks, response, index) = state?;
+            let (message, next) = match chunks.recv().await {
+                Some(text) => {
+                    let notification = JsonRpcNotification::new(
+                        RESOURCE_CHUNK_NOTIFICATION.to_string(),
+                        Some(serde_json::json!({ "uri": uri, "index": index, "text": text })),
+                    );
+                    (serde_json::to_value(notification), Some((chunks, response, index + 1)))
+                }
+                // The read is over once its handler drops the sender
+                None => {
+                    let response = match response.await {
+                        Ok(Ok(response)) => response,
+                        Ok(Err(e)) => {
+                            error!("Streamed read of {} failed: {}", uri, e);
+                            crate::protocol::JsonRpcResponse::error(request_id, e.into())
+                        }
+                        Err(e) => {
+                            error!("Streamed read of {} panicked: {}", uri, e);
+                            let error = McpError::internal_error(format!("Streamed read failed: {}", e));
+                            crate::protocol::JsonRpcResponse::error(request_id, error.into())
+                        }
+                    };
+                    (serde_json::to_value(response), None)
+                }
+            };
+
+            let event = message
+                .map(|message| sse_event(&message.to_string(), None))
+                .map_err(actix_web::Error::from);
+            Some((event, next))
+        }
+    })
+}
+
 /// Handle Streamable HTTP GET requests
 /// Opens an optional SSE stream for server-initiated messages
 async fn handle_streamable_http_get(
@@ -348,6 +700,11 @@ async fn handle_streamable_http_get(
         }
     }
 
+    if let Err(e) = authenticate(&req, &state) {
+        warn!("Rejected request: {}", e);
+        return Ok(error_response(&e));
+    }
+
     // Check Accept header - must support text/event-stream
     let accepts_sse = req
         .headers()
@@ -360,6 +717,11 @@ async fn handle_streamable_http_get(
         return Ok(HttpResponse::MethodNotAllowed().finish());
     }
 
+    // Streams belong to sessions; stateless clients get none
+    if state.config.stateless && get_session_id(&req).is_none() {
+        return Ok(HttpResponse::MethodNotAllowed().finish());
+    }
+
     // Get or create session
     let session_id = get_or_create_session(&req, &state.session_manager).await?;
 
@@ -370,16 +732,73 @@ async fn handle_streamable_http_get(
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string());
 
-    if let Some(event_id) = &last_event_id {
-        info!("Resuming stream from event ID: {}", event_id);
-        // TODO: Implement stream resumption logic
-    }
+    // Subscribe before replaying so nothing published in between is lost
+    let receiver = state.notification_hub.subscribe(&session_id).await;
 
-    // Create SSE stream for server-initiated messages
-    // For now, just send a connection confirmation
-    let stream = futures_util::stream::iter(vec![Ok::<_, actix_web::Error>(web::Bytes::from(
-        "data: {\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\",\"params\":{}}\n\n",
-    ))]);
+    let replayed = match &last_event_id {
+        Some(event_id) => match state.replay.replay_after(&session_id, event_id) {
+            Ok(events) => {
+                info!(
+                    "Resuming stream from event ID {}, replaying {} events",
+                    event_id,
+                    events.len()
+                );
+                events
+            }
+            Err(ReplayError::Evicted(event_id)) => {
+                warn!(
+                    "Cannot resume session {} from evicted event {}",
+                    session_id, event_id
+                );
+                let mut response = HttpResponse::Conflict();
+                insert_session_header(&mut response, Some(&session_id));
+                return Ok(response.json(json_rpc_error_body(
+                    -32600,
+                    &format!(
+                        "Event {} is no longer available; reconnect without Last-Event-ID to re-sync",
+                        event_id
+                    ),
+                )));
+            }
+        },
+        None => Vec::new(),
+    };
+    let replayed = futures_util::stream::iter(
+        replayed
+            .into_iter()
+            .map(|(id, payload)| Ok(sse_event(&payload, Some(&id)))),
+    );
+
+    // Stream server-initiated messages for this session as they are published
+    let max_lifetime = state
+        .config
+        .sse_max_lifetime
+        .map(std::time::Duration::from_secs);
+    let heartbeat = Some(state.config.sse_heartbeat_ms)
+        .filter(|ms| *ms > 0)
+        .map(std::time::Duration::from_millis);
+    let ping = (state.config.ping_interval_ms > 0).then(|| {
+        let interval = std::time::Duration::from_millis(state.config.ping_interval_ms);
+        StreamPing {
+            session_id: session_id.clone(),
+            interval,
+            timeout: std::time::Duration::from_millis(state.config.ping_timeout_ms),
+            pings: state.pings.clone(),
+            session_manager: state.session_manager.clone(),
+            next_ping: tokio::time::Instant::now() + interval,
+            sent_at: None,
+        }
+    });
+    let stream = futures_util::StreamExt::chain(
+        replayed,
+        notification_stream(
+            receiver,
+            max_lifetime,
+            heartbeat,
+            ping,
+            state.config.sse_retry_ms,
+        ),
+    );
 
     Ok(HttpResponse::Ok()
         .content_type("text/event-stream")
@@ -389,6 +808,162 @@ async fn handle_streamable_http_get(
         .streaming(stream))
 }
 
+/// SSE comment sent on idle streams to keep intermediaries from timing out
+const SSE_KEEP_ALIVE: &[u8] = b": keep-alive\n\n";
+
+/// Format an SSE event, tagged with an ID when it can be replayed
+fn sse_event(data: &str, id: Option<&str>) -> web::Bytes {
+    match id {
+        Some(id) => web::Bytes::from(format!("data: {}\nid: {}\n\n", data, id)),
+        None => web::Bytes::from(format!("data: {}\n\n", data)),
+    }
+}
+
+/// Server-initiated pings on one session's SSE stream
+struct StreamPing {
+    session_id: String,
+    interval: std::time::Duration,
+    timeout: std::time::Duration,
+    pings: Arc<PingTracker>,
+    session_manager: Arc<SessionManager>,
+    next_ping: tokio::time::Instant,
+    sent_at: Option<tokio::time::Instant>,
+}
+
+impl StreamPing {
+    /// When the stream next needs to act: the pending ping's deadline, or the next ping
+    fn wake_at(&self) -> tokio::time::Instant {
+        match self.sent_at {
+            Some(sent_at) if self.pings.is_outstanding(&self.session_id) => sent_at + self.timeout,
+            _ => self.next_ping,
+        }
+    }
+}
+
+/// What a session's stream does when its ping timer fires
+enum PingAction {
+    /// Send a new ping event
+    Send(web::Bytes),
+
+    /// The client didn't answer in time; the session has been reaped
+    Reaped,
+
+    /// Nothing due yet
+    Wait,
+}
+
+async fn ping_tick(ping: &mut StreamPing) -> PingAction {
+    if ping.pings.is_stale(&ping.session_id, ping.timeout) {
+        warn!(
+            "Session {} did not answer ping within {:?}, closing it",
+            ping.session_id, ping.timeout
+        );
+        ping.pings.forget(&ping.session_id);
+        ping.session_manager.remove_session(&ping.session_id).await;
+        return PingAction::Reaped;
+    }
+
+    let now = tokio::time::Instant::now();
+    if ping.pings.is_outstanding(&ping.session_id) || now < ping.next_ping {
+        return PingAction::Wait;
+    }
+
+    let request = ping.pings.start(&ping.session_id);
+    ping.sent_at = Some(now);
+    ping.next_ping = now + ping.interval;
+    match serde_json::to_string(&request) {
+        Ok(json) => PingAction::Send(sse_event(&json, None)),
+        Err(e) => {
+            error!("Failed to serialize ping: {}", e);
+            PingAction::Wait
+        }
+    }
+}
+
+/// Turn a session's notification channel into an SSE event stream
+///
+/// The stream stays open until the session's channel is dropped or
+/// `max_lifetime` elapses, in which case a final `retry:` event tells the
+/// client how long to wait before reconnecting. While idle, a `: keep-alive`
+/// comment is sent every `heartbeat` so intermediaries don't drop the
+/// connection; clients ignore comments, so they never surface as events.
+///
+/// With `ping` set, a `ping` request is sent every interval; if the client
+/// doesn't answer one in time its session is removed and the stream ends.
+/// Notifications the hub buffered for replay carry their `id:` so a
+/// reconnecting client can resume with `Last-Event-ID`.
+fn notification_stream(
+    receiver: tokio::sync::broadcast::Receiver<PublishedNotification>,
+    max_lifetime: Option<std::time::Duration>,
+    heartbeat: Option<std::time::Duration>,
+    ping: Option<StreamPing>,
+    retry_ms: u64,
+) -> impl futures_util::Stream<Item = std::result::Result<web::Bytes, actix_web::Error>> {
+    let deadline = max_lifetime.map(|lifetime| tokio::time::Instant::now() + lifetime);
+
+    futures_util::stream::unfold(Some((receiver, ping)), move |state| async move {
+        let (mut receiver, mut ping) = state?;
+        loop {
+            let lifetime_elapsed = async {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let heartbeat_due = async {
+                match heartbeat {
+                    Some(heartbeat) => tokio::time::sleep(heartbeat).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let ping_wake = ping.as_ref().map(StreamPing::wake_at);
+            let ping_due = async {
+                match ping_wake {
+                    Some(wake_at) => tokio::time::sleep_until(wake_at).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            let received = tokio::select! {
+                received = receiver.recv() => received,
+                _ = lifetime_elapsed => {
+                    debug!("SSE stream reached its maximum lifetime");
+                    let event = web::Bytes::from(format!("retry: {}\n\n", retry_ms));
+                    return Some((Ok(event), None));
+                }
+                _ = heartbeat_due => {
+                    let event = web::Bytes::from_static(SSE_KEEP_ALIVE);
+                    return Some((Ok(event), Some((receiver, ping))));
+                }
+                _ = ping_due => {
+                    if let Some(state) = ping.as_mut() {
+                        match ping_tick(state).await {
+                            PingAction::Send(event) => return Some((Ok(event), Some((receiver, ping)))),
+                            PingAction::Reaped => return None,
+                            PingAction::Wait => {}
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            match received {
+                Ok(published) => match serde_json::to_string(&published.notification) {
+                    Ok(json) => {
+                        let event = sse_event(&json, published.event_id.as_deref());
+                        return Some((Ok(event), Some((receiver, ping))));
+                    }
+                    Err(e) => error!("Failed to serialize notification: {}", e),
+                },
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("SSE stream lagged, dropped {} notifications", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
 /// Handle DELETE requests (session termination)
 async fn handle_delete_request(
     req: HttpRequest,
@@ -396,33 +971,313 @@ async fn handle_delete_request(
 ) -> ActixResult<HttpResponse> {
     info!("Handling DELETE request for session termination");
 
-    if let Some(session_id) = get_session_id(&req) {
-        state.session_manager.remove_session(&session_id).await;
-        info!("Session {} terminated", session_id);
-        Ok(HttpResponse::Ok().finish())
+    if let Err(e) = authenticate(&req, &state) {
+        warn!("Rejected request: {}", e);
+        return Ok(error_response(&e));
+    }
+
+    let Some(session_id) = get_session_id(&req) else {
+        return Ok(error_response(&McpError::invalid_request("No session ID provided")));
+    };
+
+    if state.session_manager.remove_session(&session_id).await.is_none() {
+        return Ok(error_response(&McpError::session_not_found(session_id)));
+    }
+
+    info!("Session {} terminated", session_id);
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Handle requests to paths other than the MCP endpoint
+async fn handle_not_found(req: HttpRequest) -> HttpResponse {
+    debug!("No route for {} {}", req.method(), req.path());
+    HttpResponse::NotFound().json(json_rpc_error_body(
+        -32600,
+        &format!("Not found: {}", req.path()),
+    ))
+}
+
+/// Handle unsupported HTTP methods on the MCP endpoint
+async fn handle_method_not_allowed(req: HttpRequest) -> HttpResponse {
+    warn!("Method {} not allowed on {}", req.method(), req.path());
+    HttpResponse::MethodNotAllowed()
+        .insert_header(("Allow", "GET, POST, DELETE"))
+        .json(json_rpc_error_body(
+            -32600,
+            &format!("Method not allowed: {}", req.method()),
+        ))
+}
+
+/// Check that the request body is declared as JSON (parameters such as `charset` are allowed)
+fn has_json_content_type(req: &HttpRequest) -> bool {
+    req.headers()
+        .get("Content-Type")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Answer a request that failed at the transport level with the error's status and code
+fn error_response(e: &McpError) -> HttpResponse {
+    let mut body = json_rpc_error_body(e.to_json_rpc_code(), &e.to_string());
+    if let Some(data) = e.to_json_rpc_data() {
+        body["error"]["data"] = data;
+    }
+    HttpResponse::build(e.http_status()).json(body)
+}
+
+/// Build a JSON-RPC error body for transport-level failures
+fn json_rpc_error_body(code: i32, message: &str) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "error": {
+            "code": code,
+            "message": message
+        },
+        "id": null
+    })
+}
+
+/// Handle probe requests (e.g. `/favicon.ico`) with an empty 204 response
+async fn handle_probe_request(req: HttpRequest) -> HttpResponse {
+    debug!("Ignoring probe request: {} {}", req.method(), req.path());
+    HttpResponse::NoContent().finish()
+}
+
+/// Response formats the client accepts, parsed from the Accept header
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AcceptedFormats {
+    /// Client accepts `application/json`
+    json: bool,
+
+    /// Client accepts `text/event-stream`
+    event_stream: bool,
+}
+
+impl AcceptedFormats {
+    /// Parse the Accept header of a request
+    fn from_request(req: &HttpRequest) -> Self {
+        let accept = req
+            .headers()
+            .get("Accept")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or("");
+        Self::parse(accept)
+    }
+
+    /// Parse an Accept header value, ignoring parameters such as `q`
+    fn parse(accept: &str) -> Self {
+        let mut formats = Self {
+            json: false,
+            event_stream: false,
+        };
+
+        for media_type in accept.split(',') {
+            let media_type = media_type.split(';').next().unwrap_or("").trim();
+            match media_type {
+                "application/json" | "application/*" | "*/*" => formats.json = true,
+                "text/event-stream" => formats.event_stream = true,
+                _ => {}
+            }
+        }
+
+        formats
+    }
+}
+
+/// Content codings used to compress JSON responses, in order of preference
+const COMPRESSION_ENCODINGS: [&str; 2] = ["gzip", "deflate"];
+
+/// Pick the preferred content coding the client accepts, if any
+///
+/// A coding named explicitly takes its own `q=0` refusal over the `*` wildcard.
+fn accepted_encoding(req: &HttpRequest) -> Option<&'static str> {
+    let accept_encoding = req
+        .headers()
+        .get("Accept-Encoding")
+        .and_then(|h| h.to_str().ok())?;
+
+    // (coding, refused) for every listed coding
+    let codings = accept_encoding
+        .split(',')
+        .filter_map(|coding| {
+            let mut parts = coding.split(';');
+            let name = parts.next()?.trim().to_ascii_lowercase();
+            let refused = parts.any(|param| {
+                param
+                    .trim()
+                    .strip_prefix("q=")
+                    .and_then(|q| q.parse::<f32>().ok())
+                    == Some(0.0)
+            });
+            Some((name, refused))
+        })
+        .collect::<Vec<_>>();
+    let accepts = |name: &str| {
+        codings
+            .iter()
+            .find(|(coding, _)| coding == name)
+            .map(|(_, refused)| !refused)
+    };
+
+    COMPRESSION_ENCODINGS.into_iter().find(|encoding| {
+        accepts(encoding)
+            .or_else(|| accepts("*"))
+            .unwrap_or(false)
+    })
+}
+
+/// Build a JSON response, compressing the body when the client accepts it
+fn json_response<T: serde::Serialize>(
+    req: &HttpRequest,
+    config: &HttpConfig,
+    mut response: actix_web::HttpResponseBuilder,
+    body: &T,
+) -> ActixResult<HttpResponse> {
+    let body = serde_json::to_vec(body)?;
+    response.content_type("application/json");
+    if !config.compression || body.len() < config.compression_min_size {
+        return Ok(response.body(body));
+    }
+
+    response.insert_header(("Vary", "Accept-Encoding"));
+    let Some(encoding) = accepted_encoding(req) else {
+        return Ok(response.body(body));
+    };
+
+    let compressed = if encoding == "gzip" {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body)?;
+        encoder.finish()?
     } else {
-        Ok(HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "No session ID provided"
-        })))
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body)?;
+        encoder.finish()?
+    };
+
+    response.insert_header(("Content-Encoding", encoding));
+    Ok(response.body(compressed))
+}
+
+/// Request/trace identifiers echoed back to the client for correlation
+#[derive(Debug, Clone)]
+struct TraceContext {
+    /// Client-supplied `X-Request-Id`, or a generated one
+    request_id: String,
+
+    /// Client-supplied W3C `traceparent`, if any
+    traceparent: Option<String>,
+}
+
+impl TraceContext {
+    /// Extract trace headers from a request, generating a request ID if absent
+    fn from_request(req: &HttpRequest, id_generator: &SharedIdGenerator) -> Self {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|h| h.to_str().ok())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        };
+
+        Self {
+            request_id: header("X-Request-Id").unwrap_or_else(|| id_generator.generate()),
+            traceparent: header("traceparent"),
+        }
     }
+
+    /// Add the trace headers to an HTTP response
+    fn insert_headers(&self, response: &mut actix_web::HttpResponseBuilder) {
+        response.insert_header(("X-Request-Id", self.request_id.as_str()));
+        if let Some(traceparent) = &self.traceparent {
+            response.insert_header(("traceparent", traceparent.as_str()));
+        }
+    }
+
+    /// Add the trace identifiers to the `_meta` of a successful result
+    fn attach_meta(&self, response: &mut crate::protocol::JsonRpcResponse) {
+        let Some(serde_json::Value::Object(result)) = response.result.as_mut() else {
+            return;
+        };
+
+        let meta = result
+            .entry("_meta")
+            .or_insert_with(|| serde_json::json!({}));
+        if let Some(meta) = meta.as_object_mut() {
+            meta.insert("requestId".to_string(), self.request_id.clone().into());
+            if let Some(traceparent) = &self.traceparent {
+                meta.insert("traceparent".to_string(), traceparent.clone().into());
+            }
+        }
+    }
+}
+
+/// Add the session ID header to a response, if the request has a session
+fn insert_session_header(response: &mut actix_web::HttpResponseBuilder, session_id: Option<&str>) {
+    if let Some(session_id) = session_id {
+        response.insert_header(("Mcp-Session-Id", session_id));
+    }
+}
+
+/// Release per-client protocol state whenever a session is removed or expires
+fn spawn_session_cleanup(
+    session_manager: &SessionManager,
+    protocol_handler: Arc<crate::protocol::handler::ProtocolHandler>,
+    notification_hub: Arc<NotificationHub>,
+    pings: Arc<PingTracker>,
+    replay: Arc<ReplayBuffers>,
+) -> tokio::task::JoinHandle<()> {
+    let mut removals = session_manager.subscribe_removals();
+
+    tokio::spawn(async move {
+        loop {
+            match removals.recv().await {
+                Ok(session_id) => {
+                    notification_hub.remove_session(&session_id).await;
+                    pings.forget(&session_id);
+                    replay.forget(&session_id);
+                    protocol_handler.handle_session_closed(&session_id).await;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Missed {} session removal events", skipped);
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    })
 }
 
-/// Get or create a session for the request
+/// Get the request's session, or create one if it names none
+///
+/// A session ID the server doesn't know (e.g. one that expired) is answered
+/// with a 404, telling the client to start over with a new session.
 async fn get_or_create_session(
     req: &HttpRequest,
     session_manager: &SessionManager,
 ) -> ActixResult<String> {
     if let Some(session_id) = get_session_id(req) {
-        // Validate existing session
         if session_manager.get_session(&session_id).await.is_some() {
             return Ok(session_id);
         }
+
+        let e = McpError::session_not_found(session_id);
+        warn!("Rejected request: {}", e);
+        let response = error_response(&e);
+        return Err(actix_web::error::InternalError::from_response(e.to_string(), response).into());
     }
 
     // Create new session
-    let session_id = Uuid::new_v4().to_string();
-    let session = Session::new(session_id.clone());
-    session_manager.add_session(session).await;
+    let session_id = session_manager.generate_session_id();
+    let session = session_manager.create_session(session_id.clone());
+    if !session_manager.try_add_session(session).await {
+        let response = HttpResponse::ServiceUnavailable()
+            .json(json_rpc_error_body(-32603, "Too many active sessions"));
+        return Err(actix_web::error::InternalError::from_response(
+            "Too many active sessions",
+            response,
+        )
+        .into());
+    }
 
     Ok(session_id)
 }
@@ -435,19 +1290,75 @@ fn get_session_id(req: &HttpRequest) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Headers never copied into request metadata, as they carry credentials
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization", "x-api-key"];
+
+/// Describe the request for auditing and auth: peer address, user agent and
+/// the (bounded) non-credential headers
+fn request_metadata(req: &HttpRequest) -> TransportMetadata {
+    let mut metadata = TransportMetadata {
+        source_addr: req.peer_addr(),
+        user_agent: req
+            .headers()
+            .get("User-Agent")
+            .and_then(|h| h.to_str().ok())
+            .map(|s| s.to_string()),
+        ..TransportMetadata::default()
+    };
+    metadata.capture_headers(req.headers().iter().filter_map(|(name, value)| {
+        let name = name.as_str();
+        if CREDENTIAL_HEADERS.contains(&name) {
+            return None;
+        }
+        value.to_str().ok().map(|value| (name, value))
+    }));
+    metadata
+}
+
+/// Reject requests without a valid API key when the transport requires one
+fn authenticate(req: &HttpRequest, state: &AppState) -> Result<Option<String>> {
+    let Some(api_keys) = &state.api_keys else {
+        return Ok(None);
+    };
+
+    let header = |name: &str| req.headers().get(name).and_then(|h| h.to_str().ok());
+    match crate::utils::auth::extract_api_key(header("X-API-Key"), header("Authorization")) {
+        Some(key) if crate::utils::auth::validate_api_key(key, api_keys) => {
+            Ok(Some(crate::utils::auth::api_key_principal(key)))
+        }
+        Some(_) => Err(McpError::unauthorized("Invalid API key")),
+        None => Err(McpError::unauthorized("Missing API key")),
+    }
+}
+
+/// Look up the scopes granted to the API key the request presents
+fn request_scopes(req: &HttpRequest, state: &AppState) -> Vec<String> {
+    let header = |name: &str| req.headers().get(name).and_then(|h| h.to_str().ok());
+    crate::utils::auth::extract_api_key(header("X-API-Key"), header("Authorization"))
+        .and_then(|key| state.key_scopes.get(key))
+        .cloned()
+        .unwrap_or_default()
+}
+
 /// Parse a single JSON-RPC message or batch of messages
-fn parse_message_or_batch(body: &str) -> Result<Vec<crate::protocol::AnyJsonRpcMessage>> {
+fn parse_message_or_batch(
+    body: &str,
+    max_depth: usize,
+) -> Result<Vec<crate::protocol::AnyJsonRpcMessage>> {
+    // Refuse overly nested bodies before deserializing anything
+    check_json_depth(body, max_depth)?;
+
     // Try to parse as array first (batch)
     if let Ok(batch) = serde_json::from_str::<Vec<serde_json::Value>>(body) {
         let mut messages = Vec::new();
         for value in batch {
-            let message = parse_message(&serde_json::to_string(&value)?)?;
+            let message = parse_message_with_max_depth(&serde_json::to_string(&value)?, max_depth)?;
             messages.push(message);
         }
         Ok(messages)
     } else {
         // Parse as single message
-        let message = parse_message(body)?;
+        let message = parse_message_with_max_depth(body, max_depth)?;
         Ok(vec![message])
     }
 }
@@ -462,3 +1373,1032 @@ fn is_origin_allowed(origin: &str, allowed_origins: &[String]) -> bool {
         allowed == origin || (allowed.starts_with("*.") && origin.ends_with(&allowed[1..]))
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test};
+
+    fn test_state(config: HttpConfig) -> AppState {
+        let replay = Arc::new(ReplayBuffers::new(
+            config.sse_replay_max_events,
+            config.sse_replay_max_bytes,
+        ));
+        AppState {
+            session_manager: Arc::new(
+                SessionManager::new(std::time::Duration::from_secs(config.session_timeout))
+                    .with_max_sessions(config.max_sessions),
+            ),
+            message_sender: Arc::new(RwLock::new(None)),
+            config,
+            protocol_handler: init_global_protocol_handler(),
+            notification_hub: Arc::new(NotificationHub::new().with_replay(replay.clone())),
+            pings: Arc::new(PingTracker::new()),
+            replay,
+            key_scopes: Arc::new(HashMap::new()),
+            api_keys: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_favicon_probe_returns_no_content() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::get().uri("/favicon.ico").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+
+        let req = test::TestRequest::get().uri("/").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[actix_web::test]
+    async fn test_probe_paths_configurable() {
+        let config = HttpConfig {
+            probe_paths: Vec::new(),
+            ..HttpConfig::default()
+        };
+        let app = test::init_service(HttpTransport::create_app(test_state(config))).await;
+
+        let req = test::TestRequest::get().uri("/favicon.ico").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_path_returns_json_404() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::get().uri("/bogus").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["jsonrpc"], "2.0");
+        assert_eq!(body["error"]["code"], -32600);
+    }
+
+    #[actix_web::test]
+    async fn test_unsupported_method_returns_405_with_allow() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::put().uri("/mcp").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.headers().get("Allow").unwrap(), "GET, POST, DELETE");
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], -32600);
+    }
+
+    #[actix_web::test]
+    async fn test_accept_header_parsing() {
+        let formats = AcceptedFormats::parse("application/json");
+        assert!(formats.json && !formats.event_stream);
+
+        let formats = AcceptedFormats::parse("application/json;q=0.9, text/event-stream");
+        assert!(formats.json && formats.event_stream);
+
+        let formats = AcceptedFormats::parse("text/event-stream");
+        assert!(!formats.json && formats.event_stream);
+    }
+
+    #[actix_web::test]
+    async fn test_post_json_only_client() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/json");
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["id"], 1);
+        assert!(body["result"].is_object());
+    }
+
+    #[actix_web::test]
+    async fn test_post_streaming_capable_client() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        // Single request is answered with plain JSON
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json, text/event-stream"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/json");
+
+        // Batch is streamed back as SSE events
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json, text/event-stream"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(
+                r#"[{"jsonrpc":"2.0","id":1,"method":"ping"},{"jsonrpc":"2.0","id":2,"method":"ping"}]"#,
+            )
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "text/event-stream");
+
+        let body = test::read_body(resp).await;
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert_eq!(body.matches("data: ").count(), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_large_json_response_compressed_when_accepted() {
+        use std::io::Read;
+
+        let config = HttpConfig::default();
+        let body = serde_json::json!({ "blob": "x".repeat(config.compression_min_size * 4) });
+
+        let req = test::TestRequest::default()
+            .insert_header(("Accept-Encoding", "br;q=1.0, gzip;q=0.8"))
+            .to_http_request();
+        let resp = json_response(&req, &config, HttpResponse::Ok(), &body).unwrap();
+        assert_eq!(resp.headers().get("Content-Encoding").unwrap(), "gzip");
+        let compressed = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let mut decoded = String::new();
+        flate2::read::GzDecoder::new(&compressed[..])
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&decoded).unwrap(), body);
+
+        // Identity without Accept-Encoding, or when gzip is refused
+        for accept_encoding in [None, Some("gzip;q=0")] {
+            let mut req = test::TestRequest::default();
+            if let Some(value) = accept_encoding {
+                req = req.insert_header(("Accept-Encoding", value));
+            }
+            let resp = json_response(&req.to_http_request(), &config, HttpResponse::Ok(), &body)
+                .unwrap();
+            assert!(resp.headers().get("Content-Encoding").is_none());
+            let plain = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+            assert_eq!(serde_json::from_slice::<serde_json::Value>(&plain).unwrap(), body);
+        }
+
+        // An explicit refusal wins over the wildcard
+        for (accept_encoding, expected) in [
+            ("gzip;q=0, *", Some("deflate")),
+            ("*, gzip;q=0", Some("deflate")),
+            ("gzip;q=0, deflate;q=0, *", None),
+            ("*;q=0, deflate", Some("deflate")),
+        ] {
+            let req = test::TestRequest::default()
+                .insert_header(("Accept-Encoding", accept_encoding))
+                .to_http_request();
+            let resp = json_response(&req, &config, HttpResponse::Ok(), &body).unwrap();
+            let encoding = resp.headers().get("Content-Encoding").map(|h| h.to_str().unwrap());
+            assert_eq!(encoding, expected, "{}", accept_encoding);
+        }
+
+        // Small bodies are left alone
+        let req = test::TestRequest::default()
+            .insert_header(("Accept-Encoding", "gzip"))
+            .to_http_request();
+        let resp = json_response(&req, &config, HttpResponse::Ok(), &serde_json::json!({})).unwrap();
+        assert!(resp.headers().get("Content-Encoding").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_post_without_json_accept_rejected() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_echoed() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .insert_header(("X-Request-Id", "req-123"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("X-Request-Id").unwrap(), "req-123");
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["result"]["_meta"]["requestId"], "req-123");
+
+        // A request ID is generated when the client doesn't send one
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let generated = resp.headers().get("X-Request-Id").unwrap().to_str().unwrap().to_string();
+        assert!(!generated.is_empty());
+
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["result"]["_meta"]["requestId"], generated.as_str());
+    }
+
+    #[actix_web::test]
+    async fn test_subscriptions_cleaned_up_on_session_delete() {
+        let resource_manager = Arc::new(crate::server::features::resources::ResourceManager::with_config(
+            crate::server::features::resources::ResourcesConfig {
+                allow_subscribe_to_missing: true,
+                ..Default::default()
+            },
+        ));
+        let protocol_handler = Arc::new(crate::protocol::handler::ProtocolHandler::new(
+            resource_manager.clone(),
+            Arc::new(crate::server::features::tools::ToolManager::new()),
+            Arc::new(crate::server::features::prompts::PromptManager::new()),
+            Arc::new(crate::client::features::sampling::SamplingManager::new()),
+        ));
+
+        let state = AppState {
+            protocol_handler: protocol_handler.clone(),
+            ..test_state(HttpConfig::default())
+        };
+        spawn_session_cleanup(
+            &state.session_manager,
+            protocol_handler,
+            state.notification_hub.clone(),
+            state.pings.clone(),
+            state.replay.clone(),
+        );
+
+        let session = state.session_manager.create_session("session-1".to_string());
+        state.session_manager.add_session(session).await;
+        resource_manager.subscribe("test://a", "session-1").await.unwrap();
+        resource_manager.subscribe("test://a", "session-2").await.unwrap();
+
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+        let req = test::TestRequest::delete()
+            .uri("/mcp")
+            .insert_header(("Mcp-Session-Id", "session-1"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Cleanup runs on a background task
+        for _ in 0..50 {
+            if resource_manager.get_client_subscription_count("session-1").await == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(resource_manager.get_client_subscription_count("session-1").await, 0);
+        assert_eq!(resource_manager.get_client_subscription_count("session-2").await, 1);
+    }
+
+    #[actix_web::test]
+    async fn test_experimental_capabilities_follow_key_scopes() {
+        let protocol_handler = Arc::new(
+            crate::protocol::handler::ProtocolHandler::new(
+                Arc::new(crate::server::features::resources::ResourceManager::new()),
+                Arc::new(crate::server::features::tools::ToolManager::new()),
+                Arc::new(crate::server::features::prompts::PromptManager::new()),
+                Arc::new(crate::client::features::sampling::SamplingManager::new()),
+            )
+            .with_scoped_experimental(HashMap::from([(
+                "beta".to_string(),
+                HashMap::from([("streamingTools".to_string(), serde_json::json!({}))]),
+            )])),
+        );
+        let state = AppState {
+            protocol_handler,
+            key_scopes: Arc::new(HashMap::from([(
+                "beta-key".to_string(),
+                vec!["beta".to_string()],
+            )])),
+            ..test_state(HttpConfig::default())
+        };
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let initialize = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "test", "version": "1.0"}
+            }
+        });
+
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .insert_header(("Authorization", "Bearer beta-key"))
+            .set_json(&initialize)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let experimental = &body["result"]["capabilities"]["experimental"];
+        assert!(experimental.get("streamingTools").is_some());
+        assert!(experimental.get("pagination").is_some());
+
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .set_json(&initialize)
+            .to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        let experimental = &body["result"]["capabilities"]["experimental"];
+        assert!(experimental.get("streamingTools").is_none());
+        assert!(experimental.get("pagination").is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_get_stream_delivers_pushed_notifications() {
+        use actix_web::body::MessageBody;
+
+        let state = test_state(HttpConfig::default());
+        let hub = state.notification_hub.clone();
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let req = test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let session_id = resp.headers().get("Mcp-Session-Id").unwrap().to_str().unwrap().to_string();
+        let mut body = resp.into_body();
+
+        // Nothing pending: the stream stays open without emitting anything
+        let idle = tokio::time::timeout(
+            std::time::Duration::from_millis(50),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)),
+        )
+        .await;
+        assert!(idle.is_err());
+
+        let notification = crate::protocol::JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/resources/list_changed".to_string(),
+            params: None,
+        };
+        assert_eq!(hub.send(&session_id, notification).await, 1);
+
+        let chunk = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        let chunk = String::from_utf8(chunk.to_vec()).unwrap();
+        assert!(chunk.starts_with("data: "));
+        assert!(chunk.contains("notifications/resources/list_changed"));
+    }
+
+    #[tokio::test]
+    async fn test_notify_shutdown_reaches_open_streams() {
+        let transport = HttpTransport::new(HttpConfig::default()).unwrap();
+        let mut stream = transport.notification_hub().subscribe("session-a").await;
+
+        let notification = JsonRpcNotification::new(
+            "notifications/server/shutdown".to_string(),
+            Some(serde_json::json!({ "gracePeriodMs": 5000 })),
+        );
+        assert_eq!(transport.notify_shutdown(notification).await.unwrap(), 1);
+
+        let received = stream.recv().await.unwrap().notification;
+        assert_eq!(received.method, "notifications/server/shutdown");
+        assert_eq!(received.params.unwrap()["gracePeriodMs"], 5000);
+    }
+
+    #[actix_web::test]
+    async fn test_endpoint_path_normalized_on_construction() {
+        let transport = HttpTransport::new(HttpConfig {
+            endpoint_path: "mcp/".to_string(),
+            ..HttpConfig::default()
+        })
+        .unwrap();
+        assert_eq!(transport.config.endpoint_path, "/mcp");
+
+        assert!(HttpTransport::new(HttpConfig {
+            endpoint_path: String::new(),
+            ..HttpConfig::default()
+        })
+        .is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_ephemeral_port_serves_injected_handler() {
+        let protocol_handler = Arc::new(crate::protocol::handler::ProtocolHandler::new(
+            Arc::new(crate::server::features::resources::ResourceManager::new()),
+            Arc::new(crate::server::features::tools::ToolManager::new()),
+            Arc::new(crate::server::features::prompts::PromptManager::new()),
+            Arc::new(crate::client::features::sampling::SamplingManager::new()),
+        ));
+        let transport = HttpTransport::ephemeral(protocol_handler.clone()).unwrap();
+        assert!(transport.local_addr().await.is_none());
+
+        let _channels = transport.start().await.unwrap();
+        let addr = transport.local_addr().await.unwrap();
+        assert_ne!(addr.port(), 0);
+
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(format!("http://{}/mcp", addr))
+            .header("Accept", "application/json")
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": {"name": "test", "version": "1.0"}
+                }
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["protocolVersion"], crate::protocol::PROTOCOL_VERSION);
+
+        // The request reached the injected handler, not the global one
+        assert_eq!(protocol_handler.counters().requests(), 1);
+
+        transport.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_start_fails_when_port_in_use() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let transport = HttpTransport::new(HttpConfig {
+            bind_address: "127.0.0.1".to_string(),
+            port,
+            ..HttpConfig::default()
+        })
+        .unwrap();
+
+        let err = transport.start().await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::McpError::Transport(TransportError::ConnectionFailed(_))
+        ));
+        assert!(err.to_string().contains(&port.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn test_get_stream_closes_after_max_lifetime() {
+        use actix_web::body::MessageBody;
+
+        let state = test_state(HttpConfig {
+            sse_max_lifetime: Some(1),
+            sse_retry_ms: 500,
+            ..HttpConfig::default()
+        });
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let req = test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let mut body = resp.into_body();
+
+        let chunk = tokio::time::timeout(
+            std::time::Duration::from_secs(3),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        assert_eq!(chunk, web::Bytes::from("retry: 500\n\n"));
+
+        // The stream ends after the retry hint
+        let end = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)),
+        )
+        .await
+        .unwrap();
+        assert!(end.is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_idle_get_stream_sends_heartbeats() {
+        use actix_web::body::MessageBody;
+
+        let state = test_state(HttpConfig {
+            sse_heartbeat_ms: 20,
+            ..HttpConfig::default()
+        });
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let req = test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let mut body = resp.into_body();
+
+        // Nothing is published, yet keep-alive comments keep arriving
+        for _ in 0..2 {
+            let chunk = tokio::time::timeout(
+                std::time::Duration::from_secs(1),
+                std::future::poll_fn(|cx| std::pin::Pin::new(&mut body).poll_next(cx)),
+            )
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+            assert_eq!(chunk, web::Bytes::from_static(SSE_KEEP_ALIVE));
+        }
+    }
+
+    /// Wait up to a second for the next chunk of a streamed body
+    async fn next_chunk<B: actix_web::body::MessageBody + Unpin>(body: &mut B) -> Option<web::Bytes>
+    where
+        B::Error: std::fmt::Debug,
+    {
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            std::future::poll_fn(|cx| std::pin::Pin::new(&mut *body).poll_next(cx)),
+        )
+        .await
+        .unwrap()
+        .map(|chunk| chunk.unwrap())
+    }
+
+    #[actix_web::test]
+    async fn test_unanswered_ping_reaps_session() {
+        let state = test_state(HttpConfig {
+            ping_interval_ms: 20,
+            ping_timeout_ms: 50,
+            ..HttpConfig::default()
+        });
+        let session_manager = state.session_manager.clone();
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let open_stream = || {
+            test::TestRequest::get()
+                .uri("/mcp")
+                .insert_header(("Accept", "text/event-stream"))
+                .to_request()
+        };
+        let resp = test::call_service(&app, open_stream()).await;
+        let session_id = resp.headers().get("Mcp-Session-Id").unwrap().to_str().unwrap().to_string();
+        let mut body = resp.into_body();
+
+        let chunk = next_chunk(&mut body).await.unwrap();
+        let event: serde_json::Value =
+            serde_json::from_slice(chunk.strip_prefix(b"data: ").unwrap().trim_ascii_end()).unwrap();
+        assert_eq!(event["method"], "ping");
+
+        // Answering keeps the session alive and the pings coming
+        let ack = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json, text/event-stream"))
+            .insert_header(("Mcp-Session-Id", session_id.as_str()))
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload(serde_json::json!({ "jsonrpc": "2.0", "id": event["id"], "result": {} }).to_string())
+            .to_request();
+        assert_eq!(test::call_service(&app, ack).await.status(), StatusCode::ACCEPTED);
+        let chunk = next_chunk(&mut body).await.unwrap();
+        assert!(String::from_utf8_lossy(&chunk).contains("\"ping\""));
+        assert!(session_manager.get_session(&session_id).await.is_some());
+
+        // Ignoring that one gets the session reaped and the stream closed
+        assert!(next_chunk(&mut body).await.is_none());
+        assert!(session_manager.get_session(&session_id).await.is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_resuming_from_evicted_event_requires_resync() {
+        let state = test_state(HttpConfig {
+            sse_heartbeat_ms: 0,
+            sse_replay_max_events: 2,
+            ..HttpConfig::default()
+        });
+        let hub = state.notification_hub.clone();
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let open_stream = |session_id: Option<&str>, last_event_id: Option<&str>| {
+            let mut req = test::TestRequest::get()
+                .uri("/mcp")
+                .insert_header(("Accept", "text/event-stream"));
+            if let Some(session_id) = session_id {
+                req = req.insert_header(("Mcp-Session-Id", session_id));
+            }
+            if let Some(id) = last_event_id {
+                req = req.insert_header(("Last-Event-ID", id));
+            }
+            req.to_request()
+        };
+        let resp = test::call_service(&app, open_stream(None, None)).await;
+        let session_id = resp.headers().get("Mcp-Session-Id").unwrap().to_str().unwrap().to_string();
+        let mut body = resp.into_body();
+
+        let mut ids = Vec::new();
+        for n in 0..3 {
+            let notification = JsonRpcNotification::new(
+                "notifications/message".to_string(),
+                Some(serde_json::json!({ "n": n })),
+            );
+            hub.send(&session_id, notification).await;
+            let chunk = String::from_utf8(next_chunk(&mut body).await.unwrap().to_vec()).unwrap();
+            let id = chunk.lines().find_map(|line| line.strip_prefix("id: ")).unwrap();
+            ids.push(id.to_string());
+        }
+        drop(body);
+
+        // The second event is still buffered: the third is replayed
+        let resp = test::call_service(&app, open_stream(Some(&session_id), Some(&ids[1]))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let mut body = resp.into_body();
+        let chunk = String::from_utf8(next_chunk(&mut body).await.unwrap().to_vec()).unwrap();
+        assert!(chunk.contains(&format!("id: {}", ids[2])));
+        assert!(chunk.contains("\"n\":2"));
+
+        // The first was evicted: the client is told to start over
+        let resp = test::call_service(&app, open_stream(Some(&session_id), Some(&ids[0]))).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert!(body["error"]["message"].as_str().unwrap().contains("re-sync"));
+    }
+
+    #[actix_web::test]
+    async fn test_resource_read_streamed_in_chunks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("notes.txt");
+        std::fs::write(&file, "abcdefghij".repeat(10)).unwrap();
+
+        let resource_manager = Arc::new(crate::server::features::resources::ResourceManager::new());
+        let protocol_handler = Arc::new(crate::protocol::handler::ProtocolHandler::new(
+            resource_manager.clone(),
+            Arc::new(crate::server::features::tools::ToolManager::new()),
+            Arc::new(crate::server::features::prompts::PromptManager::new()),
+            Arc::new(crate::client::features::sampling::SamplingManager::new()),
+        ));
+        protocol_handler.ready().await;
+        resource_manager
+            .register_provider(Box::new(
+                crate::server::features::resources::FileSystemProvider::new(
+                    temp_dir.path().to_path_buf(),
+                ),
+            ))
+            .await
+            .unwrap();
+
+        let state = AppState {
+            protocol_handler,
+            ..test_state(HttpConfig {
+                resource_stream_chunk_size: 32,
+                ..HttpConfig::default()
+            })
+        };
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+        let post = |body: serde_json::Value, session_id: Option<&str>| {
+            let mut req = test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json, text/event-stream"))
+                .set_json(&body);
+            if let Some(session_id) = session_id {
+                req = req.insert_header(("Mcp-Session-Id", session_id));
+            }
+            req.to_request()
+        };
+
+        let initialize = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": crate::protocol::PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "test", "version": "1.0"}
+            }
+        });
+        let resp = test::call_service(&app, post(initialize, None)).await;
+        let session_id = resp.headers().get("Mcp-Session-Id").unwrap().to_str().unwrap().to_string();
+        let initialized = serde_json::json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        test::call_service(&app, post(initialized, Some(&session_id))).await;
+
+        // Without the opt-in, the read is answered whole
+        let uri = format!("file://{}", file.display());
+        let read = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "resources/read",
+            "params": { "uri": uri }
+        });
+        let resp = test::call_service(&app, post(read, Some(&session_id))).await;
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "application/json");
+
+        let read = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 2,
+            "method": "resources/read",
+            "params": { "uri": uri, "_meta": { "stream": true } }
+        });
+        let resp = test::call_service(&app, post(read, Some(&session_id))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get("Content-Type").unwrap(), "text/event-stream");
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let events: Vec<serde_json::Value> = body
+            .split("\n\n")
+            .filter_map(|event| event.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+
+        let (response, chunks) = events.split_last().unwrap();
+        assert_eq!(chunks.len(), 4);
+        assert!(chunks.iter().all(|chunk| chunk["method"] == RESOURCE_CHUNK_NOTIFICATION));
+        let text: String = chunks
+            .iter()
+            .map(|chunk| chunk["params"]["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(text, "abcdefghij".repeat(10));
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["result"]["_meta"]["chunks"], 4);
+    }
+
+    #[actix_web::test]
+    async fn test_transport_errors_map_to_status_and_code() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig {
+            max_body_size: 64,
+            ..HttpConfig::default()
+        })))
+        .await;
+
+        let oversized = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "ping",
+            "params": { "padding": "x".repeat(100) }
+        });
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .set_json(&oversized)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], -32004);
+        assert_eq!(body["error"]["data"]["limit"], 64);
+
+        let req = test::TestRequest::delete()
+            .uri("/mcp")
+            .insert_header(("Mcp-Session-Id", "no-such-session"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], -32003);
+
+        let req = test::TestRequest::delete().uri("/mcp").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // Unknown sessions aren't silently replaced with new ones
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .insert_header(("Accept", "application/json"))
+            .insert_header(("Mcp-Session-Id", "no-such-session"))
+            .set_json(serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], -32003);
+
+        let req = test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .insert_header(("Mcp-Session-Id", "no-such-session"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_required_api_key_checked() {
+        let app = test::init_service(HttpTransport::create_app(AppState {
+            api_keys: Some(Arc::new(vec!["secret".to_string()])),
+            ..test_state(HttpConfig::default())
+        }))
+        .await;
+
+        let post = |key: Option<&str>| {
+            let mut req = test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json"));
+            if let Some(key) = key {
+                req = req.insert_header(("X-API-Key", key));
+            }
+            req.set_json(serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" }))
+                .to_request()
+        };
+
+        for key in [None, Some("wrong")] {
+            let resp = test::call_service(&app, post(key)).await;
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["error"]["code"], -32005);
+        }
+
+        let resp = test::call_service(&app, post(Some("secret"))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Accepted keys identify the caller without exposing the key
+        let state = AppState {
+            api_keys: Some(Arc::new(vec!["secret".to_string(), "other".to_string()])),
+            ..test_state(HttpConfig::default())
+        };
+        let principal = |key: &str| {
+            let req = test::TestRequest::default()
+                .insert_header(("Authorization", format!("Bearer {}", key)))
+                .to_http_request();
+            authenticate(&req, &state).unwrap().unwrap()
+        };
+        assert_eq!(principal("secret"), principal("secret"));
+        assert_ne!(principal("secret"), principal("other"));
+        assert!(!principal("secret").contains("secret"));
+
+        // Without required keys there is no authenticated identity
+        let req = test::TestRequest::default().to_http_request();
+        assert!(authenticate(&req, &test_state(HttpConfig::default())).unwrap().is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_post_content_type_checked() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig::default()))).await;
+
+        let post = |content_type: Option<&str>| {
+            let mut req = test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json"));
+            if let Some(content_type) = content_type {
+                req = req.insert_header(("Content-Type", content_type));
+            }
+            req.set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+                .to_request()
+        };
+
+        for accepted in ["application/json", "application/json; charset=utf-8", "Application/JSON"] {
+            let resp = test::call_service(&app, post(Some(accepted))).await;
+            assert_eq!(resp.status(), StatusCode::OK, "{}", accepted);
+        }
+
+        for rejected in [Some("text/plain"), Some("multipart/form-data; boundary=x"), None] {
+            let resp = test::call_service(&app, post(rejected)).await;
+            assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE, "{:?}", rejected);
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["error"]["code"], -32700);
+        }
+
+        // The check can be turned off for lenient clients
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig {
+            require_json_content_type: false,
+            ..HttpConfig::default()
+        })))
+        .await;
+        let resp = test::call_service(&app, post(Some("text/plain"))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_new_sessions_rejected_past_limit() {
+        let state = test_state(HttpConfig {
+            max_sessions: 2,
+            ..HttpConfig::default()
+        });
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        let ping = || {
+            test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json"))
+                .insert_header(("Content-Type", "application/json"))
+                .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+                .to_request()
+        };
+
+        // Each request without Mcp-Session-Id creates a new session
+        for _ in 0..2 {
+            let resp = test::call_service(&app, ping()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+
+        let resp = test::call_service(&app, ping()).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["message"], "Too many active sessions");
+    }
+
+    #[actix_web::test]
+    async fn test_deeply_nested_body_rejected() {
+        let app = test::init_service(HttpTransport::create_app(test_state(HttpConfig {
+            max_json_depth: 8,
+            ..HttpConfig::default()
+        })))
+        .await;
+        let post = |params: String| {
+            test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json"))
+                .insert_header(("Content-Type", "application/json"))
+                .set_payload(format!(
+                    r#"{{"jsonrpc":"2.0","id":1,"method":"ping","params":{}}}"#,
+                    params
+                ))
+                .to_request()
+        };
+
+        let resp = test::call_service(&app, post(format!("{}{}", "[".repeat(20), "]".repeat(20)))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"]["code"], -32700);
+
+        let resp = test::call_service(&app, post(r#"{"a":{"b":[1,2]}}"#.to_string())).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_stateless_requests_create_no_sessions() {
+        let state = test_state(HttpConfig {
+            stateless: true,
+            ..HttpConfig::default()
+        });
+        let session_manager = state.session_manager.clone();
+        let app = test::init_service(HttpTransport::create_app(state)).await;
+
+        for _ in 0..3 {
+            let req = test::TestRequest::post()
+                .uri("/mcp")
+                .insert_header(("Accept", "application/json, text/event-stream"))
+                .insert_header(("Content-Type", "application/json"))
+                .set_payload(r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#)
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert!(resp.headers().get("Mcp-Session-Id").is_none());
+            assert_eq!(
+                resp.headers().get("Content-Type").unwrap(),
+                "application/json"
+            );
+            let body: serde_json::Value = test::read_body_json(resp).await;
+            assert_eq!(body["id"], 1);
+            assert!(body["error"].is_null());
+        }
+        assert_eq!(session_manager.session_count().await, 0);
+
+        // There is no stream to open without a session
+        let req = test::TestRequest::get()
+            .uri("/mcp")
+            .insert_header(("Accept", "text/event-stream"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[actix_web::test]
+    async fn test_request_metadata_reflects_request() {
+        let peer: SocketAddr = "192.0.2.7:4100".parse().unwrap();
+        let req = test::TestRequest::post()
+            .uri("/mcp")
+            .peer_addr(peer)
+            .insert_header(("User-Agent", "inspector/1.2"))
+            .insert_header(("X-Trace", "abc"))
+            .insert_header(("Authorization", "Bearer secret"))
+            .to_http_request();
+
+        let metadata = request_metadata(&req);
+        assert_eq!(metadata.user_agent.as_deref(), Some("inspector/1.2"));
+        assert_eq!(metadata.source_addr, Some(peer));
+        assert_eq!(metadata.headers["x-trace"], "abc");
+        assert!(!metadata.headers.contains_key("authorization"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Server-initiated keepalive pings.
+//!
+//! Sessions with an open stream are periodically sent a `ping` request. A
+//! session that doesn't answer within the configured timeout is considered
+//! stale (e.g. a half-open connection) and can be reaped.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::protocol::{JsonRpcRequest, RequestId};
+
+/// A ping awaiting the client's response
+#[derive(Debug, Clone)]
+struct OutstandingPing {
+    id: RequestId,
+    sent_at: Instant,
+}
+
+/// Tracks the outstanding ping of each session
+#[derive(Debug, Default)]
+pub struct PingTracker {
+    outstanding: Mutex<HashMap<String, OutstandingPing>>,
+}
+
+impl PingTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a ping request for a session and record it as outstanding
+    ///
+    /// Any ping still outstanding for the session is replaced.
+    pub fn start(&self, session_id: &str) -> JsonRpcRequest {
+        let id = serde_json::json!(format!("ping-{}", uuid::Uuid::new_v4()));
+        let mut outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        outstanding.insert(
+            session_id.to_string(),
+            OutstandingPing {
+                id: id.clone(),
+                sent_at: Instant::now(),
+            },
+        );
+
+        JsonRpcRequest::new(id, "ping".to_string(), None)
+    }
+
+    /// Record a client's response, returning true if it answered the session's ping
+    pub fn acknowledge(&self, session_id: &str, id: &RequestId) -> bool {
+        let mut outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        match outstanding.get(session_id) {
+            Some(ping) if &ping.id == id => {
+                outstanding.remove(session_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Check whether the session's ping has gone unanswered for longer than `timeout`
+    pub fn is_stale(&self, session_id: &str, timeout: Duration) -> bool {
+        let outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        outstanding
+            .get(session_id)
+            .is_some_and(|ping| ping.sent_at.elapsed() >= timeout)
+    }
+
+    /// Check whether the session has a ping awaiting a response
+    pub fn is_outstanding(&self, session_id: &str) -> bool {
+        let outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        outstanding.contains_key(session_id)
+    }
+
+    /// Drop any outstanding ping for a session
+    pub fn forget(&self, session_id: &str) {
+        let mut outstanding = self.outstanding.lock().unwrap_or_else(|e| e.into_inner());
+        outstanding.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acknowledge_matches_outstanding_ping() {
+        let tracker = PingTracker::new();
+        let ping = tracker.start("session-a");
+        assert_eq!(ping.method, "ping");
+        assert!(tracker.is_outstanding("session-a"));
+
+        // Responses to other IDs or sessions don't count
+        assert!(!tracker.acknowledge("session-a", &serde_json::json!(1)));
+        assert!(!tracker.acknowledge("session-b", &ping.id));
+
+        assert!(tracker.acknowledge("session-a", &ping.id));
+        assert!(!tracker.is_outstanding("session-a"));
+        assert!(!tracker.is_stale("session-a", Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_ping_goes_stale() {
+        let tracker = PingTracker::new();
+        tracker.start("session-a");
+        assert!(!tracker.is_stale("session-a", Duration::from_secs(60)));
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(tracker.is_stale("session-a", Duration::from_millis(10)));
+
+        tracker.forget("session-a");
+        assert!(!tracker.is_stale("session-a", Duration::ZERO));
+    }
+}
@@ -4,15 +4,24 @@
 //! including HTTP (with streaming) and STDIO transports as defined in the specification.
 
 pub mod http;
+pub mod keepalive;
+pub mod notifications;
+pub mod pending;
+pub mod replay;
 pub mod stdio;
 pub mod session;
 
 use async_trait::async_trait;
-use std::sync::Arc;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
-use crate::error::Result;
-use crate::protocol::AnyJsonRpcMessage;
+use crate::error::{McpError, Result, TransportError};
+use crate::protocol::{AnyJsonRpcMessage, JsonRpcNotification, JsonRpcRequest};
 
 /// Transport trait for different communication methods
 #[async_trait]
@@ -26,8 +35,60 @@ pub trait Transport: Send + Sync {
     /// Stop the transport
     async fn stop(&self) -> Result<()>;
 
+    /// Push a server-initiated notification to connected clients
+    ///
+    /// Returns the number of clients the notification was delivered to.
+    async fn send_notification(
+        &self,
+        notification: JsonRpcNotification,
+        target: NotificationTarget,
+    ) -> Result<usize>;
+
+    /// Push a server-initiated request to connected clients
+    ///
+    /// Their responses come back as ordinary incoming messages. Returns the
+    /// number of clients the request was delivered to; transports that can't
+    /// carry requests toward the client fail.
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        _target: NotificationTarget,
+    ) -> Result<usize> {
+        Err(TransportError::InvalidMessage(format!(
+            "{} transport cannot send {} requests to clients",
+            self.info().transport_type,
+            request.method
+        ))
+        .into())
+    }
+
+    /// Tell connected clients the server is about to shut down
+    ///
+    /// Returns the number of clients notified.
+    async fn notify_shutdown(&self, notification: JsonRpcNotification) -> Result<usize> {
+        self.send_notification(notification, NotificationTarget::Broadcast)
+            .await
+    }
+
     /// Get transport information
     fn info(&self) -> TransportInfo;
+
+    /// Check whether the transport closed its incoming channel on purpose
+    ///
+    /// Any other closure outside of a stop is reported as a transport failure.
+    fn closed_normally(&self) -> bool {
+        false
+    }
+}
+
+/// Recipients of a server-initiated notification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationTarget {
+    /// Every connected client
+    Broadcast,
+
+    /// The client owning the given session
+    Session(String),
 }
 
 /// Transport message containing the actual JSON-RPC message and metadata
@@ -46,6 +107,12 @@ pub struct TransportMessage {
     pub metadata: TransportMetadata,
 }
 
+/// Most headers captured into [`TransportMetadata`]
+pub const MAX_METADATA_HEADERS: usize = 64;
+
+/// Most bytes of header names and values captured into [`TransportMetadata`]
+pub const MAX_METADATA_HEADER_BYTES: usize = 16 * 1024;
+
 /// Transport metadata
 #[derive(Debug, Clone)]
 pub struct TransportMetadata {
@@ -63,7 +130,7 @@ pub struct TransportMetadata {
 }
 
 /// Transport information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TransportInfo {
     /// Transport type
     pub transport_type: TransportType,
@@ -79,12 +146,47 @@ pub struct TransportInfo {
 }
 
 /// Transport type enumeration
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Also used by the configuration, so it reads and prints as the lowercase
+/// name used there (`http`, `stdio`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
 pub enum TransportType {
     Http,
     Stdio,
 }
 
+impl TransportType {
+    /// Name of the transport as written in configuration files
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransportType::Http => "http",
+            TransportType::Stdio => "stdio",
+        }
+    }
+}
+
+impl fmt::Display for TransportType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for TransportType {
+    type Err = McpError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "http" => Ok(TransportType::Http),
+            "stdio" => Ok(TransportType::Stdio),
+            _ => Err(McpError::Config(format!(
+                "Unknown transport type '{}' (expected http or stdio)",
+                s
+            ))),
+        }
+    }
+}
+
 impl Default for TransportMetadata {
     fn default() -> Self {
         Self {
@@ -96,6 +198,38 @@ impl Default for TransportMetadata {
     }
 }
 
+impl TransportMetadata {
+    /// Capture request headers, keyed by lowercase name
+    ///
+    /// At most [`MAX_METADATA_HEADERS`] headers and [`MAX_METADATA_HEADER_BYTES`]
+    /// of names and values are kept; the rest are dropped with a warning.
+    pub fn capture_headers<'a>(&mut self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let mut bytes: usize = self.headers.iter().map(|(name, value)| name.len() + value.len()).sum();
+        let mut dropped = 0;
+
+        for (name, value) in headers {
+            let size = name.len() + value.len();
+            if self.headers.len() >= MAX_METADATA_HEADERS || bytes + size > MAX_METADATA_HEADER_BYTES {
+                dropped += 1;
+                continue;
+            }
+            if let Some(previous) = self.headers.insert(name.to_ascii_lowercase(), value.to_string()) {
+                bytes -= name.len() + previous.len();
+            }
+            bytes += size;
+        }
+
+        if dropped > 0 {
+            tracing::warn!(
+                "Dropped {} request headers beyond the metadata limit of {} headers / {} bytes",
+                dropped,
+                MAX_METADATA_HEADERS,
+                MAX_METADATA_HEADER_BYTES
+            );
+        }
+    }
+}
+
 impl TransportMessage {
     /// Create a new transport message
     pub fn new(message: AnyJsonRpcMessage) -> Self {
@@ -126,6 +260,16 @@ impl TransportMessage {
             metadata,
         }
     }
+
+    /// Build the protocol request context identifying the sender
+    pub fn request_context(&self) -> crate::protocol::handler::RequestContext {
+        crate::protocol::handler::RequestContext {
+            session_id: self.session_id.clone(),
+            client_id: self.client_id.clone(),
+            metadata: Some(self.metadata.clone()),
+            ..Default::default()
+        }
+    }
 }
 
 /// Transport factory for creating transport instances
@@ -155,13 +299,44 @@ impl TransportFactory {
             }
         }
     }
+
+    /// Create a transport that dispatches requests to the given protocol handler
+    pub fn create_with_handler(
+        config: &crate::config::TransportConfig,
+        auth: &crate::config::AuthConfig,
+        protocol_handler: Arc<crate::protocol::handler::ProtocolHandler>,
+        id_generator: crate::utils::ids::SharedIdGenerator,
+    ) -> Result<Arc<dyn Transport>> {
+        match config.transport_type {
+            crate::config::TransportType::Http => {
+                let http_config = config.http.as_ref().ok_or_else(|| {
+                    crate::error::McpError::Config(
+                        "HTTP transport selected but no HTTP config provided".to_string(),
+                    )
+                })?;
+
+                let mut transport =
+                    http::HttpTransport::with_id_generator(http_config.clone(), id_generator)?
+                    .with_protocol_handler(protocol_handler)
+                    .with_key_scopes(auth.key_scopes.clone());
+                if auth.enabled && auth.method == crate::config::AuthMethod::ApiKey {
+                    transport = transport.with_required_api_keys(auth.api_keys.clone());
+                }
+                Ok(Arc::new(transport))
+            }
+            // STDIO messages are dispatched by the server loop
+            crate::config::TransportType::Stdio => Self::create(config),
+        }
+    }
 }
 
 /// Transport manager for handling multiple transports
 pub struct TransportManager {
     transports: Vec<Arc<dyn Transport>>,
-    message_sender: mpsc::Sender<TransportMessage>,
+    message_sender: Option<mpsc::Sender<TransportMessage>>,
     message_receiver: Option<mpsc::Receiver<TransportMessage>>,
+    stopping: Arc<AtomicBool>,
+    failed_transports: Arc<Mutex<Vec<TransportInfo>>>,
 }
 
 impl TransportManager {
@@ -171,8 +346,10 @@ impl TransportManager {
         
         Self {
             transports: Vec::new(),
-            message_sender: sender,
+            message_sender: Some(sender),
             message_receiver: Some(receiver),
+            stopping: Arc::new(AtomicBool::new(false)),
+            failed_transports: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -182,32 +359,111 @@ impl TransportManager {
     }
 
     /// Start all transports
+    ///
+    /// The returned receiver closes once every transport has closed its
+    /// incoming channel (e.g. STDIO reached EOF). Transports that close it
+    /// without being stopped, and not [normally](Transport::closed_normally),
+    /// are recorded in [`failed_transports`](Self::failed_transports).
     pub async fn start(&mut self) -> Result<mpsc::Receiver<TransportMessage>> {
+        let (Some(sender), Some(message_receiver)) =
+            (self.message_sender.take(), self.message_receiver.take())
+        else {
+            return Err(crate::error::McpError::Transport(
+                crate::error::TransportError::ConnectionFailed(
+                    "Message receiver already taken".to_string()
+                )
+            ));
+        };
+
         for transport in &self.transports {
             let (mut receiver, _sender) = transport.start().await?;
-            let message_sender = self.message_sender.clone();
+            let message_sender = sender.clone();
+            let transport = transport.clone();
+            let stopping = self.stopping.clone();
+            let failed_transports = self.failed_transports.clone();
             
             // Spawn a task to forward messages from this transport
             tokio::spawn(async move {
                 while let Some(message) = receiver.recv().await {
                     if let Err(e) = message_sender.send(message).await {
                         tracing::error!("Failed to forward transport message: {}", e);
-                        break;
+                        return;
                     }
                 }
+
+                // Record the failure before our sender drops, so it is visible
+                // by the time the merged channel closes
+                if !stopping.load(Ordering::Acquire) && !transport.closed_normally() {
+                    let info = transport.info();
+                    tracing::error!(
+                        "{} transport at {} closed its message channel unexpectedly",
+                        info.transport_type,
+                        info.address
+                    );
+                    failed_transports
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(info);
+                }
+                drop(message_sender);
             });
         }
 
-        self.message_receiver.take()
-            .ok_or_else(|| crate::error::McpError::Transport(
-                crate::error::TransportError::ConnectionFailed(
-                    "Message receiver already taken".to_string()
-                )
-            ))
+        Ok(message_receiver)
+    }
+
+    /// Push a notification through every transport
+    ///
+    /// Returns the total number of clients it was delivered to.
+    pub async fn send_notification(
+        &self,
+        notification: JsonRpcNotification,
+        target: NotificationTarget,
+    ) -> usize {
+        let mut delivered = 0;
+        for transport in &self.transports {
+            match transport
+                .send_notification(notification.clone(), target.clone())
+                .await
+            {
+                Ok(count) => delivered += count,
+                Err(e) => tracing::warn!("Failed to send {}: {}", notification.method, e),
+            }
+        }
+        delivered
+    }
+
+    /// Push a server-initiated request through every transport
+    ///
+    /// Returns the total number of clients it was delivered to.
+    pub async fn send_request(&self, request: JsonRpcRequest, target: NotificationTarget) -> usize {
+        let mut delivered = 0;
+        for transport in &self.transports {
+            match transport.send_request(request.clone(), target.clone()).await {
+                Ok(count) => delivered += count,
+                Err(e) => tracing::warn!("Failed to send {} request: {}", request.method, e),
+            }
+        }
+        delivered
+    }
+
+    /// Tell clients on every transport the server is about to shut down
+    ///
+    /// Returns the total number of clients notified.
+    pub async fn notify_shutdown(&self, notification: JsonRpcNotification) -> usize {
+        let mut notified = 0;
+        for transport in &self.transports {
+            match transport.notify_shutdown(notification.clone()).await {
+                Ok(count) => notified += count,
+                Err(e) => tracing::error!("Failed to send shutdown notification: {}", e),
+            }
+        }
+        notified
     }
 
     /// Stop all transports
     pub async fn stop(&self) -> Result<()> {
+        self.stopping.store(true, Ordering::Release);
         for transport in &self.transports {
             if let Err(e) = transport.stop().await {
                 tracing::error!("Failed to stop transport: {}", e);
@@ -220,6 +476,14 @@ impl TransportManager {
     pub fn get_transport_info(&self) -> Vec<TransportInfo> {
         self.transports.iter().map(|t| t.info()).collect()
     }
+
+    /// Get the transports that closed their message channel unexpectedly
+    pub fn failed_transports(&self) -> Vec<TransportInfo> {
+        self.failed_transports
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
 }
 
 impl Default for TransportManager {
@@ -227,3 +491,39 @@ impl Default for TransportManager {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_type_string_round_trip() {
+        for (transport_type, name) in [(TransportType::Http, "http"), (TransportType::Stdio, "stdio")] {
+            assert_eq!(transport_type.to_string(), name);
+            assert_eq!(name.parse::<TransportType>().unwrap(), transport_type);
+            assert_eq!(name.to_uppercase().parse::<TransportType>().unwrap(), transport_type);
+
+            let json = serde_json::to_value(transport_type).unwrap();
+            assert_eq!(json, serde_json::json!(name));
+            assert_eq!(serde_json::from_value::<TransportType>(json).unwrap(), transport_type);
+        }
+
+        assert!("websocket".parse::<TransportType>().is_err());
+    }
+
+    #[test]
+    fn test_captured_headers_are_bounded() {
+        let names: Vec<String> = (0..1000).map(|i| format!("X-Header-{}", i)).collect();
+        let mut metadata = TransportMetadata::default();
+        metadata.capture_headers(names.iter().map(|name| (name.as_str(), "value")));
+        assert_eq!(metadata.headers.len(), MAX_METADATA_HEADERS);
+        assert_eq!(metadata.headers["x-header-0"], "value");
+
+        let large = "v".repeat(4096);
+        let mut metadata = TransportMetadata::default();
+        metadata.capture_headers(names.iter().map(|name| (name.as_str(), large.as_str())));
+        let bytes: usize = metadata.headers.iter().map(|(name, value)| name.len() + value.len()).sum();
+        assert!(bytes <= MAX_METADATA_HEADER_BYTES);
+        assert_eq!(metadata.headers.len(), 3);
+    }
+}
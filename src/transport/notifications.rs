@@ -0,0 +1,181 @@
+//! Notification hub for server-initiated messages.
+//!
+//! Each session gets a broadcast channel that server components publish
+//! notifications to and that transports (e.g. the HTTP SSE stream) consume.
+//! With replay buffers attached, every notification is buffered as it is
+//! published, so a client that was disconnected at the time can still
+//! resume its stream and receive it.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, error};
+
+use crate::protocol::JsonRpcNotification;
+use crate::transport::replay::ReplayBuffers;
+
+/// Default number of notifications buffered per session
+const DEFAULT_CAPACITY: usize = 256;
+
+/// A notification as published to a session
+#[derive(Debug, Clone)]
+pub struct PublishedNotification {
+    /// ID of the buffered event, when the hub keeps events for replay
+    pub event_id: Option<String>,
+
+    /// The notification itself
+    pub notification: JsonRpcNotification,
+}
+
+/// Per-session notification channels
+pub struct NotificationHub {
+    /// Session ID -> notification channel
+    channels: RwLock<HashMap<String, broadcast::Sender<PublishedNotification>>>,
+
+    /// Buffered notifications per channel
+    capacity: usize,
+
+    /// Where published notifications are kept for stream resumption
+    replay: Option<Arc<ReplayBuffers>>,
+}
+
+impl NotificationHub {
+    /// Create a new notification hub
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create a new notification hub buffering up to `capacity` notifications per session
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            channels: RwLock::new(HashMap::new()),
+            capacity: capacity.max(1),
+            replay: None,
+        }
+    }
+
+    /// Buffer every published notification in `replay`
+    pub fn with_replay(mut self, replay: Arc<ReplayBuffers>) -> Self {
+        self.replay = Some(replay);
+        self
+    }
+
+    /// Subscribe to notifications for a session, creating its channel if needed
+    pub async fn subscribe(&self, session_id: &str) -> broadcast::Receiver<PublishedNotification> {
+        let mut channels = self.channels.write().await;
+        channels
+            .entry(session_id.to_string())
+            .or_insert_with(|| broadcast::channel(self.capacity).0)
+            .subscribe()
+    }
+
+    /// Send a notification to one session, returning how many streams received it
+    pub async fn send(&self, session_id: &str, notification: JsonRpcNotification) -> usize {
+        let channels = self.channels.read().await;
+        match channels.get(session_id) {
+            Some(sender) => sender
+                .send(self.publish(session_id, notification))
+                .unwrap_or(0),
+            None => {
+                debug!("No notification channel for session {}", session_id);
+                0
+            }
+        }
+    }
+
+    /// Send a notification to every session, returning how many streams received it
+    pub async fn broadcast(&self, notification: JsonRpcNotification) -> usize {
+        let channels = self.channels.read().await;
+        channels
+            .iter()
+            .map(|(session_id, sender)| {
+                sender
+                    .send(self.publish(session_id, notification.clone()))
+                    .unwrap_or(0)
+            })
+            .sum()
+    }
+
+    /// Buffer a notification for a session's replay, even if no stream is open
+    fn publish(&self, session_id: &str, notification: JsonRpcNotification) -> PublishedNotification {
+        let event_id = self.replay.as_ref().and_then(|replay| {
+            match serde_json::to_string(&notification) {
+                Ok(json) => Some(replay.record(session_id, &json)),
+                Err(e) => {
+                    error!("Failed to serialize notification for replay: {}", e);
+                    None
+                }
+            }
+        });
+        PublishedNotification {
+            event_id,
+            notification,
+        }
+    }
+
+    /// Drop a session's channel, ending any open streams for it
+    pub async fn remove_session(&self, session_id: &str) {
+        let mut channels = self.channels.write().await;
+        channels.remove(session_id);
+    }
+
+    /// Get the number of sessions with a notification channel
+    pub async fn session_count(&self) -> usize {
+        let channels = self.channels.read().await;
+        channels.len()
+    }
+}
+
+impl Default for NotificationHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(method: &str) -> JsonRpcNotification {
+        JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_to_session() {
+        let hub = NotificationHub::new();
+        let mut a = hub.subscribe("a").await;
+        let mut b = hub.subscribe("b").await;
+
+        assert_eq!(hub.send("a", notification("notifications/test")).await, 1);
+        assert_eq!(a.recv().await.unwrap().notification.method, "notifications/test");
+        assert!(b.try_recv().is_err());
+
+        assert_eq!(hub.broadcast(notification("notifications/all")).await, 2);
+        assert_eq!(b.recv().await.unwrap().notification.method, "notifications/all");
+
+        hub.remove_session("a").await;
+        assert_eq!(hub.send("a", notification("notifications/test")).await, 0);
+        assert_eq!(hub.session_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_notifications_buffered_while_disconnected() {
+        let replay = Arc::new(ReplayBuffers::new(10, 1024));
+        let hub = NotificationHub::new().with_replay(replay.clone());
+        let mut stream = hub.subscribe("a").await;
+
+        hub.send("a", notification("notifications/first")).await;
+        let first = stream.recv().await.unwrap().event_id.unwrap();
+        drop(stream);
+
+        // Nobody is listening, but the event is still kept for the client
+        assert_eq!(hub.send("a", notification("notifications/missed")).await, 0);
+        let replayed = replay.replay_after("a", &first).unwrap();
+        assert_eq!(replayed.len(), 1);
+        assert!(replayed[0].1.contains("notifications/missed"));
+    }
+}
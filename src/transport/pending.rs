@@ -0,0 +1,178 @@
+//! Pending response registry.
+//!
+//! Correlates outbound responses with the HTTP handlers waiting for them,
+//! keyed by session ID and JSON-RPC request ID so responses can arrive in
+//! any order.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::debug;
+
+use crate::error::{McpError, Result, TransportError};
+use crate::protocol::{JsonRpcResponse, RequestId};
+
+/// Session ID and serialized request ID
+type PendingKey = (String, String);
+
+type PendingMap = HashMap<PendingKey, oneshot::Sender<JsonRpcResponse>>;
+
+/// Registry of requests awaiting a response
+#[derive(Default)]
+pub struct PendingResponses {
+    pending: Arc<Mutex<PendingMap>>,
+}
+
+/// A registered request waiting for its response
+///
+/// Dropping it (e.g. when the waiting handler is cancelled) removes the entry.
+pub struct PendingResponse {
+    key: PendingKey,
+    receiver: oneshot::Receiver<JsonRpcResponse>,
+    pending: Arc<Mutex<PendingMap>>,
+}
+
+fn pending_key(session_id: &str, request_id: &RequestId) -> PendingKey {
+    (session_id.to_string(), request_id.to_string())
+}
+
+impl PendingResponses {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a request so its response can be delivered later
+    pub fn register(&self, session_id: &str, request_id: &RequestId) -> Result<PendingResponse> {
+        let key = pending_key(session_id, request_id);
+        let (sender, receiver) = oneshot::channel();
+
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.contains_key(&key) {
+            return Err(McpError::invalid_request(format!(
+                "Request ID {} is already pending for session {}",
+                request_id, session_id
+            )));
+        }
+        pending.insert(key.clone(), sender);
+
+        Ok(PendingResponse {
+            key,
+            receiver,
+            pending: self.pending.clone(),
+        })
+    }
+
+    /// Deliver a response to the handler waiting on it
+    ///
+    /// Returns false if nothing is waiting (unknown ID, timed out or cancelled).
+    pub fn complete(&self, session_id: &str, response: JsonRpcResponse) -> bool {
+        let key = pending_key(session_id, &response.id);
+        let sender = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.remove(&key)
+        };
+
+        match sender {
+            Some(sender) => sender.send(response).is_ok(),
+            None => {
+                debug!(
+                    "Dropping response {} for session {} with no pending request",
+                    key.1, session_id
+                );
+                false
+            }
+        }
+    }
+
+    /// Get the number of requests awaiting a response
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Check whether no requests are awaiting a response
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl PendingResponse {
+    /// Wait for the response, giving up after `timeout`
+    pub async fn wait(mut self, timeout: Duration) -> Result<JsonRpcResponse> {
+        match tokio::time::timeout(timeout, &mut self.receiver).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err(TransportError::ConnectionLost(format!(
+                "Response channel closed for request {}",
+                self.key.1
+            ))
+            .into()),
+            Err(_) => Err(McpError::internal_error(format!(
+                "Timed out waiting for response to request {}",
+                self.key.1
+            ))),
+        }
+    }
+}
+
+impl Drop for PendingResponse {
+    fn drop(&mut self) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(id: i64, value: &str) -> JsonRpcResponse {
+        JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            id: serde_json::json!(id),
+            result: Some(serde_json::json!({ "value": value })),
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_out_of_order_delivery() {
+        let registry = Arc::new(PendingResponses::new());
+        let first = registry.register("session-a", &serde_json::json!(1)).unwrap();
+        let second = registry.register("session-a", &serde_json::json!(2)).unwrap();
+        let other_session = registry.register("session-b", &serde_json::json!(1)).unwrap();
+        assert_eq!(registry.len(), 3);
+
+        let timeout = Duration::from_secs(1);
+        let first = tokio::spawn(first.wait(timeout));
+        let second = tokio::spawn(second.wait(timeout));
+        let other_session = tokio::spawn(other_session.wait(timeout));
+
+        // Responses arrive in reverse order
+        assert!(registry.complete("session-b", response(1, "b1")));
+        assert!(registry.complete("session-a", response(2, "a2")));
+        assert!(registry.complete("session-a", response(1, "a1")));
+
+        assert_eq!(first.await.unwrap().unwrap().result.unwrap()["value"], "a1");
+        assert_eq!(second.await.unwrap().unwrap().result.unwrap()["value"], "a2");
+        assert_eq!(other_session.await.unwrap().unwrap().result.unwrap()["value"], "b1");
+        assert!(registry.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_removes_pending_entry() {
+        let registry = PendingResponses::new();
+        let pending = registry.register("session-a", &serde_json::json!("req")).unwrap();
+        assert!(registry.register("session-a", &serde_json::json!("req")).is_err());
+
+        assert!(pending.wait(Duration::from_millis(10)).await.is_err());
+        assert!(registry.is_empty());
+
+        // A late response is dropped rather than delivered
+        let late = JsonRpcResponse {
+            id: serde_json::json!("req"),
+            ..response(0, "late")
+        };
+        assert!(!registry.complete("session-a", late));
+    }
+}
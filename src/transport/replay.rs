@@ -0,0 +1,141 @@
+//! Bounded per-session buffers of sent SSE events for stream resumption.
+//!
+//! Each event published to a session is tagged with an ID and kept so
+//! a client reconnecting with `Last-Event-ID` can receive what it missed.
+//! Buffers are bounded by event count and total payload size; the oldest
+//! events are evicted first. Resuming from an evicted event fails with
+//! [`ReplayError::Evicted`], telling the client to re-sync from scratch.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Why a session's stream can't be resumed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The event is older than anything still buffered (or was never sent)
+    Evicted(String),
+}
+
+/// One session's buffered events
+#[derive(Debug, Default)]
+struct SessionBuffer {
+    /// ID of the next event
+    next_id: u64,
+
+    /// Buffered `(id, payload)` pairs, oldest first
+    events: VecDeque<(u64, String)>,
+
+    /// Total payload bytes buffered
+    bytes: usize,
+}
+
+/// Replay buffers of every session
+#[derive(Debug)]
+pub struct ReplayBuffers {
+    max_events: usize,
+    max_bytes: usize,
+    sessions: Mutex<HashMap<String, SessionBuffer>>,
+}
+
+impl ReplayBuffers {
+    /// Create buffers holding at most `max_events` events and `max_bytes` bytes per session
+    pub fn new(max_events: usize, max_bytes: usize) -> Self {
+        Self {
+            max_events,
+            max_bytes,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Buffer an event for a session, returning its ID
+    pub fn record(&self, session_id: &str, payload: &str) -> String {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let buffer = sessions.entry(session_id.to_string()).or_default();
+
+        let id = buffer.next_id;
+        buffer.next_id += 1;
+        buffer.events.push_back((id, payload.to_string()));
+        buffer.bytes += payload.len();
+
+        while buffer.events.len() > self.max_events || buffer.bytes > self.max_bytes {
+            match buffer.events.pop_front() {
+                Some((_, evicted)) => buffer.bytes -= evicted.len(),
+                None => break,
+            }
+        }
+
+        id.to_string()
+    }
+
+    /// Get the events sent after `last_event_id`, oldest first
+    pub fn replay_after(
+        &self,
+        session_id: &str,
+        last_event_id: &str,
+    ) -> std::result::Result<Vec<(String, String)>, ReplayError> {
+        let evicted = || ReplayError::Evicted(last_event_id.to_string());
+        let last_id: u64 = last_event_id.parse().map_err(|_| evicted())?;
+
+        let sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        let buffer = sessions.get(session_id).ok_or_else(evicted)?;
+
+        // The event itself must still be buffered, or be the last one ever
+        // sent if everything after it was evicted too
+        let retained = buffer.events.iter().any(|(id, _)| *id == last_id)
+            || (buffer.events.is_empty() && last_id + 1 == buffer.next_id);
+        if !retained {
+            return Err(evicted());
+        }
+
+        Ok(buffer
+            .events
+            .iter()
+            .filter(|(id, _)| *id > last_id)
+            .map(|(id, payload)| (id.to_string(), payload.clone()))
+            .collect())
+    }
+
+    /// Drop a session's buffer
+    pub fn forget(&self, session_id: &str) {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+        sessions.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replay_after_returns_newer_events() {
+        let buffers = ReplayBuffers::new(10, 1024);
+        let first = buffers.record("session-a", "one");
+        buffers.record("session-a", "two");
+        buffers.record("session-a", "three");
+
+        let replayed = buffers.replay_after("session-a", &first).unwrap();
+        let payloads: Vec<_> = replayed.iter().map(|(_, payload)| payload.as_str()).collect();
+        assert_eq!(payloads, ["two", "three"]);
+
+        assert!(buffers.replay_after("session-b", &first).is_err());
+    }
+
+    #[test]
+    fn test_buffers_bounded_by_count_and_bytes() {
+        let buffers = ReplayBuffers::new(2, 1024);
+        let first = buffers.record("session-a", "one");
+        let second = buffers.record("session-a", "two");
+        buffers.record("session-a", "three");
+        assert_eq!(
+            buffers.replay_after("session-a", &first),
+            Err(ReplayError::Evicted(first.clone()))
+        );
+        assert_eq!(buffers.replay_after("session-a", &second).unwrap().len(), 1);
+
+        let buffers = ReplayBuffers::new(10, 8);
+        let first = buffers.record("session-a", "12345");
+        let second = buffers.record("session-a", "67890");
+        assert!(buffers.replay_after("session-a", &first).is_err());
+        assert!(buffers.replay_after("session-a", &second).unwrap().is_empty());
+    }
+}
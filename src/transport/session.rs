@@ -6,8 +6,12 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::error::{Result, TransportError};
+use crate::utils::clock::{system_clock, SharedClock};
+use crate::utils::ids::{id_generator, SharedIdGenerator};
 
 /// Session information
 #[derive(Debug, Clone)]
@@ -70,13 +74,31 @@ pub struct SessionManager {
 
     /// Cleanup task handle
     cleanup_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+
+    /// Time source for activity and expiry checks
+    clock: SharedClock,
+
+    /// Publishes the IDs of removed or expired sessions
+    removal_sender: broadcast::Sender<String>,
+
+    /// Maximum number of concurrent sessions (unbounded when unset)
+    max_sessions: Option<usize>,
+
+    /// Fail reads of session data that doesn't deserialize instead of ignoring it
+    strict_data: bool,
+
+    /// Source of new session IDs
+    id_generator: SharedIdGenerator,
 }
 
 impl Session {
     /// Create a new session
     pub fn new(id: String) -> Self {
-        let now = Instant::now();
+        Self::new_at(id, Instant::now())
+    }
 
+    /// Create a new session stamped with the given time
+    pub fn new_at(id: String, now: Instant) -> Self {
         Self {
             id,
             created_at: now,
@@ -89,12 +111,22 @@ impl Session {
 
     /// Update last activity time
     pub fn touch(&mut self) {
-        self.last_activity = Instant::now();
+        self.touch_at(Instant::now());
+    }
+
+    /// Update last activity time to the given instant
+    pub fn touch_at(&mut self, now: Instant) {
+        self.last_activity = now;
     }
 
     /// Check if session is expired
     pub fn is_expired(&self, timeout: Duration) -> bool {
-        self.last_activity.elapsed() > timeout
+        self.is_expired_at(timeout, Instant::now())
+    }
+
+    /// Check if session is expired as of the given instant
+    pub fn is_expired_at(&self, timeout: Duration, now: Instant) -> bool {
+        now.saturating_duration_since(self.last_activity) > timeout
     }
 
     /// Set client information
@@ -121,15 +153,60 @@ impl Session {
     pub fn remove_data(&mut self, key: &str) -> Option<serde_json::Value> {
         self.data.remove(key)
     }
+
+    /// Get session data deserialized as `T`
+    ///
+    /// A stored value that doesn't deserialize (e.g. written by an older
+    /// version of a feature) is logged and treated as absent.
+    pub fn get_data_as<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<T> {
+        match self.get_data_as_strict(key) {
+            Ok(value) => value,
+            Err(e) => {
+                warn!("Ignoring session {} data: {}", self.id, e);
+                None
+            }
+        }
+    }
+
+    /// Get session data deserialized as `T`, failing if the stored value doesn't match
+    pub fn get_data_as_strict<T: serde::de::DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let Some(value) = self.data.get(key) else {
+            return Ok(None);
+        };
+
+        serde_json::from_value(value.clone()).map(Some).map_err(|e| {
+            TransportError::InvalidSessionData {
+                key: key.to_string(),
+                reason: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    /// Set session data from a serializable value
+    pub fn set_data_as<T: serde::Serialize>(&mut self, key: String, value: &T) -> Result<()> {
+        self.data.insert(key, serde_json::to_value(value)?);
+        Ok(())
+    }
 }
 
 impl SessionManager {
     /// Create a new session manager
     pub fn new(timeout: Duration) -> Self {
+        Self::with_clock(timeout, system_clock())
+    }
+
+    /// Create a new session manager using the given clock
+    pub fn with_clock(timeout: Duration, clock: SharedClock) -> Self {
         let manager = Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             timeout,
             cleanup_handle: Arc::new(RwLock::new(None)),
+            clock,
+            removal_sender: broadcast::channel(256).0,
+            max_sessions: None,
+            strict_data: false,
+            id_generator: id_generator(),
         };
 
         // Start cleanup task
@@ -138,6 +215,39 @@ impl SessionManager {
         manager
     }
 
+    /// Limit the number of concurrent sessions accepted by `try_add_session`
+    pub fn with_max_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_sessions = Some(max_sessions);
+        self
+    }
+
+    /// Fail session data reads on undeserializable values instead of treating them as absent
+    pub fn with_strict_data(mut self, strict: bool) -> Self {
+        self.strict_data = strict;
+        self
+    }
+
+    /// Generate session IDs with the given generator instead of the process-wide one
+    pub fn with_id_generator(mut self, id_generator: SharedIdGenerator) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    /// Generate an ID for a new session
+    pub fn generate_session_id(&self) -> String {
+        self.id_generator.generate()
+    }
+
+    /// Generator new session IDs come from
+    pub fn id_generator(&self) -> &SharedIdGenerator {
+        &self.id_generator
+    }
+
+    /// Create a session stamped with the manager's clock
+    pub fn create_session(&self, id: String) -> Session {
+        Session::new_at(id, self.clock.now())
+    }
+
     /// Add a new session
     pub async fn add_session(&self, session: Session) {
         let session_id = session.id.clone();
@@ -150,6 +260,44 @@ impl SessionManager {
         info!("Added session: {}", session_id);
     }
 
+    /// Add a new session unless the session limit is reached
+    ///
+    /// Expired sessions are evicted first to make room. Returns false if the
+    /// manager is still full afterwards.
+    pub async fn try_add_session(&self, session: Session) -> bool {
+        let session_id = session.id.clone();
+
+        {
+            let mut sessions = self.sessions.write().await;
+
+            if let Some(max_sessions) = self.max_sessions {
+                if sessions.len() >= max_sessions && !sessions.contains_key(&session_id) {
+                    let now = self.clock.now();
+                    let expired: Vec<String> = sessions
+                        .iter()
+                        .filter(|(_, s)| s.is_expired_at(self.timeout, now))
+                        .map(|(id, _)| id.clone())
+                        .collect();
+                    for id in expired {
+                        sessions.remove(&id);
+                        info!("Evicted expired session: {}", id);
+                        let _ = self.removal_sender.send(id);
+                    }
+
+                    if sessions.len() >= max_sessions {
+                        warn!("Session limit of {} reached, rejecting new session", max_sessions);
+                        return false;
+                    }
+                }
+            }
+
+            sessions.insert(session_id.clone(), session);
+        }
+
+        info!("Added session: {}", session_id);
+        true
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, session_id: &str) -> Option<Session> {
         let sessions = self.sessions.read().await;
@@ -171,10 +319,29 @@ impl SessionManager {
         }
     }
 
+    /// Read a session data value as `T`, honoring the manager's strictness
+    pub async fn get_session_data<T: serde::de::DeserializeOwned>(
+        &self,
+        session_id: &str,
+        key: &str,
+    ) -> Result<Option<T>> {
+        let sessions = self.sessions.read().await;
+        let Some(session) = sessions.get(session_id) else {
+            return Ok(None);
+        };
+
+        if self.strict_data {
+            session.get_data_as_strict(key)
+        } else {
+            Ok(session.get_data_as(key))
+        }
+    }
+
     /// Touch a session (update last activity)
     pub async fn touch_session(&self, session_id: &str) -> bool {
+        let now = self.clock.now();
         self.update_session(session_id, |session| {
-            session.touch();
+            session.touch_at(now);
         })
         .await
     }
@@ -186,11 +353,17 @@ impl SessionManager {
 
         if session.is_some() {
             info!("Removed session: {}", session_id);
+            let _ = self.removal_sender.send(session_id.to_string());
         }
 
         session
     }
 
+    /// Receive the IDs of sessions as they are removed or expire
+    pub fn subscribe_removals(&self) -> broadcast::Receiver<String> {
+        self.removal_sender.subscribe()
+    }
+
     /// Get all active sessions
     pub async fn get_active_sessions(&self) -> Vec<Session> {
         let sessions = self.sessions.read().await;
@@ -211,10 +384,11 @@ impl SessionManager {
     pub async fn cleanup_expired_sessions(&self) -> usize {
         let mut sessions = self.sessions.write().await;
         let mut expired_sessions = Vec::new();
+        let now = self.clock.now();
 
         // Find expired sessions
         for (id, session) in sessions.iter() {
-            if session.is_expired(self.timeout) {
+            if session.is_expired_at(self.timeout, now) {
                 expired_sessions.push(id.clone());
             }
         }
@@ -224,6 +398,7 @@ impl SessionManager {
         for session_id in expired_sessions {
             sessions.remove(&session_id);
             info!("Cleaned up expired session: {}", session_id);
+            let _ = self.removal_sender.send(session_id);
         }
 
         if count > 0 {
@@ -237,6 +412,8 @@ impl SessionManager {
     fn start_cleanup_task(&self) {
         let sessions = self.sessions.clone();
         let timeout = self.timeout;
+        let clock = self.clock.clone();
+        let removal_sender = self.removal_sender.clone();
 
         let handle = tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(60)); // Cleanup every minute
@@ -249,8 +426,9 @@ impl SessionManager {
                 // Find expired sessions
                 {
                     let sessions_guard = sessions.read().await;
+                    let now = clock.now();
                     for (id, session) in sessions_guard.iter() {
-                        if session.is_expired(timeout) {
+                        if session.is_expired_at(timeout, now) {
                             expired_sessions.push(id.clone());
                         }
                     }
@@ -263,6 +441,7 @@ impl SessionManager {
 
                     for session_id in expired_sessions {
                         sessions_guard.remove(&session_id);
+                        let _ = removal_sender.send(session_id);
                     }
 
                     info!("Cleaned up {} expired sessions", count);
@@ -340,6 +519,7 @@ impl Drop for SessionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::clock::MockClock;
     use tokio::time::{sleep, Duration};
 
     #[tokio::test]
@@ -390,4 +570,111 @@ mod tests {
         let not_found = manager.get_session("test-session").await;
         assert!(not_found.is_none());
     }
+
+    #[tokio::test]
+    async fn test_session_expiration_with_mock_clock() {
+        let clock = Arc::new(MockClock::new());
+        let manager = SessionManager::with_clock(Duration::from_secs(3600), clock.clone());
+
+        let session = manager.create_session("test-session".to_string());
+        manager.add_session(session).await;
+
+        // Not yet expired
+        clock.advance(Duration::from_secs(1800));
+        assert_eq!(manager.cleanup_expired_sessions().await, 0);
+
+        // Activity resets the expiry window
+        assert!(manager.touch_session("test-session").await);
+        clock.advance(Duration::from_secs(1800));
+        assert_eq!(manager.cleanup_expired_sessions().await, 0);
+
+        clock.advance(Duration::from_secs(1801));
+        assert_eq!(manager.cleanup_expired_sessions().await, 1);
+        assert!(manager.get_session("test-session").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_removal_is_published() {
+        let clock = Arc::new(MockClock::new());
+        let manager = SessionManager::with_clock(Duration::from_secs(60), clock.clone());
+        let mut removals = manager.subscribe_removals();
+
+        manager.add_session(manager.create_session("closed".to_string())).await;
+        manager.add_session(manager.create_session("expired".to_string())).await;
+
+        manager.remove_session("closed").await;
+        assert_eq!(removals.recv().await.unwrap(), "closed");
+
+        clock.advance(Duration::from_secs(61));
+        manager.cleanup_expired_sessions().await;
+        assert_eq!(removals.recv().await.unwrap(), "expired");
+    }
+
+    #[tokio::test]
+    async fn test_max_sessions() {
+        let clock = Arc::new(MockClock::new());
+        let manager =
+            SessionManager::with_clock(Duration::from_secs(60), clock.clone()).with_max_sessions(2);
+
+        assert!(manager.try_add_session(manager.create_session("a".to_string())).await);
+        assert!(manager.try_add_session(manager.create_session("b".to_string())).await);
+        assert!(!manager.try_add_session(manager.create_session("c".to_string())).await);
+        assert_eq!(manager.session_count().await, 2);
+
+        // Expired sessions are evicted to make room
+        clock.advance(Duration::from_secs(61));
+        assert!(manager.try_add_session(manager.create_session("c".to_string())).await);
+        assert_eq!(manager.session_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_incompatible_session_data() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Preferences {
+            page_size: usize,
+        }
+
+        let mut session = Session::new("test-session".to_string());
+        session
+            .set_data_as("prefs".to_string(), &Preferences { page_size: 10 })
+            .unwrap();
+        assert_eq!(
+            session.get_data_as::<Preferences>("prefs"),
+            Some(Preferences { page_size: 10 })
+        );
+
+        // A value written under an older schema
+        session.set_data("prefs".to_string(), serde_json::json!({"pageSize": "ten"}));
+        assert_eq!(session.get_data_as::<Preferences>("prefs"), None);
+        assert!(matches!(
+            session.get_data_as_strict::<Preferences>("prefs"),
+            Err(crate::error::McpError::Transport(TransportError::InvalidSessionData { .. }))
+        ));
+
+        let lenient = SessionManager::new(Duration::from_secs(60));
+        lenient.add_session(session.clone()).await;
+        let value: Option<Preferences> =
+            lenient.get_session_data("test-session", "prefs").await.unwrap();
+        assert_eq!(value, None);
+
+        let strict = SessionManager::new(Duration::from_secs(60)).with_strict_data(true);
+        strict.add_session(session).await;
+        assert!(strict
+            .get_session_data::<Preferences>("test-session", "prefs")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_session_ids_use_configured_generator() {
+        let manager = SessionManager::new(Duration::from_secs(60)).with_id_generator(
+            crate::utils::ids::id_generator_for(crate::utils::ids::IdFormat::Base62, "sess_"),
+        );
+
+        let first = manager.generate_session_id();
+        let second = manager.generate_session_id();
+        assert_ne!(first, second);
+        assert!(first.starts_with("sess_"));
+        assert_eq!(first.len(), "sess_".len() + crate::utils::ids::BASE62_ID_LENGTH);
+    }
 }
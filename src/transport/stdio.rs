@@ -2,25 +2,191 @@
 //!
 //! This module implements the STDIO transport as defined in the MCP specification,
 //! allowing communication through standard input and output streams.
+//!
+//! EOF on stdin means the client has gone away: the transport closes its
+//! message channel, which lets the server shut down gracefully. Read errors
+//! are retried up to `max_read_retries` times before being treated the same way.
 
 use async_trait::async_trait;
 use futures_util::StreamExt;
-use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, error, info, warn};
 
-use crate::config::StdioConfig;
+use crate::config::{FlushPolicy, InputFraming, StdioConfig};
 use crate::error::{McpError, Result};
-use crate::protocol::{parse_message, serialize_message};
+use crate::error::TransportError;
+use crate::protocol::{
+    parse_message_with_max_depth, serialize_message, AnyJsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest,
+};
 use crate::transport::{
-    Transport, TransportInfo, TransportMessage, TransportMetadata, TransportType,
+    NotificationTarget, Transport, TransportInfo, TransportMessage, TransportMetadata,
+    TransportType,
 };
 
+/// Session every message read from the STDIO client belongs to
+pub const STDIO_SESSION_ID: &str = "stdio";
+
+/// Delay before retrying a failed stdin read
+const READ_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// Input source used in place of stdin
+type BoxedInput = Box<dyn AsyncRead + Send + Unpin>;
+
+/// Output sink used in place of stdout
+type BoxedOutput = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Why the input handler stopped reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InputEnd {
+    /// The transport was stopped
+    Shutdown,
+
+    /// The client closed its end of the stream
+    Eof,
+
+    /// Reading failed more times than the configured retries allow
+    ReadError(String),
+
+    /// The protocol handler stopped accepting messages
+    ReceiverClosed,
+}
+
+/// Buffers input lines until they hold complete JSON values
+///
+/// Brackets are tracked as lines arrive, so each byte is scanned once no
+/// matter how many lines a message spans.
+#[derive(Debug)]
+struct JsonAccumulator {
+    buffer: String,
+
+    /// Bytes of `buffer` already scanned
+    scanned: usize,
+
+    /// Open objects and arrays at the end of the scanned input
+    depth: usize,
+
+    /// Whether the scanned input ends inside a string
+    in_string: bool,
+
+    /// Whether the scanned input ends right after a backslash in a string
+    escaped: bool,
+
+    /// Whether the rest of an oversized message is being skipped
+    discarding: bool,
+
+    /// Largest message in bytes; bigger ones are discarded
+    max_size: usize,
+}
+
+impl JsonAccumulator {
+    fn new(max_size: usize) -> Self {
+        Self {
+            buffer: String::new(),
+            scanned: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            discarding: false,
+            max_size,
+        }
+    }
+
+    /// Append a line, returning the text of every JSON value it completes
+    ///
+    /// Malformed or oversized input is returned as an error and discarded so
+    /// a single bad message can't wedge the stream. Values are only delimited
+    /// here; their contents are checked when the message is parsed.
+    fn push_line(&mut self, line: &str) -> Vec<std::result::Result<String, String>> {
+        self.buffer.push_str(line);
+        self.buffer.push('\n');
+
+        let mut values = Vec::new();
+        let mut start = 0;
+        let bytes = self.buffer.as_bytes();
+        for (offset, &byte) in bytes.iter().enumerate().skip(self.scanned) {
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' if self.depth > 0 => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let end = offset + 1;
+                        if self.discarding {
+                            self.discarding = false;
+                        } else if end - start > self.max_size {
+                            values.push(Err(self.too_large()));
+                        } else {
+                            values.push(Ok(self.buffer[start..end].trim().to_string()));
+                        }
+                        start = end;
+                    }
+                }
+                _ if self.depth > 0 || byte.is_ascii_whitespace() => {}
+                _ => {
+                    // Anything else between values can't start a message
+                    values.push(Err(format!(
+                        "Unexpected input outside a JSON message: {}",
+                        self.buffer[start..].trim()
+                    )));
+                    self.reset();
+                    return values;
+                }
+            }
+        }
+
+        if self.discarding {
+            self.buffer.clear();
+        } else {
+            self.buffer.drain(..start);
+            if self.buffer.len() > self.max_size {
+                // Skip the rest of the message without buffering it
+                values.push(Err(self.too_large()));
+                self.buffer.clear();
+                self.discarding = true;
+            }
+        }
+        self.scanned = self.buffer.len();
+        values
+    }
+
+    fn too_large(&self) -> String {
+        format!("Message exceeds the maximum size of {} bytes", self.max_size)
+    }
+
+    /// Drop everything buffered and start over
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.scanned = 0;
+        self.depth = 0;
+        self.in_string = false;
+        self.escaped = false;
+        self.discarding = false;
+    }
+}
+
 /// STDIO transport implementation
 pub struct StdioTransport {
     config: StdioConfig,
-    shutdown_sender: Arc<RwLock<Option<mpsc::Sender<()>>>>,
+    shutdown_senders: Arc<RwLock<Vec<mpsc::Sender<()>>>>,
+    response_sender: Arc<RwLock<Option<mpsc::Sender<TransportMessage>>>>,
+    input: Mutex<Option<BoxedInput>>,
+    output: Mutex<Option<BoxedOutput>>,
+    closed_normally: Arc<AtomicBool>,
 }
 
 impl StdioTransport {
@@ -28,91 +194,157 @@ impl StdioTransport {
     pub fn new(config: StdioConfig) -> Result<Self> {
         Ok(Self {
             config,
-            shutdown_sender: Arc::new(RwLock::new(None)),
+            shutdown_senders: Arc::new(RwLock::new(Vec::new())),
+            response_sender: Arc::new(RwLock::new(None)),
+            input: Mutex::new(None),
+            output: Mutex::new(None),
+            closed_normally: Arc::new(AtomicBool::new(false)),
         })
     }
 
+    /// Read messages from the given reader instead of stdin
+    pub fn with_input<R>(self, reader: R) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+    {
+        *self.input.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(reader));
+        self
+    }
+
+    /// Write messages to the given writer instead of stdout
+    pub fn with_output<W>(self, writer: W) -> Self
+    where
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        *self.output.lock().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(writer));
+        self
+    }
+
     /// Handle incoming messages from stdin
-    async fn handle_stdin_messages(
-        message_sender: mpsc::Sender<TransportMessage>,
+    async fn handle_stdin_messages<R: AsyncRead + Unpin>(
+        input: R,
+        message_sender: &mpsc::Sender<TransportMessage>,
         mut shutdown_receiver: mpsc::Receiver<()>,
-        buffer_size: usize,
-        enable_stderr_logging: bool,
-    ) {
-        let stdin = tokio::io::stdin();
-        let reader = BufReader::with_capacity(buffer_size, stdin);
+        config: StdioConfig,
+    ) -> InputEnd {
+        let StdioConfig {
+            buffer_size,
+            enable_stderr_logging,
+            max_read_retries,
+            framing,
+            max_json_depth,
+            max_message_size,
+            ..
+        } = config;
+        let reader = BufReader::with_capacity(buffer_size, input);
         let mut lines = reader.lines();
+        let mut read_errors = 0;
+        let mut accumulator = JsonAccumulator::new(max_message_size);
 
-        loop {
+        'input: loop {
             tokio::select! {
                 // Check for shutdown signal
                 _ = shutdown_receiver.recv() => {
                     info!("STDIO transport received shutdown signal");
-                    break;
+                    break InputEnd::Shutdown;
                 }
 
                 // Read line from stdin
                 line_result = lines.next_line() => {
                     match line_result {
                         Ok(Some(line)) => {
+                            read_errors = 0;
+
                             if line.trim().is_empty() {
                                 continue;
                             }
 
-                            info!("Received message from stdin: {}", line);
-
-                            // Parse the message
-                            match parse_message(&line) {
-                                Ok(message) => {
-                                    let transport_message = TransportMessage {
-                                        message,
-                                        session_id: None,
-                                        client_id: Some("stdio".to_string()),
-                                        metadata: TransportMetadata::default(),
-                                    };
-
-                                    if let Err(e) = message_sender.send(transport_message).await {
-                                        error!("Failed to send message to protocol handler: {}", e);
-                                        break;
-                                    }
+                            let texts = match framing {
+                                InputFraming::Lines if line.len() > max_message_size => {
+                                    vec![Err(format!(
+                                        "Message exceeds the maximum size of {} bytes",
+                                        max_message_size
+                                    ))]
                                 }
-                                Err(e) => {
-                                    error!("Failed to parse message from stdin: {}", e);
-
-                                    if enable_stderr_logging {
-                                        if let Err(write_err) = Self::write_stderr(&format!(
-                                            "Parse error: {}\n", e
-                                        )).await {
-                                            error!("Failed to write to stderr: {}", write_err);
+                                InputFraming::Lines => vec![Ok(line)],
+                                InputFraming::Accumulate => accumulator.push_line(&line),
+                            };
+
+                            for text in texts {
+                                let parsed = text.and_then(|text| {
+                                    info!("Received message from stdin: {}", text);
+                                    parse_message_with_max_depth(&text, max_json_depth)
+                                        .map_err(|e| e.to_string())
+                                });
+
+                                match parsed {
+                                    Ok(message) => {
+                                        // The connection is a single session, so
+                                        // notifications meant for the sender reach it
+                                        let transport_message = TransportMessage {
+                                            message,
+                                            session_id: Some(STDIO_SESSION_ID.to_string()),
+                                            client_id: Some("stdio".to_string()),
+                                            metadata: TransportMetadata::default(),
+                                        };
+
+                                        if let Err(e) = message_sender.send(transport_message).await {
+                                            error!("Failed to send message to protocol handler: {}", e);
+                                            break 'input InputEnd::ReceiverClosed;
+                                        }
+                                    }
+                                    Err(e) => {
+                                        error!("Failed to parse message from stdin: {}", e);
+
+                                        if enable_stderr_logging {
+                                            if let Err(write_err) = Self::write_stderr(&format!(
+                                                "Parse error: {}\n", e
+                                            )).await {
+                                                error!("Failed to write to stderr: {}", write_err);
+                                            }
                                         }
                                     }
                                 }
                             }
                         }
                         Ok(None) => {
-                            // EOF reached
-                            info!("EOF reached on stdin");
-                            break;
+                            info!("EOF reached on stdin, client disconnected");
+                            break InputEnd::Eof;
                         }
                         Err(e) => {
-                            error!("Error reading from stdin: {}", e);
-                            break;
+                            read_errors += 1;
+                            if read_errors > max_read_retries {
+                                error!("Error reading from stdin, giving up: {}", e);
+                                break InputEnd::ReadError(e.to_string());
+                            }
+
+                            warn!(
+                                "Error reading from stdin (attempt {} of {}), retrying: {}",
+                                read_errors,
+                                max_read_retries + 1,
+                                e
+                            );
+                            tokio::time::sleep(READ_RETRY_DELAY).await;
                         }
                     }
                 }
             }
         }
-
-        info!("STDIO input handler stopped");
     }
 
     /// Handle outgoing messages to stdout
-    async fn handle_stdout_messages(
+    async fn handle_stdout_messages<W: AsyncWrite + Unpin>(
+        mut output: W,
         mut response_receiver: mpsc::Receiver<TransportMessage>,
         mut shutdown_receiver: mpsc::Receiver<()>,
         enable_stderr_logging: bool,
+        flush_policy: FlushPolicy,
     ) {
-        let mut stdout = tokio::io::stdout();
+        let mut flush_timer = match flush_policy {
+            FlushPolicy::Interval(ms) => Some(tokio::time::interval(Duration::from_millis(ms.max(1)))),
+            FlushPolicy::Always | FlushPolicy::OnIdle => None,
+        };
+        let mut unflushed = false;
 
         loop {
             tokio::select! {
@@ -122,22 +354,47 @@ impl StdioTransport {
                     break;
                 }
 
+                // Flush pending output on the interval
+                _ = async {
+                    match flush_timer.as_mut() {
+                        Some(timer) => timer.tick().await,
+                        None => std::future::pending().await,
+                    }
+                } => {
+                    if unflushed {
+                        if let Err(e) = output.flush().await {
+                            error!("Failed to flush stdout: {}", e);
+                            break;
+                        }
+                        unflushed = false;
+                    }
+                }
+
                 // Handle outgoing message
                 message = response_receiver.recv() => {
                     match message {
                         Some(transport_message) => {
                             match serialize_message(&transport_message.message) {
                                 Ok(serialized) => {
-                                    let output = format!("{}\n", serialized);
+                                    let output_line = format!("{}\n", serialized);
 
-                                    if let Err(e) = stdout.write_all(output.as_bytes()).await {
+                                    if let Err(e) = output.write_all(output_line.as_bytes()).await {
                                         error!("Failed to write to stdout: {}", e);
                                         break;
                                     }
+                                    unflushed = true;
 
-                                    if let Err(e) = stdout.flush().await {
-                                        error!("Failed to flush stdout: {}", e);
-                                        break;
+                                    let flush_now = match flush_policy {
+                                        FlushPolicy::Always => true,
+                                        FlushPolicy::OnIdle => response_receiver.is_empty(),
+                                        FlushPolicy::Interval(_) => false,
+                                    };
+                                    if flush_now {
+                                        if let Err(e) = output.flush().await {
+                                            error!("Failed to flush stdout: {}", e);
+                                            break;
+                                        }
+                                        unflushed = false;
                                     }
 
                                     info!("Sent message to stdout: {}", serialized);
@@ -164,6 +421,13 @@ impl StdioTransport {
             }
         }
 
+        // Don't leave buffered output behind
+        if unflushed {
+            if let Err(e) = output.flush().await {
+                error!("Failed to flush stdout: {}", e);
+            }
+        }
+
         info!("STDIO output handler stopped");
     }
 
@@ -177,6 +441,25 @@ impl StdioTransport {
         stderr.flush().await.map_err(|e| McpError::Io(e))?;
         Ok(())
     }
+
+    /// Queue a server-initiated message for the client's output
+    async fn send_to_client(&self, message: AnyJsonRpcMessage) -> Result<usize> {
+        let sender = self.response_sender.read().await.clone().ok_or_else(|| {
+            TransportError::ConnectionLost("STDIO transport is not running".to_string())
+        })?;
+
+        let message = TransportMessage {
+            message,
+            session_id: None,
+            client_id: Some("stdio".to_string()),
+            metadata: TransportMetadata::default(),
+        };
+        sender.send(message).await.map_err(|_| {
+            TransportError::ConnectionLost("STDIO output handler stopped".to_string())
+        })?;
+
+        Ok(1)
+    }
 }
 
 #[async_trait]
@@ -194,31 +477,77 @@ impl Transport for StdioTransport {
         let (shutdown_tx, shutdown_rx1) = mpsc::channel(1);
         let (shutdown_tx2, shutdown_rx2) = mpsc::channel(1);
 
-        // Store shutdown sender
+        // Store shutdown senders for both handlers
         {
-            let mut sender = self.shutdown_sender.write().await;
-            *sender = Some(shutdown_tx.clone());
+            let mut senders = self.shutdown_senders.write().await;
+            *senders = vec![shutdown_tx, shutdown_tx2];
         }
+        *self.response_sender.write().await = Some(response_tx.clone());
 
         // Start stdin handler
         let message_sender = message_tx.clone();
-        let buffer_size = self.config.buffer_size;
-        let enable_stderr_logging = self.config.enable_stderr_logging;
-
+        let input_config = self.config.clone();
+        let input = self
+            .input
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .unwrap_or_else(|| Box::new(tokio::io::stdin()));
+
+        let response_sender = self.response_sender.clone();
+        let closed_normally = self.closed_normally.clone();
         tokio::spawn(async move {
-            Self::handle_stdin_messages(
-                message_sender,
+            let end = Self::handle_stdin_messages(
+                input,
+                &message_sender,
                 shutdown_rx1,
-                buffer_size,
-                enable_stderr_logging,
+                input_config,
             )
             .await;
+
+            closed_normally.store(
+                matches!(end, InputEnd::Eof | InputEnd::Shutdown),
+                Ordering::Release,
+            );
+            let client_gone = match end {
+                InputEnd::Eof => {
+                    info!("STDIO client disconnected, shutting down input");
+                    true
+                }
+                InputEnd::ReadError(e) => {
+                    error!("STDIO input failed: {}", e);
+                    true
+                }
+                InputEnd::Shutdown | InputEnd::ReceiverClosed => false,
+            };
+            if client_gone {
+                // Nobody is left to read notifications
+                response_sender.write().await.take();
+            }
+
+            // Dropping the message sender tells the server no more input will arrive
+            drop(message_sender);
+            info!("STDIO input handler stopped");
         });
 
         // Start stdout handler
         let enable_stderr_logging = self.config.enable_stderr_logging;
+        let flush_policy = self.config.flush;
+        let output = self
+            .output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .unwrap_or_else(|| Box::new(tokio::io::stdout()));
         tokio::spawn(async move {
-            Self::handle_stdout_messages(response_rx, shutdown_rx2, enable_stderr_logging).await;
+            Self::handle_stdout_messages(
+                output,
+                response_rx,
+                shutdown_rx2,
+                enable_stderr_logging,
+                flush_policy,
+            )
+            .await;
         });
 
         Ok((message_rx, response_tx))
@@ -227,29 +556,63 @@ impl Transport for StdioTransport {
     async fn stop(&self) -> Result<()> {
         info!("Stopping STDIO transport");
 
-        let sender = {
-            let mut shutdown_sender = self.shutdown_sender.write().await;
-            shutdown_sender.take()
+        let senders = {
+            let mut shutdown_senders = self.shutdown_senders.write().await;
+            std::mem::take(&mut *shutdown_senders)
         };
 
-        if let Some(sender) = sender {
+        for sender in senders {
             // Send shutdown signal
             if let Err(e) = sender.send(()).await {
                 warn!("Failed to send shutdown signal: {}", e);
             }
         }
+        self.response_sender.write().await.take();
 
         Ok(())
     }
 
+    async fn send_notification(
+        &self,
+        notification: JsonRpcNotification,
+        target: NotificationTarget,
+    ) -> Result<usize> {
+        // STDIO has a single client, so any target reaches it
+        debug!("Sending {} to STDIO client ({:?})", notification.method, target);
+        self.send_to_client(AnyJsonRpcMessage::Notification(notification))
+            .await
+    }
+
+    async fn send_request(
+        &self,
+        request: JsonRpcRequest,
+        target: NotificationTarget,
+    ) -> Result<usize> {
+        debug!("Sending {} request to STDIO client ({:?})", request.method, target);
+        self.send_to_client(AnyJsonRpcMessage::Request(request)).await
+    }
+
+    async fn notify_shutdown(&self, notification: JsonRpcNotification) -> Result<usize> {
+        // A client that already disconnected has nothing to be told
+        if self.response_sender.read().await.is_none() {
+            return Ok(0);
+        }
+        self.send_notification(notification, NotificationTarget::Broadcast)
+            .await
+    }
+
     fn info(&self) -> TransportInfo {
         TransportInfo {
             transport_type: TransportType::Stdio,
             address: "stdio".to_string(),
             secure: false,
-            max_message_size: None, // No inherent limit for STDIO
+            max_message_size: Some(self.config.max_message_size),
         }
     }
+
+    fn closed_normally(&self) -> bool {
+        self.closed_normally.load(Ordering::Acquire)
+    }
 }
 
 /// STDIO transport builder for easier configuration
@@ -277,6 +640,24 @@ impl StdioTransportBuilder {
         self
     }
 
+    /// Set when stdout is flushed
+    pub fn flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.config.flush = policy;
+        self
+    }
+
+    /// Set how many consecutive read errors are retried before giving up
+    pub fn max_read_retries(mut self, retries: u32) -> Self {
+        self.config.max_read_retries = retries;
+        self
+    }
+
+    /// Set how incoming messages are delimited
+    pub fn framing(mut self, framing: InputFraming) -> Self {
+        self.config.framing = framing;
+        self
+    }
+
     /// Build the transport
     pub fn build(self) -> Result<StdioTransport> {
         StdioTransport::new(self.config)
@@ -310,7 +691,7 @@ mod tests {
         assert_eq!(info.transport_type, TransportType::Stdio);
         assert_eq!(info.address, "stdio");
         assert!(!info.secure);
-        assert!(info.max_message_size.is_none());
+        assert_eq!(info.max_message_size, Some(1024 * 1024));
     }
 
     #[tokio::test]
@@ -326,6 +707,276 @@ mod tests {
         assert!(!transport.config.enable_stderr_logging);
     }
 
+    /// Input settings used by the read loop tests
+    fn test_config(max_read_retries: u32, framing: InputFraming) -> StdioConfig {
+        StdioConfig {
+            buffer_size: 1024,
+            enable_stderr_logging: false,
+            max_read_retries,
+            framing,
+            ..StdioConfig::default()
+        }
+    }
+
+    /// Reader whose every read fails
+    struct FailingReader;
+
+    impl AsyncRead for FailingReader {
+        fn poll_read(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            _buf: &mut tokio::io::ReadBuf<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Err(std::io::Error::other("device error")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eof_closes_input() {
+        let input: &[u8] = b"{\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n";
+        let (message_tx, mut message_rx) = mpsc::channel(10);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+
+        let end =
+        StdioTransport::handle_stdin_messages(
+            input,
+            &message_tx,
+            shutdown_rx,
+            test_config(3, InputFraming::Lines),
+        )
+        .await;
+        assert_eq!(end, InputEnd::Eof);
+
+        // The message before EOF is delivered, then the channel closes
+        drop(message_tx);
+        assert!(message_rx.recv().await.is_some());
+        assert!(message_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_errors_retried_then_fatal() {
+        let (message_tx, _message_rx) = mpsc::channel(10);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+
+        let started = tokio::time::Instant::now();
+        let end = StdioTransport::handle_stdin_messages(
+            FailingReader,
+            &message_tx,
+            shutdown_rx,
+            test_config(2, InputFraming::Lines),
+        )
+        .await;
+
+        assert_eq!(end, InputEnd::ReadError("device error".to_string()));
+        assert!(started.elapsed() >= READ_RETRY_DELAY * 2);
+    }
+
+    #[tokio::test]
+    async fn test_accumulate_parses_multi_line_messages() {
+        let input: &[u8] = b"{\n  \"jsonrpc\": \"2.0\",\n  \"id\": 1,\n  \"method\": \"ping\"\n}\n\
+            {\"jsonrpc\":\"2.0\",\"method\":\"notifications/initialized\"}\n\
+            {\"jsonrpc\": oops}\n\
+            {\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"ping\"}\n";
+        let (message_tx, mut message_rx) = mpsc::channel(10);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+
+        let end = StdioTransport::handle_stdin_messages(
+            input,
+            &message_tx,
+            shutdown_rx,
+            test_config(0, InputFraming::Accumulate),
+        )
+        .await;
+        assert_eq!(end, InputEnd::Eof);
+        drop(message_tx);
+
+        // The pretty-printed request and the one-line messages around the
+        // malformed one all come through
+        let mut methods = Vec::new();
+        while let Some(message) = message_rx.recv().await {
+            match message.message {
+                AnyJsonRpcMessage::Request(request) => methods.push(request.method),
+                AnyJsonRpcMessage::Notification(notification) => methods.push(notification.method),
+                other => panic!("unexpected message: {:?}", other),
+            }
+        }
+        assert_eq!(methods, ["ping", "notifications/initialized", "ping"]);
+    }
+
+    #[test]
+    fn test_accumulator_tracks_strings_across_lines() {
+        let mut accumulator = JsonAccumulator::new(1024);
+        assert!(accumulator.push_line(r#"{"text": "a } that"#).is_empty());
+        assert!(accumulator.push_line(r#"spans \" lines {", "n": [1,"#).is_empty());
+        let values = accumulator.push_line(r#"2]} {"second": true}"#);
+        assert_eq!(
+            values,
+            [
+                Ok("{\"text\": \"a } that\nspans \\\" lines {\", \"n\": [1,\n2]}".to_string()),
+                Ok("{\"second\": true}".to_string()),
+            ]
+        );
+        assert!(accumulator.buffer.trim().is_empty());
+
+        // Stray input is reported and dropped
+        let values = accumulator.push_line("oops {\"jsonrpc\": \"2.0\"}");
+        assert!(matches!(&values[..], [Err(_)]));
+        assert_eq!(accumulator.push_line("{}"), [Ok("{}".to_string())]);
+    }
+
+    #[test]
+    fn test_accumulator_discards_oversized_messages() {
+        let mut accumulator = JsonAccumulator::new(64);
+
+        // An unterminated message is dropped once it outgrows the limit
+        assert!(accumulator.push_line("{\"data\": [").is_empty());
+        let mut errors = Vec::new();
+        for _ in 0..10 {
+            errors.extend(accumulator.push_line("\"xxxxxxxxxxxxxxxx\","));
+        }
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].as_ref().unwrap_err().contains("maximum size of 64 bytes"));
+        assert!(accumulator.buffer.is_empty());
+        assert!(accumulator.push_line("\"last\"]}").is_empty());
+
+        // A complete message over the limit is rejected too, and the stream recovers
+        let big = format!("{{\"data\": \"{}\"}}", "x".repeat(100));
+        assert!(matches!(&accumulator.push_line(&big)[..], [Err(_)]));
+        assert_eq!(accumulator.push_line("{\"ok\": 1}"), [Ok("{\"ok\": 1}".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_lines_framing_rejects_multi_line_messages() {
+        let input: &[u8] = b"{\n\"jsonrpc\": \"2.0\",\n\"id\": 1,\n\"method\": \"ping\"\n}\n";
+        let (message_tx, mut message_rx) = mpsc::channel(10);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+
+        StdioTransport::handle_stdin_messages(
+            input,
+            &message_tx,
+            shutdown_rx,
+            test_config(0, InputFraming::Lines),
+        )
+        .await;
+        drop(message_tx);
+        assert!(message_rx.recv().await.is_none());
+    }
+
+    /// Writer recording the bytes written and how often it was flushed
+    #[derive(Clone, Default)]
+    struct RecordingWriter {
+        written: Arc<Mutex<Vec<u8>>>,
+        flushes: Arc<Mutex<usize>>,
+    }
+
+    impl AsyncWrite for RecordingWriter {
+        fn poll_write(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            self.written.lock().unwrap().extend_from_slice(buf);
+            std::task::Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            *self.flushes.lock().unwrap() += 1;
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    async fn write_queued(policy: FlushPolicy, count: usize) -> RecordingWriter {
+        let writer = RecordingWriter::default();
+        let (response_tx, response_rx) = mpsc::channel(10);
+        let (_shutdown_tx, shutdown_rx) = mpsc::channel(1);
+
+        for i in 0..count {
+            let message = crate::protocol::parse_message(&format!(
+                "{{\"jsonrpc\":\"2.0\",\"method\":\"notifications/test\",\"params\":{{\"n\":{}}}}}",
+                i
+            ))
+            .unwrap();
+            response_tx
+                .send(TransportMessage {
+                    message,
+                    session_id: None,
+                    client_id: None,
+                    metadata: TransportMetadata::default(),
+                })
+                .await
+                .unwrap();
+        }
+        drop(response_tx);
+
+        StdioTransport::handle_stdout_messages(writer.clone(), response_rx, shutdown_rx, false, policy)
+            .await;
+        writer
+    }
+
+    #[tokio::test]
+    async fn test_on_idle_flushes_once_for_queued_messages() {
+        let writer = write_queued(FlushPolicy::OnIdle, 3).await;
+
+        let written = String::from_utf8(writer.written.lock().unwrap().clone()).unwrap();
+        assert_eq!(written.lines().count(), 3);
+        assert_eq!(*writer.flushes.lock().unwrap(), 1);
+
+        let writer = write_queued(FlushPolicy::Always, 3).await;
+        assert_eq!(*writer.flushes.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_notification_written_to_stdout() {
+        let writer = RecordingWriter::default();
+        // Keep the input open so the client stays connected
+        let (_client, input) = tokio::io::duplex(64);
+        let transport = StdioTransport::new(StdioConfig::default())
+            .unwrap()
+            .with_input(input)
+            .with_output(writer.clone());
+
+        let notification = JsonRpcNotification::new(
+            "notifications/tools/list_changed".to_string(),
+            None,
+        );
+        assert!(transport
+            .send_notification(notification.clone(), NotificationTarget::Broadcast)
+            .await
+            .is_err());
+
+        let (_message_rx, _response_tx) = transport.start().await.unwrap();
+        let delivered = transport
+            .send_notification(notification, NotificationTarget::Broadcast)
+            .await
+            .unwrap();
+        assert_eq!(delivered, 1);
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while *writer.flushes.lock().unwrap() == 0 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("notification was not written");
+
+        let written = String::from_utf8(writer.written.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(written.trim_end()).unwrap();
+        assert_eq!(line["method"], "notifications/tools/list_changed");
+        assert_eq!(line["jsonrpc"], "2.0");
+
+        transport.stop().await.unwrap();
+    }
+
     #[test]
     fn test_write_stderr() {
         // Test would require mocking stderr
@@ -0,0 +1,205 @@
+//! Audit logging of sensitive operations.
+//!
+//! Tool calls and resource reads produce an [`AuditRecord`] naming the
+//! principal, method, redacted arguments, timestamp and outcome. Records go
+//! to an [`AuditSink`]: either the tracing pipeline under [`AUDIT_TARGET`]
+//! (enabled regardless of the configured log level) or an append-only file of
+//! JSON lines.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::warn;
+
+use super::sanitize_for_logging;
+
+/// Tracing target audit records are emitted under
+pub const AUDIT_TARGET: &str = "mcp_audit";
+
+/// Methods that produce an audit record
+pub const AUDITED_METHODS: [&str; 2] = ["tools/call", "resources/read"];
+
+/// How an audited request ended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The request succeeded
+    Success,
+
+    /// The request failed with the given JSON-RPC error
+    Error { code: i32, message: String },
+}
+
+/// One audited operation
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    /// RFC 3339 time the request finished
+    pub timestamp: String,
+
+    /// Client the request is attributed to, if known
+    pub principal: Option<String>,
+
+    /// Session the request arrived on
+    pub session_id: Option<String>,
+
+    /// JSON-RPC method
+    pub method: String,
+
+    /// Request parameters with sensitive values redacted
+    pub arguments: Value,
+
+    /// How the request ended
+    pub outcome: AuditOutcome,
+}
+
+impl AuditRecord {
+    /// Create a record, redacting the arguments
+    pub fn new(
+        principal: Option<String>,
+        session_id: Option<String>,
+        method: impl Into<String>,
+        arguments: Option<&Value>,
+        outcome: AuditOutcome,
+    ) -> Self {
+        Self {
+            timestamp: super::generate_timestamp(),
+            principal,
+            session_id,
+            method: method.into(),
+            arguments: arguments.map(redact_arguments).unwrap_or(Value::Null),
+            outcome,
+        }
+    }
+}
+
+/// Destination for audit records
+pub trait AuditSink: Send + Sync {
+    /// Write a record
+    fn record(&self, record: &AuditRecord);
+}
+
+/// Emits audit records through tracing under [`AUDIT_TARGET`]
+#[derive(Debug, Default)]
+pub struct TracingAuditSink;
+
+impl AuditSink for TracingAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        match serde_json::to_string(record) {
+            Ok(line) => tracing::info!(target: AUDIT_TARGET, "{}", line),
+            Err(e) => warn!("Failed to serialize audit record: {}", e),
+        }
+    }
+}
+
+/// Appends audit records to a file, one JSON object per line
+#[derive(Debug)]
+pub struct FileAuditSink {
+    file: Mutex<File>,
+}
+
+impl FileAuditSink {
+    /// Open (or create) the audit file for appending
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let line = match serde_json::to_string(record) {
+            Ok(line) => line,
+            Err(e) => {
+                warn!("Failed to serialize audit record: {}", e);
+                return;
+            }
+        };
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            warn!("Failed to write audit record: {}", e);
+        }
+    }
+}
+
+/// Redact sensitive keys and string values with [`sanitize_for_logging`]
+pub fn redact_arguments(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let sanitized = sanitize_for_logging(key);
+                    if sanitized != *key {
+                        (key.clone(), Value::String(sanitized))
+                    } else {
+                        (key.clone(), redact_arguments(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_arguments).collect()),
+        Value::String(s) => Value::String(sanitize_for_logging(s)),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_arguments() {
+        let arguments = serde_json::json!({
+            "name": "deploy",
+            "arguments": {
+                "api_key": "abc123",
+                "notes": ["uses token xyz", "plain"],
+                "retries": 3
+            }
+        });
+
+        assert_eq!(
+            redact_arguments(&arguments),
+            serde_json::json!({
+                "name": "deploy",
+                "arguments": {
+                    "api_key": "[REDACTED:key]",
+                    "notes": ["[REDACTED:token]", "plain"],
+                    "retries": 3
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("mcp-audit-{}.log", uuid::Uuid::new_v4()));
+        let sink = FileAuditSink::open(&path).unwrap();
+
+        for method in AUDITED_METHODS {
+            sink.record(&AuditRecord::new(
+                Some("alice".to_string()),
+                None,
+                method,
+                None,
+                AuditOutcome::Success,
+            ));
+        }
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let records: Vec<Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1]["method"], "resources/read");
+        assert_eq!(records[1]["principal"], "alice");
+        assert_eq!(records[1]["outcome"]["status"], "success");
+    }
+}
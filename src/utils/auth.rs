@@ -16,6 +16,22 @@ pub fn extract_bearer_token(auth_header: &str) -> Option<&str> {
     }
 }
 
+/// Get the API key presented in an `X-API-Key` or bearer `Authorization` header
+pub fn extract_api_key<'a>(api_key_header: Option<&'a str>, auth_header: Option<&'a str>) -> Option<&'a str> {
+    api_key_header.or_else(|| auth_header.and_then(extract_bearer_token))
+}
+
+/// Identify the holder of an API key without revealing the key
+///
+/// The identity is a prefix of the key's SHA-256 digest, so it is stable
+/// across requests and safe to write to logs.
+pub fn api_key_principal(key: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(key.as_bytes());
+    format!("api-key:{}", crate::utils::hex_encode(&digest[..8]))
+}
+
 /// Simple JWT validation
 pub fn validate_jwt_token(token: &str, secret: &str) -> Result<bool> {
     let _ = (token, secret);
@@ -0,0 +1,87 @@
+//! Clock abstraction for time-dependent behavior.
+//!
+//! Session expiry and request tracking read time through a [`Clock`] so tests
+//! can substitute a [`MockClock`] and advance time without sleeping.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of monotonic time
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// Get the current instant
+    fn now(&self) -> Instant;
+}
+
+/// Shared clock handle
+pub type SharedClock = Arc<dyn Clock>;
+
+/// Clock backed by `Instant::now`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Manually advanced clock for tests
+#[derive(Debug)]
+pub struct MockClock {
+    /// Instant the clock was created at
+    start: Instant,
+
+    /// Time advanced since creation
+    offset: Mutex<Duration>,
+}
+
+impl MockClock {
+    /// Create a new mock clock starting at the current instant
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        let mut offset = self.offset.lock().unwrap_or_else(|e| e.into_inner());
+        *offset += duration;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        let offset = self.offset.lock().unwrap_or_else(|e| e.into_inner());
+        self.start + *offset
+    }
+}
+
+/// Get a shared handle to the system clock
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_advance() {
+        let clock = MockClock::new();
+        let before = clock.now();
+
+        assert_eq!(clock.now(), before);
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(clock.now() - before, Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,115 @@
+//! Conversion between `file://` URIs and local paths.
+//!
+//! Both directions go through [`url::Url`], which percent-decodes path
+//! segments and knows the platform's path rules: on Windows `file:///C:/dir`
+//! maps to `C:\dir` and `file://server/share/dir` to the UNC path
+//! `\\server\share\dir`. Elsewhere only local URIs (no host, or `localhost`)
+//! name a path.
+
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use url::Url;
+
+/// Why a `file://` URI or path couldn't be converted
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum FileUriError {
+    /// The URI doesn't parse
+    #[error("Invalid URI: {0}")]
+    InvalidUri(String),
+
+    /// The URI isn't a `file://` URI
+    #[error("Only file:// URIs are supported")]
+    UnsupportedScheme,
+
+    /// The URI names a remote host this platform can't address
+    #[error("Remote file host '{0}' is not supported on this platform")]
+    RemoteHost(String),
+
+    /// The URI has no usable path
+    #[error("Invalid file path in URI")]
+    InvalidPath,
+
+    /// The path is relative, so it has no URI
+    #[error("Path is not absolute: {0}")]
+    RelativePath(String),
+}
+
+/// Convert a `file://` URI to a local path
+pub fn file_uri_to_path(uri: &str) -> Result<PathBuf, FileUriError> {
+    let url = Url::parse(uri).map_err(|e| FileUriError::InvalidUri(e.to_string()))?;
+
+    if url.scheme() != "file" {
+        return Err(FileUriError::UnsupportedScheme);
+    }
+
+    match url.host_str() {
+        None | Some("") | Some("localhost") => {}
+        Some(host) if !cfg!(windows) => return Err(FileUriError::RemoteHost(host.to_string())),
+        Some(_) => {}
+    }
+
+    url.to_file_path().map_err(|_| FileUriError::InvalidPath)
+}
+
+/// Convert an absolute local path to a `file://` URI
+pub fn path_to_file_uri(path: &Path) -> Result<String, FileUriError> {
+    Url::from_file_path(path)
+        .map(String::from)
+        .map_err(|_| FileUriError::RelativePath(path.display().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_file_uris() {
+        assert_eq!(
+            file_uri_to_path("file:///tmp/my%20dir/a.txt").unwrap(),
+            PathBuf::from("/tmp/my dir/a.txt")
+        );
+        assert_eq!(
+            file_uri_to_path("file://localhost/tmp/a.txt").unwrap(),
+            PathBuf::from("/tmp/a.txt")
+        );
+        assert_eq!(
+            path_to_file_uri(Path::new("/tmp/my dir/a.txt")).unwrap(),
+            "file:///tmp/my%20dir/a.txt"
+        );
+
+        assert_eq!(
+            file_uri_to_path("file://server/share/a.txt"),
+            Err(FileUriError::RemoteHost("server".to_string()))
+        );
+        assert_eq!(
+            file_uri_to_path("http://example.com/a.txt"),
+            Err(FileUriError::UnsupportedScheme)
+        );
+        assert!(matches!(
+            path_to_file_uri(Path::new("relative/a.txt")),
+            Err(FileUriError::RelativePath(_))
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_file_uris() {
+        assert_eq!(
+            file_uri_to_path("file:///C:/Users/me/a%20b.txt").unwrap(),
+            PathBuf::from(r"C:\Users\me\a b.txt")
+        );
+        assert_eq!(
+            file_uri_to_path("file://server/share/a.txt").unwrap(),
+            PathBuf::from(r"\\server\share\a.txt")
+        );
+        assert_eq!(
+            path_to_file_uri(Path::new(r"C:\Users\me\a b.txt")).unwrap(),
+            "file:///C:/Users/me/a%20b.txt"
+        );
+        assert_eq!(
+            path_to_file_uri(Path::new(r"\\server\share\a.txt")).unwrap(),
+            "file://server/share/a.txt"
+        );
+    }
+}
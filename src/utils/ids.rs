@@ -0,0 +1,150 @@
+//! Generation of session and request IDs.
+//!
+//! IDs come from an [`IdGenerator`]: [`UuidGenerator`] (the default) produces
+//! hyphenated UUIDv4 strings and [`Base62Generator`] produces shorter base62
+//! encodings of the same 128 random bits. Either can carry a prefix such as
+//! `sess_`. Servers hand the configured generator to the components that
+//! generate IDs; the process-wide generator installed with
+//! [`set_id_generator`] is only the default for those built without one.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Characters of the base62 alphabet, in digit order
+const BASE62_ALPHABET: &[u8; 62] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// Length of a base62-encoded 128-bit value
+pub const BASE62_ID_LENGTH: usize = 22;
+
+/// Source of unique IDs
+pub trait IdGenerator: Send + Sync + fmt::Debug {
+    /// Generate a new ID
+    fn generate(&self) -> String;
+}
+
+/// Shared ID generator handle
+pub type SharedIdGenerator = Arc<dyn IdGenerator>;
+
+/// Format of generated IDs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdFormat {
+    /// Hyphenated UUIDv4, e.g. `0b4f7c1e-...`
+    #[default]
+    Uuid,
+
+    /// 22 base62 characters encoding a UUIDv4
+    Base62,
+}
+
+/// Generates hyphenated UUIDv4 IDs
+#[derive(Debug, Clone, Default)]
+pub struct UuidGenerator {
+    prefix: String,
+}
+
+impl UuidGenerator {
+    /// Create a generator prepending `prefix` to every ID
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl IdGenerator for UuidGenerator {
+    fn generate(&self) -> String {
+        format!("{}{}", self.prefix, uuid::Uuid::new_v4())
+    }
+}
+
+/// Generates fixed-length base62 IDs
+#[derive(Debug, Clone, Default)]
+pub struct Base62Generator {
+    prefix: String,
+}
+
+impl Base62Generator {
+    /// Create a generator prepending `prefix` to every ID
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl IdGenerator for Base62Generator {
+    fn generate(&self) -> String {
+        let mut value = uuid::Uuid::new_v4().as_u128();
+        let mut digits = [b'0'; BASE62_ID_LENGTH];
+        for digit in digits.iter_mut().rev() {
+            *digit = BASE62_ALPHABET[(value % 62) as usize];
+            value /= 62;
+        }
+
+        let mut id = self.prefix.clone();
+        id.extend(digits.iter().map(|&b| b as char));
+        id
+    }
+}
+
+/// Create a generator for the given format and prefix
+pub fn id_generator_for(format: IdFormat, prefix: &str) -> SharedIdGenerator {
+    match format {
+        IdFormat::Uuid => Arc::new(UuidGenerator::with_prefix(prefix)),
+        IdFormat::Base62 => Arc::new(Base62Generator::with_prefix(prefix)),
+    }
+}
+
+/// Process-wide generator; UUIDv4 without a prefix when unset
+static ID_GENERATOR: RwLock<Option<SharedIdGenerator>> = RwLock::new(None);
+
+/// Install the process-wide ID generator
+pub fn set_id_generator(generator: SharedIdGenerator) {
+    let mut current = ID_GENERATOR.write().unwrap_or_else(|e| e.into_inner());
+    *current = Some(generator);
+}
+
+/// Get the process-wide ID generator
+pub fn id_generator() -> SharedIdGenerator {
+    let current = ID_GENERATOR.read().unwrap_or_else(|e| e.into_inner());
+    current
+        .clone()
+        .unwrap_or_else(|| Arc::new(UuidGenerator::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_uuid_ids_match_format() {
+        let generator = id_generator_for(IdFormat::Uuid, "sess_");
+        let ids: HashSet<String> = (0..100).map(|_| generator.generate()).collect();
+        assert_eq!(ids.len(), 100);
+
+        for id in &ids {
+            let uuid = id.strip_prefix("sess_").unwrap();
+            assert!(uuid::Uuid::parse_str(uuid).is_ok(), "not a UUID: {}", id);
+        }
+    }
+
+    #[test]
+    fn test_base62_ids_match_format() {
+        let generator = id_generator_for(IdFormat::Base62, "req_");
+        let ids: HashSet<String> = (0..100).map(|_| generator.generate()).collect();
+        assert_eq!(ids.len(), 100);
+
+        for id in &ids {
+            let digits = id.strip_prefix("req_").unwrap();
+            assert_eq!(digits.len(), BASE62_ID_LENGTH);
+            assert!(digits.chars().all(|c| c.is_ascii_alphanumeric()), "not base62: {}", id);
+        }
+
+        let unprefixed = Base62Generator::default().generate();
+        assert_eq!(unprefixed.len(), BASE62_ID_LENGTH);
+    }
+}
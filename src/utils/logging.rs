@@ -7,6 +7,12 @@ pub fn init_logging(config: &crate::config::LoggingConfig) -> crate::Result<()>
     let filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.level));
 
+    // Audit records are emitted independently of the configured level
+    let filter = match format!("{}=info", super::audit::AUDIT_TARGET).parse() {
+        Ok(directive) => filter.add_directive(directive),
+        Err(_) => filter,
+    };
+
     let subscriber = tracing_subscriber::registry().with(filter);
 
     match config.format {
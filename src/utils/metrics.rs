@@ -0,0 +1,413 @@
+//! Request metrics.
+//!
+//! Counts requests and records their durations, labeled by method and by the
+//! feature the method belongs to (resources, tools, prompts, ...). Metrics can
+//! be rendered in the Prometheus text format. Methods the server doesn't know
+//! are counted under [`UNKNOWN_METHOD`] so clients can't grow the label set. [`MessageCounters`] keeps plain
+//! process-wide totals for setups without Prometheus.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+/// Upper bounds (in seconds) of the request duration histogram buckets
+pub const DURATION_BUCKETS: [f64; 8] = [0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0];
+
+/// Feature label for methods that don't belong to a known feature
+pub const OTHER_FEATURE: &str = "other";
+
+/// Method label for requests whose method isn't registered
+pub const UNKNOWN_METHOD: &str = "unknown";
+
+/// Methods the server handles itself
+pub const STANDARD_METHODS: [&str; 17] = [
+    "initialize",
+    "ping",
+    "resources/list",
+    "resources/templates/list",
+    "resources/read",
+    "resources/stat",
+    "resources/subscribe",
+    "resources/unsubscribe",
+    "tools/list",
+    "tools/call",
+    "prompts/list",
+    "prompts/get",
+    "sampling/createMessage",
+    "logging/setLevel",
+    "completion/complete",
+    "roots/list",
+    "admin/maintenance",
+];
+
+/// Labels attached to request metrics
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestLabels {
+    /// JSON-RPC method
+    pub method: String,
+
+    /// Feature derived from the method prefix
+    pub feature: String,
+}
+
+/// Counters and duration histogram for one label set
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestStats {
+    /// Requests handled
+    pub count: u64,
+
+    /// Requests that returned an error
+    pub errors: u64,
+
+    /// Total time spent handling requests
+    pub total_duration: Duration,
+
+    /// Cumulative counts per entry of [`DURATION_BUCKETS`]
+    pub buckets: [u64; DURATION_BUCKETS.len()],
+}
+
+/// Request metrics registry
+#[derive(Debug)]
+pub struct RequestMetrics {
+    /// Method prefix -> feature label
+    feature_labels: HashMap<String, String>,
+
+    /// Methods recorded under their own label
+    methods: RwLock<HashSet<String>>,
+
+    /// Stats per label set
+    requests: Mutex<HashMap<RequestLabels, RequestStats>>,
+}
+
+impl RequestMetrics {
+    /// Create a registry labeling the standard MCP method prefixes
+    pub fn new() -> Self {
+        let feature_labels = [
+            ("initialize", "lifecycle"),
+            ("ping", "lifecycle"),
+            ("resources", "resources"),
+            ("tools", "tools"),
+            ("prompts", "prompts"),
+            ("sampling", "sampling"),
+            ("logging", "logging"),
+            ("completion", "completion"),
+            ("roots", "roots"),
+            ("admin", "admin"),
+        ]
+        .into_iter()
+        .map(|(prefix, feature)| (prefix.to_string(), feature.to_string()))
+        .collect();
+
+        Self {
+            feature_labels,
+            methods: RwLock::new(STANDARD_METHODS.iter().map(|m| m.to_string()).collect()),
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Label methods starting with `prefix` (the part before the first '/') as `feature`
+    pub fn with_feature_label(mut self, prefix: impl Into<String>, feature: impl Into<String>) -> Self {
+        self.feature_labels.insert(prefix.into(), feature.into());
+        self
+    }
+
+    /// Record requests for `method` under their own label
+    pub fn register_method(&self, method: &str) {
+        let mut methods = self.methods.write().unwrap_or_else(|e| e.into_inner());
+        if !methods.contains(method) {
+            methods.insert(method.to_string());
+        }
+    }
+
+    /// Get the label a request for `method` is recorded under
+    pub fn method_label<'a>(&self, method: &'a str) -> &'a str {
+        let methods = self.methods.read().unwrap_or_else(|e| e.into_inner());
+        if methods.contains(method) {
+            method
+        } else {
+            UNKNOWN_METHOD
+        }
+    }
+
+    /// Get the feature label for a method
+    pub fn feature_for_method(&self, method: &str) -> &str {
+        let prefix = method.split('/').next().unwrap_or(method);
+        self.feature_labels
+            .get(prefix)
+            .map(String::as_str)
+            .unwrap_or(OTHER_FEATURE)
+    }
+
+    /// Record a handled request
+    ///
+    /// Unregistered methods are recorded as [`UNKNOWN_METHOD`].
+    pub fn record_request(&self, method: &str, duration: Duration, is_error: bool) {
+        let method = self.method_label(method);
+        let labels = RequestLabels {
+            method: method.to_string(),
+            feature: self.feature_for_method(method).to_string(),
+        };
+
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = requests.entry(labels).or_default();
+        stats.count += 1;
+        if is_error {
+            stats.errors += 1;
+        }
+        stats.total_duration += duration;
+
+        let seconds = duration.as_secs_f64();
+        for (bucket, bound) in stats.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+    }
+
+    /// Get the stats for every label set seen so far
+    pub fn snapshot(&self) -> BTreeMap<RequestLabels, RequestStats> {
+        let requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        requests
+            .iter()
+            .map(|(labels, stats)| (labels.clone(), stats.clone()))
+            .collect()
+    }
+
+    /// Get the number of requests handled for a feature
+    pub fn feature_request_count(&self, feature: &str) -> u64 {
+        let requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        requests
+            .iter()
+            .filter(|(labels, _)| labels.feature == feature)
+            .map(|(_, stats)| stats.count)
+            .sum()
+    }
+
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn render_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+
+        let _ = writeln!(out, "# TYPE mcp_requests_total counter");
+        for (labels, stats) in &snapshot {
+            let _ = writeln!(out, "mcp_requests_total{{{}}} {}", format_labels(labels), stats.count);
+        }
+
+        let _ = writeln!(out, "# TYPE mcp_request_errors_total counter");
+        for (labels, stats) in &snapshot {
+            let _ = writeln!(
+                out,
+                "mcp_request_errors_total{{{}}} {}",
+                format_labels(labels),
+                stats.errors
+            );
+        }
+
+        let _ = writeln!(out, "# TYPE mcp_request_duration_seconds histogram");
+        for (labels, stats) in &snapshot {
+            let labels = format_labels(labels);
+            for (count, bound) in stats.buckets.iter().zip(DURATION_BUCKETS) {
+                let _ = writeln!(
+                    out,
+                    "mcp_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, bound, count
+                );
+            }
+            let _ = writeln!(
+                out,
+                "mcp_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+                labels, stats.count
+            );
+            let _ = writeln!(
+                out,
+                "mcp_request_duration_seconds_sum{{{}}} {}",
+                labels,
+                stats.total_duration.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "mcp_request_duration_seconds_count{{{}}} {}",
+                labels, stats.count
+            );
+        }
+
+        out
+    }
+}
+
+impl Default for RequestMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Totals of handled messages, saturating instead of wrapping on overflow
+#[derive(Debug, Default)]
+pub struct MessageCounters {
+    requests: AtomicU64,
+    errors: AtomicU64,
+    notifications: AtomicU64,
+    sessions: AtomicU64,
+    peak_sessions: AtomicU64,
+}
+
+impl MessageCounters {
+    /// Create zeroed counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a handled request
+    pub fn record_request(&self, is_error: bool) {
+        saturating_increment(&self.requests);
+        if is_error {
+            saturating_increment(&self.errors);
+        }
+    }
+
+    /// Count a handled notification
+    pub fn record_notification(&self) {
+        saturating_increment(&self.notifications);
+    }
+
+    /// Count a newly opened client session, updating the peak
+    pub fn record_session_opened(&self) {
+        saturating_increment(&self.sessions);
+        self.peak_sessions
+            .fetch_max(self.sessions.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    /// Count a closed client session
+    pub fn record_session_closed(&self) {
+        let _ = self.sessions.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| {
+            value.checked_sub(1)
+        });
+    }
+
+    /// Get the number of requests handled
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of requests that returned an error
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of notifications handled
+    pub fn notifications(&self) -> u64 {
+        self.notifications.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of client sessions currently open
+    pub fn sessions(&self) -> u64 {
+        self.sessions.load(Ordering::Relaxed)
+    }
+
+    /// Get the largest number of client sessions open at once
+    pub fn peak_sessions(&self) -> u64 {
+        self.peak_sessions.load(Ordering::Relaxed)
+    }
+}
+
+fn saturating_increment(counter: &AtomicU64) {
+    let _ = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |value| {
+        value.checked_add(1)
+    });
+}
+
+fn format_labels(labels: &RequestLabels) -> String {
+    format!(
+        "method=\"{}\",feature=\"{}\"",
+        escape_label(&labels.method),
+        escape_label(&labels.feature)
+    )
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_labels() {
+        let metrics = RequestMetrics::new().with_feature_label("custom", "extensions");
+
+        assert_eq!(metrics.feature_for_method("tools/call"), "tools");
+        assert_eq!(metrics.feature_for_method("resources/templates/list"), "resources");
+        assert_eq!(metrics.feature_for_method("initialize"), "lifecycle");
+        assert_eq!(metrics.feature_for_method("custom/run"), "extensions");
+        assert_eq!(metrics.feature_for_method("unknown/method"), OTHER_FEATURE);
+    }
+
+    #[test]
+    fn test_histogram_buckets() {
+        let metrics = RequestMetrics::new();
+        metrics.record_request("prompts/get", Duration::from_millis(20), false);
+        metrics.record_request("prompts/get", Duration::from_secs(2), true);
+
+        let snapshot = metrics.snapshot();
+        let stats = snapshot.values().next().unwrap();
+        assert_eq!(stats.count, 2);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.buckets, [0, 0, 1, 1, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    fn test_unregistered_methods_share_a_label() {
+        let metrics = RequestMetrics::new();
+        for method in ["tools/call", "made/up", "made/up2", "custom/run"] {
+            metrics.record_request(method, Duration::from_millis(1), false);
+        }
+        metrics.register_method("custom/run");
+        metrics.record_request("custom/run", Duration::from_millis(1), false);
+
+        let methods: Vec<_> = metrics
+            .snapshot()
+            .into_iter()
+            .map(|(labels, stats)| (labels.method, labels.feature, stats.count))
+            .collect();
+        assert_eq!(
+            methods,
+            [
+                ("custom/run".to_string(), OTHER_FEATURE.to_string(), 1),
+                ("tools/call".to_string(), "tools".to_string(), 1),
+                (UNKNOWN_METHOD.to_string(), OTHER_FEATURE.to_string(), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_counters_saturate() {
+        let counters = MessageCounters::new();
+        counters.requests.store(u64::MAX - 1, Ordering::Relaxed);
+
+        counters.record_request(true);
+        counters.record_request(false);
+        assert_eq!(counters.requests(), u64::MAX);
+        assert_eq!(counters.errors(), 1);
+    }
+
+    #[test]
+    fn test_peak_sessions() {
+        let counters = MessageCounters::new();
+        counters.record_session_opened();
+        counters.record_session_opened();
+        counters.record_session_closed();
+        counters.record_session_opened();
+        counters.record_session_closed();
+        counters.record_session_closed();
+        counters.record_session_closed();
+
+        assert_eq!(counters.sessions(), 0);
+        assert_eq!(counters.peak_sessions(), 2);
+    }
+}
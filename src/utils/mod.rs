@@ -3,15 +3,23 @@
 //! This module contains various utility functions, logging setup,
 //! and other helper functionality.
 
+pub mod audit;
 pub mod auth;
+pub mod clock;
+pub mod file_uri;
+pub mod ids;
 pub mod logging;
+pub mod metrics;
+pub mod response_size;
 pub mod validation;
 
+pub use file_uri::{file_uri_to_path, path_to_file_uri, FileUriError};
+
 use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Generate a unique request ID
+/// Generate a unique request ID with the process-wide [`ids::IdGenerator`]
 pub fn generate_request_id() -> String {
-    uuid::Uuid::new_v4().to_string()
+    ids::id_generator().generate()
 }
 
 /// Generate a timestamp in ISO 8601 format
@@ -106,6 +114,17 @@ pub fn format_bytes(bytes: u64) -> String {
     format!("{:.1} {}", size, UNITS[unit_index])
 }
 
+/// Encode bytes as lowercase hex
+pub fn hex_encode(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
 /// Format duration as human-readable string
 pub fn format_duration(duration: std::time::Duration) -> String {
     let total_seconds = duration.as_secs();
@@ -258,6 +277,12 @@ mod tests {
         assert_eq!(format_bytes(1048576), "1.0 MB");
     }
 
+    #[test]
+    fn test_hex_encode() {
+        assert_eq!(hex_encode(&[]), "");
+        assert_eq!(hex_encode(&[0x00, 0x0f, 0xab, 0xff]), "000fabff");
+    }
+
     #[test]
     fn test_format_duration() {
         assert_eq!(format_duration(std::time::Duration::from_secs(30)), "30s");
@@ -0,0 +1,140 @@
+//! Response size limits.
+//!
+//! Results of `tools/call` and `resources/read` carry an array of content
+//! items (`content` or `contents`). When a result serializes to more than the
+//! configured limit it is either rejected or truncated: trailing items are
+//! dropped and the last text item is shortened until it fits, the kept text
+//! ends with [`TRUNCATION_MARKER`] and the result's `_meta` gets
+//! `"truncated": true`.
+
+use serde_json::Value;
+
+use crate::config::ResponseSizePolicy;
+use crate::error::{McpError, Result};
+
+/// Appended to the last text item of a truncated result
+pub const TRUNCATION_MARKER: &str = "\n[truncated]";
+
+/// Enforce `limit` on a result whose content items are under `items_key`
+pub fn enforce_response_size(
+    result: &mut Value,
+    items_key: &str,
+    limit: usize,
+    policy: ResponseSizePolicy,
+) -> Result<()> {
+    let size = serialized_size(result)?;
+    if size <= limit {
+        return Ok(());
+    }
+
+    match policy {
+        ResponseSizePolicy::Error => Err(McpError::response_too_large(size, limit)),
+        ResponseSizePolicy::Truncate => {
+            if let Some(result) = result.as_object_mut() {
+                result.insert("_meta".to_string(), serde_json::json!({ "truncated": true }));
+            }
+            truncate(result, items_key, limit)?;
+            Ok(())
+        }
+    }
+}
+
+/// Drop or shorten trailing items until the result fits
+fn truncate(result: &mut Value, items_key: &str, limit: usize) -> Result<()> {
+    loop {
+        let size = serialized_size(result)?;
+        if size <= limit {
+            return Ok(());
+        }
+        let excess = size - limit;
+
+        let Some(items) = result.get_mut(items_key).and_then(Value::as_array_mut) else {
+            return Err(McpError::response_too_large(size, limit));
+        };
+        let Some(last) = items.last_mut() else {
+            // Even an empty result does not fit
+            return Err(McpError::response_too_large(size, limit));
+        };
+
+        match last.get_mut("text") {
+            Some(Value::String(text)) => {
+                let text_len = text.strip_suffix(TRUNCATION_MARKER).unwrap_or(text).len();
+                let keep = text_len.saturating_sub(excess + TRUNCATION_MARKER.len());
+                if keep == 0 {
+                    items.pop();
+                    continue;
+                }
+
+                let mut cut = keep;
+                while !text.is_char_boundary(cut) {
+                    cut -= 1;
+                }
+                text.truncate(cut);
+                text.push_str(TRUNCATION_MARKER);
+            }
+            _ => {
+                items.pop();
+            }
+        }
+    }
+}
+
+fn serialized_size(value: &Value) -> Result<usize> {
+    Ok(serde_json::to_vec(value)?.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool_result(texts: &[&str]) -> Value {
+        let content = texts
+            .iter()
+            .map(|text| serde_json::json!({ "type": "text", "text": text }))
+            .collect::<Vec<_>>();
+        serde_json::json!({ "content": content, "isError": false })
+    }
+
+    #[test]
+    fn test_error_policy_reports_sizes() {
+        let mut result = tool_result(&[&"a".repeat(500)]);
+        let size = serialized_size(&result).unwrap();
+
+        let err = enforce_response_size(&mut result, "content", 100, ResponseSizePolicy::Error)
+            .unwrap_err();
+        assert_eq!(err.to_json_rpc_code(), -32000);
+        assert_eq!(
+            err.to_json_rpc_data().unwrap(),
+            serde_json::json!({ "size": size, "limit": 100 })
+        );
+
+        // Results within the limit are untouched
+        let mut small = tool_result(&["ok"]);
+        enforce_response_size(&mut small, "content", 100, ResponseSizePolicy::Error).unwrap();
+        assert_eq!(small, tool_result(&["ok"]));
+    }
+
+    #[test]
+    fn test_truncate_policy_drops_and_shortens_items() {
+        let mut result = tool_result(&[&"a".repeat(200), &"b".repeat(500), &"c".repeat(500)]);
+
+        enforce_response_size(&mut result, "content", 400, ResponseSizePolicy::Truncate).unwrap();
+        assert!(serialized_size(&result).unwrap() <= 400);
+        assert_eq!(result["_meta"]["truncated"], true);
+
+        let content = result["content"].as_array().unwrap();
+        assert_eq!(content.len(), 2);
+        assert_eq!(content[0]["text"], "a".repeat(200));
+        let text = content[1]["text"].as_str().unwrap();
+        assert!(text.starts_with('b') && text.ends_with(TRUNCATION_MARKER));
+    }
+
+    #[test]
+    fn test_truncate_respects_char_boundaries() {
+        let mut result = tool_result(&[&"é".repeat(300)]);
+
+        enforce_response_size(&mut result, "content", 200, ResponseSizePolicy::Truncate).unwrap();
+        let text = result["content"][0]["text"].as_str().unwrap();
+        assert!(text.trim_end_matches(TRUNCATION_MARKER).chars().all(|c| c == 'é'));
+    }
+}